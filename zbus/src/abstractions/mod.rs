@@ -6,6 +6,7 @@ mod executor;
 pub use executor::*;
 mod async_drop;
 pub(crate) mod async_lock;
+pub(crate) mod timer;
 pub use async_drop::*;
 
 // Not unix-specific itself but only used on unix.
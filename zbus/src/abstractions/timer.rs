@@ -0,0 +1,42 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// An abstraction over the timer API of the async runtime in use.
+#[derive(Debug)]
+pub(crate) struct Timer {
+    #[cfg(not(feature = "tokio"))]
+    inner: async_io::Timer,
+    #[cfg(feature = "tokio")]
+    inner: Pin<Box<tokio::time::Sleep>>,
+}
+
+impl Timer {
+    /// Create a timer that fires after the given `duration`.
+    pub fn after(duration: Duration) -> Self {
+        #[cfg(not(feature = "tokio"))]
+        let inner = async_io::Timer::after(duration);
+        #[cfg(feature = "tokio")]
+        let inner = Box::pin(tokio::time::sleep(duration));
+
+        Self { inner }
+    }
+}
+
+impl Future for Timer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        #[cfg(not(feature = "tokio"))]
+        {
+            Pin::new(&mut self.inner).poll(cx).map(|_| ())
+        }
+        #[cfg(feature = "tokio")]
+        {
+            self.inner.as_mut().poll(cx)
+        }
+    }
+}
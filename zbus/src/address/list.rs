@@ -0,0 +1,144 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{Error, Result};
+
+use super::{transport::Transport, Address};
+
+/// A list of bus addresses.
+///
+/// The D-Bus specification allows a semicolon-separated list of addresses wherever a single address
+/// is expected (e.g in the `DBUS_SESSION_BUS_ADDRESS` environment variable). The addresses are
+/// meant to be tried in order until a connection can be established with one of them.
+///
+/// # Example
+///
+/// ```
+/// use zbus::address::AddressList;
+///
+/// let list: AddressList = "unix:path=/tmp/dbus-foo;tcp:host=localhost,port=4142"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(list.iter().count(), 2);
+/// assert_eq!(
+///     list.to_string(),
+///     "unix:path=/tmp/dbus-foo;tcp:host=localhost,port=4142",
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressList {
+    addresses: Vec<Address>,
+}
+
+impl AddressList {
+    /// Get the addresses for the session socket respecting the `DBUS_SESSION_BUS_ADDRESS`
+    /// environment variable.
+    ///
+    /// See [`Address::session`] for the fallback if the variable is not set.
+    pub fn session() -> Result<Self> {
+        match std::env::var("DBUS_SESSION_BUS_ADDRESS") {
+            Ok(val) => Self::from_str(&val),
+            _ => Address::default_session().map(Into::into),
+        }
+    }
+
+    /// Get the addresses for the system bus respecting the `DBUS_SYSTEM_BUS_ADDRESS` environment
+    /// variable.
+    ///
+    /// See [`Address::system`] for the fallback if the variable is not set.
+    pub fn system() -> Result<Self> {
+        match std::env::var("DBUS_SYSTEM_BUS_ADDRESS") {
+            Ok(val) => Self::from_str(&val),
+            _ => Address::default_system().map(Into::into),
+        }
+    }
+
+    /// An iterator over the addresses, in the order they should be tried.
+    pub fn iter(&self) -> std::slice::Iter<'_, Address> {
+        self.addresses.iter()
+    }
+}
+
+impl Display for AddressList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, address) in self.addresses.iter().enumerate() {
+            if i > 0 {
+                f.write_str(";")?;
+            }
+            address.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for AddressList {
+    type Err = Error;
+
+    /// Parse a semicolon-separated list of D-Bus addresses.
+    ///
+    /// Empty entries (e.g due to a trailing semicolon) are ignored but the list must contain at
+    /// least one address.
+    fn from_str(addresses: &str) -> Result<Self> {
+        let addresses = addresses
+            .split(';')
+            .filter(|a| !a.is_empty())
+            .map(Address::from_str)
+            .collect::<Result<Vec<_>>>()?;
+        if addresses.is_empty() {
+            return Err(Error::Address("Empty address list".to_string()));
+        }
+
+        Ok(Self { addresses })
+    }
+}
+
+impl TryFrom<&str> for AddressList {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Self::from_str(value)
+    }
+}
+
+impl From<Address> for AddressList {
+    fn from(address: Address) -> Self {
+        Self {
+            addresses: vec![address],
+        }
+    }
+}
+
+impl From<Transport> for AddressList {
+    fn from(transport: Transport) -> Self {
+        Address::from(transport).into()
+    }
+}
+
+impl FromIterator<Address> for AddressList {
+    fn from_iter<I: IntoIterator<Item = Address>>(iter: I) -> Self {
+        Self {
+            addresses: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for AddressList {
+    type Item = Address;
+    type IntoIter = std::vec::IntoIter<Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.addresses.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AddressList {
+    type Item = &'a Address;
+    type IntoIter = std::slice::Iter<'a, Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
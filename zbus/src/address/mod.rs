@@ -9,12 +9,15 @@
 //!
 //! [Server addresses]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
 
+mod list;
+pub use list::AddressList;
+
 pub mod transport;
 
 use crate::{Error, Guid, OwnedGuid, Result};
 #[cfg(all(unix, not(target_os = "macos")))]
 use nix::unistd::Uid;
-use std::{collections::HashMap, env, str::FromStr};
+use std::{collections::HashMap, str::FromStr};
 
 use std::fmt::{Display, Formatter};
 
@@ -62,45 +65,49 @@ impl Address {
     /// Get the address for the session socket respecting the `DBUS_SESSION_BUS_ADDRESS` environment
     /// variable. If we don't recognize the value (or it's not set) we fall back to
     /// `$XDG_RUNTIME_DIR/bus`.
+    ///
+    /// If the variable contains a list of addresses, the first one is returned. Use
+    /// [`AddressList::session`] to get all of them.
     pub fn session() -> Result<Self> {
-        match env::var("DBUS_SESSION_BUS_ADDRESS") {
-            Ok(val) => Self::from_str(&val),
-            _ => {
-                #[cfg(windows)]
-                return Self::from_str("autolaunch:");
-
-                #[cfg(all(unix, not(target_os = "macos")))]
-                {
-                    let runtime_dir = env::var("XDG_RUNTIME_DIR")
-                        .unwrap_or_else(|_| format!("/run/user/{}", Uid::effective()));
-                    let path = format!("unix:path={runtime_dir}/bus");
-
-                    Self::from_str(&path)
-                }
-
-                #[cfg(target_os = "macos")]
-                return Self::from_str("launchd:env=DBUS_LAUNCHD_SESSION_BUS_SOCKET");
-            }
-        }
+        AddressList::session().map(|list| list.into_iter().next().expect("empty address list"))
     }
 
     /// Get the address for the system bus respecting the `DBUS_SYSTEM_BUS_ADDRESS` environment
     /// variable. If we don't recognize the value (or it's not set) we fall back to
     /// `/var/run/dbus/system_bus_socket`.
+    ///
+    /// If the variable contains a list of addresses, the first one is returned. Use
+    /// [`AddressList::system`] to get all of them.
     pub fn system() -> Result<Self> {
-        match env::var("DBUS_SYSTEM_BUS_ADDRESS") {
-            Ok(val) => Self::from_str(&val),
-            _ => {
-                #[cfg(all(unix, not(target_os = "macos")))]
-                return Self::from_str("unix:path=/var/run/dbus/system_bus_socket");
+        AddressList::system().map(|list| list.into_iter().next().expect("empty address list"))
+    }
+
+    pub(crate) fn default_session() -> Result<Self> {
+        #[cfg(windows)]
+        return Self::from_str("autolaunch:");
 
-                #[cfg(windows)]
-                return Self::from_str("autolaunch:");
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
+                .unwrap_or_else(|_| format!("/run/user/{}", Uid::effective()));
+            let path = format!("unix:path={runtime_dir}/bus");
 
-                #[cfg(target_os = "macos")]
-                return Self::from_str("launchd:env=DBUS_LAUNCHD_SESSION_BUS_SOCKET");
-            }
+            Self::from_str(&path)
         }
+
+        #[cfg(target_os = "macos")]
+        return Self::from_str("launchd:env=DBUS_LAUNCHD_SESSION_BUS_SOCKET");
+    }
+
+    pub(crate) fn default_system() -> Result<Self> {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        return Self::from_str("unix:path=/var/run/dbus/system_bus_socket");
+
+        #[cfg(windows)]
+        return Self::from_str("autolaunch:");
+
+        #[cfg(target_os = "macos")]
+        return Self::from_str("launchd:env=DBUS_LAUNCHD_SESSION_BUS_SOCKET");
     }
 
     /// The GUID for this address, if known.
@@ -187,7 +194,7 @@ impl From<Transport> for Address {
 mod tests {
     use super::{
         transport::{Tcp, TcpTransportFamily, Transport},
-        Address,
+        Address, AddressList,
     };
     #[cfg(target_os = "macos")]
     use crate::address::transport::Launchd;
@@ -243,6 +250,10 @@ mod tests {
             Address::from_str("tcp:host=localhost,port=4142").unwrap(),
             Transport::Tcp(Tcp::new("localhost", 4142)).into(),
         );
+        assert_eq!(
+            Address::from_str("tcp:port=4142").unwrap(),
+            Transport::Tcp(Tcp::new("localhost", 4142)).into(),
+        );
         assert_eq!(
             Address::from_str("tcp:host=localhost,port=4142,family=ipv4").unwrap(),
             Transport::Tcp(Tcp::new("localhost", 4142).set_family(Some(TcpTransportFamily::Ipv4)))
@@ -312,6 +323,11 @@ mod tests {
             Address::from_str("unix:tmpdir=/some/dir").unwrap(),
             Transport::Unix(Unix::new(UnixSocket::TmpDir("/some/dir".into()))).into(),
         );
+        assert_eq!(
+            Address::from_str("unix:runtime=yes").unwrap(),
+            Transport::Unix(Unix::new(UnixSocket::Runtime)).into(),
+        );
+        assert!(Address::from_str("unix:runtime=no").is_err());
     }
 
     #[test]
@@ -337,6 +353,10 @@ mod tests {
             .to_string(),
             "unix:tmpdir=/tmp/dbus-foo"
         );
+        assert_eq!(
+            Address::from(Transport::Unix(Unix::new(UnixSocket::Runtime))).to_string(),
+            "unix:runtime=yes"
+        );
         // FIXME: figure out how to handle abstract on Windows
         #[cfg(target_os = "linux")]
         assert_eq!(
@@ -404,6 +424,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_dbus_address_lists() {
+        assert!(AddressList::from_str("").is_err());
+        assert!(AddressList::from_str(";").is_err());
+        assert!(AddressList::from_str("unix:path=/tmp/dbus-foo;foo").is_err());
+
+        let list = AddressList::from_str("unix:path=/tmp/dbus-foo").unwrap();
+        assert_eq!(
+            list,
+            AddressList::from(Address::from(Transport::Unix(Unix::new(UnixSocket::File(
+                "/tmp/dbus-foo".into()
+            ))))),
+        );
+
+        let list =
+            AddressList::from_str("unix:path=/tmp/dbus-foo;tcp:host=localhost,port=4142;").unwrap();
+        assert_eq!(
+            list.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                Transport::Unix(Unix::new(UnixSocket::File("/tmp/dbus-foo".into()))).into(),
+                Address::from(Transport::Tcp(Tcp::new("localhost", 4142))),
+            ],
+        );
+        assert_eq!(
+            list.to_string(),
+            "unix:path=/tmp/dbus-foo;tcp:host=localhost,port=4142"
+        );
+    }
+
+    #[test]
+    fn builder_address_from_transport() {
+        let transport = Transport::Tcp(Tcp::new("localhost", 4142));
+        assert_eq!(
+            AddressList::from(transport.clone()),
+            AddressList::from(Address::from(transport.clone())),
+        );
+        // Both a single transport and an address are accepted, like a list.
+        let _builder = crate::connection::Builder::address(transport.clone()).unwrap();
+        let _builder = crate::connection::Builder::address(Address::from(transport)).unwrap();
+    }
+
     #[test]
     fn connect_tcp() {
         let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
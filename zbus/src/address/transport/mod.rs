@@ -104,7 +104,7 @@ impl Transport {
                     UnixSocket::Abstract(name) => {
                         SocketAddr::from_abstract_name(name.as_encoded_bytes())?
                     }
-                    UnixSocket::Dir(_) | UnixSocket::TmpDir(_) => {
+                    UnixSocket::Dir(_) | UnixSocket::TmpDir(_) | UnixSocket::Runtime => {
                         // you can't connect to a unix:dir
                         return Err(Error::Unsupported);
                     }
@@ -94,10 +94,8 @@ impl Tcp {
             return Err(Error::Address("`bind` isn't yet supported".into()));
         }
 
-        let host = opts
-            .get("host")
-            .ok_or_else(|| Error::Address("tcp address is missing `host`".into()))?
-            .to_string();
+        // Like the reference implementation, default to the local host.
+        let host = opts.get("host").unwrap_or(&"localhost").to_string();
         let port = opts
             .get("port")
             .ok_or_else(|| Error::Address("tcp address is missing `port`".into()))?;
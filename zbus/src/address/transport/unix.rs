@@ -36,18 +36,20 @@ impl Unix {
         let abs = opts.get("abstract");
         let dir = opts.get("dir");
         let tmpdir = opts.get("tmpdir");
-        let path = match (path, abs, dir, tmpdir) {
-            (Some(p), None, None, None) => UnixSocket::File(PathBuf::from(p)),
+        let runtime = opts.get("runtime");
+        let path = match (path, abs, dir, tmpdir, runtime) {
+            (Some(p), None, None, None, None) => UnixSocket::File(PathBuf::from(p)),
             #[cfg(target_os = "linux")]
-            (None, Some(p), None, None) => UnixSocket::Abstract(OsString::from(p)),
+            (None, Some(p), None, None, None) => UnixSocket::Abstract(OsString::from(p)),
             #[cfg(not(target_os = "linux"))]
-            (None, Some(_), None, None) => {
+            (None, Some(_), None, None, None) => {
                 return Err(crate::Error::Address(
                     "abstract sockets currently Linux-only".to_owned(),
                 ));
             }
-            (None, None, Some(p), None) => UnixSocket::Dir(PathBuf::from(p)),
-            (None, None, None, Some(p)) => UnixSocket::TmpDir(PathBuf::from(p)),
+            (None, None, Some(p), None, None) => UnixSocket::Dir(PathBuf::from(p)),
+            (None, None, None, Some(p), None) => UnixSocket::TmpDir(PathBuf::from(p)),
+            (None, None, None, None, Some(&"yes")) => UnixSocket::Runtime,
             _ => {
                 return Err(crate::Error::Address("unix: address is invalid".to_owned()));
             }
@@ -86,6 +88,11 @@ pub enum UnixSocket {
     ///
     /// This address is mostly relevant to server (typically bus broker) implementations.
     TmpDir(PathBuf),
+    /// A listenable address, in which the server creates a socket named `bus` in the
+    /// `$XDG_RUNTIME_DIR` directory.
+    ///
+    /// This address is mostly relevant to server (typically bus broker) implementations.
+    Runtime,
 }
 
 impl Display for UnixSocket {
@@ -122,6 +129,7 @@ impl Display for UnixSocket {
                 f.write_str("tmpdir=")?;
                 fmt_unix_path(f, path.as_os_str())?;
             }
+            UnixSocket::Runtime => f.write_str("runtime=yes")?,
         }
 
         Ok(())
@@ -3,6 +3,7 @@ use static_assertions::assert_impl_all;
 use std::net::TcpStream;
 #[cfg(all(unix, not(feature = "tokio")))]
 use std::os::unix::net::UnixStream;
+use std::time::Duration;
 #[cfg(feature = "tokio")]
 use tokio::net::TcpStream;
 #[cfg(all(unix, feature = "tokio"))]
@@ -15,8 +16,14 @@ use zvariant::ObjectPath;
 #[cfg(feature = "p2p")]
 use crate::Guid;
 use crate::{
-    address::Address, blocking::Connection, conn::AuthMechanism, connection::socket::BoxedSplit,
-    names::WellKnownName, object_server::Interface, utils::block_on, Error, Result,
+    address::AddressList,
+    blocking::Connection,
+    conn::{AuthMechanism, AuthMechanismHandler},
+    connection::socket::BoxedSplit,
+    names::WellKnownName,
+    object_server::Interface,
+    utils::block_on,
+    Error, Result,
 };
 
 /// A builder for [`zbus::blocking::Connection`].
@@ -39,10 +46,13 @@ impl<'a> Builder<'a> {
 
     /// Create a builder for a connection that will use the given [D-Bus bus address].
     ///
+    /// A semicolon-separated list of addresses is also accepted, in which case each address is
+    /// tried in turn. See [`crate::connection::Builder::address`] for details.
+    ///
     /// [D-Bus bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
     pub fn address<A>(address: A) -> Result<Self>
     where
-        A: TryInto<Address>,
+        A: TryInto<AddressList>,
         A::Error: Into<Error>,
     {
         crate::connection::Builder::address(address).map(Self)
@@ -95,6 +105,16 @@ impl<'a> Builder<'a> {
         Self(self.0.auth_mechanism(auth_mechanism))
     }
 
+    /// Register a custom authentication mechanism.
+    ///
+    /// See [`crate::connection::Builder::auth_handler`] for details.
+    pub fn auth_handler<H>(self, handler: H) -> Self
+    where
+        H: AuthMechanismHandler + 'static,
+    {
+        Self(self.0.auth_handler(handler))
+    }
+
     /// The to-be-created connection will be a peer-to-peer connection.
     ///
     /// This method is only available when the `p2p` feature is enabled.
@@ -122,6 +142,26 @@ impl<'a> Builder<'a> {
         self.0.server(guid).map(Self)
     }
 
+    /// Set the authorization policy for the clients of a server connection.
+    ///
+    /// See [`crate::connection::Builder::auth_policy`] for details.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    #[cfg(feature = "p2p")]
+    pub fn auth_policy<F>(self, policy: F) -> Self
+    where
+        F: Fn(&crate::fdo::ConnectionCredentials) -> bool + Send + Sync + 'static,
+    {
+        Self(self.0.auth_policy(policy))
+    }
+
+    /// Reconnect to the bus when the connection to it is lost.
+    ///
+    /// See [`crate::connection::Builder::reconnect`] for details.
+    pub fn reconnect(self, min_delay: std::time::Duration, max_delay: std::time::Duration) -> Self {
+        Self(self.0.reconnect(min_delay, max_delay))
+    }
+
     /// Set the capacity of the main (unfiltered) queue.
     ///
     /// Since typically you'd want to set this at instantiation time, you can set it through the
@@ -145,6 +185,13 @@ impl<'a> Builder<'a> {
         Self(self.0.max_queued(max))
     }
 
+    /// Set the default timeout for method call replies.
+    ///
+    /// See [`zbus::connection::Builder::method_timeout`] for details.
+    pub fn method_timeout(self, timeout: Duration) -> Self {
+        Self(self.0.method_timeout(timeout))
+    }
+
     /// Register a D-Bus [`Interface`] to be served at a given path.
     ///
     /// This is similar to [`zbus::blocking::ObjectServer::at`], except that it allows you to have
@@ -160,6 +207,69 @@ impl<'a> Builder<'a> {
         self.0.serve_at(path, iface).map(Self)
     }
 
+    /// Register a [`DynamicInterface`] to be served at a given path.
+    ///
+    /// See [`crate::connection::Builder::serve_dynamic_at`] for details.
+    ///
+    /// [`DynamicInterface`]: crate::object_server::DynamicInterface
+    pub fn serve_dynamic_at<P>(
+        self,
+        path: P,
+        iface: crate::object_server::DynamicInterface,
+    ) -> Result<Self>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        self.0.serve_dynamic_at(path, iface).map(Self)
+    }
+
+    /// Register a [`Fallback`] handler to serve the objects below a given path.
+    ///
+    /// See [`crate::connection::Builder::serve_fallback_at`] for details.
+    ///
+    /// [`Fallback`]: crate::object_server::Fallback
+    pub fn serve_fallback_at<P, F>(self, path: P, fallback: F) -> Result<Self>
+    where
+        F: crate::object_server::Fallback,
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        self.0.serve_fallback_at(path, fallback).map(Self)
+    }
+
+    /// Set the access control for all method calls handled by the object server.
+    ///
+    /// See [`crate::connection::Builder::access_control`] for details.
+    pub fn access_control<A>(self, access_control: A) -> Self
+    where
+        A: crate::object_server::AccessControl,
+    {
+        Self(self.0.access_control(access_control))
+    }
+
+    /// Add a middleware around all method calls handled by the object server.
+    ///
+    /// See [`crate::connection::Builder::middleware`] for details.
+    pub fn middleware<M>(self, middleware: M) -> Self
+    where
+        M: crate::object_server::Middleware,
+    {
+        Self(self.0.middleware(middleware))
+    }
+
+    /// Add a middleware around the method calls on the objects at and below a given path.
+    ///
+    /// See [`crate::connection::Builder::middleware_at`] for details.
+    pub fn middleware_at<P, M>(self, path: P, middleware: M) -> Result<Self>
+    where
+        M: crate::object_server::Middleware,
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        self.0.middleware_at(path, middleware).map(Self)
+    }
+
     /// Register a well-known name for this connection on the bus.
     ///
     /// This is similar to [`zbus::blocking::Connection::request_name`], except the name is
@@ -3,7 +3,7 @@
 use enumflags2::BitFlags;
 use event_listener::EventListener;
 use static_assertions::assert_impl_all;
-use std::{io, ops::Deref};
+use std::{io, ops::Deref, time::Duration};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
 use zvariant::ObjectPath;
 
@@ -50,6 +50,11 @@ impl Connection {
         self.inner.set_max_queued(max)
     }
 
+    /// The default timeout for method call replies, if any.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner.method_timeout()
+    }
+
     /// The server's GUID.
     pub fn server_guid(&self) -> &str {
         self.inner.server_guid()
@@ -96,6 +101,41 @@ impl Connection {
         )
     }
 
+    /// Send a method call, with a specific reply timeout.
+    ///
+    /// Same as [`Connection::call_method`], except that the given `timeout` is used instead of the
+    /// default one set on the connection (if any). If no reply is received within `timeout`,
+    /// [`Error::Timeout`] is returned.
+    pub fn call_method_with_timeout<'d, 'p, 'i, 'm, D, P, I, M, B>(
+        &self,
+        destination: Option<D>,
+        path: P,
+        iface: Option<I>,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Message>
+    where
+        D: TryInto<BusName<'d>>,
+        P: TryInto<ObjectPath<'p>>,
+        I: TryInto<InterfaceName<'i>>,
+        M: TryInto<MemberName<'m>>,
+        D::Error: Into<Error>,
+        P::Error: Into<Error>,
+        I::Error: Into<Error>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        block_on(self.inner.call_method_with_timeout(
+            destination,
+            path,
+            iface,
+            method_name,
+            timeout,
+            body,
+        ))
+    }
+
     /// Emit a signal.
     ///
     /// Create a signal message, and send it over the connection.
@@ -260,7 +300,8 @@ impl Connection {
     ///
     /// # Caveats
     ///
-    /// Currently `unix_group_ids` and `linux_security_label` fields are not populated.
+    /// Currently the `unix_group_ids` field is not populated, and `linux_security_label` is only
+    /// populated on Linux.
     pub fn peer_credentials(&self) -> io::Result<ConnectionCredentials> {
         block_on(self.inner.peer_credentials())
     }
@@ -1,10 +1,13 @@
 //! The object server API.
 
 use static_assertions::assert_impl_all;
+use zbus_names::InterfaceName;
 use zvariant::ObjectPath;
 
 use crate::{
-    object_server::{Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter},
+    object_server::{
+        DynamicInterface, Fallback, Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter,
+    },
     utils::block_on,
     Error, Result,
 };
@@ -177,6 +180,53 @@ impl ObjectServer {
         block_on(self.azync.remove::<I, P>(path))
     }
 
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// See [`crate::ObjectServer::at_dynamic`] for details.
+    pub fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.at_dynamic(path, iface))
+    }
+
+    /// Unregister a [`DynamicInterface`] with the given name at a given path.
+    ///
+    /// See [`crate::ObjectServer::remove_dynamic`] for details.
+    pub fn remove_dynamic<'p, 'i, P, I>(&self, path: P, name: I) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        I: TryInto<InterfaceName<'i>>,
+        I::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_dynamic(path, name))
+    }
+
+    /// Register a [`Fallback`] handler for the objects below a given path.
+    ///
+    /// See [`crate::ObjectServer::fallback_at`] for details.
+    pub fn fallback_at<'p, P, F>(&self, path: P, fallback: F) -> Result<bool>
+    where
+        F: Fallback,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.fallback_at(path, fallback))
+    }
+
+    /// Unregister the [`Fallback`] handler at a given path.
+    ///
+    /// Returns whether there was a handler at that path.
+    pub fn remove_fallback<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.remove_fallback(path))
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -227,6 +277,38 @@ impl ObjectServer {
         })
     }
 
+    /// Set the access control for all method calls handled by this object server.
+    ///
+    /// See [`crate::ObjectServer::set_access_control`] for details.
+    pub fn set_access_control<A>(&self, access_control: A)
+    where
+        A: crate::object_server::AccessControl,
+    {
+        block_on(self.azync.set_access_control(access_control))
+    }
+
+    /// Add a middleware around all method calls handled by this object server.
+    ///
+    /// See [`crate::ObjectServer::add_middleware`] for details.
+    pub fn add_middleware<M>(&self, middleware: M)
+    where
+        M: crate::object_server::Middleware,
+    {
+        block_on(self.azync.add_middleware(middleware))
+    }
+
+    /// Add a middleware around the method calls on the objects at and below a given path.
+    ///
+    /// See [`crate::ObjectServer::add_middleware_at`] for details.
+    pub fn add_middleware_at<'p, P, M>(&self, path: P, middleware: M) -> Result<()>
+    where
+        M: crate::object_server::Middleware,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        block_on(self.azync.add_middleware_at(path, middleware))
+    }
+
     /// Get a reference to the underlying async ObjectServer.
     pub fn inner(&self) -> &crate::ObjectServer {
         &self.azync
@@ -3,7 +3,7 @@
 use enumflags2::BitFlags;
 use futures_util::StreamExt;
 use static_assertions::assert_impl_all;
-use std::{fmt, ops::Deref};
+use std::{fmt, ops::Deref, time::Duration};
 use zbus_names::{BusName, InterfaceName, MemberName, UniqueName};
 use zvariant::{ObjectPath, OwnedValue, Value};
 
@@ -157,6 +157,13 @@ impl<'a> Proxy<'a> {
         self.inner().interface()
     }
 
+    /// The default timeout for method call replies, if any.
+    ///
+    /// See [`crate::Proxy::method_timeout`] for details.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner().method_timeout()
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](https://docs.rs/zbus_xml) crate for parsing the result.
@@ -227,6 +234,26 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call_method(method_name, body))
     }
 
+    /// Call a method and return the reply, with a specific reply timeout.
+    ///
+    /// See [`crate::Proxy::call_method_with_timeout`] for details.
+    pub fn call_method_with_timeout<'m, M, B>(
+        &self,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Message>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        block_on(
+            self.inner()
+                .call_method_with_timeout(method_name, timeout, body),
+        )
+    }
+
     /// Call a method and return the reply body.
     ///
     /// Use [`call_method`] instead if you need to deserialize the reply manually/separately.
@@ -242,6 +269,24 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call(method_name, body))
     }
 
+    /// Call a method and return the reply body, with a specific reply timeout.
+    ///
+    /// See [`crate::Proxy::call_with_timeout`] for details.
+    pub fn call_with_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<R>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        block_on(self.inner().call_with_timeout(method_name, timeout, body))
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -266,6 +311,28 @@ impl<'a> Proxy<'a> {
         block_on(self.inner().call_with_flags(method_name, flags, body))
     }
 
+    /// Call a method with flags and return the reply body, with a specific reply timeout.
+    ///
+    /// See [`crate::Proxy::call_with_flags_and_timeout`] for details.
+    pub fn call_with_flags_and_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        flags: BitFlags<MethodFlags>,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Option<R>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        block_on(
+            self.inner()
+                .call_with_flags_and_timeout(method_name, flags, timeout, body),
+        )
+    }
+
     /// Call a method without expecting a reply.
     ///
     /// This sets the `NoReplyExpected` flag on the calling message and does not wait for a reply.
@@ -0,0 +1,944 @@
+//! A minimal message bus implementation.
+//!
+//! This module is only available when the `bus-impl` feature is enabled.
+
+mod names;
+use names::{NameOwnerChange, NameRegistry};
+
+use enumflags2::BitFlags;
+use futures_util::{
+    future::{select, Either},
+    stream::FuturesUnordered,
+    StreamExt,
+};
+use serde::Serialize;
+use static_assertions::assert_impl_all;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use tracing::{debug, trace};
+use zvariant::DynamicType;
+
+use crate::{
+    async_lock::Mutex,
+    fdo::{self, ConnectionCredentials, RequestNameFlags},
+    message::{Flags, Type},
+    names::{BusName, OwnedUniqueName, UniqueName, WellKnownName},
+    Address, Connection, DBusError, Error, Guid, Listener, MatchRule, Message, MessageStream,
+    OwnedMatchRule, Result,
+};
+
+const BUS_NAME: &str = "org.freedesktop.DBus";
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+const BUS_INTERFACE: &str = "org.freedesktop.DBus";
+const PEER_INTERFACE: &str = "org.freedesktop.DBus.Peer";
+
+/// A D-Bus message bus.
+///
+/// This is a minimal implementation of a message bus (also known as a broker), as described in
+/// the [D-Bus specification]. It's mainly meant for tests and other scenarios where you need a
+/// private bus without spawning `dbus-daemon` or another bus implementation. It supports:
+///
+/// * assigning unique names to peers through the `Hello` method,
+/// * ownership of well-known names, including the queueing and replacement semantics of the
+///   `RequestName` and `ReleaseName` methods,
+/// * routing of unicast messages to their destination and broadcasting signals to the peers that
+///   subscribed to them with `AddMatch`,
+/// * the `NameOwnerChanged`, `NameLost` and `NameAcquired` signals.
+///
+/// Service activation, eavesdropping and security policies are not supported.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(unix)]
+/// # {
+/// use zbus::{bus::Bus, connection};
+///
+/// # zbus::block_on(async {
+/// let dir = tempfile::tempdir()?;
+/// let bus = Bus::bind(&*format!("unix:tmpdir={}", dir.path().display())).await?;
+/// let address = bus.address().clone();
+///
+/// let client = async {
+///     let conn = connection::Builder::address(address)?
+///         .name("org.zbus.MyService")?
+///         .build()
+///         .await?;
+///     let dbus = zbus::fdo::DBusProxy::new(&conn).await?;
+///     let owner = dbus.get_name_owner("org.zbus.MyService".try_into()?).await?;
+///     assert_eq!(Some(&owner), conn.unique_name());
+///
+///     Ok::<_, zbus::Error>(())
+/// };
+/// let run = bus.run();
+/// futures_util::pin_mut!(run, client);
+/// match futures_util::future::select(run, client).await {
+///     futures_util::future::Either::Right((res, _)) => res?,
+///     futures_util::future::Either::Left(_) => unreachable!("the bus never stops"),
+/// }
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// # }
+/// ```
+///
+/// [D-Bus specification]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-bus
+#[derive(Debug)]
+pub struct Bus {
+    listener: Listener,
+    state: Mutex<State>,
+}
+
+assert_impl_all!(Bus: Send, Sync, Unpin);
+
+#[derive(Debug, Default)]
+struct State {
+    // Only peers that have called `Hello`.
+    peers: HashMap<OwnedUniqueName, Peer>,
+    names: NameRegistry,
+}
+
+#[derive(Debug)]
+struct Peer {
+    conn: Connection,
+    match_rules: Vec<OwnedMatchRule>,
+}
+
+type Deliveries = Vec<(Connection, Message)>;
+
+impl Bus {
+    /// Bind the bus to the given address.
+    ///
+    /// All the listenable addresses supported by [`Listener`] are supported. Use
+    /// [`Bus::address`] to get the address peers should connect to.
+    pub async fn bind<A>(address: A) -> Result<Self>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<Error>,
+    {
+        let next_id = AtomicUsize::new(0);
+        let listener = Listener::builder(address)?
+            .setup(move |builder| {
+                let id = next_id.fetch_add(1, Ordering::Relaxed);
+
+                Ok(builder.unique_name(format!(":1.{id}"))?.hold_messages())
+            })
+            .build()
+            .await?;
+
+        Ok(Self {
+            listener,
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    /// The address peers can connect to.
+    pub fn address(&self) -> &Address {
+        self.listener.address()
+    }
+
+    /// The GUID of the bus.
+    pub fn guid(&self) -> &Guid<'_> {
+        self.listener.guid()
+    }
+
+    /// Run the bus.
+    ///
+    /// This accepts new peers and routes their messages. It never returns so you'd typically want
+    /// to spawn it as a task or run it concurrently with other futures. Dropping the returned
+    /// future stops the bus and disconnects all peers.
+    pub async fn run(self) {
+        let mut incoming = self.listener.incoming();
+        let mut peers = FuturesUnordered::new();
+
+        loop {
+            let conn = if peers.is_empty() {
+                incoming.next().await
+            } else {
+                match select(incoming.next(), peers.next()).await {
+                    Either::Left((conn, _)) => conn,
+                    Either::Right(_) => continue,
+                }
+            };
+
+            match conn {
+                Some(Ok(conn)) => peers.push(self.serve_peer(conn)),
+                Some(Err(e)) => debug!("Failed to accept a new peer: {}", e),
+                None => break,
+            }
+        }
+    }
+
+    async fn serve_peer(&self, conn: Connection) {
+        let mut stream = MessageStream::from(&conn);
+        // SAFETY: The unique name is assigned in the `Listener` setup.
+        let unique_name = conn.unique_name().unwrap().clone();
+        trace!("Peer `{}` connected", unique_name);
+
+        while let Some(msg) = stream.next().await {
+            let res = match msg {
+                Ok(msg) => self.handle_message(&conn, &unique_name, msg).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = res {
+                debug!("Disconnecting peer `{}`: {}", unique_name, e);
+
+                break;
+            }
+        }
+
+        if let Err(e) = self.remove_peer(&unique_name).await {
+            debug!("Failed to remove peer `{}`: {}", unique_name, e);
+        }
+        trace!("Peer `{}` disconnected", unique_name);
+    }
+
+    async fn handle_message(
+        &self,
+        conn: &Connection,
+        unique_name: &UniqueName<'_>,
+        msg: Message,
+    ) -> Result<()> {
+        let msg = set_sender(&msg, unique_name)?;
+        let hdr = msg.header();
+        trace!("Received message from `{}`: {:?}", unique_name, msg);
+
+        let registered = self
+            .state
+            .lock()
+            .await
+            .peers
+            .contains_key(unique_name.as_str());
+        let is_hello = hdr.destination().map(|d| d.as_str()) == Some(BUS_NAME)
+            && hdr.member().map(|m| m.as_str()) == Some("Hello");
+        if !registered && !is_hello {
+            return Err(Error::Failure(
+                "Client tried to send a message other than `Hello` without being registered"
+                    .to_string(),
+            ));
+        }
+
+        match hdr.destination() {
+            Some(BusName::Unique(dest)) if dest == BUS_NAME => {
+                self.handle_bus_call(conn, unique_name, &msg).await
+            }
+            Some(dest) => self.unicast(conn, &msg, dest).await,
+            None => {
+                let targets = self.state.lock().await.broadcast_targets(&msg);
+                deliver(
+                    targets
+                        .into_iter()
+                        .map(|conn| (conn, msg.clone()))
+                        .collect(),
+                )
+                .await;
+
+                Ok(())
+            }
+        }
+    }
+
+    async fn unicast(
+        &self,
+        conn: &Connection,
+        msg: &Message,
+        destination: &BusName<'_>,
+    ) -> Result<()> {
+        let target = self
+            .state
+            .lock()
+            .await
+            .resolve(destination)
+            .map(|peer| peer.conn.clone());
+        if let Some(target) = target {
+            deliver(vec![(target, msg.clone())]).await;
+
+            return Ok(());
+        }
+
+        trace!("No peer owns the destination `{}`", destination);
+        if expects_reply(msg) {
+            let e = fdo::Error::ServiceUnknown(format!(
+                "The name {destination} was not provided by any .service files"
+            ));
+            conn.send(&error_reply(msg, &e)?).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_bus_call(
+        &self,
+        conn: &Connection,
+        unique_name: &UniqueName<'_>,
+        msg: &Message,
+    ) -> Result<()> {
+        if msg.message_type() != Type::MethodCall {
+            trace!("Ignoring non-method-call message to the bus: {:?}", msg);
+
+            return Ok(());
+        }
+
+        let mut deliveries = Deliveries::new();
+        let reply = match self
+            .call_bus_method(conn, unique_name, msg, &mut deliveries)
+            .await
+        {
+            Ok(reply) => reply,
+            Err(e) => error_reply(msg, &e)?,
+        };
+        if expects_reply(msg) {
+            conn.send(&reply).await?;
+        }
+        deliver(deliveries).await;
+
+        Ok(())
+    }
+
+    /// Handle a method call on the bus itself.
+    ///
+    /// Messages resulting from the call (e.g signals) are added to `deliveries`, to be sent after
+    /// the reply.
+    async fn call_bus_method(
+        &self,
+        conn: &Connection,
+        unique_name: &UniqueName<'_>,
+        msg: &Message,
+        deliveries: &mut Deliveries,
+    ) -> fdo::Result<Message> {
+        let hdr = msg.header();
+        let body = msg.body();
+        let member = hdr.member().map(|m| m.as_str()).unwrap_or_default();
+        let interface = hdr.interface().map(|i| i.as_str());
+        let invalid_args = |e: Error| fdo::Error::InvalidArgs(e.to_string());
+
+        match interface {
+            Some(PEER_INTERFACE) if member == "Ping" => return method_reply(msg, &()),
+            Some(BUS_INTERFACE) | None => (),
+            Some(interface) => {
+                return Err(fdo::Error::UnknownInterface(format!(
+                    "Unknown interface `{interface}`"
+                )))
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        match member {
+            "Hello" => {
+                if state.peers.contains_key(unique_name.as_str()) {
+                    return Err(fdo::Error::Failed(
+                        "Already handled an Hello message".to_string(),
+                    ));
+                }
+                state.peers.insert(
+                    unique_name.to_owned().into(),
+                    Peer {
+                        conn: conn.clone(),
+                        match_rules: Vec::new(),
+                    },
+                );
+                deliveries.extend(state.name_owner_changed(
+                    unique_name,
+                    None,
+                    Some(unique_name),
+                )?);
+
+                method_reply(msg, unique_name)
+            }
+            "RequestName" => {
+                let (name, flags): (WellKnownName<'_>, BitFlags<RequestNameFlags>) =
+                    body.deserialize().map_err(invalid_args)?;
+                if name == BUS_NAME {
+                    return Err(fdo::Error::InvalidArgs(format!(
+                        "Connection `{unique_name}` is not allowed to own the name `{name}`"
+                    )));
+                }
+                let (reply, change) = state.names.request(name, unique_name.clone(), flags);
+                if let Some(change) = change {
+                    deliveries.extend(state.name_owner_change(&change)?);
+                }
+
+                method_reply(msg, &reply)
+            }
+            "ReleaseName" => {
+                let name: WellKnownName<'_> = body.deserialize().map_err(invalid_args)?;
+                let (reply, change) = state.names.release(&name, unique_name);
+                if let Some(change) = change {
+                    deliveries.extend(state.name_owner_change(&change)?);
+                }
+
+                method_reply(msg, &reply)
+            }
+            "ListQueuedOwners" => {
+                let name: WellKnownName<'_> = body.deserialize().map_err(invalid_args)?;
+                let owners = state
+                    .names
+                    .queued_owners(&name)
+                    .ok_or_else(|| no_owner(&name))?;
+
+                method_reply(msg, &owners)
+            }
+            "ListNames" => {
+                let names: Vec<&str> = std::iter::once(BUS_NAME)
+                    .chain(state.peers.keys().map(|name| name.as_str()))
+                    .chain(state.names.names().map(|name| name.as_str()))
+                    .collect();
+
+                method_reply(msg, &names)
+            }
+            "ListActivatableNames" => method_reply(msg, &[BUS_NAME]),
+            "NameHasOwner" => {
+                let name: BusName<'_> = body.deserialize().map_err(invalid_args)?;
+                let has_owner = name == BUS_NAME || state.resolve(&name).is_some();
+
+                method_reply(msg, &has_owner)
+            }
+            "GetNameOwner" => {
+                let name: BusName<'_> = body.deserialize().map_err(invalid_args)?;
+                if name == BUS_NAME {
+                    return method_reply(msg, &BUS_NAME);
+                }
+                let owner = state
+                    .resolve(&name)
+                    .and_then(|peer| peer.conn.unique_name())
+                    .ok_or_else(|| no_owner(&name))?;
+
+                method_reply(msg, &owner)
+            }
+            "StartServiceByName" => {
+                let (name, _flags): (BusName<'_>, u32) =
+                    body.deserialize().map_err(invalid_args)?;
+                if state.resolve(&name).is_none() {
+                    return Err(fdo::Error::ServiceUnknown(format!(
+                        "The name {name} was not provided by any .service files"
+                    )));
+                }
+
+                // DBUS_START_REPLY_ALREADY_RUNNING
+                method_reply(msg, &2u32)
+            }
+            "AddMatch" => {
+                let rule: &str = body.deserialize().map_err(invalid_args)?;
+                let rule = OwnedMatchRule::try_from(rule)
+                    .map_err(|e| fdo::Error::MatchRuleInvalid(e.to_string()))?;
+                // SAFETY: Only registered peers get this far.
+                let peer = state.peers.get_mut(unique_name.as_str()).unwrap();
+                peer.match_rules.push(rule);
+
+                method_reply(msg, &())
+            }
+            "RemoveMatch" => {
+                let rule: &str = body.deserialize().map_err(invalid_args)?;
+                let rule = OwnedMatchRule::try_from(rule)
+                    .map_err(|e| fdo::Error::MatchRuleInvalid(e.to_string()))?;
+                // SAFETY: Only registered peers get this far.
+                let peer = state.peers.get_mut(unique_name.as_str()).unwrap();
+                let pos = peer
+                    .match_rules
+                    .iter()
+                    .position(|r| *r == rule)
+                    .ok_or_else(|| {
+                        fdo::Error::MatchRuleNotFound(
+                            "The given match rule wasn't found and can't be removed".to_string(),
+                        )
+                    })?;
+                peer.match_rules.remove(pos);
+
+                method_reply(msg, &())
+            }
+            "GetId" => method_reply(msg, &self.guid().as_str()),
+            "GetConnectionUnixUser" | "GetConnectionUnixProcessID" | "GetConnectionCredentials" => {
+                let name: BusName<'_> = body.deserialize().map_err(invalid_args)?;
+                let peer_conn = state
+                    .resolve(&name)
+                    .map(|peer| peer.conn.clone())
+                    .ok_or_else(|| no_owner(&name))?;
+                // Don't hold the lock while querying the socket.
+                drop(state);
+                let creds = peer_conn
+                    .peer_credentials()
+                    .await
+                    .map_err(|e| fdo::Error::IOError(e.to_string()))?;
+
+                connection_credentials_reply(msg, member, &name, creds)
+            }
+            _ => Err(fdo::Error::UnknownMethod(format!(
+                "Unknown method `{member}`"
+            ))),
+        }
+    }
+
+    async fn remove_peer(&self, unique_name: &UniqueName<'_>) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state.peers.remove(unique_name.as_str()).is_none() {
+            // Never registered so nobody knows about it.
+            return Ok(());
+        }
+
+        let mut deliveries = Deliveries::new();
+        for change in state.names.release_all(unique_name) {
+            deliveries.extend(state.name_owner_change(&change)?);
+        }
+        deliveries.extend(state.name_owner_changed(unique_name, Some(unique_name), None)?);
+        drop(state);
+        deliver(deliveries).await;
+
+        Ok(())
+    }
+}
+
+impl State {
+    /// The peer that owns `name`.
+    fn resolve(&self, name: &BusName<'_>) -> Option<&Peer> {
+        let unique_name = match name {
+            BusName::Unique(name) => name,
+            BusName::WellKnown(name) => self.names.owner(name)?.inner(),
+        };
+
+        self.peers.get(unique_name.as_str())
+    }
+
+    /// The connections of the peers that have a match rule for `msg`.
+    fn broadcast_targets(&self, msg: &Message) -> Vec<Connection> {
+        self.peers
+            .values()
+            .filter(|peer| peer.match_rules.iter().any(|rule| self.matches(rule, msg)))
+            .map(|peer| peer.conn.clone())
+            .collect()
+    }
+
+    fn matches(&self, rule: &MatchRule<'_>, msg: &Message) -> bool {
+        // `MatchRule::matches` can't check a well-known sender name but we know its owner.
+        if let Some(BusName::WellKnown(name)) = rule.sender() {
+            let owner = self.names.owner(name).map(|owner| owner.inner());
+            if owner.is_none() || owner != msg.header().sender() {
+                return false;
+            }
+        }
+
+        rule.matches(msg).unwrap_or(false)
+    }
+
+    /// The signals to emit for the given ownership change of a well-known name.
+    fn name_owner_change(&self, change: &NameOwnerChange) -> Result<Deliveries> {
+        let old_owner = change.old_owner.as_deref();
+        let new_owner = change.new_owner.as_deref();
+        let mut deliveries = self.name_owner_changed(&change.name, old_owner, new_owner)?;
+
+        for (member, owner) in [("NameLost", old_owner), ("NameAcquired", new_owner)] {
+            if let Some(peer) = owner.and_then(|owner| self.peers.get(owner.as_str())) {
+                let signal = bus_signal(member, owner, &change.name.as_str())?;
+                deliveries.push((peer.conn.clone(), signal));
+            }
+        }
+
+        Ok(deliveries)
+    }
+
+    /// The `NameOwnerChanged` signal for `name` and, for unique names, the `NameAcquired` signal.
+    fn name_owner_changed(
+        &self,
+        name: &str,
+        old_owner: Option<&UniqueName<'_>>,
+        new_owner: Option<&UniqueName<'_>>,
+    ) -> Result<Deliveries> {
+        let body = (
+            name,
+            old_owner.map(|o| o.as_str()).unwrap_or(""),
+            new_owner.map(|o| o.as_str()).unwrap_or(""),
+        );
+        let signal = bus_signal("NameOwnerChanged", None, &body)?;
+        let mut deliveries: Deliveries = self
+            .broadcast_targets(&signal)
+            .into_iter()
+            .map(|conn| (conn, signal.clone()))
+            .collect();
+
+        // A peer acquires its unique name when it registers.
+        if let Some(new_owner) = new_owner.filter(|owner| owner.as_str() == name) {
+            if let Some(peer) = self.peers.get(new_owner.as_str()) {
+                let signal = bus_signal("NameAcquired", Some(new_owner), &name)?;
+                deliveries.push((peer.conn.clone(), signal));
+            }
+        }
+
+        Ok(deliveries)
+    }
+}
+
+/// Send messages to peers, ignoring (but logging) failures.
+///
+/// A failure to send to a peer means it's disconnecting and its own task will take care of
+/// cleaning up after it.
+async fn deliver(deliveries: Deliveries) {
+    for (conn, msg) in deliveries {
+        if let Err(e) = conn.send(&msg).await {
+            debug!(
+                "Failed to deliver message to `{:?}`: {}",
+                conn.unique_name(),
+                e
+            );
+        }
+    }
+}
+
+/// Set the sender of `msg` to `sender`.
+///
+/// The bus is responsible for setting the sender field of all messages going through it, so peers
+/// can't impersonate each other.
+fn set_sender(msg: &Message, sender: &UniqueName<'_>) -> Result<Message> {
+    let hdr = msg.header();
+    if hdr.sender() == Some(sender) {
+        return Ok(msg.clone());
+    }
+
+    let body = msg.body();
+    let data = body.data();
+    #[cfg(unix)]
+    let fds = data
+        .fds()
+        .iter()
+        .map(|fd| fd.try_to_owned().map(Into::into))
+        .collect::<zvariant::Result<Vec<_>>>()?;
+    let builder = crate::message::Builder::from(hdr.clone()).sender(sender)?;
+
+    // SAFETY: The body comes from a valid message and it's not modified.
+    unsafe {
+        builder.build_raw_body(
+            data,
+            body.signature(),
+            #[cfg(unix)]
+            fds,
+        )
+    }
+}
+
+fn expects_reply(msg: &Message) -> bool {
+    msg.message_type() == Type::MethodCall
+        && !msg
+            .primary_header()
+            .flags()
+            .contains(Flags::NoReplyExpected)
+}
+
+fn method_reply<B>(call: &Message, body: &B) -> fdo::Result<Message>
+where
+    B: Serialize + DynamicType,
+{
+    Message::method_return(&call.header())?
+        .sender(BUS_NAME)?
+        .build(body)
+        .map_err(Into::into)
+}
+
+fn error_reply(call: &Message, e: &fdo::Error) -> Result<Message> {
+    let builder = Message::error(&call.header(), e.name())?.sender(BUS_NAME)?;
+
+    match e.description() {
+        Some(description) => builder.build(&description),
+        None => builder.build(&()),
+    }
+}
+
+fn bus_signal<B>(member: &str, destination: Option<&UniqueName<'_>>, body: &B) -> Result<Message>
+where
+    B: Serialize + DynamicType,
+{
+    let mut builder = Message::signal(BUS_PATH, BUS_INTERFACE, member)?.sender(BUS_NAME)?;
+    if let Some(destination) = destination {
+        builder = builder.destination(destination.as_ref())?;
+    }
+
+    builder.build(body)
+}
+
+fn no_owner(name: &str) -> fdo::Error {
+    fdo::Error::NameHasNoOwner(format!(
+        "Could not get owner of name `{name}`: no such name"
+    ))
+}
+
+fn connection_credentials_reply(
+    msg: &Message,
+    member: &str,
+    name: &BusName<'_>,
+    creds: ConnectionCredentials,
+) -> fdo::Result<Message> {
+    match member {
+        "GetConnectionUnixUser" => {
+            let uid = creds.unix_user_id().ok_or_else(|| {
+                fdo::Error::Failed(format!("Could not determine Unix user ID of `{name}`"))
+            })?;
+
+            method_reply(msg, &uid)
+        }
+        "GetConnectionUnixProcessID" => {
+            let pid = creds.process_id().ok_or_else(|| {
+                fdo::Error::UnixProcessIdUnknown(format!(
+                    "Could not determine process ID of `{name}`"
+                ))
+            })?;
+
+            method_reply(msg, &pid)
+        }
+        _ => method_reply(msg, &creds),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        connection::{self, ConnectionEvent},
+        fdo::{DBusProxy, RequestNameReply},
+    };
+    use futures_util::{future::Either, pin_mut};
+    use ntest::timeout;
+    use std::{future::Future, time::Duration};
+    use test_log::test;
+
+    struct Greeter;
+
+    #[crate::interface(name = "org.zbus.TestBus")]
+    impl Greeter {
+        fn greet(&self, name: &str) -> String {
+            format!("Hello {name}!")
+        }
+    }
+
+    // Run `f` while the bus at `address` is running.
+    async fn with_bus<F, Fut>(address: &str, f: F) -> Result<()>
+    where
+        F: FnOnce(Address) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let bus = Bus::bind(address).await?;
+        let address = bus.address().clone();
+        let (run, test) = (bus.run(), f(address));
+        pin_mut!(run, test);
+
+        match select(run, test).await {
+            Either::Left(_) => unreachable!("the bus never stops"),
+            Either::Right((res, _)) => res,
+        }
+    }
+
+    async fn bus_connection(address: &Address) -> Result<Connection> {
+        connection::Builder::address(address.clone())?.build().await
+    }
+
+    async fn test_bus(address: Address) -> Result<()> {
+        let service = connection::Builder::address(address.clone())?
+            .name("org.zbus.TestBus")?
+            .serve_at("/org/zbus/TestBus", Greeter)?
+            .build()
+            .await?;
+        let client = bus_connection(&address).await?;
+        assert_ne!(service.unique_name(), client.unique_name());
+
+        // Unicast through a well-known name.
+        let reply = client
+            .call_method(
+                Some("org.zbus.TestBus"),
+                "/org/zbus/TestBus",
+                Some("org.zbus.TestBus"),
+                "Greet",
+                &"Maria",
+            )
+            .await?;
+        assert_eq!(reply.body().deserialize::<String>()?, "Hello Maria!");
+        // The bus sets the sender.
+        assert_eq!(
+            reply.header().sender(),
+            service.unique_name().map(|n| n.inner())
+        );
+
+        let dbus = DBusProxy::new(&client).await?;
+        let name: BusName<'_> = "org.zbus.TestBus".try_into()?;
+        let owner = dbus.get_name_owner(name.clone()).await?;
+        assert_eq!(Some(&owner), service.unique_name());
+        assert!(dbus.name_has_owner(name.clone()).await?);
+        let names = dbus.list_names().await?;
+        for name in [BUS_NAME, "org.zbus.TestBus", &client.unique_name().unwrap()] {
+            assert!(names.iter().any(|n| n.as_str() == name));
+        }
+        let no_such_name = dbus.get_name_owner("org.zbus.NoSuchName".try_into()?).await;
+        assert!(matches!(no_such_name, Err(fdo::Error::NameHasNoOwner(_))));
+        let unknown = client
+            .call_method(
+                Some("org.zbus.NoSuchName"),
+                "/org/zbus/TestBus",
+                Some("org.zbus.TestBus"),
+                "Greet",
+                &"Maria",
+            )
+            .await;
+        match unknown {
+            Err(Error::MethodError(name, _, _)) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.ServiceUnknown")
+            }
+            _ => panic!("expected a `ServiceUnknown` error"),
+        }
+
+        // The client gets `NameOwnerChanged` when the service goes away.
+        let mut owner_changed = dbus
+            .receive_name_owner_changed_with_args(&[(0, "org.zbus.TestBus")])
+            .await?;
+        drop(service);
+        let signal = owner_changed.next().await.unwrap();
+        let args = signal.args()?;
+        assert_eq!(args.old_owner().as_ref(), Some(owner.inner()));
+        assert!(args.new_owner().is_none());
+        assert!(!dbus.name_has_owner(name).await?);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_bus() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:tmpdir={}", dir.path().display());
+        crate::utils::block_on(with_bus(&address, test_bus)).unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn tcp_bus() {
+        crate::utils::block_on(with_bus("tcp:host=127.0.0.1,port=0", test_bus)).unwrap();
+    }
+
+    async fn test_name_queue(address: Address) -> Result<()> {
+        let first = bus_connection(&address).await?;
+        let second = bus_connection(&address).await?;
+        let dbus = DBusProxy::new(&first).await?;
+        let name: WellKnownName<'_> = "org.zbus.TestBus.Queue".try_into()?;
+
+        let reply = first
+            .request_name_with_flags(&name, RequestNameFlags::AllowReplacement.into())
+            .await?;
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        let reply = second
+            .request_name_with_flags(&name, BitFlags::empty())
+            .await?;
+        assert_eq!(reply, RequestNameReply::InQueue);
+        let owners = dbus.list_queued_owners(name.clone()).await?;
+        assert_eq!(
+            owners.iter().map(|o| o.as_str()).collect::<Vec<_>>(),
+            [
+                first.unique_name().unwrap().as_str(),
+                second.unique_name().unwrap().as_str()
+            ]
+        );
+
+        // The second peer takes over once the first one releases the name.
+        let mut acquired = DBusProxy::new(&second)
+            .await?
+            .receive_name_acquired_with_args(&[(0, name.as_str())])
+            .await?;
+        assert!(first.release_name(&name).await?);
+        acquired.next().await.unwrap();
+        let owner = dbus.get_name_owner(name.clone().into()).await?;
+        assert_eq!(Some(&owner), second.unique_name());
+
+        // Replacement isn't allowed by the second peer.
+        let third = bus_connection(&address).await?;
+        let reply = DBusProxy::new(&third)
+            .await?
+            .request_name(
+                name.clone(),
+                RequestNameFlags::ReplaceExisting | RequestNameFlags::DoNotQueue,
+            )
+            .await?;
+        assert_eq!(reply, RequestNameReply::Exists);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn name_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:tmpdir={}", dir.path().display());
+        crate::utils::block_on(with_bus(&address, test_name_queue)).unwrap();
+    }
+
+    async fn test_reconnect(address: &str) -> Result<()> {
+        let greet = |conn: Connection| async move {
+            conn.call_method(
+                Some("org.zbus.TestBus"),
+                "/org/zbus/TestBus",
+                Some("org.zbus.TestBus"),
+                "Greet",
+                &"Maria",
+            )
+            .await?
+            .body()
+            .deserialize::<String>()
+        };
+
+        let (service, mut events) = {
+            let bus = Bus::bind(address).await?;
+            let address = bus.address().clone();
+            let (run, test) = (bus.run(), async {
+                let service = connection::Builder::address(address.clone())?
+                    .name("org.zbus.TestBus")?
+                    .serve_at("/org/zbus/TestBus", Greeter)?
+                    .reconnect(Duration::from_millis(10), Duration::from_millis(100))
+                    .build()
+                    .await?;
+                let events = service.receive_events();
+                assert_eq!(
+                    greet(bus_connection(&address).await?).await?,
+                    "Hello Maria!"
+                );
+
+                Ok::<_, Error>((service, events))
+            });
+            pin_mut!(run, test);
+            match select(run, test).await {
+                Either::Left(_) => unreachable!("the bus never stops"),
+                Either::Right((res, _)) => res?,
+            }
+        };
+        let first_name = service.unique_name().unwrap().to_owned();
+
+        // The bus got dropped above, so the service gets disconnected and reconnects as soon as
+        // the bus is back.
+        with_bus(address, |address| async move {
+            match events.next().await.unwrap() {
+                ConnectionEvent::Disconnected(_) => (),
+                event => panic!("unexpected event: {event:?}"),
+            }
+            match events.next().await.unwrap() {
+                ConnectionEvent::Reconnected(name) => {
+                    assert_eq!(Some(&name), service.unique_name())
+                }
+                event => panic!("unexpected event: {event:?}"),
+            }
+            // The bus restarted, so unique names got reused.
+            assert_eq!(service.unique_name(), Some(&first_name));
+
+            // The service owns its name again and still serves its objects.
+            assert_eq!(
+                greet(bus_connection(&address).await?).await?,
+                "Hello Maria!"
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn reconnect() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:path={}", dir.path().join("bus").display());
+        crate::utils::block_on(test_reconnect(&address)).unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+use enumflags2::BitFlags;
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    fdo::{ReleaseNameReply, RequestNameFlags, RequestNameReply},
+    names::{OwnedUniqueName, OwnedWellKnownName, UniqueName, WellKnownName},
+};
+
+/// A change of the primary owner of a well-known name.
+#[derive(Debug)]
+pub(super) struct NameOwnerChange {
+    pub name: OwnedWellKnownName,
+    pub old_owner: Option<OwnedUniqueName>,
+    pub new_owner: Option<OwnedUniqueName>,
+}
+
+/// The well-known names on the bus, their primary owners and queues of waiting peers.
+#[derive(Debug, Default)]
+pub(super) struct NameRegistry {
+    names: HashMap<OwnedWellKnownName, NameEntry>,
+}
+
+#[derive(Debug)]
+struct NameEntry {
+    owner: NameOwner,
+    queue: VecDeque<NameOwner>,
+}
+
+#[derive(Debug)]
+struct NameOwner {
+    unique_name: OwnedUniqueName,
+    flags: BitFlags<RequestNameFlags>,
+}
+
+impl NameRegistry {
+    /// The primary owner of `name`, if any.
+    pub fn owner(&self, name: &WellKnownName<'_>) -> Option<&OwnedUniqueName> {
+        self.names
+            .get(name.as_str())
+            .map(|entry| &entry.owner.unique_name)
+    }
+
+    /// The primary owner of `name`, followed by the peers waiting in its queue.
+    pub fn queued_owners(&self, name: &WellKnownName<'_>) -> Option<Vec<OwnedUniqueName>> {
+        self.names.get(name.as_str()).map(|entry| {
+            std::iter::once(&entry.owner)
+                .chain(&entry.queue)
+                .map(|owner| owner.unique_name.clone())
+                .collect()
+        })
+    }
+
+    /// All the names that currently have a primary owner.
+    pub fn names(&self) -> impl Iterator<Item = &OwnedWellKnownName> {
+        self.names.keys()
+    }
+
+    /// Request `name` on behalf of `unique_name`, following the semantics of the
+    /// `org.freedesktop.DBus.RequestName` method.
+    pub fn request(
+        &mut self,
+        name: WellKnownName<'_>,
+        unique_name: UniqueName<'_>,
+        flags: BitFlags<RequestNameFlags>,
+    ) -> (RequestNameReply, Option<NameOwnerChange>) {
+        let requester = NameOwner {
+            unique_name: unique_name.into(),
+            flags,
+        };
+        let entry = match self.names.get_mut(name.as_str()) {
+            Some(entry) => entry,
+            None => {
+                let change = NameOwnerChange {
+                    name: name.into(),
+                    old_owner: None,
+                    new_owner: Some(requester.unique_name.clone()),
+                };
+                self.names.insert(
+                    change.name.clone(),
+                    NameEntry {
+                        owner: requester,
+                        queue: VecDeque::new(),
+                    },
+                );
+
+                return (RequestNameReply::PrimaryOwner, Some(change));
+            }
+        };
+
+        if entry.owner.unique_name == requester.unique_name {
+            entry.owner.flags = flags;
+
+            return (RequestNameReply::AlreadyOwner, None);
+        }
+        entry
+            .queue
+            .retain(|owner| owner.unique_name != requester.unique_name);
+
+        if flags.contains(RequestNameFlags::ReplaceExisting)
+            && entry
+                .owner
+                .flags
+                .contains(RequestNameFlags::AllowReplacement)
+        {
+            let new_owner = requester.unique_name.clone();
+            let old_owner = std::mem::replace(&mut entry.owner, requester);
+            let old_unique_name = old_owner.unique_name.clone();
+            if !old_owner.flags.contains(RequestNameFlags::DoNotQueue) {
+                entry.queue.push_front(old_owner);
+            }
+
+            let change = NameOwnerChange {
+                name: name.into(),
+                old_owner: Some(old_unique_name),
+                new_owner: Some(new_owner),
+            };
+
+            (RequestNameReply::PrimaryOwner, Some(change))
+        } else if flags.contains(RequestNameFlags::DoNotQueue) {
+            (RequestNameReply::Exists, None)
+        } else {
+            entry.queue.push_back(requester);
+
+            (RequestNameReply::InQueue, None)
+        }
+    }
+
+    /// Release `name` on behalf of `unique_name`, following the semantics of the
+    /// `org.freedesktop.DBus.ReleaseName` method.
+    pub fn release(
+        &mut self,
+        name: &WellKnownName<'_>,
+        unique_name: &UniqueName<'_>,
+    ) -> (ReleaseNameReply, Option<NameOwnerChange>) {
+        let entry = match self.names.get_mut(name.as_str()) {
+            Some(entry) => entry,
+            None => return (ReleaseNameReply::NonExistent, None),
+        };
+
+        if entry.owner.unique_name == *unique_name {
+            let change = match entry.queue.pop_front() {
+                Some(next_owner) => {
+                    let old_owner = std::mem::replace(&mut entry.owner, next_owner);
+
+                    NameOwnerChange {
+                        name: name.to_owned().into(),
+                        old_owner: Some(old_owner.unique_name),
+                        new_owner: Some(entry.owner.unique_name.clone()),
+                    }
+                }
+                None => {
+                    let (name, entry) = self
+                        .names
+                        .remove_entry(name.as_str())
+                        .expect("entry exists");
+
+                    NameOwnerChange {
+                        name,
+                        old_owner: Some(entry.owner.unique_name),
+                        new_owner: None,
+                    }
+                }
+            };
+
+            return (ReleaseNameReply::Released, Some(change));
+        }
+
+        let queue_len = entry.queue.len();
+        entry
+            .queue
+            .retain(|owner| owner.unique_name != *unique_name);
+        if entry.queue.len() == queue_len {
+            (ReleaseNameReply::NotOwner, None)
+        } else {
+            (ReleaseNameReply::Released, None)
+        }
+    }
+
+    /// Release all the names owned or queued for by `unique_name`.
+    ///
+    /// This is used when a peer disconnects from the bus.
+    pub fn release_all(&mut self, unique_name: &UniqueName<'_>) -> Vec<NameOwnerChange> {
+        let names: Vec<_> = self
+            .names
+            .iter()
+            .filter(|(_, entry)| {
+                std::iter::once(&entry.owner)
+                    .chain(&entry.queue)
+                    .any(|owner| owner.unique_name == *unique_name)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        names
+            .iter()
+            .filter_map(|name| self.release(name, unique_name).1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_and_release() {
+        let mut registry = NameRegistry::default();
+        let name = WellKnownName::from_static_str("org.zbus.Name").unwrap();
+        let first = UniqueName::from_static_str(":1.1").unwrap();
+        let second = UniqueName::from_static_str(":1.2").unwrap();
+        let third = UniqueName::from_static_str(":1.3").unwrap();
+
+        let (reply, change) = registry.request(
+            name.clone(),
+            first.clone(),
+            RequestNameFlags::AllowReplacement.into(),
+        );
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        let change = change.unwrap();
+        assert_eq!(change.old_owner, None);
+        assert_eq!(change.new_owner.as_deref(), Some(&first));
+
+        let (reply, change) = registry.request(name.clone(), first.clone(), BitFlags::empty());
+        assert_eq!(reply, RequestNameReply::AlreadyOwner);
+        assert!(change.is_none());
+
+        // Without `ReplaceExisting`, the second peer waits in the queue.
+        let (reply, change) = registry.request(name.clone(), second.clone(), BitFlags::empty());
+        assert_eq!(reply, RequestNameReply::InQueue);
+        assert!(change.is_none());
+
+        // The first owner no longer allows replacement.
+        let (reply, _) = registry.request(
+            name.clone(),
+            third.clone(),
+            RequestNameFlags::ReplaceExisting | RequestNameFlags::DoNotQueue,
+        );
+        assert_eq!(reply, RequestNameReply::Exists);
+
+        // Release passes the name to the next in the queue.
+        let (reply, change) = registry.release(&name, &first);
+        assert_eq!(reply, ReleaseNameReply::Released);
+        let change = change.unwrap();
+        assert_eq!(change.old_owner.as_deref(), Some(&first));
+        assert_eq!(change.new_owner.as_deref(), Some(&second));
+        assert_eq!(
+            registry.owner(&name).map(|o| o.as_ref()),
+            Some(second.clone())
+        );
+
+        let (reply, _) = registry.release(&name, &first);
+        assert_eq!(reply, ReleaseNameReply::NotOwner);
+
+        let changes = registry.release_all(&second);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new_owner, None);
+        assert!(registry.owner(&name).is_none());
+        let (reply, _) = registry.release(&name, &second);
+        assert_eq!(reply, ReleaseNameReply::NonExistent);
+    }
+
+    #[test]
+    fn replace_existing() {
+        let mut registry = NameRegistry::default();
+        let name = WellKnownName::from_static_str("org.zbus.Name").unwrap();
+        let first = UniqueName::from_static_str(":1.1").unwrap();
+        let second = UniqueName::from_static_str(":1.2").unwrap();
+
+        registry.request(
+            name.clone(),
+            first.clone(),
+            RequestNameFlags::AllowReplacement.into(),
+        );
+        let (reply, change) = registry.request(
+            name.clone(),
+            second.clone(),
+            RequestNameFlags::ReplaceExisting.into(),
+        );
+        assert_eq!(reply, RequestNameReply::PrimaryOwner);
+        let change = change.unwrap();
+        assert_eq!(change.old_owner.as_deref(), Some(&first));
+        assert_eq!(change.new_owner.as_deref(), Some(&second));
+
+        // The replaced owner didn't ask for `DoNotQueue` so it's now first in the queue.
+        let owners = registry.queued_owners(&name).unwrap();
+        assert_eq!(owners, [second, first]);
+    }
+}
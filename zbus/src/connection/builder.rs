@@ -8,6 +8,7 @@ use std::net::TcpStream;
 use std::os::unix::net::UnixStream;
 use std::{
     collections::{HashMap, HashSet},
+    time::Duration,
     vec,
 };
 #[cfg(feature = "tokio")]
@@ -23,16 +24,24 @@ use vsock::VsockStream;
 
 use zvariant::ObjectPath;
 
+#[cfg(feature = "p2p")]
+use crate::fdo::ConnectionCredentials;
 use crate::{
-    address::{self, Address},
+    address::{self, Address, AddressList},
     names::{InterfaceName, WellKnownName},
-    object_server::{ArcInterface, Interface},
+    object_server::{
+        AccessControl, ArcAccessControl, ArcFallback, ArcInterface, ArcMiddleware, DynamicInstance,
+        DynamicInterface, Fallback, Interface, Middleware,
+    },
     Connection, Error, Executor, Guid, OwnedGuid, Result,
 };
 
+#[cfg(feature = "p2p")]
+use super::handshake::AuthPolicy;
 use super::{
-    handshake::{AuthMechanism, Authenticated},
+    handshake::{AuthMechanism, AuthMechanismHandler, Authenticated},
     socket::{BoxedSplit, ReadHalf, Split, WriteHalf},
+    Reconnect,
 };
 
 const DEFAULT_MAX_QUEUED: usize = 64;
@@ -47,7 +56,7 @@ enum Target {
         feature = "tokio-vsock"
     ))]
     VsockStream(VsockStream),
-    Address(Address),
+    Address(AddressList),
     Socket(Split<Box<dyn ReadHalf>, Box<dyn WriteHalf>>),
     AuthenticatedSocket(Split<Box<dyn ReadHalf>, Box<dyn WriteHalf>>),
 }
@@ -66,10 +75,20 @@ pub struct Builder<'a> {
     p2p: bool,
     internal_executor: bool,
     interfaces: Interfaces<'a>,
+    fallbacks: HashMap<ObjectPath<'a>, ArcFallback>,
+    access_control: Option<ArcAccessControl>,
+    middleware: Vec<(ObjectPath<'a>, ArcMiddleware)>,
     names: HashSet<WellKnownName<'a>>,
     auth_mechanism: Option<AuthMechanism>,
+    auth_handlers: Vec<Box<dyn AuthMechanismHandler>>,
+    #[cfg(feature = "p2p")]
+    auth_policy: Option<AuthPolicy>,
+    method_timeout: Option<Duration>,
+    reconnect: Option<(Duration, Duration)>,
     #[cfg(feature = "bus-impl")]
     unique_name: Option<crate::names::UniqueName<'a>>,
+    #[cfg(feature = "bus-impl")]
+    hold_messages: bool,
 }
 
 assert_impl_all!(Builder<'_>: Send, Sync, Unpin);
@@ -77,12 +96,12 @@ assert_impl_all!(Builder<'_>: Send, Sync, Unpin);
 impl<'a> Builder<'a> {
     /// Create a builder for the session/user message bus connection.
     pub fn session() -> Result<Self> {
-        Ok(Self::new(Target::Address(Address::session()?)))
+        Ok(Self::new(Target::Address(AddressList::session()?)))
     }
 
     /// Create a builder for the system-wide message bus connection.
     pub fn system() -> Result<Self> {
-        Ok(Self::new(Target::Address(Address::system()?)))
+        Ok(Self::new(Target::Address(AddressList::system()?)))
     }
 
     /// Create a builder for a connection that will use the given [D-Bus bus address].
@@ -115,10 +134,14 @@ impl<'a> Builder<'a> {
     /// **Note:** The IBus address is different for each session. You can find the address for your
     /// current session using `ibus address` command.
     ///
+    /// A semicolon-separated list of addresses (see [`AddressList`]) is also accepted. In that
+    /// case, each address is tried in turn until a connection is established. If none of them
+    /// succeed, the returned error lists the failure for each address.
+    ///
     /// [D-Bus bus address]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
     pub fn address<A>(address: A) -> Result<Self>
     where
-        A: TryInto<Address>,
+        A: TryInto<AddressList>,
         A::Error: Into<Error>,
     {
         Ok(Self::new(Target::Address(
@@ -191,6 +214,25 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Register a custom authentication mechanism.
+    ///
+    /// Can be called multiple times to register several mechanisms. As a client, the custom
+    /// mechanisms are tried in the order they were registered, followed by the one set through
+    /// [`Builder::auth_mechanism`], if any. As a server, the client can use any of them.
+    ///
+    /// Unless [`Builder::auth_mechanism`] is also called, the default mechanism of the socket is
+    /// not used when custom mechanisms are registered.
+    ///
+    /// See [`AuthMechanismHandler`] for an example.
+    pub fn auth_handler<H>(mut self, handler: H) -> Self
+    where
+        H: AuthMechanismHandler + 'static,
+    {
+        self.auth_handlers.push(Box::new(handler));
+
+        self
+    }
+
     /// The to-be-created connection will be a peer-to-peer connection.
     ///
     /// This method is only available when the `p2p` feature is enabled.
@@ -222,6 +264,55 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Set the authorization policy for the clients of a server connection.
+    ///
+    /// Once a client is authenticated, `policy` is called with its credentials and the client is
+    /// rejected unless it returns `true`. This allows you to e.g accept clients of a set of users
+    /// or members of a Unix group.
+    ///
+    /// Which credentials are available depends on the platform and the socket type. On Linux, the
+    /// user, group and process IDs of clients connecting over Unix sockets are available, along
+    /// with their security label if a security module (e.g SELinux or AppArmor) is active.
+    ///
+    /// This method is only available when the `p2p` feature is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[cfg(unix)]
+    /// # {
+    /// # use zbus::{connection::Builder, Guid};
+    /// # #[cfg(not(feature = "tokio"))]
+    /// # use std::os::unix::net::UnixStream;
+    /// # #[cfg(feature = "tokio")]
+    /// # use tokio::net::UnixStream;
+    /// #
+    /// # zbus::block_on(async {
+    /// let (p0, p1) = UnixStream::pair()?;
+    /// let uid = nix::unistd::Uid::effective().as_raw();
+    ///
+    /// let server = Builder::unix_stream(p0)
+    ///     .server(Guid::generate())?
+    ///     .p2p()
+    ///     .auth_policy(move |creds| creds.unix_user_id() == Some(uid))
+    ///     .build();
+    /// let client = Builder::unix_stream(p1).p2p().build();
+    /// let (server, client) = futures_util::try_join!(server, client)?;
+    /// # drop((server, client));
+    /// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+    /// # }).unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "p2p")]
+    pub fn auth_policy<F>(mut self, policy: F) -> Self
+    where
+        F: Fn(&ConnectionCredentials) -> bool + Send + Sync + 'static,
+    {
+        self.auth_policy = Some(AuthPolicy::new(policy));
+
+        self
+    }
+
     /// Set the capacity of the main (unfiltered) queue.
     ///
     /// Since typically you'd want to set this at instantiation time, you can set it through the
@@ -253,6 +344,90 @@ impl<'a> Builder<'a> {
         self
     }
 
+    /// Set the default timeout for method call replies.
+    ///
+    /// By default, method calls wait for a reply indefinitely. When a timeout is set, calls made
+    /// through the connection (and [proxies] created on it, unless they override it) fail with
+    /// [`Error::Timeout`] if no reply is received within the given duration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # use std::time::Duration;
+    /// # use zbus::connection::Builder;
+    /// # use zbus::block_on;
+    /// #
+    /// # block_on(async {
+    /// let conn = Builder::session()?
+    ///     .method_timeout(Duration::from_secs(5))
+    ///     .build()
+    ///     .await?;
+    /// assert_eq!(conn.method_timeout(), Some(Duration::from_secs(5)));
+    ///
+    /// #     Ok::<(), zbus::Error>(())
+    /// # }).unwrap();
+    /// #
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    ///
+    /// [proxies]: crate::proxy::Builder::method_timeout
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.method_timeout = Some(timeout);
+
+        self
+    }
+
+    /// Reconnect to the bus when the connection to it is lost.
+    ///
+    /// By default, a connection becomes unusable once its socket is closed, e.g because the bus
+    /// was restarted. With this option, the connection instead keeps trying to connect to the bus
+    /// address(es) again, waiting `min_delay` before the first attempt and doubling the delay after
+    /// each failed one, up to `max_delay`. Once connected, it requests its well-known names again,
+    /// adds back the match rules of the signal streams and proxies, and keeps serving the objects
+    /// of its [`ObjectServer`].
+    ///
+    /// Method calls pending when the connection is lost fail and messages sent while it's down
+    /// are lost. You can use [`Connection::receive_events`] to know when that's the case. Note
+    /// that the connection gets a new unique name when it reconnects.
+    ///
+    /// This is only supported for bus connections created from an address, e.g through
+    /// [`Builder::session`] or [`Builder::address`]. [`Builder::build`] fails with
+    /// [`Error::Unsupported`] otherwise. Custom authentication handlers (see
+    /// [`Builder::auth_handler`]) are only used for the initial connection.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use futures_util::StreamExt;
+    /// # use zbus::connection::{Builder, ConnectionEvent};
+    /// #
+    /// # zbus::block_on(async {
+    /// let conn = Builder::system()?
+    ///     .reconnect(Duration::from_millis(100), Duration::from_secs(10))
+    ///     .build()
+    ///     .await?;
+    ///
+    /// let mut events = conn.receive_events();
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         ConnectionEvent::Disconnected(e) => println!("Lost the bus: {e}"),
+    ///         ConnectionEvent::Reconnected(name) => println!("Back on the bus as {name}"),
+    ///         _ => (),
+    ///     }
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// [`ObjectServer`]: crate::ObjectServer
+    pub fn reconnect(mut self, min_delay: Duration, max_delay: Duration) -> Self {
+        self.reconnect = Some((min_delay, max_delay.max(min_delay)));
+
+        self
+    }
+
     /// Enable or disable the internal executor thread.
     ///
     /// The thread is enabled by default.
@@ -285,6 +460,83 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Register a [`DynamicInterface`] to be served at a given path.
+    ///
+    /// This is the counterpart of [`Builder::serve_at`] for interfaces declared at runtime.
+    pub fn serve_dynamic_at<P>(mut self, path: P, iface: DynamicInterface) -> Result<Self>
+    where
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let entry = self.interfaces.entry(path).or_default();
+        let name = iface.name().clone();
+        entry.insert(
+            name.clone(),
+            ArcInterface::with_name(name, DynamicInstance(iface)),
+        );
+        Ok(self)
+    }
+
+    /// Register a [`Fallback`] handler to serve the objects below a given path.
+    ///
+    /// This is similar to [`zbus::ObjectServer::fallback_at`], except that the objects of the
+    /// handler are available immediately after the connection is established. This method will
+    /// replace any previously added handler at the same path.
+    pub fn serve_fallback_at<P, F>(mut self, path: P, fallback: F) -> Result<Self>
+    where
+        F: Fallback,
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        self.fallbacks.insert(path, ArcFallback::new(fallback));
+        Ok(self)
+    }
+
+    /// Set the access control for all method calls handled by the object server.
+    ///
+    /// This is similar to [`zbus::ObjectServer::set_access_control`], except that it ensures the
+    /// access control is in place before any method call is dispatched.
+    pub fn access_control<A>(mut self, access_control: A) -> Self
+    where
+        A: AccessControl,
+    {
+        self.access_control = Some(ArcAccessControl::new(access_control));
+
+        self
+    }
+
+    /// Add a middleware around all method calls handled by the object server.
+    ///
+    /// This is similar to [`zbus::ObjectServer::add_middleware`], except that it ensures the
+    /// middleware is in place before any method call is dispatched.
+    pub fn middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware,
+    {
+        let root = ObjectPath::from_static_str_unchecked("/");
+        self.middleware.push((root, ArcMiddleware::new(middleware)));
+
+        self
+    }
+
+    /// Add a middleware around the method calls on the objects at and below a given path.
+    ///
+    /// This is similar to [`zbus::ObjectServer::add_middleware_at`], except that it ensures the
+    /// middleware is in place before any method call is dispatched.
+    pub fn middleware_at<P, M>(mut self, path: P, middleware: M) -> Result<Self>
+    where
+        M: Middleware,
+        P: TryInto<ObjectPath<'a>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        self.middleware.push((path, ArcMiddleware::new(middleware)));
+
+        Ok(self)
+    }
+
     /// Register a well-known name for this connection on the bus.
     ///
     /// This is similar to [`zbus::Connection::request_name`], except the name is requested as part
@@ -326,6 +578,17 @@ impl<'a> Builder<'a> {
         Ok(self)
     }
 
+    /// Hold on to received messages until a [`crate::MessageStream`] is created for the connection.
+    ///
+    /// By default, messages received while there are no streams are dropped. A bus needs to see
+    /// all messages from its peers, including the ones sent right after the handshake.
+    #[cfg(feature = "bus-impl")]
+    pub(crate) fn hold_messages(mut self) -> Self {
+        self.hold_messages = true;
+
+        self
+    }
+
     /// Build the connection, consuming the builder.
     ///
     /// # Errors
@@ -351,6 +614,24 @@ impl<'a> Builder<'a> {
         #[cfg(not(feature = "p2p"))]
         let is_bus_conn = true;
 
+        let reconnect = match (self.reconnect, &self.target) {
+            (None, _) => None,
+            (Some((min_delay, max_delay)), Some(Target::Address(addresses))) if is_bus_conn => {
+                Some(Reconnect {
+                    // The bus gets a new GUID when it restarts, so we can't insist on the current
+                    // one.
+                    addresses: addresses
+                        .iter()
+                        .map(|address| Address::new(address.transport().clone()))
+                        .collect(),
+                    auth_mechanism: self.auth_mechanism,
+                    min_delay,
+                    max_delay,
+                })
+            }
+            (Some(_), _) => return Err(Error::Unsupported),
+        };
+
         let mut auth = self.connect(is_bus_conn).await?;
 
         // SAFETY: `Authenticated` is always built with these fields set to `Some`.
@@ -359,21 +640,41 @@ impl<'a> Builder<'a> {
         #[cfg(unix)]
         let already_received_fds = auth.already_received_fds.drain(..).collect();
 
-        let mut conn = Connection::new(auth, is_bus_conn, executor).await?;
+        let mut conn =
+            Connection::new(auth, is_bus_conn, executor, self.method_timeout, reconnect).await?;
         conn.set_max_queued(self.max_queued.unwrap_or(DEFAULT_MAX_QUEUED));
+        #[cfg(feature = "bus-impl")]
+        if self.hold_messages {
+            conn.inner.msg_receiver.clone().set_await_active(true);
+        }
 
-        if !self.interfaces.is_empty() {
+        if !self.interfaces.is_empty()
+            || !self.fallbacks.is_empty()
+            || self.access_control.is_some()
+            || !self.middleware.is_empty()
+        {
             let object_server = conn.ensure_object_server(false);
+            if let Some(access_control) = self.access_control {
+                object_server.set_arc_access_control(access_control).await;
+            }
+            for (path, middleware) in self.middleware {
+                object_server
+                    .add_arc_middleware(path.into(), middleware)
+                    .await;
+            }
             for (path, interfaces) in self.interfaces {
                 for (name, iface) in interfaces {
                     let added = object_server
-                        .add_arc_interface(path.clone(), name.clone(), iface.clone())
+                        .add_arc_interface(path.clone(), iface.clone())
                         .await?;
                     if !added {
                         return Err(Error::InterfaceExists(name.clone(), path.to_owned()));
                     }
                 }
             }
+            for (path, fallback) in self.fallbacks {
+                object_server.add_arc_fallback(path, fallback).await?;
+            }
 
             let started_event = Event::new();
             let listener = started_event.listen();
@@ -406,19 +707,41 @@ impl<'a> Builder<'a> {
             guid: None,
             internal_executor: true,
             interfaces: HashMap::new(),
+            fallbacks: HashMap::new(),
+            access_control: None,
+            middleware: vec![],
             names: HashSet::new(),
             auth_mechanism: None,
+            auth_handlers: vec![],
+            #[cfg(feature = "p2p")]
+            auth_policy: None,
+            method_timeout: None,
+            reconnect: None,
             #[cfg(feature = "bus-impl")]
             unique_name: None,
+            #[cfg(feature = "bus-impl")]
+            hold_messages: false,
         }
     }
 
+    /// Connect and authenticate to the bus at `addresses` again, for a reconnecting connection.
+    pub(crate) async fn redial(
+        addresses: AddressList,
+        auth_mechanism: Option<AuthMechanism>,
+    ) -> Result<Authenticated> {
+        let mut builder = Self::new(Target::Address(addresses));
+        builder.auth_mechanism = auth_mechanism;
+
+        builder.connect(true).await
+    }
+
     async fn connect(&mut self, is_bus_conn: bool) -> Result<Authenticated> {
         #[cfg(not(feature = "bus-impl"))]
         let unique_name = None;
         #[cfg(feature = "bus-impl")]
         let unique_name = self.unique_name.take().map(Into::into);
 
+        let auth_handlers = std::mem::take(&mut self.auth_handlers);
         #[allow(unused_mut)]
         let (mut stream, server_guid, authenticated) = self.target_connect().await?;
         if authenticated {
@@ -440,8 +763,14 @@ impl<'a> Builder<'a> {
             match self.guid.take() {
                 None => {
                     // SASL Handshake
-                    Authenticated::client(stream, server_guid, self.auth_mechanism, is_bus_conn)
-                        .await
+                    Authenticated::client(
+                        stream,
+                        server_guid,
+                        self.auth_mechanism,
+                        auth_handlers,
+                        is_bus_conn,
+                    )
+                    .await
                 }
                 Some(guid) => {
                     if !self.p2p {
@@ -452,7 +781,25 @@ impl<'a> Builder<'a> {
                     #[cfg(unix)]
                     let client_uid = creds.unix_user_id();
                     #[cfg(windows)]
-                    let client_sid = creds.into_windows_sid();
+                    let client_sid = creds.windows_sid().cloned();
+                    let authorization = match self.auth_policy.take() {
+                        Some(policy) => {
+                            // The groups are only looked up if a policy might need them.
+                            #[cfg(target_os = "linux")]
+                            let creds = match creds.unix_user_id() {
+                                Some(uid) if creds.unix_group_ids().is_none() => {
+                                    let gids = super::socket::unix_group_ids(uid).await;
+                                    gids.into_iter()
+                                        .flatten()
+                                        .fold(creds, |creds, gid| creds.add_unix_group_id(gid))
+                                }
+                                _ => creds,
+                            };
+
+                            Some((policy, creds))
+                        }
+                        None => None,
+                    };
 
                     Authenticated::server(
                         stream,
@@ -462,6 +809,8 @@ impl<'a> Builder<'a> {
                         #[cfg(windows)]
                         client_sid,
                         self.auth_mechanism,
+                        auth_handlers,
+                        authorization,
                         unique_name,
                     )
                     .await
@@ -469,7 +818,14 @@ impl<'a> Builder<'a> {
             }
 
             #[cfg(not(feature = "p2p"))]
-            Authenticated::client(stream, server_guid, self.auth_mechanism, is_bus_conn).await
+            Authenticated::client(
+                stream,
+                server_guid,
+                self.auth_mechanism,
+                auth_handlers,
+                is_bus_conn,
+            )
+            .await
         }
     }
 
@@ -491,9 +847,10 @@ impl<'a> Builder<'a> {
             Target::VsockStream(stream) => Async::new(stream)?.into(),
             #[cfg(feature = "tokio-vsock")]
             Target::VsockStream(stream) => stream.into(),
-            Target::Address(address) => {
+            Target::Address(addresses) => {
+                let (address, stream) = connect_address_list(addresses).await?;
                 guid = address.guid().map(|g| g.to_owned().into());
-                match address.connect().await? {
+                match stream {
                     #[cfg(any(unix, not(feature = "tokio")))]
                     address::transport::Stream::Unix(stream) => stream.into(),
                     #[cfg(unix)]
@@ -518,6 +875,33 @@ impl<'a> Builder<'a> {
     }
 }
 
+/// Connect to the first address in `addresses` that we can connect to.
+async fn connect_address_list(
+    addresses: AddressList,
+) -> Result<(Address, address::transport::Stream)> {
+    let mut errors = vec![];
+    for address in addresses {
+        match address.clone().connect().await {
+            Ok(stream) => return Ok((address, stream)),
+            Err(e) => errors.push((address, e)),
+        }
+    }
+
+    // Preserve the original error when there was just a single address to try.
+    if errors.len() == 1 {
+        return Err(errors.remove(0).1);
+    }
+    let errors = errors
+        .iter()
+        .map(|(address, e)| format!("`{address}`: {e}"))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    Err(Error::Address(format!(
+        "Failed to connect to any of the addresses: {errors}"
+    )))
+}
+
 /// Start the internal executor thread.
 ///
 /// Returns a dummy task that keep the executor ticking thread from exiting due to absence of any
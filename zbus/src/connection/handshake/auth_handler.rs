@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+use crate::{Error, Result};
+
+/// A custom SASL authentication mechanism.
+///
+/// Implement this trait to authenticate connections with a mechanism that zbus doesn't provide
+/// itself, e.g a token-based one for TCP connections between your services. Handlers are
+/// registered through [`connection::Builder::auth_handler`] and drive the exchange of the
+/// mechanism on either side of the handshake:
+///
+/// * On the client side, [`AuthMechanismHandler::initial_response`] gives the initial response to
+///   send along with the `AUTH` command and [`AuthMechanismHandler::respond`] is called for each
+///   `DATA` challenge the server sends.
+/// * On the server side, [`AuthMechanismHandler::verify`] is called for the initial response and
+///   each subsequent `DATA` from the client and decides on the next step of the exchange.
+///
+/// A handler is only used for a single handshake so it can keep the state of the exchange.
+///
+/// # Example
+///
+/// ```
+/// use zbus::{
+///     connection::{AuthMechanismHandler, AuthStep},
+///     Result,
+/// };
+///
+/// #[derive(Debug)]
+/// struct Token(&'static str);
+///
+/// #[async_trait::async_trait]
+/// impl AuthMechanismHandler for Token {
+///     fn name(&self) -> &str {
+///         "X_TOKEN"
+///     }
+///
+///     async fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+///         Ok(Some(self.0.as_bytes().to_vec()))
+///     }
+///
+///     async fn verify(&mut self, response: Option<Vec<u8>>) -> Result<AuthStep> {
+///         match response {
+///             // Ask for the token if the client didn't send it with `AUTH`.
+///             None => Ok(AuthStep::Challenge(None)),
+///             Some(token) if token == self.0.as_bytes() => Ok(AuthStep::Accepted),
+///             Some(_) => Ok(AuthStep::Rejected),
+///         }
+///     }
+/// }
+///
+/// # #[cfg(feature = "p2p")]
+/// # zbus::block_on(async {
+/// let listener = zbus::Listener::builder("tcp:host=127.0.0.1,port=0")?
+///     .setup(|builder| Ok(builder.auth_handler(Token("s3cr3t"))))
+///     .build()
+///     .await?;
+/// let client = zbus::connection::Builder::address(listener.address().clone())?
+///     .p2p()
+///     .auth_handler(Token("s3cr3t"))
+///     .build();
+/// let (client, server) = futures_util::try_join!(client, listener.accept())?;
+/// # drop((client, server));
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`connection::Builder::auth_handler`]: crate::connection::Builder::auth_handler
+#[async_trait]
+pub trait AuthMechanismHandler: Debug + Send + Sync {
+    /// The name of the mechanism, as used in the `AUTH` and `REJECTED` commands.
+    fn name(&self) -> &str;
+
+    /// The initial response the client sends along with the `AUTH` command.
+    ///
+    /// The default implementation sends none.
+    async fn initial_response(&mut self) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// The client's response to a `DATA` challenge from the server.
+    ///
+    /// The default implementation fails the handshake, as the mechanism isn't expecting any
+    /// challenges.
+    async fn respond(&mut self, challenge: Option<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+        let _ = challenge;
+
+        Err(Error::Handshake(format!(
+            "Unexpected challenge for the {} mechanism",
+            self.name()
+        )))
+    }
+
+    /// Verify the data sent by the client.
+    ///
+    /// This is called for the initial response of the `AUTH` command (`None` if the client didn't
+    /// send any) and then for each `DATA` command the client sends, as long as the previous call
+    /// returned [`AuthStep::Challenge`].
+    ///
+    /// The default implementation rejects the client.
+    async fn verify(&mut self, response: Option<Vec<u8>>) -> Result<AuthStep> {
+        let _ = response;
+
+        Ok(AuthStep::Rejected)
+    }
+}
+
+/// The next step of a server-side authentication exchange.
+///
+/// This is returned by [`AuthMechanismHandler::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthStep {
+    /// Send the given challenge to the client in a `DATA` command and wait for its response.
+    Challenge(Option<Vec<u8>>),
+    /// The client is authenticated.
+    Accepted,
+    /// The client is rejected. It's free to try another mechanism.
+    Rejected,
+}
@@ -4,11 +4,11 @@ use crate::{Error, Result};
 
 /// Authentication mechanisms
 ///
-/// Note that the `DBUS_COOKIE_SHA1` mechanism is not supported by zbus since version 5.0. The
-/// reasons are:
+/// Note that the `DBUS_COOKIE_SHA1` mechanism is only supported when the `cookie-sha1` feature is
+/// enabled, the handshake failing otherwise. It's disabled by default since:
 ///
 /// * It drags the `sha1` crate as a dependency, which can be [problematic for some users].
-/// * It makes the handshake more complex, now allowing use to pipeline all the commands.
+/// * It makes the handshake more complex, not allowing us to pipeline all the commands.
 /// * It's not widely used. If `EXTERNAL` is not an option, you might as well just use `ANONYMOUS`.
 ///
 /// See <https://dbus.freedesktop.org/doc/dbus-specification.html#auth-mechanisms>
@@ -24,6 +24,13 @@ pub enum AuthMechanism {
     /// Does not perform any authentication at all, and should not be accepted by message buses.
     /// However, it might sometimes be useful for non-message-bus uses of D-Bus.
     Anonymous,
+
+    /// This is a mechanism for authenticating clients of the same user as the server, where the
+    /// client proves that it can read a secret cookie from the user's `~/.dbus-keyrings`
+    /// directory. It can be used on transports where `EXTERNAL` is not possible, e.g `tcp:`.
+    ///
+    /// The handshake fails with this mechanism unless the `cookie-sha1` feature is enabled.
+    Cookie,
 }
 
 impl AuthMechanism {
@@ -31,6 +38,7 @@ impl AuthMechanism {
         match self {
             AuthMechanism::External => "EXTERNAL",
             AuthMechanism::Anonymous => "ANONYMOUS",
+            AuthMechanism::Cookie => "DBUS_COOKIE_SHA1",
         }
     }
 }
@@ -49,6 +57,7 @@ impl FromStr for AuthMechanism {
         match s {
             "EXTERNAL" => Ok(AuthMechanism::External),
             "ANONYMOUS" => Ok(AuthMechanism::Anonymous),
+            "DBUS_COOKIE_SHA1" => Ok(AuthMechanism::Cookie),
             _ => Err(Error::Handshake(format!("Unsupported mechanism: {s}"))),
         }
     }
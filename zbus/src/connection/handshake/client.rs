@@ -1,11 +1,12 @@
 use async_trait::async_trait;
+use std::collections::VecDeque;
 use tracing::{instrument, trace, warn};
 
 use crate::{conn::socket::ReadHalf, is_flatpak, names::OwnedUniqueName, Message};
 
 use super::{
-    sasl_auth_id, AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake,
-    OwnedGuid, Result,
+    sasl_auth_id, AuthMechanism, AuthMechanismHandler, Authenticated, BoxedSplit, Command, Common,
+    Error, Handshake, OwnedGuid, Result,
 };
 
 /// A representation of an in-progress handshake, client-side
@@ -17,22 +18,34 @@ pub struct Client {
     common: Common,
     server_guid: Option<OwnedGuid>,
     bus: bool,
+    // The built-in mechanism to try after the custom ones, if any.
+    mechanism: Option<AuthMechanism>,
+    handlers: VecDeque<Box<dyn AuthMechanismHandler>>,
 }
 
 impl Client {
     /// Start a handshake on this client socket
+    ///
+    /// The default mechanism of the socket is only used if neither `mechanism` nor any `handlers`
+    /// are given.
     pub fn new(
         socket: BoxedSplit,
         mechanism: Option<AuthMechanism>,
+        handlers: Vec<Box<dyn AuthMechanismHandler>>,
         server_guid: Option<OwnedGuid>,
         bus: bool,
     ) -> Client {
-        let mechanism = mechanism.unwrap_or_else(|| socket.read().auth_mechanism());
+        let mechanism = match mechanism {
+            None if handlers.is_empty() => Some(socket.read().auth_mechanism()),
+            mechanism => mechanism,
+        };
 
         Client {
-            common: Common::new(socket, mechanism),
+            common: Common::new(socket),
             server_guid,
             bus,
+            mechanism,
+            handlers: handlers.into(),
         }
     }
 
@@ -76,35 +89,143 @@ impl Client {
     }
 
     /// Perform the authentication handshake with the server.
+    ///
+    /// The custom mechanisms are tried first, in the order they were registered, followed by the
+    /// built-in one. On rejection, we only move on to the mechanisms the server supports.
     #[instrument(skip(self))]
     async fn authenticate(&mut self) -> Result<()> {
-        let mechanism = self.common.mechanism();
+        loop {
+            let (name, accepted) = match self.handlers.pop_front() {
+                Some(mut handler) => match self.authenticate_custom(&mut *handler).await? {
+                    Some(accepted) => (handler.name().to_owned(), accepted),
+                    None => return Ok(()),
+                },
+                None => match self.mechanism.take() {
+                    Some(mechanism) => return self.authenticate_builtin(mechanism).await,
+                    None => {
+                        return Err(Error::Handshake(
+                            "No authentication mechanism to try".to_string(),
+                        ))
+                    }
+                },
+            };
+
+            let accepted: Vec<_> = accepted.split_ascii_whitespace().collect();
+            if !accepted.is_empty() {
+                self.handlers
+                    .retain(|handler| accepted.contains(&handler.name()));
+                self.mechanism = self
+                    .mechanism
+                    .filter(|mechanism| accepted.contains(&mechanism.as_str()));
+            }
+            if self.handlers.is_empty() && self.mechanism.is_none() {
+                let list = accepted.join(", ");
+                return Err(Error::Handshake(format!(
+                    "{name} rejected by the server. Accepted mechanisms: [{list}]"
+                )));
+            }
+            trace!("{name} rejected by the server, trying the next mechanism");
+        }
+    }
+
+    /// Authenticate with a custom mechanism.
+    ///
+    /// Returns the mechanisms accepted by the server if it rejected this one.
+    #[instrument(skip(self))]
+    async fn authenticate_custom(
+        &mut self,
+        handler: &mut dyn AuthMechanismHandler,
+    ) -> Result<Option<String>> {
+        trace!("Trying {} mechanism", handler.name());
+        let initial_response = handler.initial_response().await?;
+        self.common
+            .write_command(Command::Auth(
+                Some(handler.name().to_owned()),
+                initial_response,
+            ))
+            .await?;
+
+        loop {
+            match self.common.read_command().await? {
+                Command::Ok(guid) => {
+                    trace!("Received OK from server");
+                    self.set_guid(guid)?;
+
+                    return Ok(None);
+                }
+                Command::Data(challenge) => {
+                    trace!("Received challenge from server");
+                    let response = handler.respond(challenge).await?;
+                    self.common.write_command(Command::Data(response)).await?;
+                }
+                Command::Rejected(accepted) => return Ok(Some(accepted.into_owned())),
+                Command::Error(e) => {
+                    return Err(Error::Handshake(format!("Received error from server: {e}")))
+                }
+                cmd => {
+                    return Err(Error::Handshake(format!(
+                        "Unexpected command from server: {cmd}"
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Authenticate with a built-in mechanism.
+    #[instrument(skip(self))]
+    async fn authenticate_builtin(&mut self, mechanism: AuthMechanism) -> Result<()> {
         trace!("Trying {mechanism} mechanism");
         let auth_cmd = match mechanism {
-            AuthMechanism::Anonymous => Command::Auth(Some(mechanism), Some("zbus".into())),
-            AuthMechanism::External => {
-                Command::Auth(Some(mechanism), Some(sasl_auth_id()?.into_bytes()))
+            AuthMechanism::Anonymous => {
+                Command::Auth(Some(mechanism.to_string()), Some("zbus".into()))
             }
+            AuthMechanism::External => Command::Auth(
+                Some(mechanism.to_string()),
+                Some(sasl_auth_id()?.into_bytes()),
+            ),
+            #[cfg(feature = "cookie-sha1")]
+            AuthMechanism::Cookie => Command::Auth(
+                Some(mechanism.to_string()),
+                Some(sasl_auth_id()?.into_bytes()),
+            ),
+            #[cfg(not(feature = "cookie-sha1"))]
+            AuthMechanism::Cookie => return Err(super::cookie_unsupported()),
         };
         self.common.write_command(auth_cmd).await?;
 
-        match self.common.read_command().await? {
-            Command::Ok(guid) => {
-                trace!("Received OK from server");
-                self.set_guid(guid)?;
+        // Only `DBUS_COOKIE_SHA1` needs more than one round trip.
+        #[cfg_attr(not(feature = "cookie-sha1"), allow(clippy::never_loop))]
+        loop {
+            match self.common.read_command().await? {
+                Command::Ok(guid) => {
+                    trace!("Received OK from server");
+                    self.set_guid(guid)?;
 
-                Ok(())
-            }
-            Command::Rejected(accepted) => {
-                let list = accepted.replace(" ", ", ");
-                Err(Error::Handshake(format!(
-                    "{mechanism} rejected by the server. Accepted mechanisms: [{list}]"
-                )))
+                    return Ok(());
+                }
+                #[cfg(feature = "cookie-sha1")]
+                Command::Data(Some(challenge)) if mechanism == AuthMechanism::Cookie => {
+                    trace!("Received cookie challenge from server");
+                    let response = cookie_response(challenge).await?;
+                    self.common
+                        .write_command(Command::Data(Some(response)))
+                        .await?;
+                }
+                Command::Rejected(accepted) => {
+                    let list = accepted.replace(" ", ", ");
+                    return Err(Error::Handshake(format!(
+                        "{mechanism} rejected by the server. Accepted mechanisms: [{list}]"
+                    )));
+                }
+                Command::Error(e) => {
+                    return Err(Error::Handshake(format!("Received error from server: {e}")))
+                }
+                cmd => {
+                    return Err(Error::Handshake(format!(
+                        "Unexpected command from server: {cmd}"
+                    )))
+                }
             }
-            Command::Error(e) => Err(Error::Handshake(format!("Received error from server: {e}"))),
-            cmd => Err(Error::Handshake(format!(
-                "Unexpected command from server: {cmd}"
-            ))),
         }
     }
 
@@ -189,9 +310,9 @@ impl Handshake for Client {
 
         trace!("Handshake done");
         #[cfg(unix)]
-        let (socket, mut recv_buffer, received_fds, cap_unix_fd, _) = self.common.into_components();
+        let (socket, mut recv_buffer, received_fds, cap_unix_fd) = self.common.into_components();
         #[cfg(not(unix))]
-        let (socket, mut recv_buffer, _, _) = self.common.into_components();
+        let (socket, mut recv_buffer, _) = self.common.into_components();
         let (mut read, write) = socket.take();
 
         // If we're a bus connection, we need to read the unique name from `Hello` response.
@@ -228,6 +349,37 @@ fn create_hello_method_call() -> Message {
         .unwrap()
 }
 
+/// Compute the response to a `DBUS_COOKIE_SHA1` challenge from the server.
+///
+/// The challenge is of the form `<context> <cookie ID> <server challenge>` and the response is
+/// `<client challenge> <hex-encoded SHA-1 hash>`.
+#[cfg(feature = "cookie-sha1")]
+async fn cookie_response(challenge: Vec<u8>) -> Result<Vec<u8>> {
+    use super::cookies::{random_challenge, CookieContext, Keyring};
+
+    let challenge = String::from_utf8(challenge)
+        .map_err(|e| Error::Handshake(format!("Invalid cookie challenge: {e}")))?;
+    let mut split = challenge.split_ascii_whitespace();
+    let (context, id, server_challenge) = match (split.next(), split.next(), split.next()) {
+        (Some(context), Some(id), Some(server_challenge)) => (context, id, server_challenge),
+        _ => return Err(Error::Handshake("Invalid cookie challenge".into())),
+    };
+    let context = CookieContext::try_from(std::borrow::Cow::Owned(context.to_string()))?;
+    let id = id
+        .parse()
+        .map_err(|e| Error::Handshake(format!("Invalid cookie ID `{id}`: {e}")))?;
+    let cookie = crate::Task::spawn_blocking(
+        move || Keyring::new()?.lookup(&context, id),
+        "read cookie keyring",
+    )
+    .await?;
+
+    let client_challenge = random_challenge();
+    let hash = cookie.hash(server_challenge, &client_challenge);
+
+    Ok(format!("{client_challenge} {hash}").into_bytes())
+}
+
 async fn receive_hello_response(
     read: &mut Box<dyn ReadHalf>,
     recv_buffer: &mut Vec<u8>,
@@ -1,6 +1,6 @@
 use tracing::{instrument, trace};
 
-use super::{AuthMechanism, BoxedSplit, Command};
+use super::{BoxedSplit, Command};
 use crate::{Error, Result};
 
 // Common code for the client and server side of the handshake.
@@ -11,20 +11,18 @@ pub(super) struct Common {
     #[cfg(unix)]
     received_fds: Vec<std::os::fd::OwnedFd>,
     cap_unix_fd: bool,
-    mechanism: AuthMechanism,
     first_command: bool,
 }
 
 impl Common {
     /// Start a handshake on this client socket
-    pub fn new(socket: BoxedSplit, mechanism: AuthMechanism) -> Self {
+    pub fn new(socket: BoxedSplit) -> Self {
         Self {
             socket,
             recv_buffer: Vec::new(),
             #[cfg(unix)]
             received_fds: Vec::new(),
             cap_unix_fd: false,
-            mechanism,
             first_command: true,
         }
     }
@@ -42,10 +40,6 @@ impl Common {
         self.cap_unix_fd = cap_unix_fd;
     }
 
-    pub fn mechanism(&self) -> AuthMechanism {
-        self.mechanism
-    }
-
     pub fn into_components(self) -> IntoComponentsReturn {
         (
             self.socket,
@@ -53,7 +47,6 @@ impl Common {
             #[cfg(unix)]
             self.received_fds,
             self.cap_unix_fd,
-            self.mechanism,
         )
     }
 
@@ -175,12 +168,6 @@ impl Common {
 }
 
 #[cfg(unix)]
-type IntoComponentsReturn = (
-    BoxedSplit,
-    Vec<u8>,
-    Vec<std::os::fd::OwnedFd>,
-    bool,
-    AuthMechanism,
-);
+type IntoComponentsReturn = (BoxedSplit, Vec<u8>, Vec<std::os::fd::OwnedFd>, bool);
 #[cfg(not(unix))]
-type IntoComponentsReturn = (BoxedSplit, Vec<u8>, bool, AuthMechanism);
+type IntoComponentsReturn = (BoxedSplit, Vec<u8>, bool);
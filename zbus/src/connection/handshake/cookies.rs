@@ -0,0 +1,459 @@
+use sha1::{Digest, Sha1};
+use std::{
+    borrow::Cow,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, trace};
+
+use crate::{Error, Result};
+
+// The timeouts used by the reference implementation.
+//
+// A new cookie is created if the newest one is older than this.
+const NEW_COOKIE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+// Cookies older than this are removed from the keyring.
+const EXPIRE_COOKIE_TIMEOUT: Duration = Duration::from_secs(7 * 60);
+// Cookies created further than this in the future are considered invalid and removed.
+const MAX_TIME_TRAVEL: Duration = Duration::from_secs(5 * 60);
+// How many times and how long to wait between attempts to lock the keyring before we assume the
+// lock is stale and break it.
+const MAX_LOCK_ATTEMPTS: u32 = 32;
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The name of a cookie keyring, as sent by the server in its challenge.
+///
+/// As it's used as a file name, the specification doesn't allow it to contain any slashes,
+/// backslashes, dots or whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CookieContext<'c>(Cow<'c, str>);
+
+impl CookieContext<'_> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'c> TryFrom<Cow<'c, str>> for CookieContext<'c> {
+    type Error = Error;
+
+    fn try_from(value: Cow<'c, str>) -> Result<Self> {
+        if value.is_empty() {
+            return Err(Error::Handshake("Empty cookie context".into()));
+        } else if !value.is_ascii() || value.contains(['/', '\\', '.', ' ', '\n', '\r', '\t']) {
+            return Err(Error::Handshake(
+                "Invalid characters in cookie context".into(),
+            ));
+        }
+
+        Ok(Self(value))
+    }
+}
+
+impl Default for CookieContext<'_> {
+    fn default() -> Self {
+        Self(Cow::Borrowed("org_freedesktop_general"))
+    }
+}
+
+impl fmt::Display for CookieContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A secret cookie from a keyring.
+#[derive(Clone, PartialEq, Eq)]
+pub(super) struct Cookie {
+    pub id: u32,
+    created: u64,
+    cookie: String,
+}
+
+impl Cookie {
+    /// Compute the hash the client is expected to reply with for the given challenges.
+    pub fn hash(&self, server_challenge: &str, client_challenge: &str) -> String {
+        let input = format!("{server_challenge}:{client_challenge}:{}", self.cookie);
+
+        hex::encode(Sha1::digest(input))
+    }
+}
+
+impl fmt::Debug for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't leak the secret into logs.
+        f.debug_struct("Cookie")
+            .field("id", &self.id)
+            .field("created", &self.created)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The directory holding the cookie keyrings, `~/.dbus-keyrings` by default.
+#[derive(Debug, Clone)]
+pub(super) struct Keyring {
+    dir: PathBuf,
+}
+
+impl Keyring {
+    /// The keyring directory of the current user.
+    pub fn new() -> Result<Self> {
+        let home = xdg_home::home_dir()
+            .ok_or_else(|| Error::Handshake("Failed to determine home directory".into()))?;
+
+        Ok(Self::with_dir(home.join(".dbus-keyrings")))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Look up the cookie with the given ID.
+    ///
+    /// This is what the client side of the authentication needs.
+    pub fn lookup(&self, context: &CookieContext<'_>, id: u32) -> Result<Cookie> {
+        self.check_permissions()?;
+
+        self.read(context)?
+            .into_iter()
+            .find(|cookie| cookie.id == id)
+            .ok_or_else(|| {
+                Error::Handshake(format!(
+                    "DBus cookie ID {id} not found in `{context}` keyring"
+                ))
+            })
+    }
+
+    /// Get a recent cookie, creating one if needed.
+    ///
+    /// This is what the server side of the authentication needs. Expired cookies are removed from
+    /// the keyring in the process. The keyring directory is created, if it doesn't already exist.
+    pub fn current_cookie(&self, context: &CookieContext<'_>) -> Result<Cookie> {
+        self.create_dir()?;
+        self.check_permissions()?;
+        let _lock = KeyringLock::acquire(&self.dir, context)?;
+
+        let now = now()?;
+        let cookies = match self.read(context) {
+            Ok(cookies) => cookies,
+            Err(Error::InputOutput(e)) if e.kind() == io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+        let n_cookies = cookies.len();
+        // Don't reuse the IDs of pruned cookies, clients might still be looking them up.
+        let max_id = cookies.iter().map(|cookie| cookie.id).max();
+        let mut cookies: Vec<_> = cookies
+            .into_iter()
+            .filter(|cookie| {
+                cookie.created <= now + MAX_TIME_TRAVEL.as_secs()
+                    && now.saturating_sub(cookie.created) <= EXPIRE_COOKIE_TIMEOUT.as_secs()
+            })
+            .collect();
+        let mut changed = cookies.len() != n_cookies;
+
+        let recent = cookies
+            .iter()
+            .filter(|cookie| now.saturating_sub(cookie.created) < NEW_COOKIE_TIMEOUT.as_secs())
+            .max_by_key(|cookie| cookie.created)
+            .cloned();
+        let cookie = match recent {
+            Some(cookie) => cookie,
+            None => {
+                let id = max_id
+                    .map(|id| id.wrapping_add(1))
+                    .unwrap_or_else(rand::random);
+                let cookie = Cookie {
+                    id,
+                    created: now,
+                    cookie: hex::encode(rand::random::<[u8; 24]>()),
+                };
+                trace!("Created new cookie with ID {} in `{}` keyring", id, context);
+                cookies.push(cookie.clone());
+                changed = true;
+
+                cookie
+            }
+        };
+
+        if changed {
+            self.write(context, &cookies)?;
+        }
+
+        Ok(cookie)
+    }
+
+    fn create_dir(&self) -> Result<()> {
+        if self.dir.exists() {
+            return Ok(());
+        }
+
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+
+            builder.mode(0o700);
+        }
+        builder.create(&self.dir)?;
+
+        Ok(())
+    }
+
+    /// The keyring directory must be owned by, and only accessible to, the current user.
+    fn check_permissions(&self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let metadata = fs::metadata(&self.dir)?;
+            if !metadata.is_dir() {
+                return Err(Error::Handshake(format!(
+                    "DBus keyring `{}` is not a directory",
+                    self.dir.display()
+                )));
+            }
+            if metadata.uid() != nix::unistd::Uid::effective().as_raw() {
+                return Err(Error::Handshake(format!(
+                    "DBus keyring `{}` is not owned by the current user",
+                    self.dir.display()
+                )));
+            }
+            if metadata.mode() & 0o077 != 0 {
+                return Err(Error::Handshake(format!(
+                    "DBus keyring `{}` has invalid permissions",
+                    self.dir.display()
+                )));
+            }
+        }
+        // FIXME: Check the directory ACL on Windows.
+
+        Ok(())
+    }
+
+    fn read(&self, context: &CookieContext<'_>) -> Result<Vec<Cookie>> {
+        let path = self.dir.join(context.as_str());
+        trace!("Reading keyring {:?}", path);
+        let content = fs::read_to_string(&path)?;
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(n, line)| {
+                let invalid = |what| {
+                    Error::Handshake(format!(
+                        "DBus keyring `{}` has {what} at line {}",
+                        path.display(),
+                        n + 1
+                    ))
+                };
+                let mut split = line.split_whitespace();
+                let id = split
+                    .next()
+                    .and_then(|id| id.parse().ok())
+                    .ok_or_else(|| invalid("an invalid cookie ID"))?;
+                let created = split
+                    .next()
+                    .and_then(|created| created.parse().ok())
+                    .ok_or_else(|| invalid("an invalid creation time"))?;
+                let cookie = split
+                    .next()
+                    .ok_or_else(|| invalid("a missing cookie"))?
+                    .to_string();
+
+                Ok(Cookie {
+                    id,
+                    created,
+                    cookie,
+                })
+            })
+            .collect()
+    }
+
+    /// Atomically replace the keyring file with the given cookies.
+    fn write(&self, context: &CookieContext<'_>, cookies: &[Cookie]) -> Result<()> {
+        let path = self.dir.join(context.as_str());
+        let tmp_path = self
+            .dir
+            .join(format!("{context}.{:x}.tmp", rand::random::<u32>()));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            options.mode(0o600);
+        }
+
+        let res = options.open(&tmp_path).and_then(|mut file| {
+            for cookie in cookies {
+                writeln!(file, "{} {} {}", cookie.id, cookie.created, cookie.cookie)?;
+            }
+            file.sync_all()?;
+
+            fs::rename(&tmp_path, &path)
+        });
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+
+        res.map_err(Into::into)
+    }
+}
+
+/// An exclusive lock on a keyring, released on drop.
+#[derive(Debug)]
+struct KeyringLock {
+    path: PathBuf,
+}
+
+impl KeyringLock {
+    fn acquire(dir: &Path, context: &CookieContext<'_>) -> Result<Self> {
+        let path = dir.join(format!("{context}.lock"));
+        let create = || OpenOptions::new().write(true).create_new(true).open(&path);
+
+        for _ in 0..MAX_LOCK_ATTEMPTS {
+            match create() {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    std::thread::sleep(LOCK_RETRY_INTERVAL)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        // Whoever held the lock must have died while holding it.
+        debug!("Breaking stale keyring lock {:?}", path);
+        let _ = fs::remove_file(&path);
+        create().map(|_: File| Self { path }).map_err(Into::into)
+    }
+}
+
+impl Drop for KeyringLock {
+    fn drop(&mut self) {
+        // Nothing we can do about errors here.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A random challenge string.
+pub(super) fn random_challenge() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
+
+fn now() -> Result<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .map_err(|e| Error::Handshake(format!("Invalid system time: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyring() -> (tempfile::TempDir, Keyring) {
+        let dir = tempfile::tempdir().unwrap();
+        let keyring = Keyring::with_dir(dir.path().join("keyrings"));
+
+        (dir, keyring)
+    }
+
+    #[test]
+    fn context() {
+        for context in ["", "foo/bar", "..", "foo bar", "foo.bar", "föö"] {
+            assert!(CookieContext::try_from(Cow::Borrowed(context)).is_err());
+        }
+        let context = CookieContext::try_from(Cow::Borrowed("org_zbus_test")).unwrap();
+        assert_eq!(context.as_str(), "org_zbus_test");
+    }
+
+    #[test]
+    fn current_cookie_is_reused() {
+        let (_dir, keyring) = keyring();
+        let context = CookieContext::default();
+
+        let cookie = keyring.current_cookie(&context).unwrap();
+        assert_eq!(keyring.current_cookie(&context).unwrap(), cookie);
+        assert_eq!(keyring.lookup(&context, cookie.id).unwrap(), cookie);
+        assert!(keyring.lookup(&context, cookie.id.wrapping_add(1)).is_err());
+        // The lock is released.
+        assert!(!keyring.dir.join("org_freedesktop_general.lock").exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&keyring.dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+    }
+
+    #[test]
+    fn expired_cookies() {
+        let (_dir, keyring) = keyring();
+        let context = CookieContext::default();
+        keyring.create_dir().unwrap();
+        let now = now().unwrap();
+        let old = Cookie {
+            id: 1,
+            created: now - NEW_COOKIE_TIMEOUT.as_secs() - 1,
+            cookie: "old".to_string(),
+        };
+        let expired = Cookie {
+            id: 2,
+            created: now - EXPIRE_COOKIE_TIMEOUT.as_secs() - 1,
+            cookie: "expired".to_string(),
+        };
+        let future = Cookie {
+            id: 3,
+            created: now + MAX_TIME_TRAVEL.as_secs() + 60,
+            cookie: "future".to_string(),
+        };
+        keyring
+            .write(&context, &[old.clone(), expired, future])
+            .unwrap();
+
+        // The old cookie is still valid but too old to be used for new authentications.
+        let cookie = keyring.current_cookie(&context).unwrap();
+        assert_eq!(cookie.id, 4);
+        let ids: Vec<_> = keyring
+            .read(&context)
+            .unwrap()
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, [old.id, cookie.id]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, keyring) = keyring();
+        let context = CookieContext::default();
+        let cookie = keyring.current_cookie(&context).unwrap();
+
+        fs::set_permissions(&keyring.dir, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(keyring.lookup(&context, cookie.id).is_err());
+        assert!(keyring.current_cookie(&context).is_err());
+    }
+
+    #[test]
+    fn hash() {
+        let cookie = Cookie {
+            id: 1,
+            created: 0,
+            cookie: "cookie".to_string(),
+        };
+        assert_eq!(
+            cookie.hash("server", "client"),
+            "da886d42e1fee942674b87d4f0d6c3d35804d5eb"
+        );
+    }
+}
@@ -1,22 +1,32 @@
+mod auth_handler;
 mod auth_mechanism;
 mod client;
 mod command;
 mod common;
+#[cfg(feature = "cookie-sha1")]
+// The keyring maintenance is only needed by the server side.
+#[cfg_attr(not(feature = "p2p"), allow(dead_code))]
+mod cookies;
 #[cfg(feature = "p2p")]
 mod server;
 
 use async_trait::async_trait;
 #[cfg(unix)]
 use nix::unistd::Uid;
+#[cfg(feature = "p2p")]
+use std::fmt;
 use std::fmt::Debug;
 use zbus_names::OwnedUniqueName;
 
+#[cfg(feature = "p2p")]
+use crate::fdo::ConnectionCredentials;
 #[cfg(windows)]
 use crate::win32;
 use crate::{Error, OwnedGuid, Result};
 
 use super::socket::{BoxedSplit, ReadHalf, WriteHalf};
 
+pub use auth_handler::{AuthMechanismHandler, AuthStep};
 pub use auth_mechanism::AuthMechanism;
 use client::Client;
 use command::Command;
@@ -52,9 +62,10 @@ impl Authenticated {
         socket: BoxedSplit,
         server_guid: Option<OwnedGuid>,
         mechanism: Option<AuthMechanism>,
+        handlers: Vec<Box<dyn AuthMechanismHandler>>,
         bus: bool,
     ) -> Result<Self> {
-        Client::new(socket, mechanism, server_guid, bus)
+        Client::new(socket, mechanism, handlers, server_guid, bus)
             .perform()
             .await
     }
@@ -69,6 +80,8 @@ impl Authenticated {
         #[cfg(unix)] client_uid: Option<u32>,
         #[cfg(windows)] client_sid: Option<String>,
         auth_mechanism: Option<AuthMechanism>,
+        handlers: Vec<Box<dyn AuthMechanismHandler>>,
+        authorization: Option<(AuthPolicy, ConnectionCredentials)>,
         unique_name: Option<OwnedUniqueName>,
     ) -> Result<Self> {
         Server::new(
@@ -79,6 +92,8 @@ impl Authenticated {
             #[cfg(windows)]
             client_sid,
             auth_mechanism,
+            handlers,
+            authorization,
             unique_name,
         )?
         .perform()
@@ -86,6 +101,34 @@ impl Authenticated {
     }
 }
 
+/// The authorization policy of a server.
+///
+/// See [`crate::connection::Builder::auth_policy`].
+#[cfg(feature = "p2p")]
+pub(crate) struct AuthPolicy(Box<dyn Fn(&ConnectionCredentials) -> bool + Send + Sync>);
+
+#[cfg(feature = "p2p")]
+impl AuthPolicy {
+    pub fn new<F>(policy: F) -> Self
+    where
+        F: Fn(&ConnectionCredentials) -> bool + Send + Sync + 'static,
+    {
+        Self(Box::new(policy))
+    }
+
+    /// Whether the client with the given credentials is allowed to connect.
+    pub fn allows(&self, credentials: &ConnectionCredentials) -> bool {
+        (self.0)(credentials)
+    }
+}
+
+#[cfg(feature = "p2p")]
+impl Debug for AuthPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthPolicy").finish_non_exhaustive()
+    }
+}
+
 #[async_trait]
 pub trait Handshake {
     /// Perform the handshake.
@@ -111,6 +154,12 @@ fn sasl_auth_id() -> Result<String> {
     Ok(id)
 }
 
+/// The error of handshakes using `DBUS_COOKIE_SHA1`, when it's not supported.
+#[cfg(not(feature = "cookie-sha1"))]
+fn cookie_unsupported() -> Error {
+    Error::Handshake("`DBUS_COOKIE_SHA1` requires the `cookie-sha1` feature".into())
+}
+
 #[cfg(feature = "p2p")]
 #[cfg(unix)]
 #[cfg(test)]
@@ -157,9 +206,17 @@ mod tests {
         let (p0, p1) = create_async_socket_pair();
 
         let guid = OwnedGuid::from(Guid::generate());
-        let client = Client::new(p0.into(), None, Some(guid.clone()), false);
-        let server =
-            Server::new(p1.into(), guid, Some(Uid::effective().into()), None, None).unwrap();
+        let client = Client::new(p0.into(), None, vec![], Some(guid.clone()), false);
+        let server = Server::new(
+            p1.into(),
+            guid,
+            Some(Uid::effective().into()),
+            None,
+            vec![],
+            None,
+            None,
+        )
+        .unwrap();
 
         // proceed to the handshakes
         let (client, server) = crate::utils::block_on(join(
@@ -180,6 +237,8 @@ mod tests {
             Guid::generate().into(),
             Some(Uid::effective().into()),
             None,
+            vec![],
+            None,
             None,
         )
         .unwrap();
@@ -208,6 +267,8 @@ mod tests {
             Guid::generate().into(),
             Some(Uid::effective().into()),
             None,
+            vec![],
+            None,
             None,
         )
         .unwrap();
@@ -234,6 +295,8 @@ mod tests {
             Guid::generate().into(),
             Some(Uid::effective().into()),
             None,
+            vec![],
+            None,
             None,
         )
         .unwrap();
@@ -251,6 +314,8 @@ mod tests {
             Guid::generate().into(),
             Some(Uid::effective().into()),
             Some(AuthMechanism::Anonymous),
+            vec![],
+            None,
             None,
         )
         .unwrap();
@@ -268,6 +333,8 @@ mod tests {
             Guid::generate().into(),
             Some(Uid::effective().into()),
             Some(AuthMechanism::Anonymous),
+            vec![],
+            None,
             None,
         )
         .unwrap();
@@ -276,4 +343,263 @@ mod tests {
             .unwrap();
         crate::utils::block_on(server.perform()).unwrap();
     }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_handshake() {
+        let (p0, p1) = create_async_socket_pair();
+
+        let guid = OwnedGuid::from(Guid::generate());
+        let client = Client::new(
+            p0.into(),
+            Some(AuthMechanism::Cookie),
+            vec![],
+            Some(guid.clone()),
+            false,
+        );
+        let server = Server::new(
+            p1.into(),
+            guid,
+            Some(Uid::effective().into()),
+            Some(AuthMechanism::Cookie),
+            vec![],
+            None,
+            None,
+        )
+        .unwrap();
+
+        let (client, server) = crate::utils::block_on(join(
+            async move { client.perform().await.unwrap() },
+            async move { server.perform().await.unwrap() },
+        ));
+
+        assert_eq!(client.server_guid, server.server_guid);
+    }
+
+    #[cfg(feature = "cookie-sha1")]
+    #[test]
+    #[timeout(15000)]
+    fn wrong_cookie_response() {
+        let (mut p0, p1) = create_async_socket_pair();
+        let server = Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            Some(Uid::effective().into()),
+            Some(AuthMechanism::Cookie),
+            vec![],
+            None,
+            None,
+        )
+        .unwrap();
+
+        crate::utils::block_on(
+            p0.write_all(
+                format!(
+                    "\0AUTH DBUS_COOKIE_SHA1 {}\r\nDATA {}\r\nBEGIN\r\n",
+                    hex::encode(sasl_auth_id().unwrap()),
+                    hex::encode("abcd 0123456789abcdef0123456789abcdef01234567"),
+                )
+                .as_bytes(),
+            ),
+        )
+        .unwrap();
+        // The server rejects the response and then fails when the client hangs up.
+        drop(p0);
+        crate::utils::block_on(server.perform()).unwrap_err();
+    }
+
+    #[cfg(not(feature = "cookie-sha1"))]
+    #[test]
+    #[timeout(15000)]
+    fn cookie_unsupported() {
+        let (p0, p1) = create_async_socket_pair();
+
+        let client = Client::new(p0.into(), Some(AuthMechanism::Cookie), vec![], None, false);
+        crate::utils::block_on(client.perform()).unwrap_err();
+        Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            Some(Uid::effective().into()),
+            Some(AuthMechanism::Cookie),
+            vec![],
+            None,
+            None,
+        )
+        .unwrap_err();
+    }
+
+    #[derive(Debug)]
+    struct Token {
+        token: &'static str,
+        // The server asks for the token as a challenge, instead of an initial response.
+        challenged: bool,
+    }
+
+    impl Token {
+        fn new(token: &'static str) -> Self {
+            Self {
+                token,
+                challenged: false,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AuthMechanismHandler for Token {
+        fn name(&self) -> &str {
+            "X_TOKEN"
+        }
+
+        async fn respond(&mut self, challenge: Option<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+            assert_eq!(challenge.as_deref(), Some(&b"token?"[..]));
+
+            Ok(Some(self.token.as_bytes().to_vec()))
+        }
+
+        async fn verify(&mut self, response: Option<Vec<u8>>) -> Result<AuthStep> {
+            match response {
+                None if !self.challenged => {
+                    self.challenged = true;
+
+                    Ok(AuthStep::Challenge(Some(b"token?".to_vec())))
+                }
+                Some(token) if self.challenged && token == self.token.as_bytes() => {
+                    Ok(AuthStep::Accepted)
+                }
+                _ => Ok(AuthStep::Rejected),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Unknown;
+
+    #[async_trait]
+    impl AuthMechanismHandler for Unknown {
+        fn name(&self) -> &str {
+            "X_UNKNOWN"
+        }
+    }
+
+    fn custom_handshake(
+        client_handlers: Vec<Box<dyn AuthMechanismHandler>>,
+        server_token: &'static str,
+    ) -> Result<()> {
+        let (p0, p1) = create_async_socket_pair();
+
+        let guid = OwnedGuid::from(Guid::generate());
+        let client = Client::new(p0.into(), None, client_handlers, Some(guid.clone()), false);
+        let server = Server::new(
+            p1.into(),
+            guid,
+            Some(Uid::effective().into()),
+            None,
+            vec![Box::new(Token::new(server_token))],
+            None,
+            None,
+        )?;
+
+        let (client, server) = crate::utils::block_on(join(client.perform(), server.perform()));
+        let (client, server) = (client?, server?);
+        assert_eq!(client.server_guid, server.server_guid);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn custom_mechanism() {
+        custom_handshake(vec![Box::new(Token::new("s3cr3t"))], "s3cr3t").unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn custom_mechanism_fallback() {
+        // The server rejects the unknown mechanism and the client falls back to the next one.
+        custom_handshake(
+            vec![Box::new(Unknown), Box::new(Token::new("s3cr3t"))],
+            "s3cr3t",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn custom_mechanism_rejected() {
+        let (mut p0, p1) = create_async_socket_pair();
+        let server = Server::new(
+            p1.into(),
+            Guid::generate().into(),
+            Some(Uid::effective().into()),
+            None,
+            vec![Box::new(Token::new("s3cr3t"))],
+            None,
+            None,
+        )
+        .unwrap();
+
+        // Without an explicit mechanism, the default one of the socket is not accepted.
+        crate::utils::block_on(
+            p0.write_all(
+                format!(
+                    "\0AUTH EXTERNAL {}\r\nAUTH X_TOKEN\r\nDATA {}\r\nBEGIN\r\n",
+                    hex::encode(sasl_auth_id().unwrap()),
+                    hex::encode("wrong"),
+                )
+                .as_bytes(),
+            ),
+        )
+        .unwrap();
+        drop(p0);
+        crate::utils::block_on(server.perform()).unwrap_err();
+    }
+
+    fn policy_handshake(policy: AuthPolicy) -> (Result<Authenticated>, Result<Authenticated>) {
+        let (p0, p1) = create_async_socket_pair();
+
+        let uid = Uid::effective().into();
+        let guid = OwnedGuid::from(Guid::generate());
+        let client = Client::new(p0.into(), None, vec![], Some(guid.clone()), false);
+        let creds = crate::fdo::ConnectionCredentials::default()
+            .set_unix_user_id(uid)
+            .add_unix_group_id(1234);
+        let server = Server::new(
+            p1.into(),
+            guid,
+            Some(uid),
+            None,
+            vec![],
+            Some((policy, creds)),
+            None,
+        )
+        .unwrap();
+
+        crate::utils::block_on(join(client.perform(), server.perform()))
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn auth_policy() {
+        let policy = AuthPolicy::new(|creds| {
+            creds
+                .unix_group_ids()
+                .is_some_and(|gids| gids.contains(&1234))
+        });
+        let (client, server) = policy_handshake(policy);
+        client.unwrap();
+        server.unwrap();
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn auth_policy_rejected() {
+        let uid = u32::from(Uid::effective());
+        let policy = AuthPolicy::new(move |creds| creds.unix_user_id() != Some(uid));
+        let (client, server) = policy_handshake(policy);
+        let err = client.unwrap_err();
+        assert!(matches!(err, Error::Handshake(_)), "{err}");
+        // The client gives up and hangs up on the server.
+        server.unwrap_err();
+    }
 }
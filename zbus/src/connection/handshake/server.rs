@@ -1,10 +1,18 @@
 use async_trait::async_trait;
+#[cfg(feature = "cookie-sha1")]
+use tracing::debug;
 use tracing::{instrument, trace};
 
-use crate::names::OwnedUniqueName;
+use crate::{fdo::ConnectionCredentials, names::OwnedUniqueName};
 
+#[cfg(feature = "cookie-sha1")]
 use super::{
-    AuthMechanism, Authenticated, BoxedSplit, Command, Common, Error, Handshake, OwnedGuid, Result,
+    cookies::{random_challenge, Cookie, CookieContext, Keyring},
+    sasl_auth_id,
+};
+use super::{
+    AuthMechanism, AuthMechanismHandler, AuthPolicy, AuthStep, Authenticated, BoxedSplit, Command,
+    Common, Error, Handshake, OwnedGuid, Result,
 };
 
 /*
@@ -15,6 +23,8 @@ use super::{
 enum ServerHandshakeStep {
     WaitingForAuth,
     WaitingForData(AuthMechanism),
+    // The index of the handler of the custom mechanism.
+    WaitingForCustomData(usize),
     WaitingForBegin,
     Done,
 }
@@ -32,6 +42,13 @@ pub struct Server {
     #[cfg(windows)]
     client_sid: Option<String>,
     unique_name: Option<OwnedUniqueName>,
+    mechanism: Option<AuthMechanism>,
+    handlers: Vec<Box<dyn AuthMechanismHandler>>,
+    // The authorization policy and the credentials of the client to check against it.
+    authorization: Option<(AuthPolicy, ConnectionCredentials)>,
+    // The cookie and the challenge sent to the client, while waiting for the response.
+    #[cfg(feature = "cookie-sha1")]
+    cookie_challenge: Option<(Cookie, String)>,
 }
 
 impl Server {
@@ -41,12 +58,21 @@ impl Server {
         #[cfg(unix)] client_uid: Option<u32>,
         #[cfg(windows)] client_sid: Option<String>,
         mechanism: Option<AuthMechanism>,
+        handlers: Vec<Box<dyn AuthMechanismHandler>>,
+        authorization: Option<(AuthPolicy, ConnectionCredentials)>,
         unique_name: Option<OwnedUniqueName>,
     ) -> Result<Self> {
-        let mechanism = mechanism.unwrap_or_else(|| socket.read().auth_mechanism());
+        // Only fall back to the default mechanism of the socket if there are no custom ones, or
+        // we'd e.g end up accepting `ANONYMOUS` on TCP sockets.
+        let mechanism = match mechanism {
+            None if handlers.is_empty() => Some(socket.read().auth_mechanism()),
+            #[cfg(not(feature = "cookie-sha1"))]
+            Some(AuthMechanism::Cookie) => return Err(super::cookie_unsupported()),
+            mechanism => mechanism,
+        };
 
         Ok(Server {
-            common: Common::new(socket, mechanism),
+            common: Common::new(socket),
             step: ServerHandshakeStep::WaitingForAuth,
             #[cfg(unix)]
             client_uid,
@@ -54,11 +80,24 @@ impl Server {
             client_sid,
             guid,
             unique_name,
+            mechanism,
+            handlers,
+            authorization,
+            #[cfg(feature = "cookie-sha1")]
+            cookie_challenge: None,
         })
     }
 
     #[instrument(skip(self))]
     async fn auth_ok(&mut self) -> Result<()> {
+        if let Some((policy, credentials)) = &self.authorization {
+            if !policy.allows(credentials) {
+                trace!("Client not allowed by the authorization policy");
+
+                return self.rejected_error().await;
+            }
+        }
+
         let guid = self.guid.clone();
         let cmd = Command::Ok(guid);
         trace!("Sending authentication OK");
@@ -92,6 +131,91 @@ impl Server {
         }
     }
 
+    /// Send a `DBUS_COOKIE_SHA1` challenge to the client identifying itself as `sasl_id`.
+    ///
+    /// The keyring is in the home directory of the server's user so only clients of the same user
+    /// can be authenticated with this mechanism.
+    #[cfg(feature = "cookie-sha1")]
+    #[instrument(skip(self))]
+    async fn send_cookie_challenge(&mut self, sasl_id: &[u8]) -> Result<()> {
+        if sasl_id != sasl_auth_id()?.as_bytes() {
+            trace!("Client is not of the same user as the server");
+
+            return self.rejected_error().await;
+        }
+
+        let context = CookieContext::default();
+        let cookie = crate::Task::spawn_blocking(
+            move || Keyring::new()?.current_cookie(&context),
+            "update cookie keyring",
+        )
+        .await;
+        let cookie = match cookie {
+            Ok(cookie) => cookie,
+            Err(e) => {
+                debug!("Failed to get a cookie from the keyring: {e}");
+
+                return self.rejected_error().await;
+            }
+        };
+        let server_challenge = random_challenge();
+        let challenge = format!(
+            "{} {} {server_challenge}",
+            CookieContext::default(),
+            cookie.id
+        );
+        trace!("Sending cookie challenge");
+        self.common
+            .write_command(Command::Data(Some(challenge.into_bytes())))
+            .await?;
+        self.cookie_challenge = Some((cookie, server_challenge));
+        self.step = ServerHandshakeStep::WaitingForData(AuthMechanism::Cookie);
+
+        Ok(())
+    }
+
+    /// Check the client's response to the `DBUS_COOKIE_SHA1` challenge.
+    #[cfg(feature = "cookie-sha1")]
+    #[instrument(skip(self))]
+    async fn check_cookie_response(&mut self, response: &[u8]) -> Result<()> {
+        // SAFETY: Only called while waiting for a response to the challenge.
+        let (cookie, server_challenge) = self.cookie_challenge.take().unwrap();
+        let response = std::str::from_utf8(response)
+            .map_err(|e| Error::Handshake(format!("Invalid cookie response: {e}")))?;
+        let auth_ok = match response.split_once(' ') {
+            Some((client_challenge, hash)) => {
+                !client_challenge.is_empty()
+                    && constant_time_eq(
+                        cookie.hash(&server_challenge, client_challenge).as_bytes(),
+                        hash.as_bytes(),
+                    )
+            }
+            None => false,
+        };
+
+        if auth_ok {
+            self.auth_ok().await
+        } else {
+            self.rejected_error().await
+        }
+    }
+
+    /// Let the handler of a custom mechanism verify the data from the client.
+    #[instrument(skip(self))]
+    async fn check_custom_auth(&mut self, index: usize, data: Option<Vec<u8>>) -> Result<()> {
+        match self.handlers[index].verify(data).await? {
+            AuthStep::Challenge(challenge) => {
+                trace!("Sending challenge");
+                self.common.write_command(Command::Data(challenge)).await?;
+                self.step = ServerHandshakeStep::WaitingForCustomData(index);
+
+                Ok(())
+            }
+            AuthStep::Accepted => self.auth_ok().await,
+            AuthStep::Rejected => self.rejected_error().await,
+        }
+    }
+
     #[instrument(skip(self))]
     async fn unsupported_command_error(&mut self) -> Result<()> {
         let cmd = Command::Error("Unsupported or misplaced command".to_string());
@@ -102,7 +226,14 @@ impl Server {
 
     #[instrument(skip(self))]
     async fn rejected_error(&mut self) -> Result<()> {
-        let cmd = Command::Rejected(self.common.mechanism().as_str().into());
+        let mechanisms = self
+            .handlers
+            .iter()
+            .map(|handler| handler.name())
+            .chain(self.mechanism.as_ref().map(AuthMechanism::as_str))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let cmd = Command::Rejected(mechanisms.into());
         trace!("Sending authentication error");
         self.common.write_command(cmd).await?;
         self.step = ServerHandshakeStep::WaitingForAuth;
@@ -116,6 +247,9 @@ impl Server {
         match self.step {
             ServerHandshakeStep::WaitingForAuth => self.handle_auth().await?,
             ServerHandshakeStep::WaitingForData(mech) => self.handle_auth_data(mech).await?,
+            ServerHandshakeStep::WaitingForCustomData(index) => {
+                self.handle_custom_auth_data(index).await?
+            }
             ServerHandshakeStep::WaitingForBegin => self.finalize().await?,
             ServerHandshakeStep::Done => return Ok(true),
         }
@@ -132,12 +266,22 @@ impl Server {
         let reply = self.common.read_command().await?;
         match reply {
             Command::Auth(requested_mech, resp) => {
-                let mech = self.common.mechanism();
-                if requested_mech != Some(mech) {
-                    self.rejected_error().await?;
-
-                    return Ok(());
+                let requested_mech = requested_mech.as_deref();
+                let custom = self
+                    .handlers
+                    .iter()
+                    .position(|handler| Some(handler.name()) == requested_mech);
+                if let Some(index) = custom {
+                    return self.check_custom_auth(index, resp).await;
                 }
+                let mech = match self.mechanism {
+                    Some(mech) if requested_mech == Some(mech.as_str()) => mech,
+                    _ => {
+                        self.rejected_error().await?;
+
+                        return Ok(());
+                    }
+                };
 
                 match &resp {
                     None => {
@@ -148,6 +292,10 @@ impl Server {
                     Some(sasl_id) => match mech {
                         AuthMechanism::Anonymous => self.auth_ok().await?,
                         AuthMechanism::External => self.check_external_auth(sasl_id).await?,
+                        #[cfg(feature = "cookie-sha1")]
+                        AuthMechanism::Cookie => self.send_cookie_challenge(sasl_id).await?,
+                        #[cfg(not(feature = "cookie-sha1"))]
+                        AuthMechanism::Cookie => unreachable!("rejected by `Server::new`"),
                     },
                 }
             }
@@ -174,11 +322,37 @@ impl Server {
                 self.check_external_auth(&data).await?;
             }
             (AuthMechanism::Anonymous, Command::Data(_)) => self.auth_ok().await?,
+            #[cfg(feature = "cookie-sha1")]
+            (AuthMechanism::Cookie, Command::Data(Some(data))) => {
+                if self.cookie_challenge.is_some() {
+                    self.check_cookie_response(&data).await?;
+                } else {
+                    self.send_cookie_challenge(&data).await?;
+                }
+            }
             (_, _) => self.unsupported_command_error().await?,
         }
         Ok(())
     }
 
+    /// Handle the data receiving step of a custom authentication mechanism.
+    #[instrument(skip(self))]
+    async fn handle_custom_auth_data(&mut self, index: usize) -> Result<()> {
+        assert_eq!(self.step, ServerHandshakeStep::WaitingForCustomData(index));
+
+        trace!("Waiting for authentication data");
+        match self.common.read_command().await? {
+            Command::Data(data) => self.check_custom_auth(index, data).await?,
+            Command::Cancel | Command::Error(_) => {
+                trace!("Received CANCEL or ERROR command from the client");
+                self.rejected_error().await?;
+            }
+            _ => self.unsupported_command_error().await?,
+        }
+
+        Ok(())
+    }
+
     /// Finalize the handshake.
     #[instrument(skip(self))]
     async fn finalize(&mut self) -> Result<()> {
@@ -224,9 +398,9 @@ impl Handshake for Server {
 
         trace!("Handshake done");
         #[cfg(unix)]
-        let (socket, recv_buffer, received_fds, cap_unix_fd, _) = self.common.into_components();
+        let (socket, recv_buffer, received_fds, cap_unix_fd) = self.common.into_components();
         #[cfg(not(unix))]
-        let (socket, recv_buffer, _, _) = self.common.into_components();
+        let (socket, recv_buffer, _) = self.common.into_components();
         let (read, write) = socket.take();
         Ok(Authenticated {
             socket_write: write,
@@ -241,3 +415,10 @@ impl Handshake for Server {
         })
     }
 }
+
+/// Compare `a` & `b` in constant time, i.e without bailing out at the first difference, so that
+/// the time taken doesn't tell how much of a secret was guessed right.
+#[cfg(feature = "cookie-sha1")]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
@@ -9,8 +9,12 @@ use std::{
     io::{self, ErrorKind},
     num::NonZeroU32,
     pin::Pin,
-    sync::{Arc, OnceLock, Weak},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock, Weak,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, info_span, instrument, trace, trace_span, warn, Instrument};
 use zbus_names::{BusName, ErrorName, InterfaceName, MemberName, OwnedUniqueName, WellKnownName};
@@ -23,7 +27,8 @@ use crate::{
     async_lock::{Mutex, Semaphore, SemaphorePermit},
     fdo::{ConnectionCredentials, ReleaseNameReply, RequestNameFlags, RequestNameReply},
     is_flatpak,
-    message::{Flags, Message, Type},
+    message::{Flags, Message, Sequence, Type},
+    timer::Timer,
     DBusError, Error, Executor, MatchRule, MessageStream, ObjectServer, OwnedGuid, OwnedMatchRule,
     Result, Task,
 };
@@ -38,8 +43,12 @@ mod socket_reader;
 use socket_reader::SocketReader;
 
 pub(crate) mod handshake;
-pub use handshake::AuthMechanism;
 use handshake::Authenticated;
+pub use handshake::{AuthMechanism, AuthMechanismHandler, AuthStep};
+
+mod reconnect;
+use reconnect::Reconnect;
+pub use reconnect::{ConnectionEvent, ConnectionEventStream};
 
 const DEFAULT_MAX_QUEUED: usize = 64;
 const DEFAULT_MAX_METHOD_RETURN_QUEUED: usize = 8;
@@ -52,8 +61,12 @@ pub(crate) struct ConnectionInner {
     cap_unix_fd: bool,
     #[cfg(feature = "p2p")]
     bus_conn: bool,
-    unique_name: OnceLock<OwnedUniqueName>,
-    registered_names: Mutex<HashMap<WellKnownName<'static>, NameStatus>>,
+    unique_name: UniqueNames,
+    registered_names:
+        Mutex<HashMap<WellKnownName<'static>, (NameStatus, BitFlags<RequestNameFlags>)>>,
+    method_timeout: Option<Duration>,
+    reconnect: Option<Reconnect>,
+    closed: AtomicBool,
 
     activity_event: Arc<Event>,
     socket_write: Mutex<Box<dyn socket::WriteHalf>>,
@@ -73,10 +86,30 @@ pub(crate) struct ConnectionInner {
 
     object_server: OnceLock<ObjectServer>,
     object_server_dispatch_task: OnceLock<Task<()>>,
+    // The replies to the method calls being watched by the object server, keyed by the caller and
+    // serial number of the call.
+    reply_watches: std::sync::Mutex<HashMap<ReplyKey, ReplyWatch>>,
+    // Set once a reply is watched, i.e once the object server has middleware, so the replies
+    // don't need to be checked against `reply_watches` until then.
+    watching_replies: AtomicBool,
+
+    events: Broadcaster<ConnectionEvent>,
+    events_receiver: InactiveReceiver<ConnectionEvent>,
 
     drop_event: Event,
 }
 
+impl ConnectionInner {
+    fn emit_event(&self, event: ConnectionEvent) {
+        // Errors only mean there are no receivers.
+        let _ = self.events.try_broadcast(event);
+    }
+
+    fn closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
 impl Drop for ConnectionInner {
     fn drop(&mut self) {
         // Notify anyone waiting that the connection is going away. Since we're being dropped, it's
@@ -88,6 +121,49 @@ impl Drop for ConnectionInner {
 
 type Subscriptions = HashMap<OwnedMatchRule, (u64, InactiveReceiver<Result<Message>>)>;
 
+/// The unique names of a connection, one for each time it connected to the bus.
+///
+/// Reconnecting connections get a new unique name each time they reconnect. The previous ones are
+/// kept around (as the first link of the chain), so [`Connection::unique_name`] can hand out
+/// references.
+#[derive(Debug, Default)]
+struct UniqueNames {
+    name: OnceLock<OwnedUniqueName>,
+    next: OnceLock<Box<UniqueNames>>,
+}
+
+impl UniqueNames {
+    /// The current unique name, if any.
+    fn get(&self) -> Option<&OwnedUniqueName> {
+        self.current().name.get()
+    }
+
+    /// Set the unique name, after (re)connecting to the bus.
+    fn set(&self, name: OwnedUniqueName) {
+        let current = self.current();
+        if let Err(name) = current.name.set(name) {
+            // Only the socket reader sets it after the connection is established.
+            let next = Box::new(UniqueNames {
+                name: OnceLock::from(name),
+                next: OnceLock::new(),
+            });
+            current
+                .next
+                .set(next)
+                .expect("unique name set concurrently");
+        }
+    }
+
+    fn current(&self) -> &Self {
+        let mut current = self;
+        while let Some(next) = current.next.get() {
+            current = next;
+        }
+
+        current
+    }
+}
+
 pub(crate) type MsgBroadcaster = Broadcaster<Result<Message>>;
 
 /// A D-Bus connection.
@@ -221,6 +297,7 @@ assert_impl_all!(Connection: Send, Sync, Unpin);
 pub(crate) struct PendingMethodCall {
     stream: Option<MessageStream>,
     serial: NonZeroU32,
+    timeout: Option<Timer>,
 }
 
 impl Future for PendingMethodCall {
@@ -239,7 +316,7 @@ impl Future for PendingMethodCall {
 
 impl OrderedFuture for PendingMethodCall {
     type Output = Result<Message>;
-    type Ordering = zbus::message::Sequence;
+    type Ordering = Sequence;
 
     fn poll_before(
         self: Pin<&mut Self>,
@@ -278,7 +355,18 @@ impl OrderedFuture for PendingMethodCall {
                     Poll::Ready(PollResult::Terminated) => {
                         return Poll::Ready(None);
                     }
-                    Poll::Pending => return Poll::Pending,
+                    Poll::Pending => {
+                        if let Some(timeout) = &mut this.timeout {
+                            if Pin::new(timeout).poll(cx).is_ready() {
+                                this.stream = None;
+                                this.timeout = None;
+
+                                return Poll::Ready(Some((Sequence::LAST, Err(Error::Timeout))));
+                            }
+                        }
+
+                        return Poll::Pending;
+                    }
                 }
             }
         }
@@ -295,9 +383,85 @@ impl Connection {
         }
 
         self.inner.activity_event.notify(usize::MAX);
-        let mut write = self.inner.socket_write.lock().await;
+        {
+            let mut write = self.inner.socket_write.lock().await;
+
+            write.send_message(msg).await?;
+        }
+
+        if matches!(msg.message_type(), Type::MethodReturn | Type::Error)
+            && self.inner.watching_replies.load(Ordering::Acquire)
+        {
+            let mut watches = self.inner.reply_watches.lock().expect("lock poisoned");
+            if !watches.is_empty() {
+                let hdr = msg.header();
+                let caller = match hdr.destination() {
+                    Some(BusName::Unique(name)) => Some(name.to_owned().into()),
+                    _ => None,
+                };
+                if let Some(watch) = hdr
+                    .reply_serial()
+                    .and_then(|serial| watches.get_mut(&(caller, serial)))
+                {
+                    watch.reply = Some(msg.clone());
+                    if let Some(sent) = &watch.deferred {
+                        sent.notify(usize::MAX);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 
-        write.send_message(msg).await
+    /// Start keeping the reply to the method call of the given header, once it's sent.
+    pub(crate) fn watch_reply(&self, call: &crate::message::Header<'_>) {
+        self.inner.watching_replies.store(true, Ordering::Release);
+        self.inner
+            .reply_watches
+            .lock()
+            .expect("lock poisoned")
+            .insert(reply_key(call), ReplyWatch::default());
+    }
+
+    /// Mark the reply to the method call of the given header as deferred, if it's watched.
+    ///
+    /// Called by [`ReplyHandle`](crate::object_server::ReplyHandle), so the reply keeps being
+    /// watched after the method returned.
+    pub(crate) fn defer_reply(&self, call: &crate::message::Header<'_>) {
+        if !self.inner.watching_replies.load(Ordering::Acquire) {
+            return;
+        }
+
+        if let Some(watch) = self
+            .inner
+            .reply_watches
+            .lock()
+            .expect("lock poisoned")
+            .get_mut(&reply_key(call))
+        {
+            watch.deferred.get_or_insert_with(Event::new);
+        }
+    }
+
+    /// Stop watching the reply to the method call of the given header, returning it if it was
+    /// sent.
+    ///
+    /// If the reply is deferred and wasn't sent yet, the reply is still watched and a listener
+    /// notified once it's sent is returned instead.
+    pub(crate) fn take_reply(&self, call: &crate::message::Header<'_>) -> WatchedReply {
+        let mut watches = self.inner.reply_watches.lock().expect("lock poisoned");
+        let key = reply_key(call);
+        match watches.get(&key) {
+            Some(ReplyWatch {
+                reply: None,
+                deferred: Some(sent),
+            }) => WatchedReply::Deferred(sent.listen()),
+            _ => match watches.remove(&key).and_then(|watch| watch.reply) {
+                Some(reply) => WatchedReply::Sent(reply),
+                None => WatchedReply::None,
+            },
+        }
     }
 
     /// Send a method call.
@@ -305,7 +469,10 @@ impl Connection {
     /// Create a method-call message, send it over the connection, then wait for the reply.
     ///
     /// On successful reply, an `Ok(Message)` is returned. On error, an `Err` is returned. D-Bus
-    /// error replies are returned as [`Error::MethodError`].
+    /// error replies are returned as [`Error::MethodError`]. If a [method timeout] is set on the
+    /// connection and no reply is received in time, [`Error::Timeout`] is returned.
+    ///
+    /// [method timeout]: Builder::method_timeout
     pub async fn call_method<'d, 'p, 'i, 'm, D, P, I, M, B>(
         &self,
         destination: Option<D>,
@@ -331,6 +498,46 @@ impl Connection {
             interface,
             method_name,
             BitFlags::empty(),
+            self.method_timeout(),
+            body,
+        )
+        .await?
+        .expect("no reply")
+        .await
+    }
+
+    /// Send a method call, with a specific reply timeout.
+    ///
+    /// Same as [`Connection::call_method`], except that the given `timeout` is used instead of the
+    /// default one set on the connection (if any). If no reply is received within `timeout`,
+    /// [`Error::Timeout`] is returned.
+    pub async fn call_method_with_timeout<'d, 'p, 'i, 'm, D, P, I, M, B>(
+        &self,
+        destination: Option<D>,
+        path: P,
+        interface: Option<I>,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Message>
+    where
+        D: TryInto<BusName<'d>>,
+        P: TryInto<ObjectPath<'p>>,
+        I: TryInto<InterfaceName<'i>>,
+        M: TryInto<MemberName<'m>>,
+        D::Error: Into<Error>,
+        P::Error: Into<Error>,
+        I::Error: Into<Error>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        self.call_method_raw(
+            destination,
+            path,
+            interface,
+            method_name,
+            BitFlags::empty(),
+            Some(timeout),
             body,
         )
         .await?
@@ -345,9 +552,11 @@ impl Connection {
     /// [`Connection::call_method`] instead.
     ///
     /// If the `flags` do not contain `MethodFlags::NoReplyExpected`, the return value is
-    /// guaranteed to be `Ok(Some(_))`, if there was no error encountered.
+    /// guaranteed to be `Ok(Some(_))`, if there was no error encountered. If `timeout` is given,
+    /// the returned object resolves to [`Error::Timeout`] if no reply arrives in time.
     ///
     /// INTERNAL NOTE: If this method is ever made pub, flags should become `BitFlags<MethodFlags>`.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn call_method_raw<'d, 'p, 'i, 'm, D, P, I, M, B>(
         &self,
         destination: Option<D>,
@@ -355,6 +564,7 @@ impl Connection {
         interface: Option<I>,
         method_name: M,
         flags: BitFlags<Flags>,
+        timeout: Option<Duration>,
         body: &B,
     ) -> Result<Option<PendingMethodCall>>
     where
@@ -397,7 +607,11 @@ impl Connection {
         if flags.contains(Flags::NoReplyExpected) {
             Ok(None)
         } else {
-            Ok(Some(PendingMethodCall { stream, serial }))
+            Ok(Some(PendingMethodCall {
+                stream,
+                serial,
+                timeout: timeout.map(Timer::after),
+            }))
         }
     }
 
@@ -441,7 +655,7 @@ impl Connection {
     ///
     /// Given an existing message (likely a method call), send a reply back to the caller with the
     /// given `body`.
-    pub async fn reply<B>(&self, call: &zbus::message::Header<'_>, body: &B) -> Result<()>
+    pub async fn reply<B>(&self, call: &crate::message::Header<'_>, body: &B) -> Result<()>
     where
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
@@ -461,7 +675,7 @@ impl Connection {
     /// with the given `error_name` and `body`.
     pub async fn reply_error<'e, E, B>(
         &self,
-        call: &zbus::message::Header<'_>,
+        call: &crate::message::Header<'_>,
         error_name: E,
         body: &B,
     ) -> Result<()>
@@ -486,7 +700,7 @@ impl Connection {
     /// using one of the standard interface reply types.
     pub async fn reply_dbus_error(
         &self,
-        call: &zbus::message::Header<'_>,
+        call: &crate::message::Header<'_>,
         err: impl DBusError,
     ) -> Result<()> {
         let _permit = acquire_serial_num_semaphore().await;
@@ -625,13 +839,13 @@ impl Connection {
         let mut names = self.inner.registered_names.lock().await;
 
         match names.get(&well_known_name) {
-            Some(NameStatus::Owner(_)) => return Ok(RequestNameReply::AlreadyOwner),
-            Some(NameStatus::Queued(_)) => return Ok(RequestNameReply::InQueue),
+            Some((NameStatus::Owner(_), _)) => return Ok(RequestNameReply::AlreadyOwner),
+            Some((NameStatus::Queued(_), _)) => return Ok(RequestNameReply::InQueue),
             None => (),
         }
 
         if !self.is_bus() {
-            names.insert(well_known_name.to_owned(), NameStatus::Owner(None));
+            names.insert(well_known_name.to_owned(), (NameStatus::Owner(None), flags));
 
             return Ok(RequestNameReply::PrimaryOwner);
         }
@@ -724,7 +938,7 @@ impl Connection {
                                 Some(signal) => match signal {
                                     Ok(_) => {
                                         let mut names = inner.registered_names.lock().await;
-                                        if let Some(status) = names.get_mut(&well_known_name) {
+                                        if let Some((status, _)) = names.get_mut(&well_known_name) {
                                             let task = name_lost_fut.map(|fut| {
                                                 inner.executor.spawn(fut, &lost_task_name)
                                             });
@@ -759,7 +973,7 @@ impl Connection {
             RequestNameReply::Exists => return Err(Error::NameTaken),
         };
 
-        names.insert(well_known_name.to_owned(), status);
+        names.insert(well_known_name.to_owned(), (status, flags));
 
         Ok(reply)
     }
@@ -819,7 +1033,8 @@ impl Connection {
     /// The unique name of the connection, if set/applicable.
     ///
     /// The unique name is assigned by the message bus or set manually using
-    /// [`Connection::set_unique_name`].
+    /// [`Connection::set_unique_name`]. Reconnecting connections get a new one each time they
+    /// reconnect to the bus.
     pub fn unique_name(&self) -> Option<&OwnedUniqueName> {
         self.inner.unique_name.get()
     }
@@ -855,6 +1070,13 @@ impl Connection {
         self.inner.msg_receiver.clone().set_capacity(max);
     }
 
+    /// The default timeout for method call replies, if any.
+    ///
+    /// See [`Builder::method_timeout`] for details.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner.method_timeout
+    }
+
     /// The server's GUID.
     pub fn server_guid(&self) -> &OwnedGuid {
         &self.inner.server_guid
@@ -952,7 +1174,11 @@ impl Connection {
                     let mut stream = match weak_conn.upgrade() {
                         Some(conn) => {
                             let mut builder = MatchRule::builder().msg_type(Type::MethodCall);
-                            if let Some(unique_name) = conn.unique_name() {
+                            // The unique name changes on reconnection, so it's checked for each
+                            // call instead.
+                            if let (Some(unique_name), None) =
+                                (conn.unique_name(), &conn.inner.reconnect)
+                            {
                                 builder = builder.destination(&**unique_name).expect("unique name");
                             }
                             let rule = builder.build();
@@ -985,6 +1211,19 @@ impl Connection {
                     }) {
                         if let Some(conn) = weak_conn.upgrade() {
                             let hdr = msg.header();
+                            if let (Some(BusName::Unique(dest)), Some(unique_name), Some(_)) =
+                                (hdr.destination(), conn.unique_name(), &conn.inner.reconnect)
+                            {
+                                // Left over from before we reconnected.
+                                if dest.as_str() != unique_name.as_str() {
+                                    trace!(
+                                        "Got a method call for a previous unique name: {}",
+                                        dest
+                                    );
+
+                                    continue;
+                                }
+                            }
                             // If we're connected to a bus, skip the destination check as the
                             // server will only send us method calls destined to us.
                             if !conn.is_bus() {
@@ -1129,6 +1368,8 @@ impl Connection {
         auth: Authenticated,
         #[allow(unused)] bus_connection: bool,
         executor: Executor<'static>,
+        method_timeout: Option<Duration>,
+        reconnect: Option<Reconnect>,
     ) -> Result<Self> {
         #[cfg(unix)]
         let cap_unix_fd = auth.cap_unix_fd;
@@ -1160,6 +1401,10 @@ impl Connection {
         let msg_senders = Arc::new(Mutex::new(msg_senders));
         let subscriptions = Mutex::new(HashMap::new());
 
+        let (mut events, events_receiver) = broadcast(DEFAULT_MAX_QUEUED);
+        events.set_overflow(true);
+        events.set_await_active(false);
+
         let connection = Self {
             inner: Arc::new(ConnectionInner {
                 activity_event: Arc::new(Event::new()),
@@ -1169,16 +1414,23 @@ impl Connection {
                 cap_unix_fd,
                 #[cfg(feature = "p2p")]
                 bus_conn: bus_connection,
-                unique_name: OnceLock::new(),
+                unique_name: UniqueNames::default(),
                 subscriptions,
                 object_server: OnceLock::new(),
                 object_server_dispatch_task: OnceLock::new(),
+                reply_watches: std::sync::Mutex::new(HashMap::new()),
+                watching_replies: AtomicBool::new(false),
                 executor,
                 socket_reader_task: OnceLock::new(),
                 msg_senders,
                 msg_receiver,
                 method_return_receiver,
                 registered_names: Mutex::new(HashMap::new()),
+                method_timeout,
+                reconnect,
+                closed: AtomicBool::new(false),
+                events,
+                events_receiver: events_receiver.deactivate(),
                 drop_event: Event::new(),
             }),
         };
@@ -1214,7 +1466,8 @@ impl Connection {
     ///
     /// # Caveats
     ///
-    /// Currently `unix_group_ids` and `linux_security_label` fields are not populated.
+    /// Currently the `unix_group_ids` field is not populated, and `linux_security_label` is only
+    /// populated on Linux.
     pub async fn peer_credentials(&self) -> io::Result<ConnectionCredentials> {
         self.inner
             .socket_write
@@ -1224,10 +1477,23 @@ impl Connection {
             .await
     }
 
+    /// Receive the [`ConnectionEvent`]s of this connection.
+    ///
+    /// This is mostly useful for [reconnecting] connections, to know when the connection to the
+    /// bus is lost and re-established. Only events that happen after this call are received.
+    ///
+    /// [reconnecting]: Builder::reconnect
+    pub fn receive_events(&self) -> ConnectionEventStream {
+        ConnectionEventStream {
+            receiver: self.inner.events_receiver.activate_cloned(),
+        }
+    }
+
     /// Close the connection.
     ///
     /// After this call, all reading and writing operations will fail.
     pub async fn close(self) -> Result<()> {
+        self.inner.closed.store(true, Ordering::SeqCst);
         self.inner.activity_event.notify(usize::MAX);
         self.inner
             .socket_write
@@ -1304,6 +1570,7 @@ impl Connection {
                     #[cfg(unix)]
                     already_received_fds,
                     inner.activity_event.clone(),
+                    self.into(),
                 )
                 .spawn(&inner.executor),
             )
@@ -1313,6 +1580,7 @@ impl Connection {
     fn set_unique_name_(&self, name: OwnedUniqueName) {
         self.inner
             .unique_name
+            .name
             .set(name)
             // programmer (probably our) error if this fails.
             .expect("unique name already set");
@@ -1369,6 +1637,35 @@ async fn acquire_serial_num_semaphore() -> Option<SemaphorePermit<'static>> {
     }
 }
 
+/// The caller and serial number of a method call.
+type ReplyKey = (Option<OwnedUniqueName>, NonZeroU32);
+
+/// A reply watched by the object server.
+#[derive(Debug, Default)]
+struct ReplyWatch {
+    reply: Option<Message>,
+    // Notified once the reply is sent, if it's deferred to a `ReplyHandle`.
+    deferred: Option<Event>,
+}
+
+/// The state of a reply watched by the object server, once the method call returned.
+#[derive(Debug)]
+pub(crate) enum WatchedReply {
+    /// The reply was sent.
+    Sent(Message),
+    /// The reply is deferred to a `ReplyHandle` and wasn't sent yet.
+    Deferred(EventListener),
+    /// No reply was sent.
+    None,
+}
+
+fn reply_key(call: &crate::message::Header<'_>) -> ReplyKey {
+    (
+        call.sender().map(|s| s.to_owned().into()),
+        call.primary().serial_num(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1443,6 +1740,118 @@ mod tests {
         assert!(!name_has_owner);
     }
 
+    #[test]
+    #[timeout(15000)]
+    fn method_timeout() {
+        crate::utils::block_on(test_method_timeout());
+    }
+
+    async fn test_method_timeout() {
+        struct Sluggish;
+
+        #[crate::interface(name = "org.zbus.TestMethodTimeout")]
+        impl Sluggish {
+            async fn never_return(&self) {
+                std::future::pending::<()>().await;
+            }
+
+            fn ping(&self) -> u32 {
+                42
+            }
+        }
+
+        let service = Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/TestMethodTimeout", Sluggish)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let service_name = service.unique_name().unwrap().to_owned();
+
+        let client = Builder::session()
+            .unwrap()
+            .method_timeout(Duration::from_millis(100))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(client.method_timeout(), Some(Duration::from_millis(100)));
+
+        let err = client
+            .call_method(
+                Some(&service_name),
+                "/org/zbus/TestMethodTimeout",
+                Some("org.zbus.TestMethodTimeout"),
+                "NeverReturn",
+                &(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::Timeout);
+        assert!(matches!(
+            crate::fdo::Error::from(err),
+            crate::fdo::Error::NoReply(_)
+        ));
+
+        // The connection is still usable after a timeout, and the per-call timeout overrides the
+        // default one.
+        let reply = client
+            .call_method_with_timeout(
+                Some(&service_name),
+                "/org/zbus/TestMethodTimeout",
+                Some("org.zbus.TestMethodTimeout"),
+                "Ping",
+                Duration::from_secs(10),
+                &(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply.body().deserialize::<u32>().unwrap(), 42);
+
+        let proxy = crate::Proxy::new(
+            &client,
+            &service_name,
+            "/org/zbus/TestMethodTimeout",
+            "org.zbus.TestMethodTimeout",
+        )
+        .await
+        .unwrap();
+        assert_eq!(proxy.method_timeout(), Some(Duration::from_millis(100)));
+        let err = proxy
+            .call_with_timeout::<_, _, ()>("NeverReturn", Duration::from_millis(50), &())
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::Timeout);
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn address_list_failover() {
+        crate::utils::block_on(async {
+            let session = crate::address::AddressList::session().unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            let bogus = format!("unix:path={}", dir.path().join("bogus").display());
+
+            let conn = Builder::address(&*format!("{bogus};{session}"))
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            assert!(conn.unique_name().is_some());
+
+            let bogus2 = format!("unix:path={}", dir.path().join("bogus2").display());
+            let err = Builder::address(&*format!("{bogus};{bogus2}"))
+                .unwrap()
+                .build()
+                .await
+                .unwrap_err();
+            let Error::Address(msg) = err else {
+                panic!("unexpected error: {err:?}");
+            };
+            assert!(msg.contains(&bogus) && msg.contains(&bogus2), "{msg}");
+        });
+    }
+
     #[tokio::test(start_paused = true)]
     #[timeout(15000)]
     async fn test_graceful_shutdown() {
@@ -1725,6 +2134,55 @@ mod p2p_tests {
         )
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[timeout(15000)]
+    fn unix_peer_credentials() {
+        crate::utils::block_on(async {
+            let (client, server) = unix_p2p_pipe().await.unwrap();
+            let creds = server.peer_credentials().await.unwrap();
+            assert_eq!(
+                creds.unix_user_id(),
+                Some(nix::unistd::Uid::effective().as_raw())
+            );
+            assert_eq!(creds.process_id(), Some(std::process::id()));
+            // The groups are only looked up for authorization policies.
+            assert!(creds.unix_group_ids().is_none());
+            // Only available if a security module is active.
+            if let Some(label) = creds.linux_security_label() {
+                assert_eq!(label.iter().position(|b| *b == 0), Some(label.len() - 1));
+            }
+            drop(client);
+        });
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[timeout(15000)]
+    fn unix_auth_policy_groups() {
+        #[cfg(not(feature = "tokio"))]
+        use std::os::unix::net::UnixStream;
+        #[cfg(feature = "tokio")]
+        use tokio::net::UnixStream;
+
+        crate::utils::block_on(async {
+            let gid = nix::unistd::Gid::effective().as_raw();
+            let (p0, p1) = UnixStream::pair().unwrap();
+            let server = Builder::unix_stream(p0)
+                .server(Guid::generate())
+                .unwrap()
+                .p2p()
+                .auth_policy(move |creds| {
+                    creds
+                        .unix_group_ids()
+                        .is_some_and(|gids| gids.contains(&gid))
+                })
+                .build();
+            let client = Builder::unix_stream(p1).p2p().build();
+            let (_server, _client) = futures_util::try_join!(server, client).unwrap();
+        });
+    }
+
     #[cfg(any(
         all(feature = "vsock", not(feature = "tokio")),
         feature = "tokio-vsock"
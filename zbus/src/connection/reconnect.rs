@@ -0,0 +1,133 @@
+use async_broadcast::Receiver;
+use futures_core::Stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tracing::{debug, trace, warn};
+use zbus_names::OwnedUniqueName;
+
+use crate::{address::AddressList, message::Type, timer::Timer, AuthMechanism, Connection, Error};
+
+use super::{handshake::Authenticated, Builder, WeakConnection};
+
+/// The settings of a reconnecting connection.
+#[derive(Debug, Clone)]
+pub(crate) struct Reconnect {
+    pub addresses: AddressList,
+    pub auth_mechanism: Option<AuthMechanism>,
+    pub min_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Reconnect {
+    /// Connect to the bus again, retrying with an exponential backoff.
+    ///
+    /// Returns `None` if the connection went away in the meantime.
+    pub async fn redial(&self, conn: &WeakConnection) -> Option<Authenticated> {
+        let mut delay = self.min_delay;
+        loop {
+            Timer::after(delay).await;
+            {
+                let conn = conn.upgrade()?;
+                if conn.inner.closed() {
+                    return None;
+                }
+            }
+
+            trace!("Reconnecting to `{}`", self.addresses);
+            match Builder::redial(self.addresses.clone(), self.auth_mechanism).await {
+                Ok(auth) => return Some(auth),
+                Err(e) => {
+                    debug!("Failed to reconnect, retrying in {delay:?}: {e}");
+                    delay = (delay * 2).min(self.max_delay);
+                }
+            }
+        }
+    }
+}
+
+/// Restore the state of a connection on the bus, after it reconnected.
+///
+/// This adds back the match rules and requests the well-known names again.
+pub(crate) async fn restore(conn: Connection) {
+    let rules: Vec<_> = conn
+        .inner
+        .subscriptions
+        .lock()
+        .await
+        .keys()
+        .filter(|rule| rule.msg_type().unwrap_or(Type::Signal) == Type::Signal)
+        .cloned()
+        .collect();
+    for rule in rules {
+        let res = conn
+            .call_method(
+                Some("org.freedesktop.DBus"),
+                "/org/freedesktop/DBus",
+                Some("org.freedesktop.DBus"),
+                "AddMatch",
+                &rule,
+            )
+            .await;
+        if let Err(e) = res {
+            warn!("Failed to add back match rule `{}`: {e}", *rule);
+        }
+    }
+
+    let names: Vec<_> = conn
+        .inner
+        .registered_names
+        .lock()
+        .await
+        .drain()
+        .map(|(name, (_, flags))| (name, flags))
+        .collect();
+    for (name, flags) in names {
+        if let Err(e) = conn.request_name_with_flags(name.clone(), flags).await {
+            warn!("Failed to request back name `{name}`: {e}");
+        }
+    }
+
+    if let Some(unique_name) = conn.unique_name() {
+        debug!("Reconnected as `{unique_name}`");
+        conn.inner
+            .emit_event(ConnectionEvent::Reconnected(unique_name.clone()));
+    }
+}
+
+/// A change in the state of a [`Connection`].
+///
+/// Use [`Connection::receive_events`] to get notified of these.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ConnectionEvent {
+    /// The connection to the peer was lost, because of the given error.
+    ///
+    /// Unless the connection is [reconnecting], it's unusable from then on.
+    ///
+    /// [reconnecting]: Builder::reconnect
+    Disconnected(Error),
+    /// The connection to the bus was established again, under the given unique name.
+    ///
+    /// By this point, the well-known names have been requested again and the match rules added
+    /// back.
+    Reconnected(OwnedUniqueName),
+}
+
+/// A [`Stream`] of [`ConnectionEvent`]s.
+///
+/// Use [`Connection::receive_events`] to create an instance of this type.
+#[derive(Debug)]
+pub struct ConnectionEventStream {
+    pub(super) receiver: Receiver<ConnectionEvent>,
+}
+
+impl Stream for ConnectionEventStream {
+    type Item = ConnectionEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
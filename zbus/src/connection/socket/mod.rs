@@ -12,6 +12,8 @@ pub(crate) mod command;
 pub(crate) use command::Command;
 mod tcp;
 mod unix;
+#[cfg(all(target_os = "linux", feature = "p2p"))]
+pub(crate) use unix::unix_group_ids;
 mod vsock;
 
 #[cfg(not(feature = "tokio"))]
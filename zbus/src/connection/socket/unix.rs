@@ -335,13 +335,15 @@ fn get_unix_peer_creds_blocking(fd: RawFd) -> io::Result<crate::fdo::ConnectionC
     {
         use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
 
-        getsockopt(&fd, PeerCredentials)
-            .map(|creds| {
-                crate::fdo::ConnectionCredentials::default()
-                    .set_process_id(creds.pid() as _)
-                    .set_unix_user_id(creds.uid())
-            })
-            .map_err(|e| e.into())
+        let creds = getsockopt(&fd, PeerCredentials)?;
+        let mut conn_creds = crate::fdo::ConnectionCredentials::default()
+            .set_process_id(creds.pid() as _)
+            .set_unix_user_id(creds.uid());
+        if let Some(label) = peer_security_label(fd) {
+            conn_creds = conn_creds.set_linux_security_label(label);
+        }
+
+        Ok(conn_creds)
     }
 
     #[cfg(any(
@@ -359,6 +361,76 @@ fn get_unix_peer_creds_blocking(fd: RawFd) -> io::Result<crate::fdo::ConnectionC
     }
 }
 
+// The security label of the peer (e.g its SELinux context or AppArmor profile), as returned by
+// `SO_PEERSEC`, followed by a single nul byte as per the D-Bus specification.
+//
+// `None` if no security module labels the socket.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn peer_security_label(fd: BorrowedFd<'_>) -> Option<Vec<u8>> {
+    use nix::{errno::Errno, libc};
+
+    let mut label = vec![0u8; 256];
+    loop {
+        let mut len = label.len() as libc::socklen_t;
+        // SAFETY: `label` is valid for writes of `len` bytes.
+        let ret = unsafe {
+            libc::getsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERSEC,
+                label.as_mut_ptr().cast(),
+                &mut len,
+            )
+        };
+        if ret == 0 {
+            label.truncate(len as usize);
+            break;
+        }
+        match Errno::last() {
+            // The label is bigger than the buffer, and `len` is now its actual size.
+            Errno::ERANGE if len as usize > label.len() => label.resize(len as usize, 0),
+            _ => return None,
+        }
+    }
+
+    // Some modules include the trailing nul byte, some don't.
+    while label.last() == Some(&0) {
+        label.pop();
+    }
+    if label.is_empty() {
+        return None;
+    }
+    label.push(0);
+
+    Some(label)
+}
+
+// The group IDs of the user, in numerically sorted order.
+//
+// Like `dbus-daemon` does when `SO_PEERGROUPS` isn't available, we look up the groups of the user
+// rather than those of the peer process. As that involves NSS lookups, it's only done on demand.
+#[cfg(all(target_os = "linux", feature = "p2p"))]
+pub(crate) async fn unix_group_ids(uid: u32) -> Option<Vec<u32>> {
+    crate::Task::spawn_blocking(move || unix_group_ids_blocking(uid), "unix group ids").await
+}
+
+#[cfg(all(target_os = "linux", feature = "p2p"))]
+fn unix_group_ids_blocking(uid: u32) -> Option<Vec<u32>> {
+    use nix::unistd::{getgrouplist, Uid, User};
+
+    let user = User::from_uid(Uid::from_raw(uid)).ok()??;
+    let name = std::ffi::CString::new(user.name).ok()?;
+    let mut gids: Vec<_> = getgrouplist(&name, user.gid)
+        .ok()?
+        .into_iter()
+        .map(|gid| gid.as_raw())
+        .collect();
+    gids.sort_unstable();
+    gids.dedup();
+
+    Some(gids)
+}
+
 // Send 0 byte as a separate SCM_CREDS message.
 #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
 async fn send_zero_byte(fd: &impl AsRawFd) -> io::Result<usize> {
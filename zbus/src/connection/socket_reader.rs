@@ -4,10 +4,11 @@ use event_listener::Event;
 use tracing::{debug, instrument, trace};
 
 use crate::{
-    async_lock::Mutex, connection::MsgBroadcaster, Executor, Message, OwnedMatchRule, Task,
+    async_lock::Mutex, connection::MsgBroadcaster, message::Type, Executor, Message,
+    OwnedMatchRule, Task,
 };
 
-use super::socket::ReadHalf;
+use super::{reconnect, socket::ReadHalf, ConnectionEvent, WeakConnection};
 
 #[derive(Debug)]
 pub(crate) struct SocketReader {
@@ -18,6 +19,7 @@ pub(crate) struct SocketReader {
     already_received_fds: Vec<std::os::fd::OwnedFd>,
     prev_seq: u64,
     activity_event: Arc<Event>,
+    connection: WeakConnection,
 }
 
 impl SocketReader {
@@ -27,6 +29,7 @@ impl SocketReader {
         already_received_bytes: Vec<u8>,
         #[cfg(unix)] already_received_fds: Vec<std::os::fd::OwnedFd>,
         activity_event: Arc<Event>,
+        connection: WeakConnection,
     ) -> Self {
         Self {
             socket,
@@ -36,6 +39,7 @@ impl SocketReader {
             already_received_fds,
             prev_seq: 0,
             activity_event,
+            connection,
         }
     }
 
@@ -54,6 +58,13 @@ impl SocketReader {
                 Err(e) => trace!("Error reading from the socket: {:?}", e),
             };
 
+            if let Err(e) = &msg {
+                self.emit_event(ConnectionEvent::Disconnected(e.clone()));
+                if self.reconnect(&msg).await {
+                    continue;
+                }
+            }
+
             let mut senders = self.senders.lock().await;
             for (rule, sender) in &*senders {
                 if let Ok(msg) = &msg {
@@ -98,6 +109,66 @@ impl SocketReader {
         }
     }
 
+    fn emit_event(&self, event: ConnectionEvent) {
+        if let Some(conn) = self.connection.upgrade() {
+            conn.inner.emit_event(event);
+        }
+    }
+
+    /// Reconnect to the bus if the connection is a reconnecting one.
+    ///
+    /// Returns `false` if the connection isn't, or if it went away before we could reconnect.
+    async fn reconnect(&mut self, err: &crate::Result<Message>) -> bool {
+        let conn = match self.connection.upgrade() {
+            Some(conn) if conn.inner.reconnect.is_some() && !conn.inner.closed() => conn,
+            _ => return false,
+        };
+
+        // Fail the pending method calls, as their replies are lost with the connection. All other
+        // streams stay open for the messages we'll receive once reconnected.
+        for (rule, sender) in &*self.senders.lock().await {
+            if let Some(Type::MethodReturn | Type::Error) = rule.as_ref().and_then(|r| r.msg_type())
+            {
+                let _ = sender.broadcast_direct(err.clone()).await;
+            }
+        }
+
+        // Don't keep the connection alive while we wait for the bus to come back.
+        let reconnect = conn.inner.reconnect.clone().unwrap();
+        drop(conn);
+        let mut auth = match reconnect.redial(&self.connection).await {
+            Some(auth) => auth,
+            None => return false,
+        };
+        let conn = match self.connection.upgrade() {
+            Some(conn) => conn,
+            None => return false,
+        };
+
+        // SAFETY: `Authenticated` is always built with these fields set to `Some`.
+        self.socket = auth.socket_read.take().unwrap();
+        self.already_received_bytes = auth.already_received_bytes.drain(..).collect();
+        #[cfg(unix)]
+        {
+            self.already_received_fds = auth.already_received_fds.drain(..).collect();
+        }
+        *conn.inner.socket_write.lock().await = auth.socket_write;
+        if let Some(unique_name) = auth.unique_name.take() {
+            conn.inner.unique_name.set(unique_name);
+        }
+        if let Some(object_server) = conn.inner.object_server.get() {
+            object_server.forget_callers().await;
+        }
+
+        // This needs us to receive the replies, so it has to run in its own task.
+        let executor = conn.inner.executor.clone();
+        executor
+            .spawn(reconnect::restore(conn), "restore connection state")
+            .detach();
+
+        true
+    }
+
     #[instrument(skip(self))]
     async fn read_socket(&mut self) -> crate::Result<Message> {
         self.activity_event.notify(usize::MAX);
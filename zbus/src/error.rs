@@ -1,7 +1,7 @@
 use static_assertions::assert_impl_all;
 use std::{convert::Infallible, error, fmt, io, sync::Arc};
 use zbus_names::{Error as NamesError, InterfaceName, OwnedErrorName};
-use zvariant::{Error as VariantError, ObjectPath};
+use zvariant::{Error as VariantError, ObjectPath, Signature};
 
 use crate::{
     fdo,
@@ -61,6 +61,18 @@ pub enum Error {
     InvalidSerial,
     /// The given interface already exists at the given path.
     InterfaceExists(InterfaceName<'static>, ObjectPath<'static>),
+    /// No reply was received for a method call within the timeout.
+    ///
+    /// When converted to a [`fdo::Error`], this becomes [`fdo::Error::NoReply`].
+    Timeout,
+    /// The named member (method, property or signal) isn't declared by the interface.
+    MemberNotFound(String),
+    /// The arguments or value of the named member don't match its declared signature.
+    ///
+    /// Contains the name of the member, the index of the first offending argument (if the
+    /// arguments only differ in type), the expected signature and the actual one. The signatures
+    /// are the ones of the offending argument, if any, or of all the arguments otherwise.
+    SignatureMismatch(String, Option<usize>, Signature, Signature),
 }
 
 assert_impl_all!(Error: Send, Sync, Unpin);
@@ -88,6 +100,11 @@ impl PartialEq for Error {
             (Error::InputOutput(_), Self::InputOutput(_)) => false,
             (Self::Failure(s1), Self::Failure(s2)) => s1 == s2,
             (Self::InterfaceExists(s1, s2), Self::InterfaceExists(o1, o2)) => s1 == o1 && s2 == o2,
+            (Self::Timeout, Self::Timeout) => true,
+            (Self::MemberNotFound(s), Self::MemberNotFound(o)) => s == o,
+            (Self::SignatureMismatch(s1, s2, s3, s4), Self::SignatureMismatch(o1, o2, o3, o4)) => {
+                s1 == o1 && s2 == o2 && s3 == o3 && s4 == o4
+            }
             (_, _) => false,
         }
     }
@@ -117,6 +134,9 @@ impl error::Error for Error {
             Error::MissingParameter(_) => None,
             Error::InvalidSerial => None,
             Error::InterfaceExists(_, _) => None,
+            Error::Timeout => None,
+            Error::MemberNotFound(_) => None,
+            Error::SignatureMismatch(_, _, _, _) => None,
         }
     }
 }
@@ -152,6 +172,20 @@ impl fmt::Display for Error {
             }
             Error::InvalidSerial => write!(f, "Serial number in the message header is 0"),
             Error::InterfaceExists(i, p) => write!(f, "Interface `{i}` already exists at `{p}`"),
+            Error::Timeout => write!(f, "Timed out waiting for a method reply"),
+            Error::MemberNotFound(m) => write!(f, "Member `{m}` not found"),
+            Error::SignatureMismatch(m, arg, expected, actual) => {
+                write!(f, "Signature mismatch for `{m}`")?;
+                if let Some(arg) = arg {
+                    write!(f, " argument {arg}")?;
+                }
+                write!(
+                    f,
+                    ": expected `{}`, got `{}`",
+                    expected.to_string_no_parens(),
+                    actual.to_string_no_parens(),
+                )
+            }
         }
     }
 }
@@ -182,6 +216,11 @@ impl Clone for Error {
             Error::MissingParameter(p) => Error::MissingParameter(p),
             Error::InvalidSerial => Error::InvalidSerial,
             Error::InterfaceExists(i, p) => Error::InterfaceExists(i.clone(), p.clone()),
+            Error::Timeout => Error::Timeout,
+            Error::MemberNotFound(m) => Error::MemberNotFound(m.clone()),
+            Error::SignatureMismatch(m, arg, expected, actual) => {
+                Error::SignatureMismatch(m.clone(), *arg, expected.clone(), actual.clone())
+            }
         }
     }
 }
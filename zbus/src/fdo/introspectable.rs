@@ -5,7 +5,7 @@
 
 use static_assertions::assert_impl_all;
 
-use super::{Error, Result};
+use super::Result;
 use crate::{interface, message::Header, ObjectServer};
 
 /// Service-side implementation for the `org.freedesktop.DBus.Introspectable` interface.
@@ -27,12 +27,8 @@ impl Introspectable {
         #[zbus(header)] header: Header<'_>,
     ) -> Result<String> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
-        let root = server.root().read().await;
-        let node = root
-            .get_child(path)
-            .ok_or_else(|| Error::UnknownObject(format!("Unknown object '{path}'")))?;
 
-        Ok(node.introspect().await)
+        server.introspect(path).await
     }
 }
 
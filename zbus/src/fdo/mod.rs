@@ -22,6 +22,13 @@ pub use object_manager::{
     ObjectManagerProxy,
 };
 
+pub(crate) mod object_manager_client;
+pub use object_manager_client::{
+    ObjectManagerClient, ObjectManagerEvent, ObjectManagerEventStream,
+};
+
+pub mod polkit;
+
 pub(crate) mod peer;
 pub(crate) use peer::Peer;
 pub use peer::PeerProxy;
@@ -8,7 +8,7 @@ use std::{borrow::Cow, collections::HashMap};
 use zbus_names::{InterfaceName, OwnedInterfaceName};
 use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
 
-use super::{Error, Result};
+use super::Result;
 use crate::{interface, message::Header, object_server::SignalEmitter, Connection, ObjectServer};
 
 /// The type returned by the [`ObjectManagerProxy::get_managed_objects`] method.
@@ -53,12 +53,8 @@ impl ObjectManager {
         #[zbus(header)] header: Header<'_>,
     ) -> Result<ManagedObjects> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
-        let root = server.root().read().await;
-        let node = root
-            .get_child(path)
-            .ok_or_else(|| Error::UnknownObject(format!("Unknown object '{path}'")))?;
 
-        node.get_managed_objects(server, connection).await
+        server.managed_objects(path, connection).await
     }
 
     /// This signal is emitted when either a new object is added or when an existing object gains
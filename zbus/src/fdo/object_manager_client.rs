@@ -0,0 +1,534 @@
+use async_broadcast::{broadcast, InactiveReceiver, Receiver, Sender};
+use futures_core::Stream;
+use futures_util::{stream, StreamExt};
+use static_assertions::assert_impl_all;
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+use tracing::{debug, trace};
+use zbus_names::{BusName, InterfaceName, OwnedInterfaceName, UniqueName};
+use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
+
+use super::{ManagedObjects, ObjectManagerProxy};
+use crate::{
+    fdo,
+    message::{Sequence, Type},
+    proxy::{self, CacheProperties, Defaults},
+    Connection, Error, MatchRule, Message, MessageStream, Proxy, Result, Task,
+};
+
+/// The maximum number of events queued for each [`ObjectManagerEventStream`].
+const MAX_QUEUED_EVENTS: usize = 64;
+
+/// Client-side mirror of the objects exposed by a service through an [`ObjectManager`].
+///
+/// On creation, this fetches all the objects managed by the object manager of the service,
+/// along with their interfaces and properties, and then keeps this local tree up to date in the
+/// background by tracking the `InterfacesAdded`, `InterfacesRemoved` and `PropertiesChanged`
+/// signals of the service. If the service goes away, the tree is emptied, and it's fetched again
+/// once a new owner of the destination name shows up.
+///
+/// Changes to the tree can be tracked through [`ObjectManagerClient::receive_events`], and
+/// [`ObjectManagerClient::proxy`] gives you proxies for the objects, with their properties cache
+/// populated from the tree.
+///
+/// # Example
+///
+/// ```no_run
+/// # use zbus::{fdo::ObjectManagerClient, names::InterfaceName, proxy, Connection};
+/// #
+/// #[proxy(interface = "org.bluez.Device1", default_service = "org.bluez")]
+/// trait Device1 {
+///     #[zbus(property)]
+///     fn name(&self) -> zbus::Result<String>;
+/// }
+///
+/// # zbus::block_on(async {
+/// let conn = Connection::system().await?;
+/// let client = ObjectManagerClient::new(&conn, "org.bluez", "/").await?;
+/// let device1 = InterfaceName::from_static_str_unchecked("org.bluez.Device1");
+/// for path in client.objects_with_interface(&device1) {
+///     let device: Device1Proxy<'_> = client.proxy(path).await?;
+///     // This doesn't need a round trip to the service.
+///     println!("{}", device.cached_name()?.unwrap_or_default());
+/// }
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`ObjectManager`]: super::ObjectManager
+#[derive(Debug, Clone)]
+pub struct ObjectManagerClient {
+    inner: Arc<ObjectManagerClientInner>,
+}
+
+#[derive(Debug)]
+struct ObjectManagerClientInner {
+    proxy: ObjectManagerProxy<'static>,
+    objects: Arc<RwLock<ManagedObjects>>,
+    events_receiver: InactiveReceiver<ObjectManagerEvent>,
+    #[allow(unused)]
+    task: Task<()>,
+}
+
+assert_impl_all!(ObjectManagerClient: Send, Sync, Unpin);
+
+impl ObjectManagerClient {
+    /// Create a client for the object manager at `path`, of the service at `destination`.
+    ///
+    /// If `destination` is a well-known name that currently has no owner, the client starts with
+    /// an empty tree.
+    pub async fn new<D, P>(conn: &Connection, destination: D, path: P) -> Result<Self>
+    where
+        D: TryInto<BusName<'static>>,
+        D::Error: Into<Error>,
+        P: TryInto<ObjectPath<'static>>,
+        P::Error: Into<Error>,
+    {
+        let proxy = ObjectManagerProxy::builder(conn)
+            .destination(destination)?
+            .path(path)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+
+        // Subscribe to all the signals of the managed objects before fetching them, so we don't
+        // miss any change.
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender(proxy.inner().destination().clone())?
+            .path_namespace(proxy.inner().path().clone())?
+            .build();
+        let signals = MessageStream::for_match_rule(rule, conn, None).await?;
+        let owner_changes = if conn.is_bus() {
+            Some(proxy.inner().receive_owner_changed().await?)
+        } else {
+            None
+        };
+
+        let (objects, since, owner) = match fetch(&proxy).await {
+            Ok((objects, since, owner)) => (objects, Some(since), owner),
+            Err(e) if is_unowned(&proxy, &e) => {
+                debug!("`{}` has no owner yet", proxy.inner().destination());
+
+                (ManagedObjects::new(), None, None)
+            }
+            Err(e) => return Err(e),
+        };
+        let objects = Arc::new(RwLock::new(objects));
+
+        let (mut events, events_receiver) = broadcast(MAX_QUEUED_EVENTS);
+        events.set_overflow(true);
+        events.set_await_active(false);
+
+        let updater = Updater {
+            proxy: proxy.clone(),
+            objects: objects.clone(),
+            events,
+            since,
+            owner,
+        };
+        let updates = stream::select(
+            signals.map(Update::Signal),
+            stream::iter(owner_changes).flatten().map(Update::Owner),
+        );
+        let task = conn
+            .executor()
+            .spawn(updater.run(updates), "object manager client");
+
+        Ok(Self {
+            inner: Arc::new(ObjectManagerClientInner {
+                proxy,
+                objects,
+                events_receiver: events_receiver.deactivate(),
+                task,
+            }),
+        })
+    }
+
+    /// The connection of the client.
+    pub fn connection(&self) -> &Connection {
+        self.inner.proxy.inner().connection()
+    }
+
+    /// The destination of the object manager.
+    pub fn destination(&self) -> &BusName<'static> {
+        self.inner.proxy.inner().destination()
+    }
+
+    /// The path of the object manager.
+    pub fn path(&self) -> &ObjectPath<'static> {
+        self.inner.proxy.inner().path()
+    }
+
+    /// A snapshot of all the managed objects, with their interfaces and properties.
+    pub fn objects(&self) -> ManagedObjects {
+        self.inner.objects.read().expect("lock poisoned").clone()
+    }
+
+    /// The paths of all the managed objects.
+    pub fn paths(&self) -> Vec<OwnedObjectPath> {
+        self.inner
+            .objects
+            .read()
+            .expect("lock poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// The paths of the managed objects implementing `interface`.
+    pub fn objects_with_interface(&self, interface: &InterfaceName<'_>) -> Vec<OwnedObjectPath> {
+        self.inner
+            .objects
+            .read()
+            .expect("lock poisoned")
+            .iter()
+            .filter(|(_, interfaces)| interfaces.keys().any(|i| i.as_ref() == *interface))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// The interfaces of the managed object at `path`.
+    ///
+    /// The list is empty if there is no such object.
+    pub fn interfaces(&self, path: &ObjectPath<'_>) -> Vec<OwnedInterfaceName> {
+        self.inner
+            .objects
+            .read()
+            .expect("lock poisoned")
+            .get(&key(path))
+            .map(|interfaces| interfaces.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The properties of `interface` on the managed object at `path`.
+    ///
+    /// Returns `None` if there is no such object or if it doesn't implement `interface`.
+    pub fn properties(
+        &self,
+        path: &ObjectPath<'_>,
+        interface: &InterfaceName<'_>,
+    ) -> Option<HashMap<String, OwnedValue>> {
+        self.inner
+            .objects
+            .read()
+            .expect("lock poisoned")
+            .get(&key(path))
+            .and_then(|interfaces| interfaces.get(interface.as_str()))
+            .cloned()
+    }
+
+    /// The value of the property `property_name` of `interface` on the managed object at `path`.
+    ///
+    /// Returns `None` if there is no such object or property. Note that properties that are
+    /// invalidated by the service are removed from the tree.
+    pub fn property<T>(
+        &self,
+        path: &ObjectPath<'_>,
+        interface: &InterfaceName<'_>,
+        property_name: &str,
+    ) -> Result<Option<T>>
+    where
+        T: TryFrom<OwnedValue>,
+        T::Error: Into<Error>,
+    {
+        self.inner
+            .objects
+            .read()
+            .expect("lock poisoned")
+            .get(&key(path))
+            .and_then(|interfaces| interfaces.get(interface.as_str()))
+            .and_then(|properties| properties.get(property_name))
+            .map(|value| T::try_from(value.try_clone()?).map_err(Into::into))
+            .transpose()
+    }
+
+    /// Create a proxy of type `T` for the managed object at `path`.
+    ///
+    /// The interface of the proxy is the default one of `T` (typically the one given to the
+    /// [`macro@crate::proxy`] macro). Unless caching is disabled for the proxy, its properties
+    /// cache is populated from the tree, rather than being fetched from the service again.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InterfaceNotFound`] if the object doesn't exist or doesn't implement the interface.
+    pub async fn proxy<'p, T, P>(&self, path: P) -> Result<T>
+    where
+        T: Defaults + From<Proxy<'static>>,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?.into_owned();
+        let interface = T::INTERFACE
+            .clone()
+            .ok_or(Error::MissingParameter("interface"))?;
+        if self.properties(&path, &interface).is_none() {
+            return Err(Error::InterfaceNotFound);
+        }
+        let client = self.clone();
+        let (snapshot_path, snapshot_interface) = (path.clone(), interface.clone());
+
+        proxy::Builder::<T>::new(self.connection())
+            .destination(self.destination().clone())?
+            .path(path)?
+            .interface(interface)?
+            .cached_values(move || {
+                client
+                    .properties(&snapshot_path, &snapshot_interface)
+                    .ok_or(Error::InterfaceNotFound)
+            })
+            .build()
+            .await
+    }
+
+    /// Receive the changes to the tree.
+    ///
+    /// Only changes that happen after this call are received. Use
+    /// [`ObjectManagerClient::objects`] to get the initial state.
+    pub fn receive_events(&self) -> ObjectManagerEventStream {
+        ObjectManagerEventStream {
+            receiver: self.inner.events_receiver.activate_cloned(),
+        }
+    }
+}
+
+/// A change in the tree of an [`ObjectManagerClient`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ObjectManagerEvent {
+    /// An object was added, or an existing object gained interfaces.
+    InterfacesAdded {
+        /// The path of the object.
+        path: OwnedObjectPath,
+        /// The added interfaces and their properties.
+        interfaces: HashMap<OwnedInterfaceName, HashMap<String, OwnedValue>>,
+    },
+    /// An object was removed, or an existing object lost interfaces.
+    ///
+    /// The object is removed from the tree once it has no interfaces left.
+    InterfacesRemoved {
+        /// The path of the object.
+        path: OwnedObjectPath,
+        /// The removed interfaces.
+        interfaces: Vec<OwnedInterfaceName>,
+    },
+    /// Properties of an object changed.
+    PropertiesChanged {
+        /// The path of the object.
+        path: OwnedObjectPath,
+        /// The interface the properties belong to.
+        interface: OwnedInterfaceName,
+        /// The properties that changed, with their new value.
+        changed: HashMap<String, OwnedValue>,
+        /// The properties that were invalidated, without their new value being sent.
+        invalidated: Vec<String>,
+    },
+}
+
+/// A [`Stream`] of [`ObjectManagerEvent`]s.
+///
+/// Use [`ObjectManagerClient::receive_events`] to create an instance of this type.
+#[derive(Debug)]
+pub struct ObjectManagerEventStream {
+    receiver: Receiver<ObjectManagerEvent>,
+}
+
+impl Stream for ObjectManagerEventStream {
+    type Item = ObjectManagerEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+enum Update {
+    Signal(Result<Message>),
+    Owner(Option<UniqueName<'static>>),
+}
+
+/// Keeps the tree up to date, in the background task of the client.
+struct Updater {
+    proxy: ObjectManagerProxy<'static>,
+    objects: Arc<RwLock<ManagedObjects>>,
+    events: Sender<ObjectManagerEvent>,
+    /// The receive position of the last `GetManagedObjects` reply, if any. Signals received
+    /// before it are already reflected in the tree.
+    since: Option<Sequence>,
+    /// The unique name of the current owner of the destination, on bus connections.
+    owner: Option<UniqueName<'static>>,
+}
+
+impl Updater {
+    async fn run<S>(mut self, mut updates: S)
+    where
+        S: Stream<Item = Update> + Unpin,
+    {
+        while let Some(update) = updates.next().await {
+            match update {
+                Update::Signal(Ok(msg)) => {
+                    if self.since.is_some_and(|since| msg.recv_position() > since) {
+                        if let Err(e) = self.handle_signal(&msg) {
+                            debug!("Failed to handle signal: {e}");
+                        }
+                    }
+                }
+                Update::Signal(Err(e)) => debug!("Error receiving signals: {e}"),
+                Update::Owner(owner) => self.handle_owner_change(owner).await,
+            }
+        }
+        trace!("Stopped tracking `{}`", self.proxy.inner().destination());
+    }
+
+    fn handle_signal(&self, msg: &Message) -> Result<()> {
+        let header = msg.header();
+        // The match rule lets the signals of any connection through if the destination is a
+        // well-known name, so only the ones of its current owner are taken into account.
+        if self.proxy.inner().connection().is_bus() && header.sender() != self.owner.as_ref() {
+            return Ok(());
+        }
+        let (Some(path), Some(interface), Some(member)) =
+            (header.path(), header.interface(), header.member())
+        else {
+            return Ok(());
+        };
+
+        let event = match (interface.as_str(), member.as_str()) {
+            ("org.freedesktop.DBus.ObjectManager", "InterfacesAdded")
+                if path == self.proxy.inner().path() =>
+            {
+                let (path, interfaces): (
+                    OwnedObjectPath,
+                    HashMap<OwnedInterfaceName, HashMap<String, OwnedValue>>,
+                ) = msg.body().deserialize()?;
+                let mut objects = self.objects.write().expect("lock poisoned");
+                let object = objects.entry(path.clone()).or_default();
+                for (interface, properties) in &interfaces {
+                    object.insert(interface.clone(), properties.clone());
+                }
+
+                ObjectManagerEvent::InterfacesAdded { path, interfaces }
+            }
+            ("org.freedesktop.DBus.ObjectManager", "InterfacesRemoved")
+                if path == self.proxy.inner().path() =>
+            {
+                let (path, interfaces): (OwnedObjectPath, Vec<OwnedInterfaceName>) =
+                    msg.body().deserialize()?;
+                let mut objects = self.objects.write().expect("lock poisoned");
+                if let Some(object) = objects.get_mut(&path) {
+                    for interface in &interfaces {
+                        object.remove(interface);
+                    }
+                    if object.is_empty() {
+                        objects.remove(&path);
+                    }
+                }
+
+                ObjectManagerEvent::InterfacesRemoved { path, interfaces }
+            }
+            ("org.freedesktop.DBus.Properties", "PropertiesChanged") => {
+                let (interface, changed, invalidated): (
+                    OwnedInterfaceName,
+                    HashMap<String, OwnedValue>,
+                    Vec<String>,
+                ) = msg.body().deserialize()?;
+                let mut objects = self.objects.write().expect("lock poisoned");
+                // Only track the interfaces the object manager told us about.
+                let Some(properties) = objects
+                    .get_mut(&key(path))
+                    .and_then(|interfaces| interfaces.get_mut(&interface))
+                else {
+                    return Ok(());
+                };
+                for name in &invalidated {
+                    properties.remove(name);
+                }
+                for (name, value) in &changed {
+                    properties.insert(name.clone(), value.clone());
+                }
+
+                ObjectManagerEvent::PropertiesChanged {
+                    path: path.to_owned().into(),
+                    interface,
+                    changed,
+                    invalidated,
+                }
+            }
+            _ => return Ok(()),
+        };
+        let _ = self.events.try_broadcast(event);
+
+        Ok(())
+    }
+
+    async fn handle_owner_change(&mut self, owner: Option<UniqueName<'static>>) {
+        trace!(
+            "Owner of `{}` changed to {owner:?}",
+            self.proxy.inner().destination()
+        );
+
+        // Whatever the new owner is, the objects of the previous one are gone.
+        self.since = None;
+        self.owner = None;
+        let removed = std::mem::take(&mut *self.objects.write().expect("lock poisoned"));
+        for (path, interfaces) in removed {
+            let interfaces = interfaces.into_keys().collect();
+            let _ = self
+                .events
+                .try_broadcast(ObjectManagerEvent::InterfacesRemoved { path, interfaces });
+        }
+        if owner.is_none() {
+            return;
+        }
+
+        let (objects, since, owner) = match fetch(&self.proxy).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                debug!(
+                    "Failed to get the objects of `{}`: {e}",
+                    self.proxy.inner().destination()
+                );
+
+                return;
+            }
+        };
+        self.since = Some(since);
+        self.owner = owner;
+        *self.objects.write().expect("lock poisoned") = objects.clone();
+        for (path, interfaces) in objects {
+            let _ = self
+                .events
+                .try_broadcast(ObjectManagerEvent::InterfacesAdded { path, interfaces });
+        }
+    }
+}
+
+/// The key of the object at `path` in [`ManagedObjects`].
+fn key(path: &ObjectPath<'_>) -> OwnedObjectPath {
+    path.to_owned().into()
+}
+
+/// Fetch all the managed objects, along with the receive position and the sender of the reply.
+async fn fetch(
+    proxy: &ObjectManagerProxy<'_>,
+) -> Result<(ManagedObjects, Sequence, Option<UniqueName<'static>>)> {
+    let reply = proxy.inner().call_method("GetManagedObjects", &()).await?;
+    let owner = reply.header().sender().map(|sender| sender.to_owned());
+
+    Ok((reply.body().deserialize()?, reply.recv_position(), owner))
+}
+
+/// If `e` means that the well-known destination of `proxy` currently has no owner.
+fn is_unowned(proxy: &ObjectManagerProxy<'_>, e: &Error) -> bool {
+    if !matches!(proxy.inner().destination(), BusName::WellKnown(_)) {
+        return false;
+    }
+
+    matches!(
+        fdo::Error::from(e.clone()),
+        fdo::Error::ServiceUnknown(_) | fdo::Error::NameHasNoOwner(_)
+    )
+}
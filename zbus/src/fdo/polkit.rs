@@ -0,0 +1,222 @@
+//! Integration with [polkit], the authorization framework of most Linux systems.
+//!
+//! System services typically check with polkit whether the caller of a privileged method is
+//! authorized to perform the associated action. This module provides a proxy for the polkit
+//! authority and [`check_authorization`], that performs the check for a method call. The
+//! `polkit_action` attribute of the [`macro@crate::interface`] macro uses the latter to do it for
+//! you before the method is called:
+//!
+//! ```no_run
+//! use zbus::interface;
+//!
+//! struct Power;
+//!
+//! #[interface(name = "org.zbus.Power1")]
+//! impl Power {
+//!     #[zbus(polkit_action = "org.zbus.power1.reboot")]
+//!     async fn reboot(&self) {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! [polkit]: https://www.freedesktop.org/software/polkit/docs/latest/
+
+use enumflags2::{bitflags, BitFlags};
+use serde::{Deserialize, Serialize};
+use static_assertions::assert_impl_all;
+use std::collections::HashMap;
+use tracing::debug;
+use zbus_names::UniqueName;
+use zvariant::{OwnedValue, Type, Value};
+
+use super::{Error, Result};
+use crate::{
+    message::{self, Header},
+    proxy,
+    proxy::CacheProperties,
+    Connection,
+};
+
+/// The flags of the [`check_authorization`] method.
+///
+/// [`check_authorization`]: AuthorityProxy::check_authorization
+#[bitflags]
+#[repr(u32)]
+#[derive(Type, Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
+pub enum CheckAuthorizationFlags {
+    /// If the subject can obtain the authorization through authentication, and an authentication
+    /// agent is available, then attempt to do so. This may take a long time, as the user has to
+    /// interact with the authentication agent.
+    AllowUserInteraction = 0x01,
+}
+
+assert_impl_all!(CheckAuthorizationFlags: Send, Sync, Unpin);
+
+/// The entity that an authorization check is about.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct Subject {
+    /// The kind of subject, e.g `system-bus-name` or `unix-process`.
+    pub subject_kind: String,
+    /// The details of the subject, depending on its kind.
+    pub subject_details: HashMap<String, OwnedValue>,
+}
+
+assert_impl_all!(Subject: Send, Sync, Unpin);
+
+impl Subject {
+    /// A `system-bus-name` subject, for the peer with the given unique name on the system bus.
+    ///
+    /// This is the recommended subject for checking the caller of a method, as polkit gets its
+    /// credentials from the bus.
+    pub fn system_bus_name(name: &UniqueName<'_>) -> Self {
+        Self::new("system-bus-name", [("name", Value::from(name.as_str()))])
+    }
+
+    /// A `unix-process` subject.
+    ///
+    /// If `start_time` is `0`, polkit looks it up itself.
+    pub fn unix_process(pid: u32, start_time: u64, uid: Option<u32>) -> Self {
+        let mut details = vec![("pid", Value::from(pid)), ("start-time", start_time.into())];
+        if let Some(uid) = uid {
+            // polkit expects a signed integer for historical reasons.
+            details.push(("uid", Value::from(uid as i32)));
+        }
+
+        Self::new("unix-process", details)
+    }
+
+    fn new<'v>(kind: &str, details: impl IntoIterator<Item = (&'static str, Value<'v>)>) -> Self {
+        Self {
+            subject_kind: kind.to_string(),
+            subject_details: details
+                .into_iter()
+                .map(|(k, v)| {
+                    let v = v
+                        .try_into()
+                        .expect("Subject details never contain file descriptors");
+
+                    (k.to_string(), v)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The result of an authorization check.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct AuthorizationResult {
+    /// Whether the subject is authorized for the action.
+    pub is_authorized: bool,
+    /// Whether the subject could be authorized if it authenticated.
+    pub is_challenge: bool,
+    /// Details about the result.
+    pub details: HashMap<String, String>,
+}
+
+assert_impl_all!(AuthorizationResult: Send, Sync, Unpin);
+
+/// Proxy for the `org.freedesktop.PolicyKit1.Authority` interface.
+#[proxy(
+    interface = "org.freedesktop.PolicyKit1.Authority",
+    default_service = "org.freedesktop.PolicyKit1",
+    default_path = "/org/freedesktop/PolicyKit1/Authority"
+)]
+pub trait Authority {
+    /// Checks if `subject` is authorized to perform the action with identifier `action_id`.
+    ///
+    /// If `cancellation_id` is non-empty, the check can be cancelled through
+    /// [`AuthorityProxy::cancel_check_authorization`].
+    fn check_authorization(
+        &self,
+        subject: &Subject,
+        action_id: &str,
+        details: &HashMap<&str, &str>,
+        flags: BitFlags<CheckAuthorizationFlags>,
+        cancellation_id: &str,
+    ) -> Result<AuthorizationResult>;
+
+    /// Cancels an authorization check.
+    fn cancel_check_authorization(&self, cancellation_id: &str) -> Result<()>;
+
+    /// The features supported by the currently used polkit backend.
+    #[zbus(property)]
+    fn backend_features(&self) -> Result<u32>;
+
+    /// The name of the currently used polkit backend.
+    #[zbus(property)]
+    fn backend_name(&self) -> Result<String>;
+
+    /// The version of the currently used polkit backend.
+    #[zbus(property)]
+    fn backend_version(&self) -> Result<String>;
+}
+
+assert_impl_all!(AuthorityProxy<'_>: Send, Sync, Unpin);
+#[cfg(feature = "blocking-api")]
+assert_impl_all!(AuthorityProxyBlocking<'_>: Send, Sync, Unpin);
+
+/// Check if the sender of a method call is authorized to perform the action `action_id`.
+///
+/// The check is made with the polkit authority on the bus of `connection`, which therefore must be
+/// a connection to the system bus. User interaction is allowed if the method call has the
+/// [`message::Flags::AllowInteractiveAuth`] flag.
+///
+/// # Errors
+///
+/// [`Error::InteractiveAuthorizationRequired`] if the caller could be authorized through
+/// authentication but didn't allow interaction, and [`Error::AccessDenied`] if it's not authorized
+/// at all or the check failed.
+pub async fn check_authorization(
+    connection: &Connection,
+    header: &Header<'_>,
+    action_id: &str,
+) -> Result<()> {
+    if !connection.is_bus() {
+        return Err(Error::AccessDenied(
+            "polkit authorization is only supported on bus connections".into(),
+        ));
+    }
+    let sender = header
+        .sender()
+        .ok_or_else(|| Error::AccessDenied("Missing sender".into()))?;
+    let flags = if header
+        .primary()
+        .flags()
+        .contains(message::Flags::AllowInteractiveAuth)
+    {
+        CheckAuthorizationFlags::AllowUserInteraction.into()
+    } else {
+        BitFlags::empty()
+    };
+
+    let result = AuthorityProxy::builder(connection)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?
+        .check_authorization(
+            &Subject::system_bus_name(sender),
+            action_id,
+            &HashMap::new(),
+            flags,
+            "",
+        )
+        .await
+        .map_err(|e| {
+            debug!("polkit authorization check for `{action_id}` failed: {e}");
+
+            Error::AccessDenied(format!("Failed to check authorization for `{action_id}`"))
+        })?;
+
+    if result.is_authorized {
+        Ok(())
+    } else if result.is_challenge && flags.is_empty() {
+        Err(Error::InteractiveAuthorizationRequired(format!(
+            "Interactive authorization required for `{action_id}`"
+        )))
+    } else {
+        Err(Error::AccessDenied(format!(
+            "Not authorized for `{action_id}`"
+        )))
+    }
+}
@@ -6,10 +6,15 @@
 use static_assertions::assert_impl_all;
 use std::{borrow::Cow, collections::HashMap};
 use zbus_names::InterfaceName;
-use zvariant::{OwnedValue, Value};
+use zvariant::{ObjectPath, OwnedValue, Value};
 
 use super::{Error, Result};
-use crate::{interface, message::Header, object_server::SignalEmitter, Connection, ObjectServer};
+use crate::{
+    interface,
+    message::Header,
+    object_server::{ArcInterface, SignalEmitter},
+    Connection, ObjectServer,
+};
 
 /// Service-side implementation for the `org.freedesktop.DBus.Properties` interface.
 /// This interface is implemented automatically for any object registered to the
@@ -35,13 +40,8 @@ impl Properties {
         #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
     ) -> Result<OwnedValue> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
-        let root = server.root().read().await;
-        let iface = root
-            .get_child(path)
-            .and_then(|node| node.interface_lock(interface_name.as_ref()))
-            .ok_or_else(|| {
-                Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
-            })?;
+        let iface = lookup_interface(server, path, &interface_name).await?;
+        server.check_property_access(&iface, conn, &header).await?;
 
         let res = iface
             .instance
@@ -69,13 +69,10 @@ impl Properties {
         #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
     ) -> Result<()> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
-        let root = server.root().read().await;
-        let iface = root
-            .get_child(path)
-            .and_then(|node| node.interface_lock(interface_name.as_ref()))
-            .ok_or_else(|| {
-                Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
-            })?;
+        let iface = lookup_interface(server, path, &interface_name).await?;
+        server
+            .check_property_access(&iface, connection, &header)
+            .await?;
 
         match iface.instance.read().await.set(
             property_name,
@@ -125,13 +122,10 @@ impl Properties {
         #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
     ) -> Result<HashMap<String, OwnedValue>> {
         let path = header.path().ok_or(crate::Error::MissingField)?;
-        let root = server.root().read().await;
-        let iface = root
-            .get_child(path)
-            .and_then(|node| node.interface_lock(interface_name.as_ref()))
-            .ok_or_else(|| {
-                Error::UnknownInterface(format!("Unknown interface '{interface_name}'"))
-            })?;
+        let iface = lookup_interface(server, path, &interface_name).await?;
+        server
+            .check_property_access(&iface, connection, &header)
+            .await?;
 
         let res = iface
             .instance
@@ -153,6 +147,32 @@ impl Properties {
     ) -> zbus::Result<()>;
 }
 
+/// Look up the interface `name` of the object at `path`, the one the call was dispatched to.
+///
+/// Unless the object is one of a fallback handler, it's the interface that is reported unknown if
+/// the object was removed in the meantime.
+async fn lookup_interface(
+    server: &ObjectServer,
+    path: &ObjectPath<'_>,
+    name: &InterfaceName<'_>,
+) -> Result<ArcInterface> {
+    match server.lookup_interface(path, name).await {
+        Err(Error::UnknownObject(_))
+            if server
+                .root()
+                .read()
+                .await
+                .fallback_for(path, false)
+                .is_none() =>
+        {
+            Err(Error::UnknownInterface(format!(
+                "Unknown interface '{name}'"
+            )))
+        }
+        res => res,
+    }
+}
+
 assert_impl_all!(PropertiesProxy<'_>: Send, Sync, Unpin);
 #[cfg(feature = "blocking-api")]
 assert_impl_all!(PropertiesProxyBlocking<'_>: Send, Sync, Unpin);
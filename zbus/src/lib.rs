@@ -66,6 +66,13 @@ pub use connection as conn;
 pub use connection::handshake::AuthMechanism;
 pub use connection::Connection;
 
+#[cfg(feature = "p2p")]
+pub mod listener;
+#[cfg(feature = "p2p")]
+pub use listener::Listener;
+#[cfg(feature = "bus-impl")]
+pub mod bus;
+
 mod message_stream;
 pub use message_stream::*;
 mod abstractions;
@@ -80,6 +87,8 @@ pub use proxy::Proxy;
 pub mod object_server;
 pub use object_server::ObjectServer;
 
+pub mod pcap;
+
 mod utils;
 pub use utils::*;
 
@@ -106,4 +115,6 @@ pub mod export {
 }
 
 pub use zbus_names as names;
+#[cfg(feature = "xml")]
+pub use zbus_xml as xml;
 pub use zvariant;
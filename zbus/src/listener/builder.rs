@@ -0,0 +1,105 @@
+use static_assertions::assert_impl_all;
+use std::{fmt, sync::Arc};
+
+use crate::{conn::AuthMechanism, connection, Address, Error, Guid, OwnedGuid, Result};
+
+use super::{socket::ListenerSocket, Listener, Setup};
+
+/// A builder for [`zbus::Listener`].
+///
+/// [`zbus::Listener`]: crate::Listener
+#[must_use]
+pub struct Builder {
+    address: Address,
+    guid: Option<OwnedGuid>,
+    auth_mechanism: Option<AuthMechanism>,
+    setup: Option<Setup>,
+}
+
+assert_impl_all!(Builder: Send, Sync, Unpin);
+
+impl Builder {
+    /// Create a builder for a listener that will bind to the given address.
+    pub fn new<A>(address: A) -> Result<Self>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<Error>,
+    {
+        let address = address.try_into().map_err(Into::into)?;
+        let guid = address.guid().map(|g| g.to_owned().into());
+
+        Ok(Self {
+            address,
+            guid,
+            auth_mechanism: None,
+            setup: None,
+        })
+    }
+
+    /// The GUID of the server.
+    ///
+    /// If not set, the GUID from the address is used. If the address doesn't specify one either, a
+    /// random GUID is generated.
+    pub fn guid<G>(mut self, guid: G) -> Result<Self>
+    where
+        G: TryInto<Guid<'static>>,
+        G::Error: Into<Error>,
+    {
+        self.guid = Some(guid.try_into().map_err(Into::into)?.into());
+
+        Ok(self)
+    }
+
+    /// Specify the mechanism to use for authenticating clients.
+    ///
+    /// By default, the mechanism appropriate for the socket type is used.
+    pub fn auth_mechanism(mut self, auth_mechanism: AuthMechanism) -> Self {
+        self.auth_mechanism = Some(auth_mechanism);
+
+        self
+    }
+
+    /// Set up each connection before it's created.
+    ///
+    /// The given function is called with the [`connection::Builder`] for each incoming
+    /// connection, after it has been set up as a peer-to-peer server connection. This is
+    /// typically used to serve the same objects on all the connections, using
+    /// [`connection::Builder::serve_at`].
+    pub fn setup<F>(mut self, setup: F) -> Self
+    where
+        F: Fn(connection::Builder<'static>) -> Result<connection::Builder<'static>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.setup = Some(Arc::new(setup));
+
+        self
+    }
+
+    /// Bind to the address and build the listener.
+    pub async fn build(self) -> Result<Listener> {
+        let guid = self.guid.unwrap_or_else(|| Guid::generate().into());
+        let (socket, address, socket_file) = ListenerSocket::bind(self.address.transport()).await?;
+        let address = address.set_guid(guid.clone())?;
+
+        Ok(Listener {
+            socket,
+            address,
+            guid,
+            auth_mechanism: self.auth_mechanism,
+            setup: self.setup,
+            socket_file,
+        })
+    }
+}
+
+impl fmt::Debug for Builder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("address", &self.address)
+            .field("guid", &self.guid)
+            .field("auth_mechanism", &self.auth_mechanism)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,331 @@
+//! The listener for accepting peer-to-peer connections.
+//!
+//! This module is only available when the `p2p` feature is enabled.
+
+mod builder;
+pub use builder::Builder;
+
+mod socket;
+use socket::ListenerSocket;
+
+use futures_core::Stream;
+use futures_util::StreamExt;
+use static_assertions::assert_impl_all;
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use crate::{conn::AuthMechanism, connection, Address, Connection, Guid, OwnedGuid, Result};
+
+/// The maximum number of client handshakes [`Listener::incoming`] runs concurrently.
+const MAX_CONCURRENT_HANDSHAKES: usize = 16;
+
+type Setup =
+    Arc<dyn Fn(connection::Builder<'static>) -> Result<connection::Builder<'static>> + Send + Sync>;
+
+/// A listener for peer-to-peer D-Bus connections.
+///
+/// A `Listener` binds to a listenable [`Address`] and creates a fully authenticated server-side
+/// [`Connection`] for each client that connects to it. Besides the addresses you can connect to,
+/// the following listenable addresses are supported:
+///
+/// * `unix:dir=<path>` and `unix:tmpdir=<path>`: a socket file with a random name is created in the
+///   given directory.
+/// * `unix:runtime=yes`: the socket file is `$XDG_RUNTIME_DIR/bus`.
+/// * `tcp:host=<host>,port=0`: a free port is picked by the OS. The host defaults to `localhost`,
+///   e.g `tcp:port=0` listens on a free port of the local host.
+///
+/// [`Listener::address`] gives you the resolved address that clients need to connect to.
+///
+/// Socket files created by the listener are removed when it's dropped.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(unix)]
+/// # {
+/// use futures_util::StreamExt;
+/// use zbus::{connection, interface, Listener};
+///
+/// struct Greeter;
+///
+/// #[interface(name = "org.zbus.MyGreeter1")]
+/// impl Greeter {
+///     fn say_hello(&self, name: &str) -> String {
+///         format!("Hello {}!", name)
+///     }
+/// }
+///
+/// # zbus::block_on(async {
+/// let dir = tempfile::tempdir()?;
+/// let listener = Listener::builder(&*format!("unix:dir={}", dir.path().display()))?
+///     .setup(|builder| builder.serve_at("/org/zbus/MyGreeter", Greeter))
+///     .build()
+///     .await?;
+/// let address = listener.address().clone();
+///
+/// let client = async {
+///     let conn = connection::Builder::address(address)?.p2p().build().await?;
+///     let reply = conn
+///         .call_method(
+///             None::<()>,
+///             "/org/zbus/MyGreeter",
+///             Some("org.zbus.MyGreeter1"),
+///             "SayHello",
+///             &"Maria",
+///         )
+///         .await?;
+///     reply.body().deserialize::<String>().map(|reply| (reply, conn))
+/// };
+/// let server = async {
+///     // Every incoming connection is already authenticated and serving our `Greeter`.
+///     let mut incoming = listener.incoming();
+///     incoming.next().await.unwrap()
+/// };
+///
+/// let ((reply, _client_conn), _server_conn) = futures_util::try_join!(client, server)?;
+/// assert_eq!(reply, "Hello Maria!");
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// # }
+/// ```
+pub struct Listener {
+    socket: ListenerSocket,
+    address: Address,
+    guid: OwnedGuid,
+    auth_mechanism: Option<AuthMechanism>,
+    setup: Option<Setup>,
+    // The socket file to remove on drop, if any.
+    socket_file: Option<PathBuf>,
+}
+
+assert_impl_all!(Listener: Send, Sync, Unpin);
+
+impl Listener {
+    /// Bind to the given address, with the default settings.
+    ///
+    /// Use [`Listener::builder`] if you need to customize the listener.
+    pub async fn bind<A>(address: A) -> Result<Self>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<crate::Error>,
+    {
+        Builder::new(address)?.build().await
+    }
+
+    /// Create a builder for a listener that will bind to the given address.
+    pub fn builder<A>(address: A) -> Result<Builder>
+    where
+        A: TryInto<Address>,
+        A::Error: Into<crate::Error>,
+    {
+        Builder::new(address)
+    }
+
+    /// The address clients can connect to.
+    ///
+    /// In contrast to the address the listener was bound to, this is always a connectable address
+    /// and it includes the GUID of the server.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// The GUID of the server.
+    pub fn guid(&self) -> &Guid<'_> {
+        self.guid.inner()
+    }
+
+    /// Wait for the next client and create a server-side connection for it.
+    ///
+    /// The returned connection is fully authenticated and set up according to the
+    /// [`Builder::setup`] callback, if any.
+    pub async fn accept(&self) -> Result<Connection> {
+        let builder = self.socket.accept().await?;
+
+        self.build_connection(builder).await
+    }
+
+    /// A stream of incoming connections.
+    ///
+    /// Unlike calling [`Listener::accept`] in a loop, multiple client handshakes are handled
+    /// concurrently so a client that is slow to authenticate doesn't hold back others. Failures
+    /// (e.g a client failing to authenticate) are yielded as errors but do not end the stream.
+    pub fn incoming(&self) -> impl Stream<Item = Result<Connection>> + Unpin + '_ {
+        futures_util::stream::unfold(self, |listener| async move {
+            Some((listener.socket.accept().await, listener))
+        })
+        .map(move |builder| async move { self.build_connection(builder?).await })
+        .buffer_unordered(MAX_CONCURRENT_HANDSHAKES)
+        .boxed()
+    }
+
+    async fn build_connection(&self, builder: connection::Builder<'static>) -> Result<Connection> {
+        let mut builder = builder.server(self.guid.clone())?.p2p();
+        if let Some(mechanism) = self.auth_mechanism {
+            builder = builder.auth_mechanism(mechanism);
+        }
+        if let Some(setup) = &self.setup {
+            builder = setup(builder)?;
+        }
+
+        builder.build().await
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener")
+            .field("socket", &self.socket)
+            .field("address", &self.address)
+            .field("guid", &self.guid)
+            .field("auth_mechanism", &self.auth_mechanism)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Some(path) = &self.socket_file {
+            // Nothing we can do about errors here.
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::address::transport::Unix;
+    use crate::address::transport::{Transport, UnixSocket};
+    use futures_util::TryStreamExt;
+    use ntest::timeout;
+    use test_log::test;
+
+    struct Echo;
+
+    #[crate::interface(name = "org.zbus.TestListener")]
+    impl Echo {
+        fn echo(&self, s: &str) -> String {
+            s.to_string()
+        }
+    }
+
+    async fn test_listener(address: &str) -> Result<()> {
+        let listener = Listener::builder(address)?
+            .setup(|builder| builder.serve_at("/org/zbus/TestListener", Echo))
+            .build()
+            .await?;
+        let address = listener.address().clone();
+        assert_eq!(address.guid(), Some(listener.guid()));
+
+        let clients = async {
+            for s in ["hello", "world"] {
+                let conn = connection::Builder::address(address.clone())?
+                    .p2p()
+                    .build()
+                    .await?;
+                let reply = conn
+                    .call_method(
+                        None::<()>,
+                        "/org/zbus/TestListener",
+                        Some("org.zbus.TestListener"),
+                        "Echo",
+                        &s,
+                    )
+                    .await?;
+                assert_eq!(reply.body().deserialize::<String>()?, s);
+            }
+
+            Ok(())
+        };
+        let server = listener.incoming().take(2).try_collect::<Vec<_>>();
+        let (_, conns) = futures_util::try_join!(clients, server)?;
+        assert_eq!(conns.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn tcp_listener() {
+        crate::utils::block_on(test_listener("tcp:host=127.0.0.1,port=0")).unwrap();
+        crate::utils::block_on(test_listener("tcp:port=0")).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_dir_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:dir={}", dir.path().display());
+        crate::utils::block_on(test_listener(&address)).unwrap();
+        // The socket file is removed along with the listener.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_runtime_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR");
+        std::env::set_var("XDG_RUNTIME_DIR", dir.path());
+        let res = crate::utils::block_on(async {
+            let listener = Listener::bind("unix:runtime=yes").await?;
+            assert_eq!(
+                listener.address().transport(),
+                &Transport::Unix(Unix::new(UnixSocket::File(dir.path().join("bus")))),
+            );
+            drop(listener);
+
+            test_listener("unix:runtime=yes").await
+        });
+        match runtime_dir {
+            Some(runtime_dir) => std::env::set_var("XDG_RUNTIME_DIR", runtime_dir),
+            None => std::env::remove_var("XDG_RUNTIME_DIR"),
+        }
+        res.unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[timeout(15000)]
+    fn unix_path_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = format!("unix:path={}", dir.path().join("socket").display());
+        crate::utils::block_on(test_listener(&address)).unwrap();
+    }
+
+    #[test]
+    fn resolved_address() {
+        crate::utils::block_on(async {
+            for (address, host) in [
+                ("tcp:host=127.0.0.1,port=0", "127.0.0.1"),
+                ("tcp:port=0", "localhost"),
+            ] {
+                let listener = Listener::bind(address).await.unwrap();
+                match listener.address().transport() {
+                    Transport::Tcp(tcp) => {
+                        assert_eq!(tcp.host(), host);
+                        assert_ne!(tcp.port(), 0);
+                    }
+                    _ => panic!("unexpected transport"),
+                }
+            }
+
+            #[cfg(unix)]
+            {
+                let dir = tempfile::tempdir().unwrap();
+                let address = format!("unix:tmpdir={}", dir.path().display());
+                let listener = Listener::bind(&*address).await.unwrap();
+                match listener.address().transport() {
+                    Transport::Unix(unix) => match unix.path() {
+                        UnixSocket::File(path) => assert!(path.starts_with(dir.path())),
+                        _ => panic!("unexpected unix socket"),
+                    },
+                    _ => panic!("unexpected transport"),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(not(feature = "tokio"))]
+use async_io::Async;
+#[cfg(unix)]
+use std::os::unix::net::SocketAddr;
+use std::{
+    net::{SocketAddr as TcpSocketAddr, ToSocketAddrs},
+    path::PathBuf,
+};
+
+#[cfg(unix)]
+use crate::address::transport::{Unix, UnixSocket};
+use crate::{
+    address::transport::{Tcp, TcpTransportFamily, Transport},
+    connection, Address, Error, Guid, Result,
+};
+
+#[cfg(all(unix, not(feature = "tokio")))]
+type UnixListener = Async<std::os::unix::net::UnixListener>;
+#[cfg(all(unix, feature = "tokio"))]
+type UnixListener = tokio::net::UnixListener;
+#[cfg(not(feature = "tokio"))]
+type TcpListener = Async<std::net::TcpListener>;
+#[cfg(feature = "tokio")]
+type TcpListener = tokio::net::TcpListener;
+
+/// The listening socket of a [`super::Listener`].
+#[derive(Debug)]
+pub(super) enum ListenerSocket {
+    #[cfg(unix)]
+    Unix(UnixListener),
+    Tcp(TcpListener),
+}
+
+impl ListenerSocket {
+    /// Bind to the given transport.
+    ///
+    /// Returns the socket, the (connectable) address to use for connecting to it and the socket
+    /// file that was created, if any.
+    pub(super) async fn bind(transport: &Transport) -> Result<(Self, Address, Option<PathBuf>)> {
+        match transport {
+            #[cfg(unix)]
+            Transport::Unix(unix) => bind_unix(unix),
+            Transport::Tcp(tcp) => bind_tcp(tcp).await,
+            _ => Err(Error::Unsupported),
+        }
+    }
+
+    /// Wait for a client to connect.
+    ///
+    /// Returns a connection builder for the new client socket.
+    pub(super) async fn accept(&self) -> Result<connection::Builder<'static>> {
+        match self {
+            #[cfg(all(unix, not(feature = "tokio")))]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+
+                Ok(connection::Builder::unix_stream(stream.into_inner()?))
+            }
+            #[cfg(all(unix, feature = "tokio"))]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+
+                Ok(connection::Builder::unix_stream(stream))
+            }
+            #[cfg(not(feature = "tokio"))]
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+
+                Ok(connection::Builder::tcp_stream(stream.into_inner()?))
+            }
+            #[cfg(feature = "tokio")]
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+
+                Ok(connection::Builder::tcp_stream(stream))
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn bind_unix(unix: &Unix) -> Result<(ListenerSocket, Address, Option<PathBuf>)> {
+    let random_path = |dir: &PathBuf| dir.join(format!("dbus-{}", Guid::generate()));
+    let path = match unix.path() {
+        UnixSocket::File(path) => path.clone(),
+        #[cfg(target_os = "linux")]
+        UnixSocket::Abstract(name) => {
+            use std::os::linux::net::SocketAddrExt;
+
+            let addr = SocketAddr::from_abstract_name(name.as_encoded_bytes())?;
+            let listener = unix_listener(&addr)?;
+            let address = Transport::Unix(Unix::new(UnixSocket::Abstract(name.clone()))).into();
+
+            return Ok((listener, address, None));
+        }
+        // We don't (yet) make use of abstract sockets for `tmpdir`, which the spec allows.
+        UnixSocket::Dir(dir) | UnixSocket::TmpDir(dir) => random_path(dir),
+        UnixSocket::Runtime => {
+            let dir = std::env::var_os("XDG_RUNTIME_DIR")
+                .ok_or_else(|| Error::Address("`XDG_RUNTIME_DIR` is not set".to_owned()))?;
+
+            PathBuf::from(dir).join("bus")
+        }
+    };
+    let listener = unix_listener(&SocketAddr::from_pathname(&path)?)?;
+    let address = Transport::Unix(Unix::new(UnixSocket::File(path.clone()))).into();
+
+    Ok((listener, address, Some(path)))
+}
+
+#[cfg(unix)]
+fn unix_listener(addr: &SocketAddr) -> Result<ListenerSocket> {
+    let listener = std::os::unix::net::UnixListener::bind_addr(addr)?;
+    listener.set_nonblocking(true)?;
+
+    #[cfg(not(feature = "tokio"))]
+    let listener = Async::new(listener)?;
+    #[cfg(feature = "tokio")]
+    let listener = tokio::net::UnixListener::from_std(listener)?;
+
+    Ok(ListenerSocket::Unix(listener))
+}
+
+async fn bind_tcp(tcp: &Tcp) -> Result<(ListenerSocket, Address, Option<PathBuf>)> {
+    if tcp.nonce_file().is_some() {
+        return Err(Error::Unsupported);
+    }
+
+    let host = tcp.bind().unwrap_or(tcp.host()).to_owned();
+    let (port, family) = (tcp.port(), tcp.family());
+    let addrs = crate::Task::spawn_blocking(
+        move || -> Result<Vec<TcpSocketAddr>> {
+            let addrs = (host.as_str(), port)
+                .to_socket_addrs()?
+                .filter(|a| match family {
+                    Some(TcpTransportFamily::Ipv4) => a.is_ipv4(),
+                    Some(TcpTransportFamily::Ipv6) => a.is_ipv6(),
+                    None => true,
+                });
+
+            Ok(addrs.collect())
+        },
+        "resolve tcp listen address",
+    )
+    .await
+    .map_err(|e| Error::Address(format!("Failed to resolve TCP addresses: {e}")))?;
+
+    let listener = std::net::TcpListener::bind(&addrs[..])?;
+    listener.set_nonblocking(true)?;
+    let port = listener.local_addr()?.port();
+
+    #[cfg(not(feature = "tokio"))]
+    let listener = Async::new(listener)?;
+    #[cfg(feature = "tokio")]
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+
+    let address = Transport::Tcp(Tcp::new(tcp.host(), port).set_family(family)).into();
+
+    Ok((ListenerSocket::Tcp(listener), address, None))
+}
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use std::{collections::HashMap, fmt, sync::Arc};
+use tracing::trace;
+
+use crate::{
+    async_lock::Mutex,
+    fdo::{self, ConnectionCredentials, DBusProxy},
+    message::Header,
+    names::OwnedUniqueName,
+    Connection,
+};
+
+/// The maximum number of caller credentials we keep around.
+const MAX_CACHED_CREDENTIALS: usize = 1024;
+
+/// Access control for method calls handled by the [`ObjectServer`].
+///
+/// An access control is consulted before a method is called, with the header of the method call
+/// and the credentials of the caller. If it returns an error (typically
+/// [`fdo::Error::AccessDenied`]), the method is not called and the error is returned to the caller
+/// instead.
+///
+/// Access controls can be set for the whole object server (see
+/// [`ObjectServer::set_access_control`] and [`connection::Builder::access_control`]) or for
+/// individual interfaces and methods through the `access_control` attribute of the
+/// [`macro@crate::interface`] macro. All the applicable ones must allow a call for it to go
+/// through.
+///
+/// This trait is implemented for all functions and closures of the right signature, so you
+/// typically only need to implement it yourself if the check is asynchronous.
+///
+/// # Example
+///
+/// ```
+/// use zbus::{fdo, interface, message::Header};
+///
+/// fn root_only(_: &Header<'_>, creds: &fdo::ConnectionCredentials) -> fdo::Result<()> {
+///     if creds.unix_user_id() == Some(0) {
+///         Ok(())
+///     } else {
+///         Err(fdo::Error::AccessDenied("Only root is allowed".into()))
+///     }
+/// }
+///
+/// struct Service;
+///
+/// #[interface(name = "org.zbus.Service1")]
+/// impl Service {
+///     fn status(&self) -> &str {
+///         "Running"
+///     }
+///
+///     #[zbus(access_control = "root_only")]
+///     fn restart(&self) {}
+/// }
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::set_access_control`]: crate::ObjectServer::set_access_control
+/// [`connection::Builder::access_control`]: crate::connection::Builder::access_control
+#[async_trait]
+pub trait AccessControl: Send + Sync + 'static {
+    /// Check if the caller is allowed to make the method call of the given `header`.
+    async fn check(
+        &self,
+        header: &Header<'_>,
+        credentials: &ConnectionCredentials,
+    ) -> fdo::Result<()>;
+}
+
+#[async_trait]
+impl<F> AccessControl for F
+where
+    F: Fn(&Header<'_>, &ConnectionCredentials) -> fdo::Result<()> + Send + Sync + 'static,
+{
+    async fn check(
+        &self,
+        header: &Header<'_>,
+        credentials: &ConnectionCredentials,
+    ) -> fdo::Result<()> {
+        self(header, credentials)
+    }
+}
+
+/// A type for a reference-counted [`AccessControl`] trait-object, with a manual Debug impl.
+#[derive(Clone)]
+pub(crate) struct ArcAccessControl(pub Arc<dyn AccessControl>);
+
+impl ArcAccessControl {
+    pub fn new<A>(access_control: A) -> Self
+    where
+        A: AccessControl,
+    {
+        Self(Arc::new(access_control))
+    }
+}
+
+impl fmt::Debug for ArcAccessControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arc<dyn AccessControl>")
+            .finish_non_exhaustive()
+    }
+}
+
+/// The credentials of the callers, cached per unique name.
+///
+/// The cache is cleared when the connection reconnects to the bus, since the unique names of the
+/// callers may then belong to other peers. We don't track peers leaving the bus though, so the
+/// cache is also flushed once it grows too big.
+#[derive(Debug, Default)]
+pub(crate) struct CredentialsCache {
+    credentials: Mutex<Credentials>,
+}
+
+#[derive(Debug, Default)]
+struct Credentials {
+    // The key is `None` for the peer of a peer-to-peer connection.
+    by_sender: HashMap<Option<OwnedUniqueName>, Arc<ConnectionCredentials>>,
+    // Bumped on each clear, so credentials fetched before aren't cached after.
+    generation: u64,
+}
+
+impl CredentialsCache {
+    /// The credentials of the sender of the message with the given `header`.
+    pub async fn get(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+    ) -> fdo::Result<Arc<ConnectionCredentials>> {
+        let sender = if connection.is_bus() {
+            let sender = header
+                .sender()
+                .ok_or_else(|| fdo::Error::AccessDenied("Missing sender".into()))?;
+
+            Some(OwnedUniqueName::from(sender.to_owned()))
+        } else {
+            None
+        };
+        let generation = {
+            let cache = self.credentials.lock().await;
+            if let Some(credentials) = cache.by_sender.get(&sender) {
+                return Ok(credentials.clone());
+            }
+
+            cache.generation
+        };
+
+        trace!("Fetching credentials of {sender:?}");
+        let credentials = match &sender {
+            Some(sender) => {
+                DBusProxy::new(connection)
+                    .await?
+                    .get_connection_credentials(sender.into())
+                    .await
+            }
+            None => connection
+                .peer_credentials()
+                .await
+                .map_err(|e| fdo::Error::IOError(e.to_string())),
+        }
+        .map_err(|e| {
+            fdo::Error::AccessDenied(format!("Failed to get the credentials of the caller: {e}"))
+        })?;
+        let credentials = Arc::new(credentials);
+
+        let mut cache = self.credentials.lock().await;
+        if cache.generation == generation {
+            if cache.by_sender.len() >= MAX_CACHED_CREDENTIALS {
+                cache.by_sender.clear();
+            }
+            cache.by_sender.insert(sender, credentials.clone());
+        }
+
+        Ok(credentials)
+    }
+
+    /// Forget all the credentials.
+    pub async fn clear(&self) {
+        let mut cache = self.credentials.lock().await;
+        cache.by_sender.clear();
+        cache.generation += 1;
+    }
+}
+
+#[cfg(all(test, unix, feature = "bus-impl"))]
+mod tests {
+    use futures_util::{
+        future::{select, Either},
+        pin_mut, StreamExt,
+    };
+    use ntest::timeout;
+    use std::{future::Future, time::Duration};
+    use test_log::test;
+
+    use super::*;
+    use crate::{
+        bus::Bus,
+        connection::{self, ConnectionEvent},
+    };
+
+    struct Greeter;
+
+    #[crate::interface(name = "org.zbus.Greeter")]
+    impl Greeter {
+        fn greet(&self) -> &str {
+            "Hello!"
+        }
+    }
+
+    // Run `f` while a bus is running at `address`.
+    async fn with_bus<F: Future>(address: &str, f: F) -> F::Output {
+        let bus = Bus::bind(address).await.unwrap();
+        let run = bus.run();
+        pin_mut!(run, f);
+
+        match select(run, f).await {
+            Either::Left(_) => unreachable!("the bus never stops"),
+            Either::Right((output, _)) => output,
+        }
+    }
+
+    // Call the service from a new client, returning the unique name of the client.
+    async fn greet(address: &str, service: &Connection) -> OwnedUniqueName {
+        let client = connection::Builder::address(address)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        client
+            .call_method(
+                service.unique_name(),
+                "/org/zbus/Greeter",
+                Some("org.zbus.Greeter"),
+                "Greet",
+                &(),
+            )
+            .await
+            .unwrap();
+
+        client.unique_name().unwrap().clone()
+    }
+
+    async fn cached_senders(service: &Connection) -> Vec<Option<OwnedUniqueName>> {
+        let credentials = service.object_server().credentials.credentials.lock().await;
+
+        credentials.by_sender.keys().cloned().collect()
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn reconnect() {
+        crate::block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let address = format!("unix:path={}", dir.path().join("bus").display());
+
+            let (service, mut events) = with_bus(&address, async {
+                let service = connection::Builder::address(&*address)
+                    .unwrap()
+                    .serve_at("/org/zbus/Greeter", Greeter)
+                    .unwrap()
+                    .access_control(|_: &Header<'_>, _: &ConnectionCredentials| Ok(()))
+                    .reconnect(Duration::from_millis(10), Duration::from_millis(100))
+                    .build()
+                    .await
+                    .unwrap();
+                let events = service.receive_events();
+                let client = greet(&address, &service).await;
+                assert_eq!(cached_senders(&service).await, [Some(client)]);
+
+                (service, events)
+            })
+            .await;
+
+            // The bus got dropped above, so the service reconnects once the bus is back.
+            with_bus(&address, async {
+                while !matches!(
+                    events.next().await.unwrap(),
+                    ConnectionEvent::Reconnected(_)
+                ) {}
+                // The unique names of the new bus are given from scratch again, so the credentials
+                // of the previous callers can't be trusted anymore.
+                assert!(cached_senders(&service).await.is_empty());
+
+                let client = greet(&address, &service).await;
+                assert_eq!(cached_senders(&service).await, [Some(client)]);
+            })
+            .await;
+        });
+    }
+}
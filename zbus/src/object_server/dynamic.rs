@@ -0,0 +1,726 @@
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Write},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+use zbus_names::{InterfaceName, MemberName, PropertyName};
+use zvariant::{OwnedValue, Signature, Structure, StructureBuilder, Value};
+
+use crate::{
+    fdo,
+    message::{Flags, Header, Message},
+    Connection, Error, ObjectServer, Result,
+};
+
+use super::{DispatchResult, ErasedInterface, SignalEmitter};
+
+type MethodFuture = Pin<Box<dyn Future<Output = fdo::Result<Vec<OwnedValue>>> + Send>>;
+type MethodHandler = Arc<dyn Fn(Message, SignalEmitter<'static>) -> MethodFuture + Send + Sync>;
+type PropertyGetter = Arc<dyn Fn() -> fdo::Result<OwnedValue> + Send + Sync>;
+type PropertySetter = Arc<dyn Fn(&Value<'_>) -> fdo::Result<()> + Send + Sync>;
+
+/// An interface whose members are declared at runtime.
+///
+/// The [`macro@crate::interface`] macro requires the interface to be known at compile time. This
+/// type is for the cases where it isn't, e.g a plugin host exposing interfaces loaded from its
+/// configuration. The methods, properties and signals are declared with their D-Bus signatures and
+/// backed by closures:
+///
+/// * Method handlers receive the method call [`Message`] and return the values of the reply. The
+///   signature of the call is checked against the declared input arguments before the handler is
+///   called, and the values returned against the declared output arguments.
+/// * Property getters return the current value of the property, and setters receive the new value
+///   once checked against the declared signature. `PropertiesChanged` is emitted after a successful
+///   set.
+/// * Signals are only declared, for the purpose of introspection, and emitted through
+///   [`DynamicInterface::emit_signal`].
+///
+/// Members can also be declared from an introspection document with [`DynamicInterface::from_xml`]
+/// (requires the `xml` feature), in which case the handlers are attached afterwards. Calling a
+/// method or accessing a property without a handler results in a `NotSupported` error.
+///
+/// The interface is registered on the object server with [`ObjectServer::at_dynamic`] or
+/// [`connection::Builder::serve_dynamic_at`]. It's cheap to clone, so you can keep a copy around to
+/// emit signals.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+/// use zbus::{object_server::DynamicInterface, zvariant::Str};
+///
+/// # zbus::block_on(async {
+/// let name = Arc::new(Mutex::new(String::from("zbus")));
+/// let getter_name = name.clone();
+/// let setter_name = name.clone();
+/// let iface = DynamicInterface::new("org.zbus.Greeter1")?
+///     .method("SayHello", &["s"], &["s"], |msg, _| async move {
+///         let who: String = msg.body().deserialize()?;
+///
+///         Ok(vec![Str::from(format!("Hello {who}!")).into()])
+///     })?
+///     .property("Name", "s", move || {
+///         let name = getter_name.lock().unwrap().clone();
+///
+///         Ok(Str::from(name).into())
+///     })?
+///     .property_setter("Name", move |value| {
+///         *setter_name.lock().unwrap() = value.try_into().map_err(zbus::Error::from)?;
+///
+///         Ok(())
+///     })?
+///     .signal("Greeted", &["s"])?;
+///
+/// let _conn = zbus::connection::Builder::session()?
+///     .serve_dynamic_at("/org/zbus/Greeter", iface)?
+///     .build()
+///     .await?;
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`ObjectServer::at_dynamic`]: crate::ObjectServer::at_dynamic
+/// [`connection::Builder::serve_dynamic_at`]: crate::connection::Builder::serve_dynamic_at
+#[derive(Clone)]
+pub struct DynamicInterface {
+    name: InterfaceName<'static>,
+    methods: BTreeMap<String, DynamicMethod>,
+    properties: BTreeMap<String, DynamicProperty>,
+    signals: BTreeMap<String, Vec<Arg>>,
+}
+
+#[derive(Clone)]
+struct DynamicMethod {
+    in_args: Vec<Arg>,
+    out_args: Vec<Arg>,
+    handler: Option<MethodHandler>,
+}
+
+#[derive(Clone)]
+struct DynamicProperty {
+    signature: Signature,
+    read: bool,
+    write: bool,
+    getter: Option<PropertyGetter>,
+    setter: Option<PropertySetter>,
+}
+
+#[derive(Clone, Debug)]
+struct Arg {
+    name: Option<String>,
+    signature: Signature,
+}
+
+impl DynamicInterface {
+    /// Create an interface without any members.
+    pub fn new<N>(name: N) -> Result<Self>
+    where
+        N: TryInto<InterfaceName<'static>>,
+        N::Error: Into<Error>,
+    {
+        Ok(Self {
+            name: name.try_into().map_err(Into::into)?,
+            methods: BTreeMap::new(),
+            properties: BTreeMap::new(),
+            signals: BTreeMap::new(),
+        })
+    }
+
+    /// Create an interface with the members declared in an introspection document.
+    ///
+    /// The handlers of the methods and properties are to be attached with
+    /// [`DynamicInterface::method_handler`], [`DynamicInterface::property_getter`] and
+    /// [`DynamicInterface::property_setter`].
+    #[cfg(feature = "xml")]
+    pub fn from_xml(iface: &zbus_xml::Interface<'_>) -> Result<Self> {
+        use zbus_xml::ArgDirection;
+
+        let arg = |arg: &zbus_xml::Arg| Arg {
+            name: arg.name().map(ToString::to_string),
+            signature: arg.ty().inner().clone(),
+        };
+        let mut this = Self::new(iface.name().to_owned())?;
+        for method in iface.methods() {
+            let (out_args, in_args): (Vec<_>, Vec<_>) = method
+                .args()
+                .iter()
+                .partition(|a| a.direction() == Some(ArgDirection::Out));
+            this.methods.insert(
+                method.name().to_string(),
+                DynamicMethod {
+                    in_args: in_args.into_iter().map(arg).collect(),
+                    out_args: out_args.into_iter().map(arg).collect(),
+                    handler: None,
+                },
+            );
+        }
+        for property in iface.properties() {
+            let access = property.access();
+            this.properties.insert(
+                property.name().to_string(),
+                DynamicProperty {
+                    signature: property.ty().inner().clone(),
+                    read: access.read(),
+                    write: access.write(),
+                    getter: None,
+                    setter: None,
+                },
+            );
+        }
+        for signal in iface.signals() {
+            this.signals.insert(
+                signal.name().to_string(),
+                signal.args().iter().map(arg).collect(),
+            );
+        }
+
+        Ok(this)
+    }
+
+    /// The name of the interface.
+    pub fn name(&self) -> &InterfaceName<'static> {
+        &self.name
+    }
+
+    /// Declare a method, handled by `handler`.
+    ///
+    /// `in_args` and `out_args` are the signatures of each input and output argument respectively,
+    /// e.g `&["s", "u"]` for a string and an unsigned 32-bit integer, `&["(su)"]` for a single
+    /// structure of those, or `&[]` for none.
+    ///
+    /// The handler is called with the method call and a [`SignalEmitter`] for the object it was
+    /// made on, and returns the values of the reply. This replaces any previously declared method
+    /// with the same name.
+    pub fn method<'m, M, F, Fut>(
+        mut self,
+        name: M,
+        in_args: &[&str],
+        out_args: &[&str],
+        handler: F,
+    ) -> Result<Self>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        F: Fn(Message, SignalEmitter<'static>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<Vec<OwnedValue>>> + Send + 'static,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        self.methods.insert(
+            name.to_string(),
+            DynamicMethod {
+                in_args: args(in_args)?,
+                out_args: args(out_args)?,
+                handler: None,
+            },
+        );
+
+        self.method_handler(name, handler)
+    }
+
+    /// Set the handler of a declared method.
+    ///
+    /// See [`DynamicInterface::method`] for details. Fails if no such method was declared.
+    pub fn method_handler<'m, M, F, Fut>(mut self, name: M, handler: F) -> Result<Self>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        F: Fn(Message, SignalEmitter<'static>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = fdo::Result<Vec<OwnedValue>>> + Send + 'static,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        let method = self
+            .methods
+            .get_mut(name.as_str())
+            .ok_or_else(|| Error::Failure(format!("Unknown method `{name}`")))?;
+        method.handler = Some(Arc::new(move |msg, emitter| {
+            Box::pin(handler(msg, emitter)) as MethodFuture
+        }));
+
+        Ok(self)
+    }
+
+    /// Declare a read-only property, with the given signature and getter.
+    ///
+    /// Use [`DynamicInterface::property_setter`] to make it writable. This replaces any previously
+    /// declared property with the same name.
+    pub fn property<'p, P, S, G>(mut self, name: P, signature: S, getter: G) -> Result<Self>
+    where
+        P: TryInto<PropertyName<'p>>,
+        P::Error: Into<Error>,
+        S: TryInto<Signature>,
+        S::Error: Into<Error>,
+        G: Fn() -> fdo::Result<OwnedValue> + Send + Sync + 'static,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        let signature = signature.try_into().map_err(Into::into)?;
+        self.properties.insert(
+            name.to_string(),
+            DynamicProperty {
+                signature,
+                read: true,
+                write: false,
+                getter: None,
+                setter: None,
+            },
+        );
+
+        self.property_getter(name, getter)
+    }
+
+    /// Set the getter of a declared property.
+    ///
+    /// Fails if no such property was declared.
+    pub fn property_getter<'p, P, G>(mut self, name: P, getter: G) -> Result<Self>
+    where
+        P: TryInto<PropertyName<'p>>,
+        P::Error: Into<Error>,
+        G: Fn() -> fdo::Result<OwnedValue> + Send + Sync + 'static,
+    {
+        let property = self.property_mut(name)?;
+        property.read = true;
+        property.getter = Some(Arc::new(getter));
+
+        Ok(self)
+    }
+
+    /// Set the setter of a declared property, making it writable.
+    ///
+    /// The setter is only called with values of the declared signature. Fails if no such property
+    /// was declared.
+    pub fn property_setter<'p, P, S>(mut self, name: P, setter: S) -> Result<Self>
+    where
+        P: TryInto<PropertyName<'p>>,
+        P::Error: Into<Error>,
+        S: Fn(&Value<'_>) -> fdo::Result<()> + Send + Sync + 'static,
+    {
+        let property = self.property_mut(name)?;
+        property.write = true;
+        property.setter = Some(Arc::new(setter));
+
+        Ok(self)
+    }
+
+    /// Declare a signal, with the signatures of each of its arguments.
+    ///
+    /// See [`DynamicInterface::method`] for the form of `args`. This replaces any previously
+    /// declared signal with the same name.
+    pub fn signal<'m, M>(mut self, name: M, args: &[&str]) -> Result<Self>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+        let args = self::args(args)?;
+        self.signals.insert(name.to_string(), args);
+
+        Ok(self)
+    }
+
+    /// Emit a declared signal, with the given arguments.
+    ///
+    /// Fails if no such signal was declared or if the arguments don't match its signature.
+    pub async fn emit_signal(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        name: &str,
+        args: Vec<OwnedValue>,
+    ) -> Result<()> {
+        let signal = self
+            .signals
+            .get(name)
+            .ok_or_else(|| Error::Failure(format!("Unknown signal `{name}`")))?;
+        let body = body(signal, args)
+            .map_err(|e| Error::Failure(format!("Invalid arguments for signal `{name}`: {e}")))?;
+
+        emitter.emit(&self.name, name, &body).await
+    }
+
+    /// Emit the `PropertiesChanged` signal for the given properties.
+    ///
+    /// The new values of the readable properties are fetched from their getters, while the others
+    /// are invalidated.
+    pub async fn properties_changed(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        names: &[&str],
+    ) -> Result<()> {
+        let mut changed = HashMap::new();
+        let mut invalidated = Vec::new();
+        for name in names {
+            let property = self
+                .properties
+                .get(*name)
+                .ok_or_else(|| Error::Failure(format!("Unknown property `{name}`")))?;
+            match &property.getter {
+                Some(_) if property.read => {
+                    changed.insert(*name, Value::from(self.get_property(name, property)?));
+                }
+                _ => invalidated.push(*name),
+            }
+        }
+
+        fdo::Properties::properties_changed(emitter, self.name.clone(), changed, invalidated.into())
+            .await
+    }
+
+    fn property_mut<'p, P>(&mut self, name: P) -> Result<&mut DynamicProperty>
+    where
+        P: TryInto<PropertyName<'p>>,
+        P::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+
+        self.properties
+            .get_mut(name.as_str())
+            .ok_or_else(|| Error::Failure(format!("Unknown property `{name}`")))
+    }
+
+    fn get_property(&self, name: &str, property: &DynamicProperty) -> fdo::Result<OwnedValue> {
+        if !property.read {
+            return Err(fdo::Error::NotSupported(format!(
+                "Property `{name}` is not readable"
+            )));
+        }
+        let getter = property.getter.as_ref().ok_or_else(|| {
+            fdo::Error::NotSupported(format!("Property `{name}` is not implemented"))
+        })?;
+        let value = conform(getter()?, &property.signature)
+            .map_err(|e| fdo::Error::Failed(format!("Invalid value for property `{name}`: {e}")))?;
+
+        value
+            .try_into()
+            .map_err(|e: zvariant::Error| fdo::Error::Failed(e.to_string()))
+    }
+}
+
+impl fmt::Debug for DynamicInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicInterface")
+            .field("name", &self.name)
+            .field("methods", &self.methods.keys())
+            .field("properties", &self.properties.keys())
+            .field("signals", &self.signals.keys())
+            .finish()
+    }
+}
+
+/// Parse the signatures of arguments, each of a single complete type.
+fn args(signatures: &[&str]) -> Result<Vec<Arg>> {
+    signatures
+        .iter()
+        .map(|s| {
+            let signature = Signature::try_from(*s)?;
+            // Several types would be parsed as a structure of them.
+            if signature == Signature::Unit || signature.to_string() != *s {
+                return Err(Error::Failure(format!(
+                    "`{s}` is not the signature of a single argument"
+                )));
+            }
+
+            Ok(Arg {
+                name: None,
+                signature,
+            })
+        })
+        .collect()
+}
+
+/// The signature of a message body made of the given arguments.
+fn body_signature(args: &[Arg]) -> Signature {
+    DynamicBody::signature_of(args.iter().map(|a| a.signature.clone()).collect())
+}
+
+/// Make `value` of the given signature, wrapping it in a variant if need be.
+fn conform(
+    value: OwnedValue,
+    signature: &Signature,
+) -> std::result::Result<Value<'static>, String> {
+    let value = Value::from(value);
+    if value.value_signature() == signature {
+        Ok(value)
+    } else if *signature == Signature::Variant {
+        Ok(Value::Value(Box::new(value)))
+    } else {
+        Err(format!(
+            "expected `{signature}`, got `{}`",
+            value.value_signature()
+        ))
+    }
+}
+
+/// A message body made of the given values.
+fn body(
+    args: &[Arg],
+    values: Vec<OwnedValue>,
+) -> std::result::Result<DynamicBody<'static>, String> {
+    if args.len() != values.len() {
+        return Err(format!(
+            "expected {} values, got {}",
+            args.len(),
+            values.len()
+        ));
+    }
+    let values = args
+        .iter()
+        .zip(values)
+        .map(|(arg, value)| conform(value, &arg.signature))
+        .collect::<std::result::Result<_, _>>()?;
+
+    DynamicBody::new(values).map_err(|e| e.to_string())
+}
+
+/// A message body made of values only known at runtime.
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+pub(crate) enum DynamicBody<'b> {
+    Empty(()),
+    Values(Structure<'b>),
+}
+
+impl<'b> DynamicBody<'b> {
+    pub fn new(values: Vec<Value<'b>>) -> zvariant::Result<Self> {
+        if values.is_empty() {
+            return Ok(Self::Empty(()));
+        }
+
+        values
+            .into_iter()
+            .fold(StructureBuilder::new(), StructureBuilder::append_field)
+            .build()
+            .map(Self::Values)
+    }
+
+    /// The signature of a message body made of values of the given signatures.
+    pub fn signature_of(mut signatures: Vec<Signature>) -> Signature {
+        match signatures.len() {
+            0 => Signature::Unit,
+            1 => signatures.remove(0),
+            _ => Signature::structure(signatures),
+        }
+    }
+}
+
+impl zvariant::DynamicType for DynamicBody<'_> {
+    fn signature(&self) -> Signature {
+        match self {
+            Self::Empty(()) => Signature::Unit,
+            Self::Values(structure) => structure.signature().clone(),
+        }
+    }
+}
+
+/// The [`ErasedInterface`] implementation of a [`DynamicInterface`] registered on the object
+/// server.
+pub(crate) struct DynamicInstance(pub DynamicInterface);
+
+#[async_trait]
+impl ErasedInterface for DynamicInstance {
+    async fn get(
+        &self,
+        property_name: &str,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<OwnedValue>> {
+        let property = self.0.properties.get(property_name)?;
+
+        Some(self.0.get_property(property_name, property))
+    }
+
+    async fn get_all(
+        &self,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>> {
+        self.0
+            .properties
+            .iter()
+            .filter(|(_, property)| property.read && property.getter.is_some())
+            .map(|(name, property)| Ok((name.clone(), self.0.get_property(name, property)?)))
+            .collect()
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        _server: &'call ObjectServer,
+        _connection: &'call Connection,
+        _header: Option<&'call Header<'_>>,
+        emitter: &'call SignalEmitter<'_>,
+    ) -> DispatchResult<'call> {
+        let Some(property) = self.0.properties.get(property_name) else {
+            return DispatchResult::NotFound;
+        };
+
+        DispatchResult::Async(Box::pin(async move {
+            if !property.write {
+                return Err(fdo::Error::PropertyReadOnly(format!(
+                    "Property `{property_name}` is read-only"
+                ))
+                .into());
+            }
+            let setter = property.setter.as_ref().ok_or_else(|| {
+                fdo::Error::NotSupported(format!("Property `{property_name}` is not implemented"))
+            })?;
+            // The values of `v` properties are unwrapped, so they can be of any type.
+            if property.signature != Signature::Variant
+                && value.value_signature() != &property.signature
+            {
+                return Err(fdo::Error::InvalidArgs(format!(
+                    "Expected `{}` for property `{property_name}`, got `{}`",
+                    property.signature,
+                    value.value_signature(),
+                ))
+                .into());
+            }
+            setter(value)?;
+
+            self.0.properties_changed(emitter, &[property_name]).await
+        }))
+    }
+
+    async fn set_mut(
+        &mut self,
+        _property_name: &str,
+        _value: &Value<'_>,
+        _server: &ObjectServer,
+        _connection: &Connection,
+        _header: Option<&Header<'_>>,
+        _emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<()>> {
+        None
+    }
+
+    fn call<'call>(
+        &'call self,
+        _server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        let Some(method) = self.0.methods.get(name.as_str()) else {
+            return DispatchResult::NotFound;
+        };
+
+        DispatchResult::Async(Box::pin(async move {
+            let hdr = msg.header();
+            let reply = async {
+                let handler = method.handler.as_ref().ok_or_else(|| {
+                    fdo::Error::NotSupported(format!("Method `{name}` is not implemented"))
+                })?;
+                let expected = body_signature(&method.in_args);
+                let signature = msg.body().signature().clone();
+                if signature != expected {
+                    return Err(fdo::Error::InvalidArgs(format!(
+                        "Expected arguments of signature `{}`, got `{}`",
+                        expected.to_string_no_parens(),
+                        signature.to_string_no_parens(),
+                    )));
+                }
+                let path = hdr.path().ok_or(Error::MissingField)?;
+                let emitter = SignalEmitter::new(connection, path)?.into_owned();
+                let values = handler(msg.clone(), emitter).await?;
+
+                body(&method.out_args, values).map_err(|e| {
+                    fdo::Error::Failed(format!("Invalid reply from method `{name}`: {e}"))
+                })
+            }
+            .await;
+
+            if hdr.primary().flags().contains(Flags::NoReplyExpected) {
+                return Ok(());
+            }
+            match reply {
+                Ok(body) => connection.reply(&hdr, &body).await,
+                Err(e) => connection.reply_dbus_error(&hdr, e).await,
+            }
+        }))
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        _server: &'call ObjectServer,
+        _connection: &'call Connection,
+        _msg: &'call Message,
+        _name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        DispatchResult::NotFound
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        let write_args = |writer: &mut dyn Write, args: &[Arg], dir: &str, level: usize| {
+            for arg in args {
+                let name = match &arg.name {
+                    Some(name) => format!("name=\"{name}\" "),
+                    None => String::new(),
+                };
+                writeln!(
+                    writer,
+                    "{:indent$}<arg {name}type=\"{}\"{dir}/>",
+                    "",
+                    arg.signature,
+                    indent = level
+                )
+                .unwrap();
+            }
+        };
+
+        writeln!(
+            writer,
+            "{:indent$}<interface name=\"{}\">",
+            "",
+            self.0.name,
+            indent = level
+        )
+        .unwrap();
+        {
+            let level = level + 2;
+            for (name, method) in &self.0.methods {
+                writeln!(
+                    writer,
+                    "{:indent$}<method name=\"{name}\">",
+                    "",
+                    indent = level
+                )
+                .unwrap();
+                write_args(writer, &method.in_args, " direction=\"in\"", level + 2);
+                write_args(writer, &method.out_args, " direction=\"out\"", level + 2);
+                writeln!(writer, "{:indent$}</method>", "", indent = level).unwrap();
+            }
+            for (name, args) in &self.0.signals {
+                writeln!(
+                    writer,
+                    "{:indent$}<signal name=\"{name}\">",
+                    "",
+                    indent = level
+                )
+                .unwrap();
+                write_args(writer, args, "", level + 2);
+                writeln!(writer, "{:indent$}</signal>", "", indent = level).unwrap();
+            }
+            for (name, property) in &self.0.properties {
+                let access = match (property.read, property.write) {
+                    (true, true) => "readwrite",
+                    (false, true) => "write",
+                    _ => "read",
+                };
+                writeln!(
+                    writer,
+                    "{:indent$}<property name=\"{name}\" type=\"{}\" access=\"{access}\"/>",
+                    "",
+                    property.signature,
+                    indent = level
+                )
+                .unwrap();
+            }
+        }
+        writeln!(writer, "{:indent$}</interface>", "", indent = level).unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use std::{collections::HashMap, fmt, sync::Arc};
+use zbus_names::InterfaceName;
+use zvariant::{ObjectPath, OwnedObjectPath};
+
+use crate::fdo;
+
+use super::{ArcInterface, DynamicInstance, DynamicInterface, Interface};
+
+/// A handler for the objects under a given path that are resolved on demand.
+///
+/// Registering every object up front (through [`ObjectServer::at`]) isn't practical for services
+/// exposing a large or ever-changing number of objects, e.g one per row of a database table. A
+/// fallback handler can be registered at a path instead, with [`ObjectServer::fallback_at`] or
+/// [`connection::Builder::serve_fallback_at`]. Whenever a method call is made on an object below
+/// that path that isn't registered on the object server, the handler is asked to resolve it into
+/// its interfaces and the call is dispatched to those.
+///
+/// The interfaces are resolved for each call and dropped right after, so they should hold no
+/// state of their own but rather act on the underlying data (e.g the database row). The standard
+/// interfaces (Peer, Introspectable, Properties) are added to the resolved objects on your behalf.
+///
+/// [`Fallback::children`] enumerates the objects of the handler, for the purpose of introspection
+/// and of the `GetManagedObjects` method of the [`ObjectManager`] interface, if one is registered
+/// at or above the path of the handler.
+///
+/// # Example
+///
+/// ```no_run
+/// use zbus::{
+///     fdo,
+///     interface,
+///     object_server::{Fallback, ResolvedObject},
+///     zvariant::{ObjectPath, OwnedObjectPath},
+/// };
+///
+/// struct Row {
+///     id: u32,
+/// }
+///
+/// #[interface(name = "org.zbus.Row1")]
+/// impl Row {
+///     #[zbus(property)]
+///     fn id(&self) -> u32 {
+///         self.id
+///     }
+/// }
+///
+/// struct Table;
+///
+/// #[async_trait::async_trait]
+/// impl Fallback for Table {
+///     async fn resolve(&self, path: &ObjectPath<'_>) -> fdo::Result<Option<ResolvedObject>> {
+///         let id = path.strip_prefix("/org/zbus/Table/").and_then(|id| id.parse().ok());
+///
+///         Ok(id.map(|id| ResolvedObject::new().interface(Row { id })))
+///     }
+///
+///     async fn children(&self) -> fdo::Result<Vec<OwnedObjectPath>> {
+///         (0..3)
+///             .map(|id| Ok(ObjectPath::try_from(format!("/org/zbus/Table/{id}"))?.into()))
+///             .collect::<zbus::Result<_>>()
+///             .map_err(Into::into)
+///     }
+/// }
+///
+/// # zbus::block_on(async {
+/// let _conn = zbus::connection::Builder::session()?
+///     .serve_fallback_at("/org/zbus/Table", Table)?
+///     .build()
+///     .await?;
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`ObjectServer::at`]: crate::ObjectServer::at
+/// [`ObjectServer::fallback_at`]: crate::ObjectServer::fallback_at
+/// [`connection::Builder::serve_fallback_at`]: crate::connection::Builder::serve_fallback_at
+/// [`ObjectManager`]: crate::fdo::ObjectManager
+#[async_trait]
+pub trait Fallback: Send + Sync + 'static {
+    /// Resolve the object at `path`.
+    ///
+    /// `path` is always below the path the handler is registered at. Returns `None` if there is
+    /// no such object.
+    async fn resolve(&self, path: &ObjectPath<'_>) -> fdo::Result<Option<ResolvedObject>>;
+
+    /// The paths of all the objects of the handler.
+    ///
+    /// The default implementation returns none, i.e the objects are not discoverable.
+    async fn children(&self) -> fdo::Result<Vec<OwnedObjectPath>> {
+        Ok(vec![])
+    }
+}
+
+/// An object resolved by a [`Fallback`] handler.
+#[derive(Debug, Default)]
+pub struct ResolvedObject {
+    pub(crate) interfaces: HashMap<InterfaceName<'static>, ArcInterface>,
+}
+
+impl ResolvedObject {
+    /// Create an object without any interfaces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an interface to the object.
+    ///
+    /// This replaces any previously added interface with the same name.
+    pub fn interface<I>(mut self, iface: I) -> Self
+    where
+        I: Interface,
+    {
+        self.interfaces.insert(I::name(), ArcInterface::new(iface));
+
+        self
+    }
+
+    /// Add a [`DynamicInterface`] to the object.
+    ///
+    /// This replaces any previously added interface with the same name.
+    pub fn dynamic_interface(mut self, iface: DynamicInterface) -> Self {
+        let name = iface.name().clone();
+        self.interfaces.insert(
+            name.clone(),
+            ArcInterface::with_name(name, DynamicInstance(iface)),
+        );
+
+        self
+    }
+}
+
+/// A type for a reference-counted [`Fallback`] trait-object, with a manual Debug impl.
+#[derive(Clone)]
+pub(crate) struct ArcFallback(pub Arc<dyn Fallback>);
+
+impl ArcFallback {
+    pub fn new<F>(fallback: F) -> Self
+    where
+        F: Fallback,
+    {
+        Self(Arc::new(fallback))
+    }
+}
+
+impl fmt::Debug for ArcFallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arc<dyn Fallback>").finish_non_exhaustive()
+    }
+}
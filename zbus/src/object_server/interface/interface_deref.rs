@@ -5,11 +5,11 @@ use std::{
 
 use crate::async_lock::{RwLockReadGuard, RwLockWriteGuard};
 
-use super::Interface;
+use super::{ErasedInterface, Interface};
 
 /// Opaque structure that derefs to an `Interface` type.
 pub struct InterfaceDeref<'d, I> {
-    pub(super) iface: RwLockReadGuard<'d, dyn Interface>,
+    pub(super) iface: RwLockReadGuard<'d, dyn ErasedInterface>,
     pub(super) phantom: PhantomData<I>,
 }
 
@@ -26,7 +26,7 @@ where
 
 /// Opaque structure that mutably derefs to an `Interface` type.
 pub struct InterfaceDerefMut<'d, I> {
-    pub(super) iface: RwLockWriteGuard<'d, dyn Interface>,
+    pub(super) iface: RwLockWriteGuard<'d, dyn ErasedInterface>,
     pub(super) phantom: PhantomData<I>,
 }
 
@@ -1,6 +1,6 @@
-use std::{marker::PhantomData, sync::Arc};
+use std::{marker::PhantomData, sync::Arc, time::Duration};
 
-use super::{Interface, InterfaceDeref, InterfaceDerefMut, SignalEmitter};
+use super::{ErasedInterface, InterfaceDeref, InterfaceDerefMut, SignalEmitter};
 use crate::async_lock::RwLock;
 
 /// Wrapper over an interface, along with its corresponding `SignalEmitter`
@@ -8,7 +8,7 @@ use crate::async_lock::RwLock;
 /// [`InterfaceRef::get`] and [`InterfaceRef::get_mut`].
 pub struct InterfaceRef<I> {
     pub(crate) emitter: SignalEmitter<'static>,
-    pub(crate) lock: Arc<RwLock<dyn Interface>>,
+    pub(crate) lock: Arc<RwLock<dyn ErasedInterface>>,
     pub(crate) phantom: PhantomData<I>,
 }
 
@@ -99,6 +99,27 @@ where
         &self.emitter
     }
 
+    /// Create a clone of `self` whose signal emitter batches the `PropertiesChanged` signals.
+    ///
+    /// See [`SignalEmitter::batched`] for details.
+    pub fn batched(&self) -> Self {
+        Self {
+            emitter: self.emitter.batched(),
+            ..self.clone()
+        }
+    }
+
+    /// Create a clone of `self` whose signal emitter batches the `PropertiesChanged` signals
+    /// within a time window.
+    ///
+    /// See [`SignalEmitter::batched_for`] for details.
+    pub fn batched_for(&self, window: Duration) -> Self {
+        Self {
+            emitter: self.emitter.batched_for(window),
+            ..self.clone()
+        }
+    }
+
     #[deprecated(since = "0.5.0", note = "Please use `signal_emitter` instead.")]
     pub fn signal_context(&self) -> &SignalEmitter<'static> {
         &self.emitter
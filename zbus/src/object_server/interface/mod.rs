@@ -20,7 +20,7 @@ use crate::{
     async_lock::RwLock,
     fdo,
     message::{self, Header, Message},
-    object_server::SignalEmitter,
+    object_server::{AccessControl, ConcurrencyLimits, SignalEmitter},
     Connection, ObjectServer,
 };
 
@@ -136,15 +136,277 @@ pub trait Interface: Any + Send + Sync {
         name: MemberName<'call>,
     ) -> DispatchResult<'call>;
 
+    /// The access control of the interface, if `method` is `None`, or of the given method.
+    ///
+    /// The [`ObjectServer`] checks the one of a method before calling it, after its own access
+    /// control (if any) allowed the call. The one of the interface is checked for the accesses to
+    /// its properties through `org.freedesktop.DBus.Properties`. The default implementation returns
+    /// `None`.
+    fn access_control(
+        &self,
+        method: Option<&MemberName<'_>>,
+    ) -> Option<&'static dyn AccessControl> {
+        let _ = method;
+
+        None
+    }
+
+    /// The ID of the [polkit] action the caller of the given method must be authorized for, if any.
+    ///
+    /// The [`ObjectServer`] checks it with [`fdo::polkit::check_authorization`] after the access
+    /// controls allowed the call. The default implementation returns `None`.
+    ///
+    /// [polkit]: https://www.freedesktop.org/software/polkit/docs/latest/
+    /// [`fdo::polkit::check_authorization`]: crate::fdo::polkit::check_authorization
+    fn polkit_action(&self, method: &MemberName<'_>) -> Option<&'static str> {
+        let _ = method;
+
+        None
+    }
+
+    /// The concurrency limits of the interface, if `method` is `None`, or of the given method.
+    ///
+    /// See [`ConcurrencyLimits`] for details. The limits of each interface and method are read
+    /// once, on the first call handled by the [`ObjectServer`]. The default implementation returns
+    /// `None`, i.e no limits.
+    fn concurrency_limits(&self, method: Option<&MemberName<'_>>) -> Option<ConcurrencyLimits> {
+        let _ = method;
+
+        None
+    }
+
+    /// Whether the call of the given method is cancelled if the caller disconnects.
+    ///
+    /// When connected to a bus, the [`ObjectServer`] keeps track of the callers of the methods
+    /// being called and drops the futures of the method calls whose caller left the bus, as
+    /// nobody would receive the reply anyway. Note that a method can only be cancelled while it
+    /// is waiting (i.e at an `.await`), so blocking methods always run to completion. The default
+    /// implementation returns `true`.
+    fn cancellable(&self, method: &MemberName<'_>) -> bool {
+        let _ = method;
+
+        true
+    }
+
     /// Write introspection XML to the writer, with the given indentation level.
     fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize);
 }
 
-/// A type for a reference-counted Interface trait-object, with associated run-time details and a
+/// The [`Interface`] methods used by the [`ObjectServer`] to dispatch messages, without the name of
+/// the interface.
+///
+/// It's implemented for all [`Interface`] types, and for the interfaces whose name is only known
+/// at runtime, which can't implement [`Interface::name`].
+#[async_trait]
+pub(crate) trait ErasedInterface: Any + Send + Sync {
+    fn spawn_tasks_for_methods(&self) -> bool {
+        true
+    }
+
+    async fn get(
+        &self,
+        property_name: &str,
+        server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&message::Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<OwnedValue>>;
+
+    async fn get_all(
+        &self,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&message::Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>>;
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        object_server: &'call ObjectServer,
+        connection: &'call Connection,
+        header: Option<&'call message::Header<'_>>,
+        emitter: &'call SignalEmitter<'_>,
+    ) -> DispatchResult<'call>;
+
+    async fn set_mut(
+        &mut self,
+        property_name: &str,
+        value: &Value<'_>,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<()>>;
+
+    fn call<'call>(
+        &'call self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call>;
+
+    fn call_mut<'call>(
+        &'call mut self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call>;
+
+    fn access_control(
+        &self,
+        method: Option<&MemberName<'_>>,
+    ) -> Option<&'static dyn AccessControl> {
+        let _ = method;
+
+        None
+    }
+
+    fn polkit_action(&self, method: &MemberName<'_>) -> Option<&'static str> {
+        let _ = method;
+
+        None
+    }
+
+    fn concurrency_limits(&self, method: Option<&MemberName<'_>>) -> Option<ConcurrencyLimits> {
+        let _ = method;
+
+        None
+    }
+
+    fn cancellable(&self, method: &MemberName<'_>) -> bool {
+        let _ = method;
+
+        true
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize);
+}
+
+#[async_trait]
+impl<I> ErasedInterface for I
+where
+    I: Interface,
+{
+    fn spawn_tasks_for_methods(&self) -> bool {
+        Interface::spawn_tasks_for_methods(self)
+    }
+
+    async fn get(
+        &self,
+        property_name: &str,
+        server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&message::Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<OwnedValue>> {
+        Interface::get(self, property_name, server, connection, header, emitter).await
+    }
+
+    async fn get_all(
+        &self,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&message::Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> fdo::Result<HashMap<String, OwnedValue>> {
+        Interface::get_all(self, object_server, connection, header, emitter).await
+    }
+
+    fn set<'call>(
+        &'call self,
+        property_name: &'call str,
+        value: &'call Value<'_>,
+        object_server: &'call ObjectServer,
+        connection: &'call Connection,
+        header: Option<&'call message::Header<'_>>,
+        emitter: &'call SignalEmitter<'_>,
+    ) -> DispatchResult<'call> {
+        Interface::set(
+            self,
+            property_name,
+            value,
+            object_server,
+            connection,
+            header,
+            emitter,
+        )
+    }
+
+    async fn set_mut(
+        &mut self,
+        property_name: &str,
+        value: &Value<'_>,
+        object_server: &ObjectServer,
+        connection: &Connection,
+        header: Option<&Header<'_>>,
+        emitter: &SignalEmitter<'_>,
+    ) -> Option<fdo::Result<()>> {
+        Interface::set_mut(
+            self,
+            property_name,
+            value,
+            object_server,
+            connection,
+            header,
+            emitter,
+        )
+        .await
+    }
+
+    fn call<'call>(
+        &'call self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        Interface::call(self, server, connection, msg, name)
+    }
+
+    fn call_mut<'call>(
+        &'call mut self,
+        server: &'call ObjectServer,
+        connection: &'call Connection,
+        msg: &'call Message,
+        name: MemberName<'call>,
+    ) -> DispatchResult<'call> {
+        Interface::call_mut(self, server, connection, msg, name)
+    }
+
+    fn access_control(
+        &self,
+        method: Option<&MemberName<'_>>,
+    ) -> Option<&'static dyn AccessControl> {
+        Interface::access_control(self, method)
+    }
+
+    fn polkit_action(&self, method: &MemberName<'_>) -> Option<&'static str> {
+        Interface::polkit_action(self, method)
+    }
+
+    fn concurrency_limits(&self, method: Option<&MemberName<'_>>) -> Option<ConcurrencyLimits> {
+        Interface::concurrency_limits(self, method)
+    }
+
+    fn cancellable(&self, method: &MemberName<'_>) -> bool {
+        Interface::cancellable(self, method)
+    }
+
+    fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+        Interface::introspect_to_writer(self, writer, level)
+    }
+}
+
+/// A type for a reference-counted interface trait-object, with associated run-time details and a
 /// manual Debug impl.
 #[derive(Clone)]
 pub(crate) struct ArcInterface {
-    pub instance: Arc<RwLock<dyn Interface>>,
+    pub name: InterfaceName<'static>,
+    pub instance: Arc<RwLock<dyn ErasedInterface>>,
     pub spawn_tasks_for_methods: bool,
 }
 
@@ -152,9 +414,18 @@ impl ArcInterface {
     pub fn new<I>(iface: I) -> Self
     where
         I: Interface,
+    {
+        Self::with_name(I::name(), iface)
+    }
+
+    /// For interfaces whose name is only known at runtime.
+    pub fn with_name<I>(name: InterfaceName<'static>, iface: I) -> Self
+    where
+        I: ErasedInterface,
     {
         let spawn_tasks_for_methods = iface.spawn_tasks_for_methods();
         Self {
+            name,
             instance: Arc::new(RwLock::new(iface)),
             spawn_tasks_for_methods,
         }
@@ -164,6 +435,7 @@ impl ArcInterface {
 impl fmt::Debug for ArcInterface {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Arc<RwLock<dyn Interface>>")
+            .field("name", &self.name)
             .finish_non_exhaustive()
     }
 }
@@ -173,14 +445,14 @@ impl fmt::Debug for ArcInterface {
 // just check the type ID and do the downcast ourself.
 //
 // See https://github.com/rust-lang/rust/issues/65991 for a rustc feature that will make it
-// possible to get a `dyn Any` ref directly from a `dyn Interface` ref; once that is stable, we can
-// remove this unsafe code.
-impl dyn Interface {
+// possible to get a `dyn Any` ref directly from a `dyn ErasedInterface` ref; once that is stable,
+// we can remove this unsafe code.
+impl dyn ErasedInterface {
     /// Return Any of self
     pub(crate) fn downcast_ref<T: Any>(&self) -> Option<&T> {
-        if <dyn Interface as Any>::type_id(self) == TypeId::of::<T>() {
+        if <dyn ErasedInterface as Any>::type_id(self) == TypeId::of::<T>() {
             // SAFETY: If type ID matches, it means object is of type T
-            Some(unsafe { &*(self as *const dyn Interface as *const T) })
+            Some(unsafe { &*(self as *const dyn ErasedInterface as *const T) })
         } else {
             None
         }
@@ -188,9 +460,9 @@ impl dyn Interface {
 
     /// Return Any of self
     pub(crate) fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
-        if <dyn Interface as Any>::type_id(self) == TypeId::of::<T>() {
+        if <dyn ErasedInterface as Any>::type_id(self) == TypeId::of::<T>() {
             // SAFETY: If type ID matches, it means object is of type T
-            Some(unsafe { &mut *(self as *mut dyn Interface as *mut T) })
+            Some(unsafe { &mut *(self as *mut dyn ErasedInterface as *mut T) })
         } else {
             None
         }
@@ -0,0 +1,377 @@
+use event_listener::Event;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use zbus_names::{InterfaceName, MemberName};
+
+use crate::{async_lock, fdo, message::Header, names::OwnedUniqueName};
+
+/// Concurrency limits for the method calls of an interface, or of one of its methods.
+///
+/// By default, the [`ObjectServer`] handles all the method calls it receives as they come (see
+/// [`Interface::spawn_tasks_for_methods`]), so a client flooding it with calls can make it use an
+/// unbounded amount of resources, to the detriment of the other clients. Limits are a way to
+/// prevent that:
+///
+/// * [`ConcurrencyLimits::max_in_flight`] is the maximum number of calls handled at the same time.
+///   The calls beyond that are queued until a call being handled completes.
+/// * [`ConcurrencyLimits::max_queued`] is the maximum number of calls waiting in the queue. The
+///   calls beyond that are rejected with an `org.freedesktop.DBus.Error.LimitsExceeded` error.
+/// * [`ConcurrencyLimits::max_per_sender`] is the maximum number of calls from the same sender
+///   that are handled or queued at the same time, so a single client can't take all the capacity.
+///   The calls beyond that are rejected with an `org.freedesktop.DBus.Error.LimitsExceeded` error.
+///
+/// When a call completes, the queued call whose sender has the fewest calls being handled goes
+/// next, so the queue is shared fairly between the senders.
+///
+/// The limits are set with the `limits` attribute of the [`macro@crate::interface`] macro, or
+/// through [`Interface::concurrency_limits`]. The limits of an interface are enforced for all the
+/// objects implementing it together, and apply on top of the limits of its methods.
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`Interface::spawn_tasks_for_methods`]: crate::object_server::Interface::spawn_tasks_for_methods
+/// [`Interface::concurrency_limits`]: crate::object_server::Interface::concurrency_limits
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    max_in_flight: Option<usize>,
+    max_queued: Option<usize>,
+    max_per_sender: Option<usize>,
+}
+
+impl ConcurrencyLimits {
+    /// Create limits that don't limit anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of calls handled at the same time.
+    ///
+    /// Unlimited by default.
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max);
+
+        self
+    }
+
+    /// Set the maximum number of calls waiting to be handled.
+    ///
+    /// Unlimited by default. Only relevant if [`ConcurrencyLimits::max_in_flight`] is set.
+    pub fn max_queued(mut self, max: usize) -> Self {
+        self.max_queued = Some(max);
+
+        self
+    }
+
+    /// Set the maximum number of calls from the same sender, handled or waiting to be.
+    ///
+    /// Unlimited by default.
+    pub fn max_per_sender(mut self, max: usize) -> Self {
+        self.max_per_sender = Some(max);
+
+        self
+    }
+
+    /// The maximum number of calls handled at the same time, if any.
+    pub fn in_flight_limit(&self) -> Option<usize> {
+        self.max_in_flight
+    }
+
+    /// The maximum number of calls waiting to be handled, if any.
+    pub fn queued_limit(&self) -> Option<usize> {
+        self.max_queued
+    }
+
+    /// The maximum number of calls from the same sender, if any.
+    pub fn per_sender_limit(&self) -> Option<usize> {
+        self.max_per_sender
+    }
+}
+
+type LimiterKey = (InterfaceName<'static>, Option<MemberName<'static>>);
+
+/// The limiters of the interfaces and methods served by the object server.
+#[derive(Debug, Default)]
+pub(crate) struct Limiters {
+    limiters: async_lock::Mutex<HashMap<LimiterKey, Arc<Limiter>>>,
+}
+
+impl Limiters {
+    /// Wait for the method call of the given `header` to be allowed to run under `limits`.
+    ///
+    /// `member` is `None` for the limits of the whole interface. The call can go on for as long as
+    /// the returned permit is kept around.
+    pub async fn acquire(
+        &self,
+        header: &Header<'_>,
+        interface: &InterfaceName<'_>,
+        member: Option<&MemberName<'_>>,
+        limits: ConcurrencyLimits,
+    ) -> fdo::Result<Permit> {
+        let key = (interface.to_owned(), member.map(|m| m.to_owned()));
+        let limiter = self
+            .limiters
+            .lock()
+            .await
+            .entry(key)
+            .or_insert_with(|| Arc::new(Limiter::new(limits)))
+            .clone();
+        let sender = header.sender().map(|s| s.to_owned().into());
+
+        limiter.acquire(sender).await
+    }
+}
+
+/// The limiter of an interface or a method.
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    limits: ConcurrencyLimits,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    in_flight: usize,
+    // The calls of each sender: `(in_flight, queued)`.
+    senders: HashMap<Option<OwnedUniqueName>, (usize, usize)>,
+    queue: VecDeque<Arc<Waiter>>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    sender: Option<OwnedUniqueName>,
+    granted: AtomicBool,
+    event: Event,
+}
+
+impl Limiter {
+    fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            limits,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    async fn acquire(self: Arc<Self>, sender: Option<OwnedUniqueName>) -> fdo::Result<Permit> {
+        let waiter = {
+            let mut state = self.state.lock().expect("poisoned lock");
+            let (in_flight, queued) = state.senders.get(&sender).copied().unwrap_or_default();
+            if self
+                .limits
+                .max_per_sender
+                .is_some_and(|max| in_flight + queued >= max)
+            {
+                return Err(fdo::Error::LimitsExceeded(
+                    "Too many pending calls from the same sender".into(),
+                ));
+            }
+
+            if self
+                .limits
+                .max_in_flight
+                .map_or(true, |max| state.in_flight < max)
+            {
+                state.in_flight += 1;
+                state.senders.entry(sender.clone()).or_default().0 += 1;
+
+                return Ok(Permit {
+                    limiter: self.clone(),
+                    sender,
+                });
+            }
+
+            if self
+                .limits
+                .max_queued
+                .is_some_and(|max| state.queue.len() >= max)
+            {
+                return Err(fdo::Error::LimitsExceeded("Too many pending calls".into()));
+            }
+            let waiter = Arc::new(Waiter {
+                sender: sender.clone(),
+                granted: AtomicBool::new(false),
+                event: Event::new(),
+            });
+            state.queue.push_back(waiter.clone());
+            state.senders.entry(sender).or_default().1 += 1;
+
+            waiter
+        };
+
+        // Leave the queue, or give back the slot if it was granted already, if the call is
+        // dropped while waiting.
+        let mut queued = Queued {
+            limiter: self.clone(),
+            waiter: Some(waiter.clone()),
+        };
+        while !waiter.granted.load(Ordering::Acquire) {
+            let listener = waiter.event.listen();
+            if waiter.granted.load(Ordering::Acquire) {
+                break;
+            }
+            listener.await;
+        }
+        queued.waiter = None;
+
+        Ok(Permit {
+            limiter: self,
+            sender: waiter.sender.clone(),
+        })
+    }
+
+    /// Give back a slot, granting it to the next queued call if any.
+    fn release(&self, sender: &Option<OwnedUniqueName>) {
+        let mut state = self.state.lock().expect("poisoned lock");
+        state.in_flight -= 1;
+        state.remove_in_flight(sender);
+
+        while self
+            .limits
+            .max_in_flight
+            .map_or(true, |max| state.in_flight < max)
+        {
+            // The next waiter is the one whose sender has the fewest calls in flight.
+            let Some(next) = state
+                .queue
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, w)| state.senders.get(&w.sender).map_or(0, |s| s.0))
+                .map(|(i, _)| i)
+            else {
+                break;
+            };
+            let waiter = state.queue.remove(next).expect("valid index");
+            state.in_flight += 1;
+            let counts = state.senders.entry(waiter.sender.clone()).or_default();
+            counts.0 += 1;
+            counts.1 -= 1;
+            waiter.granted.store(true, Ordering::Release);
+            waiter.event.notify(1);
+        }
+    }
+}
+
+impl State {
+    fn remove_in_flight(&mut self, sender: &Option<OwnedUniqueName>) {
+        if let Some(counts) = self.senders.get_mut(sender) {
+            counts.0 -= 1;
+            if *counts == (0, 0) {
+                self.senders.remove(sender);
+            }
+        }
+    }
+}
+
+/// A call waiting in the queue of a limiter.
+struct Queued {
+    limiter: Arc<Limiter>,
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+
+        // Slots are granted with the lock held.
+        let mut state = self.limiter.state.lock().expect("poisoned lock");
+        if waiter.granted.load(Ordering::Acquire) {
+            drop(state);
+            self.limiter.release(&waiter.sender);
+
+            return;
+        }
+        state.queue.retain(|w| !Arc::ptr_eq(w, &waiter));
+        if let Some(counts) = state.senders.get_mut(&waiter.sender) {
+            counts.1 -= 1;
+            if *counts == (0, 0) {
+                state.senders.remove(&waiter.sender);
+            }
+        }
+    }
+}
+
+/// The permission for a method call to run, given back when dropped.
+#[derive(Debug)]
+pub(crate) struct Permit {
+    limiter: Arc<Limiter>,
+    sender: Option<OwnedUniqueName>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.limiter.release(&self.sender);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::poll;
+    use ntest::timeout;
+    use std::task::Poll;
+    use test_log::test;
+
+    use super::*;
+
+    fn sender(name: &'static str) -> Option<OwnedUniqueName> {
+        Some(OwnedUniqueName::try_from(name).unwrap())
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn limiter() {
+        crate::block_on(async {
+            let limits = ConcurrencyLimits::new()
+                .max_in_flight(2)
+                .max_queued(2)
+                .max_per_sender(3);
+            let limiter = Arc::new(Limiter::new(limits));
+            let (a, b, c) = (sender(":1.1"), sender(":1.2"), sender(":1.3"));
+
+            let a1 = limiter.clone().acquire(a.clone()).await.unwrap();
+            let a2 = limiter.clone().acquire(a.clone()).await.unwrap();
+            let mut a3 = Box::pin(limiter.clone().acquire(a.clone()));
+            assert!(poll!(&mut a3).is_pending());
+            let mut b1 = Box::pin(limiter.clone().acquire(b.clone()));
+            assert!(poll!(&mut b1).is_pending());
+
+            // Too many calls from `a`.
+            assert!(matches!(
+                limiter.clone().acquire(a.clone()).await,
+                Err(fdo::Error::LimitsExceeded(_))
+            ));
+            // The queue is full.
+            assert!(matches!(
+                limiter.clone().acquire(c.clone()).await,
+                Err(fdo::Error::LimitsExceeded(_))
+            ));
+
+            // `b` has fewer calls in flight than `a`, so it goes first even though it came last.
+            drop(a1);
+            let b1 = b1.await.unwrap();
+            assert!(poll!(&mut a3).is_pending());
+
+            // Calls leave the queue when dropped.
+            let mut c1 = Box::pin(limiter.clone().acquire(c.clone()));
+            assert!(poll!(&mut c1).is_pending());
+            drop(c1);
+            drop(b1);
+            let a3 = a3.await.unwrap();
+            let mut c2 = Box::pin(limiter.clone().acquire(c.clone()));
+            assert!(poll!(&mut c2).is_pending());
+
+            drop((a2, a3));
+            let Poll::Ready(Ok(_c2)) = poll!(&mut c2) else {
+                panic!("c2 should have been granted a slot");
+            };
+            let state = limiter.state.lock().unwrap();
+            assert_eq!(state.in_flight, 1);
+            assert!(state.queue.is_empty());
+            assert_eq!(state.senders.len(), 1);
+        });
+    }
+}
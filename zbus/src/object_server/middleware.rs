@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::{fdo, message::Header, Message};
+
+/// A layer around the method calls dispatched by the [`ObjectServer`].
+///
+/// Middleware get to act on every method call handled by the object server, no matter the
+/// interface, which makes them the place for cross-cutting concerns like logging, metrics,
+/// authorization or rate limiting:
+///
+/// * [`Middleware::before`] is called before the method is, and can reject the call by returning an
+///   error. The error is then returned to the caller and the method isn't called.
+/// * [`Middleware::after`] is called once the call has been handled, with its result and the time
+///   it took.
+///
+/// Middleware can be added to the whole object server (see [`ObjectServer::add_middleware`] and
+/// [`connection::Builder::middleware`]) or to the objects at and below a given path (see
+/// [`ObjectServer::add_middleware_at`] and [`connection::Builder::middleware_at`]). They are
+/// layered in the order they were added: the `before` hooks are called in that order and the
+/// `after` hooks in reverse order. Only the `after` hooks of the middleware whose `before` hook
+/// was called and succeeded are called.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use zbus::{fdo, message::Header, object_server::Middleware, Message};
+///
+/// struct Logger;
+///
+/// #[async_trait::async_trait]
+/// impl Middleware for Logger {
+///     async fn after(
+///         &self,
+///         _msg: &Message,
+///         header: &Header<'_>,
+///         result: &fdo::Result<()>,
+///         elapsed: Duration,
+///     ) {
+///         let member = header.member().unwrap();
+///         match result {
+///             Ok(()) => println!("`{member}` succeeded in {elapsed:?}"),
+///             Err(e) => println!("`{member}` failed in {elapsed:?}: {e}"),
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`ObjectServer::add_middleware`]: crate::ObjectServer::add_middleware
+/// [`ObjectServer::add_middleware_at`]: crate::ObjectServer::add_middleware_at
+/// [`connection::Builder::middleware`]: crate::connection::Builder::middleware
+/// [`connection::Builder::middleware_at`]: crate::connection::Builder::middleware_at
+#[async_trait]
+pub trait Middleware: Send + Sync + 'static {
+    /// Called before the method call of the given `header` is dispatched.
+    ///
+    /// This happens before the access controls are checked. The default implementation lets all
+    /// calls through.
+    async fn before(&self, msg: &Message, header: &Header<'_>) -> fdo::Result<()> {
+        let _ = (msg, header);
+
+        Ok(())
+    }
+
+    /// Called once the method call of the given `header` has been handled.
+    ///
+    /// `result` is an error if the method returned one, or if the call couldn't be made at all
+    /// (e.g the method doesn't exist or the call was rejected). It's an
+    /// `org.freedesktop.DBus.Error.NoReply` error if the call was cancelled because the caller
+    /// disconnected (see [`Interface::cancellable`]). `elapsed` is the time the call took to be
+    /// handled, not counting the `before` hooks. For methods replying through a [`ReplyHandle`],
+    /// the call is handled once the reply is sent. The default implementation does nothing.
+    ///
+    /// [`Interface::cancellable`]: crate::object_server::Interface::cancellable
+    /// [`ReplyHandle`]: crate::object_server::ReplyHandle
+    async fn after(
+        &self,
+        msg: &Message,
+        header: &Header<'_>,
+        result: &fdo::Result<()>,
+        elapsed: Duration,
+    ) {
+        let _ = (msg, header, result, elapsed);
+    }
+
+    /// Whether a panic of the method should be turned into an error.
+    ///
+    /// If any of the middleware applicable to a call returns `true`, a panic while handling the
+    /// call is caught and an `org.freedesktop.DBus.Error.Failed` error is returned to the caller
+    /// (and passed to the `after` hooks) instead. Otherwise, the panic propagates as usual. The
+    /// default implementation returns `false`.
+    fn catch_panics(&self) -> bool {
+        false
+    }
+}
+
+/// A [`Middleware`] that turns panics of methods into errors.
+///
+/// See [`Middleware::catch_panics`] for details.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CatchPanics;
+
+#[async_trait]
+impl Middleware for CatchPanics {
+    fn catch_panics(&self) -> bool {
+        true
+    }
+}
+
+/// A type for a reference-counted [`Middleware`] trait-object, with a manual Debug impl.
+#[derive(Clone)]
+pub(crate) struct ArcMiddleware(pub Arc<dyn Middleware>);
+
+impl ArcMiddleware {
+    pub fn new<M>(middleware: M) -> Self
+    where
+        M: Middleware,
+    {
+        Self(Arc::new(middleware))
+    }
+}
+
+impl fmt::Debug for ArcMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arc<dyn Middleware>")
+            .finish_non_exhaustive()
+    }
+}
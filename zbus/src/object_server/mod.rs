@@ -1,25 +1,65 @@
 //! The object server API.
 
-use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+use futures_util::FutureExt;
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    panic::AssertUnwindSafe,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tracing::{debug, instrument, trace, trace_span, Instrument};
 
 use static_assertions::assert_impl_all;
-use zbus_names::InterfaceName;
-use zvariant::{ObjectPath, Value};
+use zbus_names::{InterfaceName, MemberName};
+use zvariant::{ObjectPath, OwnedObjectPath, Value};
 
 use crate::{
     async_lock::RwLock,
-    connection::WeakConnection,
+    connection::{WatchedReply, WeakConnection},
     fdo,
-    fdo::ObjectManager,
-    message::{Header, Message},
+    fdo::{Introspectable, ManagedObjects, ObjectManager},
+    message::{Flags, Header, Message, Type},
     Connection, Error, Result,
 };
 
+mod access_control;
+pub use access_control::AccessControl;
+pub(crate) use access_control::ArcAccessControl;
+use access_control::CredentialsCache;
+
+mod dynamic;
+#[cfg(feature = "xml")]
+pub(crate) use dynamic::DynamicBody;
+pub(crate) use dynamic::DynamicInstance;
+pub use dynamic::DynamicInterface;
+
+mod fallback;
+pub(crate) use fallback::ArcFallback;
+pub use fallback::{Fallback, ResolvedObject};
+
 mod interface;
-pub(crate) use interface::ArcInterface;
+pub(crate) use interface::{ArcInterface, ErasedInterface};
 pub use interface::{DispatchResult, Interface, InterfaceDeref, InterfaceDerefMut, InterfaceRef};
 
+mod limits;
+pub use limits::ConcurrencyLimits;
+use limits::{Limiters, Permit};
+
+mod middleware;
+pub(crate) use middleware::ArcMiddleware;
+pub use middleware::{CatchPanics, Middleware};
+
+mod pending_calls;
+use pending_calls::PendingCalls;
+
+mod properties_batch;
+
+mod reply_handle;
+pub use reply_handle::ReplyHandle;
+
 mod signal_emitter;
 pub use signal_emitter::SignalEmitter;
 #[deprecated(since = "5.0.0", note = "Please use `SignalEmitter` instead.")]
@@ -92,6 +132,12 @@ pub(crate) use node::Node;
 pub struct ObjectServer {
     conn: WeakConnection,
     root: Arc<RwLock<Node>>,
+    access_control: Arc<RwLock<Option<ArcAccessControl>>>,
+    // The middleware, in the order they were added, along with the path they apply at and below.
+    middleware: Arc<RwLock<Vec<(OwnedObjectPath, ArcMiddleware)>>>,
+    credentials: Arc<CredentialsCache>,
+    pending_calls: Arc<PendingCalls>,
+    limiters: Arc<Limiters>,
 }
 
 assert_impl_all!(ObjectServer: Send, Sync, Unpin);
@@ -104,6 +150,11 @@ impl ObjectServer {
             root: Arc::new(RwLock::new(Node::new(
                 "/".try_into().expect("zvariant bug"),
             ))),
+            access_control: Arc::new(RwLock::new(None)),
+            middleware: Arc::default(),
+            credentials: Arc::default(),
+            pending_calls: Arc::default(),
+            limiters: Arc::default(),
         }
     }
 
@@ -111,6 +162,15 @@ impl ObjectServer {
         &self.root
     }
 
+    /// Forget about the callers, after the connection reconnected to the bus.
+    ///
+    /// Their calls can't be replied to anymore, and their unique names may now belong to other
+    /// peers.
+    pub(crate) async fn forget_callers(&self) {
+        self.credentials.clear().await;
+        self.pending_calls.cancel_all().await;
+    }
+
     /// Register a D-Bus [`Interface`] at a given path (see the example above).
     ///
     /// Typically you'd want your interfaces to be registered immediately after the associated
@@ -125,14 +185,27 @@ impl ObjectServer {
         P: TryInto<ObjectPath<'p>>,
         P::Error: Into<Error>,
     {
-        self.add_arc_interface(path, I::name(), ArcInterface::new(iface))
-            .await
+        self.add_arc_interface(path, ArcInterface::new(iface)).await
+    }
+
+    /// Register a [`DynamicInterface`] at a given path.
+    ///
+    /// This is the counterpart of [`ObjectServer::at`] for interfaces declared at runtime.
+    ///
+    /// If the interface already exists at this path, returns false.
+    pub async fn at_dynamic<'p, P>(&self, path: P, iface: DynamicInterface) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let iface = ArcInterface::with_name(iface.name().clone(), DynamicInstance(iface));
+
+        self.add_arc_interface(path, iface).await
     }
 
     pub(crate) async fn add_arc_interface<'p, P>(
         &self,
         path: P,
-        name: InterfaceName<'static>,
         arc_iface: ArcInterface,
     ) -> Result<bool>
     where
@@ -143,7 +216,8 @@ impl ObjectServer {
         let mut root = self.root().write().await;
         let (node, manager_path) = root.get_child_mut(&path, true);
         let node = node.unwrap();
-        let added = node.add_arc_interface(name.clone(), arc_iface);
+        let name = arc_iface.name.clone();
+        let added = node.add_arc_interface(arc_iface);
         if added {
             if name == ObjectManager::name() {
                 // Just added an object manager. Need to signal all managed objects under it.
@@ -190,17 +264,40 @@ impl ObjectServer {
         I: Interface,
         P: TryInto<ObjectPath<'p>>,
         P::Error: Into<Error>,
+    {
+        self.remove_interface(path, I::name()).await
+    }
+
+    /// Unregister a [`DynamicInterface`] with the given name at a given path.
+    ///
+    /// This is the counterpart of [`ObjectServer::remove`] for interfaces declared at runtime.
+    pub async fn remove_dynamic<'p, 'i, P, I>(&self, path: P, name: I) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+        I: TryInto<InterfaceName<'i>>,
+        I::Error: Into<Error>,
+    {
+        let name = name.try_into().map_err(Into::into)?;
+
+        self.remove_interface(path, name.into_owned()).await
+    }
+
+    async fn remove_interface<'p, P>(&self, path: P, name: InterfaceName<'static>) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
     {
         let path = path.try_into().map_err(Into::into)?;
         let mut root = self.root.write().await;
         let (node, manager_path) = root.get_child_mut(&path, false);
         let node = node.ok_or(Error::InterfaceNotFound)?;
-        if !node.remove_interface(I::name()) {
+        if !node.remove_interface(name.clone()) {
             return Err(Error::InterfaceNotFound);
         }
         if let Some(manager_path) = manager_path {
             let ctxt = SignalEmitter::new(&self.connection(), manager_path.clone())?;
-            ObjectManager::interfaces_removed(&ctxt, path.clone(), (&[I::name()]).into()).await?;
+            ObjectManager::interfaces_removed(&ctxt, path.clone(), (&[name]).into()).await?;
         }
         if node.is_empty() {
             let mut path_parts = path.rsplit('/').filter(|i| !i.is_empty());
@@ -217,6 +314,68 @@ impl ObjectServer {
         Ok(false)
     }
 
+    /// Register a [`Fallback`] handler for the objects below a given path.
+    ///
+    /// Method calls on objects below `path` that aren't registered on the object server are then
+    /// dispatched to the objects resolved by the handler. See [`Fallback`] for details.
+    ///
+    /// If a handler is already registered at this path, returns false.
+    pub async fn fallback_at<'p, P, F>(&self, path: P, fallback: F) -> Result<bool>
+    where
+        F: Fallback,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        self.add_arc_fallback(path, ArcFallback::new(fallback))
+            .await
+    }
+
+    pub(crate) async fn add_arc_fallback<'p, P>(
+        &self,
+        path: P,
+        fallback: ArcFallback,
+    ) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let mut root = self.root().write().await;
+        let (node, _) = root.get_child_mut(&path, true);
+
+        Ok(node.unwrap().set_fallback(fallback))
+    }
+
+    /// Unregister the [`Fallback`] handler at a given path.
+    ///
+    /// Returns whether there was a handler at that path.
+    pub async fn remove_fallback<'p, P>(&self, path: P) -> Result<bool>
+    where
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        let mut root = self.root.write().await;
+        let node = match root.get_child_mut(&path, false) {
+            (Some(node), _) => node,
+            (None, _) => return Ok(false),
+        };
+        if !node.remove_fallback() {
+            return Ok(false);
+        }
+        if node.is_empty() {
+            if let Some((parent, last_part)) = path.rsplit_once('/').filter(|(_, p)| !p.is_empty())
+            {
+                let ppath = ObjectPath::from_str_unchecked(parent);
+                if let (Some(parent), _) = root.get_child_mut(&ppath, false) {
+                    parent.remove_node(last_part);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Get the interface at the given path.
     ///
     /// # Errors
@@ -292,9 +451,300 @@ impl ObjectServer {
         })
     }
 
+    /// Set the access control for all method calls handled by this object server.
+    ///
+    /// This replaces any previously set access control. Use
+    /// [`zbus::connection::Builder::access_control`] instead if you want it in place before any
+    /// method calls are dispatched.
+    ///
+    /// See [`AccessControl`] for details.
+    pub async fn set_access_control<A>(&self, access_control: A)
+    where
+        A: AccessControl,
+    {
+        self.set_arc_access_control(ArcAccessControl::new(access_control))
+            .await
+    }
+
+    pub(crate) async fn set_arc_access_control(&self, access_control: ArcAccessControl) {
+        *self.access_control.write().await = Some(access_control);
+    }
+
+    /// Add a middleware around all method calls handled by this object server.
+    ///
+    /// The middleware is layered inside the ones previously added. Use
+    /// [`zbus::connection::Builder::middleware`] instead if you want it in place before any method
+    /// calls are dispatched.
+    ///
+    /// See [`Middleware`] for details.
+    pub async fn add_middleware<M>(&self, middleware: M)
+    where
+        M: Middleware,
+    {
+        let root = ObjectPath::from_static_str_unchecked("/");
+
+        self.add_arc_middleware(root.into(), ArcMiddleware::new(middleware))
+            .await
+    }
+
+    /// Add a middleware around the method calls on the objects at and below a given path.
+    ///
+    /// The middleware is layered inside the ones previously added. Use
+    /// [`zbus::connection::Builder::middleware_at`] instead if you want it in place before any
+    /// method calls are dispatched.
+    ///
+    /// See [`Middleware`] for details.
+    pub async fn add_middleware_at<'p, P, M>(&self, path: P, middleware: M) -> Result<()>
+    where
+        M: Middleware,
+        P: TryInto<ObjectPath<'p>>,
+        P::Error: Into<Error>,
+    {
+        let path = path.try_into().map_err(Into::into)?;
+        self.add_arc_middleware(path.into(), ArcMiddleware::new(middleware))
+            .await;
+
+        Ok(())
+    }
+
+    pub(crate) async fn add_arc_middleware(
+        &self,
+        path: OwnedObjectPath,
+        middleware: ArcMiddleware,
+    ) {
+        self.middleware.write().await.push((path, middleware));
+    }
+
+    /// The middleware applicable to the method calls on the object at `path`.
+    async fn middleware_for(&self, path: &ObjectPath<'_>) -> Vec<ArcMiddleware> {
+        self.middleware
+            .read()
+            .await
+            .iter()
+            .filter(|(prefix, _)| {
+                let prefix = prefix.as_str();
+
+                prefix == "/"
+                    || path
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map(|(_, middleware)| middleware.clone())
+            .collect()
+    }
+
+    /// Look up the interface `name` of the object at `path`.
+    ///
+    /// Objects in the tree take precedence over the ones resolved by fallback handlers.
+    pub(crate) async fn lookup_interface(
+        &self,
+        path: &ObjectPath<'_>,
+        name: &InterfaceName<'_>,
+    ) -> fdo::Result<ArcInterface> {
+        let (fallback, registered) = {
+            let root = self.root.read().await;
+            let node = root.get_child(path);
+            if let Some(iface) = node.and_then(|node| node.interface_lock(name.as_ref())) {
+                return Ok(iface);
+            }
+
+            match root.fallback_for(path, false) {
+                Some(fallback) => (fallback, node.is_some()),
+                None if node.is_some() => {
+                    return Err(fdo::Error::UnknownInterface(format!(
+                        "Unknown interface '{name}'"
+                    )))
+                }
+                None => {
+                    return Err(fdo::Error::UnknownObject(format!(
+                        "Unknown object '{path}'"
+                    )))
+                }
+            }
+        };
+        if *name == Introspectable::name() {
+            // Introspection resolves the object itself, and also works for the paths leading to
+            // the objects of the handler.
+            return Ok(ArcInterface::new(Introspectable));
+        }
+
+        let object = match fallback.0.resolve(path).await? {
+            Some(object) => object,
+            // Objects registered below the handler exist regardless of it.
+            None if registered => {
+                return Err(fdo::Error::UnknownInterface(format!(
+                    "Unknown interface '{name}'"
+                )))
+            }
+            None => {
+                return Err(fdo::Error::UnknownObject(format!(
+                    "Unknown object '{path}'"
+                )))
+            }
+        };
+
+        Node::resolved(path.to_owned().into(), object)
+            .interface_lock(name.as_ref())
+            .ok_or_else(|| fdo::Error::UnknownInterface(format!("Unknown interface '{name}'")))
+    }
+
+    /// Introspect the object at `path`, including the objects of fallback handlers.
+    pub(crate) async fn introspect(&self, path: &ObjectPath<'_>) -> fdo::Result<String> {
+        let (fallback, children_fallback) = {
+            let root = self.root.read().await;
+
+            (
+                root.fallback_for(path, false),
+                root.fallback_for(path, true),
+            )
+        };
+        let mut children = vec![];
+        if let Some(fallback) = children_fallback {
+            let prefix = match path.as_str() {
+                "/" => "/".to_string(),
+                path => format!("{path}/"),
+            };
+            for child in fallback.0.children().await? {
+                let Some(name) = child
+                    .as_str()
+                    .strip_prefix(&prefix)
+                    .and_then(|rest| rest.split('/').next())
+                else {
+                    continue;
+                };
+                children.push(name.to_string());
+            }
+            children.sort_unstable();
+            children.dedup();
+        }
+
+        if let Some(node) = self.root.read().await.get_child(path) {
+            return Ok(node.introspect(&children).await);
+        }
+        let object = match fallback {
+            Some(fallback) => fallback.0.resolve(path).await?,
+            None => None,
+        };
+        match object {
+            Some(object) => Ok(Node::resolved(path.to_owned().into(), object)
+                .introspect(&children)
+                .await),
+            None if !children.is_empty() => Ok(Node::new(path.to_owned().into())
+                .introspect(&children)
+                .await),
+            None => Err(fdo::Error::UnknownObject(format!(
+                "Unknown object '{path}'"
+            ))),
+        }
+    }
+
+    /// All the objects below `path`, including the objects of fallback handlers.
+    pub(crate) async fn managed_objects(
+        &self,
+        path: &ObjectPath<'_>,
+        connection: &Connection,
+    ) -> fdo::Result<ManagedObjects> {
+        let (mut objects, fallbacks) = {
+            let root = self.root.read().await;
+            let node = root
+                .get_child(path)
+                .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))?;
+            let mut fallbacks = node.fallbacks();
+            fallbacks.extend(root.fallback_for(path, false));
+
+            (node.get_managed_objects(self, connection).await?, fallbacks)
+        };
+
+        let prefix = match path.as_str() {
+            "/" => "/".to_string(),
+            path => format!("{path}/"),
+        };
+        for fallback in fallbacks {
+            for child in fallback.0.children().await? {
+                if !child.as_str().starts_with(&prefix) || objects.contains_key(&child) {
+                    continue;
+                }
+                let Some(object) = fallback.0.resolve(&child).await? else {
+                    continue;
+                };
+                let interfaces = Node::resolved(child.clone(), object)
+                    .get_managed_interfaces(self, connection)
+                    .await?;
+                objects.insert(child, interfaces);
+            }
+        }
+
+        Ok(objects)
+    }
+
+    /// Check the access controls applicable to a method call.
+    async fn check_access(
+        &self,
+        iface_access: Option<&dyn AccessControl>,
+        connection: &Connection,
+        hdr: &Header<'_>,
+    ) -> fdo::Result<()> {
+        let server_access = self.access_control.read().await.clone();
+        if server_access.is_none() && iface_access.is_none() {
+            return Ok(());
+        }
+
+        let credentials = self.credentials.get(connection, hdr).await?;
+        if let Some(access_control) = server_access {
+            access_control.0.check(hdr, &credentials).await?;
+        }
+        if let Some(access_control) = iface_access {
+            access_control.check(hdr, &credentials).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Check the access control of `iface`, for a call to `org.freedesktop.DBus.Properties`.
+    ///
+    /// The access control of the object server already allowed the call itself.
+    pub(crate) async fn check_property_access(
+        &self,
+        iface: &ArcInterface,
+        connection: &Connection,
+        hdr: &Header<'_>,
+    ) -> fdo::Result<()> {
+        let Some(access_control) = iface.instance.read().await.access_control(None) else {
+            return Ok(());
+        };
+        let credentials = self.credentials.get(connection, hdr).await?;
+
+        access_control.check(hdr, &credentials).await
+    }
+
+    /// Wait for the concurrency limits applicable to a method call to allow it.
+    ///
+    /// `limits` are the ones of the method and of the interface, in that order.
+    async fn acquire_permits(
+        &self,
+        limits: [Option<ConcurrencyLimits>; 2],
+        hdr: &Header<'_>,
+        iface_name: &InterfaceName<'_>,
+        member: &MemberName<'_>,
+    ) -> fdo::Result<Vec<Permit>> {
+        let mut permits = vec![];
+        // The narrower limits first, so the calls waiting on them don't hold the wider ones.
+        for (method, limits) in [Some(member), None].into_iter().zip(limits) {
+            if let Some(limits) = limits {
+                let permit = self
+                    .limiters
+                    .acquire(hdr, iface_name, method, limits)
+                    .await?;
+                permits.push(permit);
+            }
+        }
+
+        Ok(permits)
+    }
+
     async fn dispatch_call_to_iface(
         &self,
-        iface: Arc<RwLock<dyn Interface>>,
+        iface: Arc<RwLock<dyn ErasedInterface>>,
         connection: &Connection,
         msg: &Message,
         hdr: &Header<'_>,
@@ -306,6 +756,26 @@ impl ObjectServer {
             .interface()
             .ok_or_else(|| fdo::Error::Failed("Missing interface".into()))?;
 
+        // The interface mustn't be locked while waiting for the access controls and the limits,
+        // or the calls holding the permits could never get a write lock.
+        let (iface_access, polkit_action, limits, cancellable) = {
+            let iface = iface.read().await;
+
+            (
+                iface.access_control(Some(member)),
+                iface.polkit_action(member),
+                [Some(member), None].map(|method| iface.concurrency_limits(method)),
+                iface.cancellable(member),
+            )
+        };
+        self.check_access(iface_access, connection, hdr).await?;
+        if let Some(action) = polkit_action {
+            fdo::polkit::check_authorization(connection, hdr, action).await?;
+        }
+        let _permits = self
+            .acquire_permits(limits, hdr, iface_name, member)
+            .await?;
+
         trace!("acquiring read lock on interface `{}`", iface_name);
         let read_lock = iface.read().await;
         trace!("acquired read lock on interface `{}`", iface_name);
@@ -316,10 +786,7 @@ impl ObjectServer {
                 )));
             }
             DispatchResult::Async(f) => {
-                return f.await.map_err(|e| match e {
-                    Error::FDO(e) => *e,
-                    e => fdo::Error::Failed(format!("{e}")),
-                });
+                return self.run_handler(connection, hdr, cancellable, f).await;
             }
             DispatchResult::RequiresMut => {}
         }
@@ -331,10 +798,7 @@ impl ObjectServer {
             DispatchResult::NotFound => {}
             DispatchResult::RequiresMut => {}
             DispatchResult::Async(f) => {
-                return f.await.map_err(|e| match e {
-                    Error::FDO(e) => *e,
-                    e => fdo::Error::Failed(format!("{e}")),
-                });
+                return self.run_handler(connection, hdr, cancellable, f).await;
             }
         }
         drop(write_lock);
@@ -343,6 +807,33 @@ impl ObjectServer {
         )))
     }
 
+    /// Run the future of a method call, cancelling it if `cancellable` and the caller disconnects.
+    async fn run_handler<F>(
+        &self,
+        connection: &Connection,
+        hdr: &Header<'_>,
+        cancellable: bool,
+        handler: F,
+    ) -> fdo::Result<()>
+    where
+        F: Future<Output = Result<()>>,
+    {
+        let result = if cancellable {
+            // There's nobody left to reply to if the handler is cancelled.
+            self.pending_calls
+                .run(connection, hdr, handler)
+                .await
+                .unwrap_or(Ok(()))
+        } else {
+            handler.await
+        };
+
+        result.map_err(|e| match e {
+            Error::FDO(e) => *e,
+            e => fdo::Error::Failed(format!("{e}")),
+        })
+    }
+
     async fn dispatch_method_call_try(
         &self,
         connection: &Connection,
@@ -370,16 +861,14 @@ impl ObjectServer {
 
         // Ensure the root lock isn't held while dispatching the message. That
         // way, the object server can be mutated during that time.
-        let (iface, with_spawn) = {
-            let root = self.root.read().await;
-            let node = root
-                .get_child(path)
-                .ok_or_else(|| fdo::Error::UnknownObject(format!("Unknown object '{path}'")))?;
-
-            let iface = node.interface_lock(iface_name.as_ref()).ok_or_else(|| {
-                fdo::Error::UnknownInterface(format!("Unknown interface '{iface_name}'"))
-            })?;
-            (iface.instance, iface.spawn_tasks_for_methods)
+        let middleware = self.middleware_for(path).await;
+        let (iface, with_spawn) = match self.lookup_interface(path, iface_name).await {
+            Ok(iface) => (iface.instance, iface.spawn_tasks_for_methods),
+            Err(e) => {
+                return self
+                    .dispatch_layered(&middleware, connection, msg, hdr, async { Err(e) })
+                    .await
+            }
         };
 
         if with_spawn {
@@ -392,8 +881,9 @@ impl ObjectServer {
                     async move {
                         let server = connection.object_server();
                         let hdr = msg.header();
+                        let call = server.dispatch_call_to_iface(iface, &connection, &msg, &hdr);
                         if let Err(e) = server
-                            .dispatch_call_to_iface(iface, &connection, &msg, &hdr)
+                            .dispatch_layered(&middleware, &connection, &msg, &hdr, call)
                             .await
                         {
                             // When not spawning a task, this error is handled by the caller.
@@ -412,11 +902,86 @@ impl ObjectServer {
                 .detach();
             Ok(())
         } else {
-            self.dispatch_call_to_iface(iface, connection, msg, hdr)
+            let call = self.dispatch_call_to_iface(iface, connection, msg, hdr);
+
+            self.dispatch_layered(&middleware, connection, msg, hdr, call)
                 .await
         }
     }
 
+    /// Run `call` inside the given middleware.
+    ///
+    /// Like `call`, this returns the errors that still need to be replied to the caller.
+    async fn dispatch_layered<F>(
+        &self,
+        middleware: &[ArcMiddleware],
+        connection: &Connection,
+        msg: &Message,
+        hdr: &Header<'_>,
+        call: F,
+    ) -> fdo::Result<()>
+    where
+        F: Future<Output = fdo::Result<()>>,
+    {
+        if middleware.is_empty() {
+            return call.await;
+        }
+
+        let mut entered = 0;
+        let mut result = Ok(());
+        for layer in middleware {
+            result = layer.0.before(msg, hdr).await;
+            if result.is_err() {
+                break;
+            }
+            entered += 1;
+        }
+
+        let start = Instant::now();
+        let mut reply = WatchedReply::None;
+        if result.is_ok() {
+            // Methods reply by themselves, so the reply has to be caught on its way out to know
+            // whether the call succeeded.
+            connection.watch_reply(hdr);
+            result = if middleware.iter().any(|layer| layer.0.catch_panics()) {
+                AssertUnwindSafe(call)
+                    .catch_unwind()
+                    .await
+                    .unwrap_or_else(|panic| Err(panic_error(hdr, panic)))
+            } else {
+                call.await
+            };
+            reply = connection.take_reply(hdr);
+        }
+        let layers = &middleware[..entered];
+
+        match reply {
+            WatchedReply::Deferred(sent) if result.is_ok() => {
+                // The call is only complete once the `ReplyHandle` is used.
+                let layers = layers.to_vec();
+                let connection = connection.clone();
+                let msg = msg.clone();
+                connection
+                    .executor()
+                    .clone()
+                    .spawn(
+                        async move {
+                            sent.await;
+                            let hdr = msg.header();
+                            let reply = connection.take_reply(&hdr);
+                            let elapsed = start.elapsed();
+                            call_after(&layers, &msg, &hdr, &Ok(()), reply, elapsed).await;
+                        },
+                        "deferred reply middleware",
+                    )
+                    .detach();
+            }
+            reply => call_after(layers, msg, hdr, &result, reply, start.elapsed()).await,
+        }
+
+        result
+    }
+
     /// Dispatch an incoming message to a registered interface.
     ///
     /// The object server will handle the message by:
@@ -449,6 +1014,48 @@ impl ObjectServer {
     }
 }
 
+/// Call the `after` hooks of `layers`, for the call of `msg` that returned `result`.
+async fn call_after(
+    layers: &[ArcMiddleware],
+    msg: &Message,
+    hdr: &Header<'_>,
+    result: &fdo::Result<()>,
+    reply: WatchedReply,
+    elapsed: Duration,
+) {
+    let outcome = match reply {
+        WatchedReply::Sent(reply) if result.is_ok() && reply.message_type() == Type::Error => {
+            Err(fdo::Error::from(Error::from(reply)))
+        }
+        // Methods always reply unless asked not to, so the call was cancelled.
+        WatchedReply::None
+            if result.is_ok()
+                && !msg
+                    .primary_header()
+                    .flags()
+                    .contains(Flags::NoReplyExpected) =>
+        {
+            Err(fdo::Error::NoReply("The caller disconnected".into()))
+        }
+        _ => result.clone(),
+    };
+    for layer in layers.iter().rev() {
+        layer.0.after(msg, hdr, &outcome, elapsed).await;
+    }
+}
+
+/// The error to reply with, for the method call of `hdr` that panicked.
+fn panic_error(hdr: &Header<'_>, panic: Box<dyn Any + Send>) -> fdo::Error {
+    let member = hdr.member().map(|m| m.as_str()).unwrap_or_default();
+    let reason = panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown reason");
+
+    fdo::Error::Failed(format!("Method `{member}` panicked: {reason}"))
+}
+
 #[cfg(feature = "blocking-api")]
 impl From<crate::blocking::ObjectServer> for ObjectServer {
     fn from(server: crate::blocking::ObjectServer) -> Self {
@@ -5,7 +5,7 @@ use std::{
     fmt::Write,
 };
 
-use zbus_names::InterfaceName;
+use zbus_names::{InterfaceName, OwnedInterfaceName};
 use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
 
 use crate::{
@@ -14,13 +14,14 @@ use crate::{
     Connection, ObjectServer,
 };
 
-use super::{ArcInterface, Interface};
+use super::{ArcFallback, ArcInterface, Interface, ResolvedObject};
 
 #[derive(Default, Debug)]
 pub(crate) struct Node {
     path: OwnedObjectPath,
     children: HashMap<String, Node>,
     interfaces: HashMap<InterfaceName<'static>, ArcInterface>,
+    fallback: Option<ArcFallback>,
 }
 
 impl Node {
@@ -36,6 +37,17 @@ impl Node {
         node
     }
 
+    /// Create a detached node for an object resolved by a fallback handler.
+    pub(crate) fn resolved(path: OwnedObjectPath, object: ResolvedObject) -> Self {
+        let mut node = Self::new(path);
+        for iface in object.interfaces.into_values() {
+            // The standard interfaces can't be overridden.
+            node.add_arc_interface(iface);
+        }
+
+        node
+    }
+
     // Get the child Node at path.
     pub(crate) fn get_child(&self, path: &ObjectPath<'_>) -> Option<&Node> {
         let mut node = self;
@@ -101,24 +113,74 @@ impl Node {
     }
 
     pub(super) fn is_empty(&self) -> bool {
-        !self.interfaces.keys().any(|k| {
-            *k != Peer::name()
-                && *k != Introspectable::name()
-                && *k != Properties::name()
-                && *k != ObjectManager::name()
-        })
+        self.fallback.is_none()
+            && !self.interfaces.keys().any(|k| {
+                *k != Peer::name()
+                    && *k != Introspectable::name()
+                    && *k != Properties::name()
+                    && *k != ObjectManager::name()
+            })
+    }
+
+    pub(super) fn set_fallback(&mut self, fallback: ArcFallback) -> bool {
+        if self.fallback.is_some() {
+            return false;
+        }
+        self.fallback = Some(fallback);
+
+        true
+    }
+
+    pub(super) fn remove_fallback(&mut self) -> bool {
+        self.fallback.take().is_some()
+    }
+
+    /// The fallback handler responsible for the objects at `path`, if any.
+    ///
+    /// That's the one of the closest node above `path`, or at `path` if `include_self` is `true`.
+    pub(crate) fn fallback_for(
+        &self,
+        path: &ObjectPath<'_>,
+        include_self: bool,
+    ) -> Option<ArcFallback> {
+        let mut node = self;
+        let mut fallback = None;
+
+        for i in path.split('/').skip(1) {
+            if i.is_empty() {
+                continue;
+            }
+            fallback = node.fallback.as_ref().or(fallback);
+            match node.children.get(i) {
+                Some(n) => node = n,
+                None => return fallback.cloned(),
+            }
+        }
+        if include_self {
+            fallback = node.fallback.as_ref().or(fallback);
+        }
+
+        fallback.cloned()
+    }
+
+    /// The fallback handlers of this node and its descendants.
+    pub(crate) fn fallbacks(&self) -> Vec<ArcFallback> {
+        let mut fallbacks = vec![];
+        let mut node_list = vec![self];
+        while let Some(node) = node_list.pop() {
+            fallbacks.extend(node.fallback.clone());
+            node_list.extend(node.children.values());
+        }
+
+        fallbacks
     }
 
     pub(super) fn remove_node(&mut self, node: &str) -> bool {
         self.children.remove(node).is_some()
     }
 
-    pub(super) fn add_arc_interface(
-        &mut self,
-        name: InterfaceName<'static>,
-        arc_iface: ArcInterface,
-    ) -> bool {
-        match self.interfaces.entry(name) {
+    pub(super) fn add_arc_interface(&mut self, arc_iface: ArcInterface) -> bool {
+        match self.interfaces.entry(arc_iface.name.clone()) {
             Entry::Vacant(e) => {
                 e.insert(arc_iface);
                 true
@@ -131,10 +193,14 @@ impl Node {
     where
         I: Interface,
     {
-        self.add_arc_interface(I::name(), ArcInterface::new(iface))
+        self.add_arc_interface(ArcInterface::new(iface))
     }
 
-    async fn introspect_to_writer<W: Write + Send>(&self, writer: &mut W) {
+    async fn introspect_to_writer<W: Write + Send>(
+        &self,
+        writer: &mut W,
+        extra_children: &[String],
+    ) {
         enum Fragment<'a> {
             /// Represent an unclosed node tree, could be further splitted into sub-`Fragment`s.
             Node {
@@ -196,6 +262,15 @@ impl Node {
                             .await
                             .introspect_to_writer(writer, level + 2);
                     }
+
+                    if level == 0 {
+                        // The children of fallback handlers, that aren't in the tree.
+                        for name in extra_children {
+                            if !node.children.contains_key(name) {
+                                writeln!(writer, "  <node name=\"{name}\"/>").unwrap();
+                            }
+                        }
+                    }
                 }
                 Fragment::End { level } => {
                     writeln!(writer, "{:indent$}</node>", "", indent = level).unwrap();
@@ -204,10 +279,11 @@ impl Node {
         }
     }
 
-    pub(crate) async fn introspect(&self) -> String {
+    /// Introspect the node, listing `extra_children` as child nodes too.
+    pub(crate) async fn introspect(&self, extra_children: &[String]) -> String {
         let mut xml = String::with_capacity(1024);
 
-        self.introspect_to_writer(&mut xml).await;
+        self.introspect_to_writer(&mut xml, extra_children).await;
 
         xml
     }
@@ -222,19 +298,9 @@ impl Node {
         // Recursively get all properties of all interfaces of descendants.
         let mut node_list: Vec<_> = self.children.values().collect();
         while let Some(node) = node_list.pop() {
-            let mut interfaces = HashMap::new();
-            for iface_name in node.interfaces.keys().filter(|n| {
-                // Filter standard interfaces.
-                *n != &Peer::name()
-                    && *n != &Introspectable::name()
-                    && *n != &Properties::name()
-                    && *n != &ObjectManager::name()
-            }) {
-                let props = node
-                    .get_properties(object_server, connection, iface_name.clone())
-                    .await?;
-                interfaces.insert(iface_name.clone().into(), props);
-            }
+            let interfaces = node
+                .get_managed_interfaces(object_server, connection)
+                .await?;
             managed_objects.insert(node.path.clone(), interfaces);
             node_list.extend(node.children.values());
         }
@@ -242,6 +308,29 @@ impl Node {
         Ok(managed_objects)
     }
 
+    /// The non-standard interfaces of the node, with all their properties.
+    pub(crate) async fn get_managed_interfaces(
+        &self,
+        object_server: &ObjectServer,
+        connection: &Connection,
+    ) -> fdo::Result<HashMap<OwnedInterfaceName, HashMap<String, OwnedValue>>> {
+        let mut interfaces = HashMap::new();
+        for iface_name in self.interfaces.keys().filter(|n| {
+            // Filter standard interfaces.
+            *n != &Peer::name()
+                && *n != &Introspectable::name()
+                && *n != &Properties::name()
+                && *n != &ObjectManager::name()
+        }) {
+            let props = self
+                .get_properties(object_server, connection, iface_name.clone())
+                .await?;
+            interfaces.insert(iface_name.clone().into(), props);
+        }
+
+        Ok(interfaces)
+    }
+
     pub(super) async fn get_properties(
         &self,
         object_server: &ObjectServer,
@@ -0,0 +1,150 @@
+use futures_util::{
+    future::{abortable, AbortHandle},
+    StreamExt,
+};
+use std::{collections::HashMap, future::Future, num::NonZeroU32, sync::Arc};
+use tracing::{debug, trace};
+
+use crate::{
+    async_lock::Mutex,
+    fdo::NameOwnerChanged,
+    message::{Header, Type},
+    names::{BusName, OwnedUniqueName},
+    Connection, MatchRule, Task,
+};
+
+type Calls = Arc<Mutex<HashMap<OwnedUniqueName, HashMap<NonZeroU32, AbortHandle>>>>;
+
+/// The method calls being handled by the [`ObjectServer`], by caller.
+///
+/// The handlers of the calls are cancelled when their caller disconnects from the bus, so they
+/// don't keep running to produce a reply that nobody will receive.
+///
+/// [`ObjectServer`]: crate::ObjectServer
+#[derive(Debug, Default)]
+pub(crate) struct PendingCalls {
+    calls: Calls,
+    /// The task watching for callers leaving the bus, started on the first call from the bus.
+    watch_task: Mutex<Option<Task<()>>>,
+}
+
+impl PendingCalls {
+    /// Run `handler`, the handler of the method call of the given `header`.
+    ///
+    /// Returns `None` if the handler was cancelled because the caller disconnected before it
+    /// completed.
+    pub async fn run<F>(
+        &self,
+        connection: &Connection,
+        header: &Header<'_>,
+        handler: F,
+    ) -> Option<F::Output>
+    where
+        F: Future,
+    {
+        // Peer-to-peer calls end with the connection itself.
+        let caller = match (connection.is_bus(), header.sender()) {
+            (true, Some(sender)) => OwnedUniqueName::from(sender.to_owned()),
+            _ => return Some(handler.await),
+        };
+        self.watch_callers(connection).await;
+
+        let serial = header.primary().serial_num();
+        let (handler, abort_handle) = abortable(handler);
+        self.calls
+            .lock()
+            .await
+            .entry(caller.clone())
+            .or_default()
+            .insert(serial, abort_handle);
+
+        let output = handler.await.ok();
+
+        let mut calls = self.calls.lock().await;
+        if let Some(caller_calls) = calls.get_mut(&caller) {
+            caller_calls.remove(&serial);
+            if caller_calls.is_empty() {
+                calls.remove(&caller);
+            }
+        }
+        if output.is_none() {
+            debug!(
+                "Cancelled the call to `{}`: {caller} disconnected",
+                header.member().map(|m| m.as_str()).unwrap_or_default(),
+            );
+        }
+
+        output
+    }
+
+    /// Cancel all the calls.
+    pub async fn cancel_all(&self) {
+        for (caller, caller_calls) in self.calls.lock().await.drain() {
+            trace!("Cancelling the {} call(s) of {caller}", caller_calls.len());
+            for abort_handle in caller_calls.into_values() {
+                abort_handle.abort();
+            }
+        }
+    }
+
+    /// Watch for callers leaving the bus, cancelling their calls when they do.
+    ///
+    /// A single subscription to `NameOwnerChanged` is shared by all the callers, for the lifetime
+    /// of the object server.
+    async fn watch_callers(&self, connection: &Connection) {
+        let mut watch_task = self.watch_task.lock().await;
+        if watch_task.is_some() {
+            return;
+        }
+
+        let rule = MatchRule::builder()
+            .msg_type(Type::Signal)
+            .sender("org.freedesktop.DBus")
+            .expect("bus name")
+            .interface("org.freedesktop.DBus")
+            .expect("interface name")
+            .member("NameOwnerChanged")
+            .expect("member name")
+            // Only when a name lost its owner.
+            .arg(2, "")
+            .expect("arg index")
+            .build();
+        let mut stream = match connection.add_match(rule.into(), None).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                // We'll try again on the next call.
+                debug!("Failed to watch for callers leaving: {}", e);
+
+                return;
+            }
+        };
+        let calls = self.calls.clone();
+
+        *watch_task = Some(connection.executor().spawn(
+            async move {
+                while let Some(msg) = stream.next().await {
+                    let Some(signal) = msg.ok().and_then(NameOwnerChanged::from_message) else {
+                        continue;
+                    };
+                    let Ok(args) = signal.args() else {
+                        continue;
+                    };
+                    let BusName::Unique(name) = args.name() else {
+                        continue;
+                    };
+                    let caller = OwnedUniqueName::from(name.to_owned());
+                    if let Some(caller_calls) = calls.lock().await.remove(&caller) {
+                        trace!(
+                            "{caller} left, cancelling its {} call(s)",
+                            caller_calls.len()
+                        );
+                        for abort_handle in caller_calls.into_values() {
+                            abort_handle.abort();
+                        }
+                    }
+                }
+            },
+            "ObjectServer watch of callers",
+        ));
+    }
+}
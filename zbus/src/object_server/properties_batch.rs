@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::debug;
+use zbus_names::{BusName, OwnedBusName, OwnedInterfaceName};
+use zvariant::{OwnedObjectPath, OwnedValue};
+
+use crate::{message::Message, timer::Timer, Connection, Result};
+
+/// The `PropertiesChanged` signals of a batching [`SignalEmitter`], waiting to be emitted.
+///
+/// The changes are merged per interface (and destination), so each interface gets a single signal
+/// with the latest values of its changed properties, and the properties invalidated since.
+///
+/// [`SignalEmitter`]: crate::object_server::SignalEmitter
+#[derive(Debug)]
+pub(crate) struct PropertiesBatch {
+    conn: Connection,
+    path: OwnedObjectPath,
+    window: Option<Duration>,
+    pending: Mutex<Vec<Pending>>,
+}
+
+/// The pending changes of an interface.
+#[derive(Debug)]
+struct Pending {
+    destination: Option<OwnedBusName>,
+    interface: OwnedInterfaceName,
+    changed: HashMap<String, OwnedValue>,
+    invalidated: Vec<String>,
+}
+
+type PropertiesChangedBody = (OwnedInterfaceName, HashMap<String, OwnedValue>, Vec<String>);
+
+impl PropertiesBatch {
+    pub fn new(conn: Connection, path: OwnedObjectPath, window: Option<Duration>) -> Self {
+        Self {
+            conn,
+            path,
+            window,
+            pending: Mutex::new(vec![]),
+        }
+    }
+
+    /// Merge the `PropertiesChanged` signal of the given `body` into the batch.
+    pub fn add<B>(self: &Arc<Self>, destination: Option<&BusName<'_>>, body: &B) -> Result<()>
+    where
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        // The body is typically generic, so go through its encoding to get to the changes.
+        let msg = Message::signal(
+            &self.path,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+        )?
+        .build(body)?;
+        let (interface, changed, invalidated): PropertiesChangedBody = msg.body().deserialize()?;
+        let destination = destination.map(|d| OwnedBusName::from(d.to_owned()));
+
+        let mut pending = self.pending.lock().expect("poisoned lock");
+        if pending.is_empty() {
+            if let Some(window) = self.window {
+                let batch = self.clone();
+                self.conn
+                    .executor()
+                    .spawn(
+                        async move {
+                            Timer::after(window).await;
+                            if let Err(e) = batch.flush().await {
+                                debug!("Failed to emit batched `PropertiesChanged`: {e}");
+                            }
+                        },
+                        "PropertiesChanged batch",
+                    )
+                    .detach();
+            }
+        }
+        let i = match pending
+            .iter()
+            .position(|p| p.destination == destination && p.interface == interface)
+        {
+            Some(i) => i,
+            None => {
+                pending.push(Pending {
+                    destination,
+                    interface,
+                    changed: HashMap::new(),
+                    invalidated: vec![],
+                });
+
+                pending.len() - 1
+            }
+        };
+        let entry = &mut pending[i];
+        for (name, value) in changed {
+            entry.invalidated.retain(|n| *n != name);
+            entry.changed.insert(name, value);
+        }
+        for name in invalidated {
+            entry.changed.remove(&name);
+            if !entry.invalidated.contains(&name) {
+                entry.invalidated.push(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Emit the pending changes, one `PropertiesChanged` signal per interface.
+    pub async fn flush(&self) -> Result<()> {
+        let pending = mem::take(&mut *self.pending.lock().expect("poisoned lock"));
+
+        emit(&self.conn, &self.path, pending).await
+    }
+}
+
+impl Drop for PropertiesBatch {
+    fn drop(&mut self) {
+        let pending = mem::take(self.pending.get_mut().expect("poisoned lock"));
+        if pending.is_empty() {
+            return;
+        }
+
+        // Changes are not lost just because the emitter went away before they were flushed.
+        let conn = self.conn.clone();
+        let path = self.path.clone();
+        self.conn
+            .executor()
+            .spawn(
+                async move {
+                    if let Err(e) = emit(&conn, &path, pending).await {
+                        debug!("Failed to emit batched `PropertiesChanged`: {e}");
+                    }
+                },
+                "PropertiesChanged batch",
+            )
+            .detach();
+    }
+}
+
+async fn emit(conn: &Connection, path: &OwnedObjectPath, pending: Vec<Pending>) -> Result<()> {
+    for p in pending {
+        conn.emit_signal(
+            p.destination.as_ref().map(|d| d.as_ref()),
+            path,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            &(p.interface, p.changed, p.invalidated),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
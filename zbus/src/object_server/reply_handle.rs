@@ -0,0 +1,145 @@
+use static_assertions::assert_impl_all;
+use std::marker::PhantomData;
+use tracing::{debug, trace};
+
+use crate::{
+    fdo,
+    message::{Flags, Header, Message},
+    Connection, DBusError, Result,
+};
+
+/// A handle to reply to a method call after its handler has returned.
+///
+/// Methods of an [`macro@crate::interface`] taking a `#[zbus(reply_handle)]` argument don't reply
+/// when they return. Instead, they hand the `ReplyHandle` over to whatever produces the reply
+/// eventually (e.g another task, waiting for an event), which then sends it with
+/// [`ReplyHandle::reply`] or [`ReplyHandle::reply_error`]. `T` is the type of the reply.
+///
+/// If the handle is dropped without having been used, an `org.freedesktop.DBus.Error.NoReply`
+/// error is sent to the caller, so it doesn't wait for a reply that will never come.
+///
+/// Note that the [`ObjectServer`] considers the call complete once the method returns, so neither
+/// the [concurrency limits](crate::object_server::ConcurrencyLimits) nor the cancellation of calls
+/// whose caller disconnected apply to the deferred part of the call. The `after` hooks of
+/// [middleware](crate::object_server::Middleware) are called once the reply is sent though.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Mutex;
+/// use zbus::{interface, object_server::ReplyHandle};
+///
+/// struct Dialog {
+///     questions: Mutex<Vec<ReplyHandle<String>>>,
+/// }
+///
+/// #[interface(name = "org.myservice.Dialog")]
+/// impl Dialog {
+///     /// Ask a question, replying once somebody answers it.
+///     fn ask(&self, question: &str, #[zbus(reply_handle)] reply: ReplyHandle<String>) {
+///         println!("{question}");
+///         self.questions.lock().unwrap().push(reply);
+///     }
+///
+///     /// Answer all the pending questions.
+///     async fn answer(&self, answer: String) -> zbus::fdo::Result<()> {
+///         let questions = std::mem::take(&mut *self.questions.lock().unwrap());
+///         for question in questions {
+///             question.reply(&answer).await?;
+///         }
+///
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// [`ObjectServer`]: crate::ObjectServer
+#[derive(Debug)]
+pub struct ReplyHandle<T> {
+    connection: Connection,
+    call: Option<Message>,
+    phantom: PhantomData<fn(T)>,
+}
+
+assert_impl_all!(ReplyHandle<()>: Send, Sync, Unpin);
+
+impl<T> ReplyHandle<T>
+where
+    T: serde::Serialize + zvariant::DynamicType,
+{
+    /// Create a handle to reply to the given method `call`, on `connection`.
+    pub fn new(connection: &Connection, call: &Message) -> Self {
+        if expects_reply(&call.header()) {
+            connection.defer_reply(&call.header());
+        }
+
+        Self {
+            connection: connection.clone(),
+            call: Some(call.clone()),
+            phantom: PhantomData,
+        }
+    }
+
+    /// The header of the method call to reply to.
+    pub fn header(&self) -> Header<'_> {
+        self.call
+            .as_ref()
+            .expect("reply handle already used")
+            .header()
+    }
+
+    /// Send the reply.
+    pub async fn reply(mut self, reply: &T) -> Result<()> {
+        let call = self.call.take().expect("reply handle already used");
+        let hdr = call.header();
+        if !expects_reply(&hdr) {
+            trace!("No reply expected for {:?} by the caller.", call);
+
+            return Ok(());
+        }
+
+        self.connection.reply(&hdr, reply).await
+    }
+
+    /// Send an error reply.
+    pub async fn reply_error(mut self, error: impl DBusError) -> Result<()> {
+        let call = self.call.take().expect("reply handle already used");
+        let hdr = call.header();
+        if !expects_reply(&hdr) {
+            trace!("No reply expected for {:?} by the caller.", call);
+
+            return Ok(());
+        }
+
+        self.connection.reply_dbus_error(&hdr, error).await
+    }
+}
+
+impl<T> Drop for ReplyHandle<T> {
+    fn drop(&mut self) {
+        let Some(call) = self.call.take() else {
+            return;
+        };
+        if !expects_reply(&call.header()) {
+            return;
+        }
+
+        let connection = self.connection.clone();
+        self.connection
+            .executor()
+            .spawn(
+                async move {
+                    let err = fdo::Error::NoReply("The reply handle was dropped".into());
+                    if let Err(e) = connection.reply_dbus_error(&call.header(), err).await {
+                        debug!("Failed to reply to {:?}: {}", call, e);
+                    }
+                },
+                "ReplyHandle drop",
+            )
+            .detach();
+    }
+}
+
+fn expects_reply(hdr: &Header<'_>) -> bool {
+    !hdr.primary().flags().contains(Flags::NoReplyExpected)
+}
@@ -1,5 +1,7 @@
+use std::{sync::Arc, time::Duration};
 use zbus_names::{BusName, InterfaceName, MemberName};
 
+use super::properties_batch::PropertiesBatch;
 use crate::{zvariant::ObjectPath, Connection, Error, Result};
 
 /// A signal emitter.
@@ -13,6 +15,7 @@ pub struct SignalEmitter<'s> {
     conn: Connection,
     path: ObjectPath<'s>,
     destination: Option<BusName<'s>>,
+    batch: Option<Arc<PropertiesBatch>>,
 }
 
 impl<'s> SignalEmitter<'s> {
@@ -27,6 +30,7 @@ impl<'s> SignalEmitter<'s> {
                 conn: conn.clone(),
                 path: p,
                 destination: None,
+                batch: None,
             })
             .map_err(Into::into)
     }
@@ -37,6 +41,7 @@ impl<'s> SignalEmitter<'s> {
             conn,
             path,
             destination: None,
+            batch: None,
         }
     }
 
@@ -49,6 +54,15 @@ impl<'s> SignalEmitter<'s> {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
+        let interface = interface.try_into().map_err(Into::into)?;
+        let signal_name = signal_name.try_into().map_err(Into::into)?;
+        if let Some(batch) = &self.batch {
+            if interface == "org.freedesktop.DBus.Properties" && signal_name == "PropertiesChanged"
+            {
+                return batch.add(self.destination.as_ref(), body);
+            }
+        }
+
         self.conn
             .emit_signal(
                 self.destination.as_ref(),
@@ -60,6 +74,94 @@ impl<'s> SignalEmitter<'s> {
             .await
     }
 
+    /// Create a clone of `self` that batches the `PropertiesChanged` signals.
+    ///
+    /// Instead of being emitted right away, the `PropertiesChanged` signals emitted through the
+    /// returned emitter (and its clones), e.g by the `<property>_changed` and
+    /// `<property>_invalidate` methods generated by the [`macro@crate::interface`] macro, are merged
+    /// into a single signal per interface. The merged signals are emitted on
+    /// [`SignalEmitter::flush`], or once the returned emitter and all its clones are dropped.
+    ///
+    /// The other signals are emitted right away, as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # use zbus::{Connection, interface};
+    /// #
+    /// struct Position {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// #[interface(name = "org.myiface.Position")]
+    /// impl Position {
+    ///     #[zbus(property)]
+    ///     fn x(&self) -> i32 {
+    ///         self.x
+    ///     }
+    ///
+    ///     #[zbus(property)]
+    ///     fn y(&self) -> i32 {
+    ///         self.y
+    ///     }
+    /// }
+    ///
+    /// # async_io::block_on(async {
+    /// # let connection = Connection::session().await?;
+    /// # let path = "/org/zbus/path";
+    /// # connection.object_server().at(path, Position { x: 0, y: 0 }).await?;
+    /// let iface_ref = connection
+    ///     .object_server()
+    ///     .interface::<_, Position>(path)
+    ///     .await?;
+    /// let mut iface = iface_ref.get_mut().await;
+    /// iface.x = 4;
+    /// iface.y = 2;
+    ///
+    /// // A single `PropertiesChanged` signal for both properties.
+    /// let emitter = iface_ref.signal_emitter().batched();
+    /// iface.x_changed(&emitter).await?;
+    /// iface.y_changed(&emitter).await?;
+    /// emitter.flush().await?;
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// # })?;
+    /// #
+    /// # Ok::<_, Box<dyn Error + Send + Sync>>(())
+    /// ```
+    pub fn batched(&self) -> Self {
+        self.with_batch(None)
+    }
+
+    /// Create a clone of `self` that batches the `PropertiesChanged` signals within a time window.
+    ///
+    /// Same as [`SignalEmitter::batched`], except that the merged signals are also emitted once
+    /// `window` has elapsed since the first change of the batch. This is useful to rate-limit the
+    /// signals of properties that change often.
+    pub fn batched_for(&self, window: Duration) -> Self {
+        self.with_batch(Some(window))
+    }
+
+    /// Emit the pending `PropertiesChanged` signals of a batching emitter.
+    ///
+    /// See [`SignalEmitter::batched`] for details. This does nothing if `self` doesn't batch.
+    pub async fn flush(&self) -> Result<()> {
+        match &self.batch {
+            Some(batch) => batch.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    fn with_batch(&self, window: Option<Duration>) -> Self {
+        let batch = PropertiesBatch::new(self.conn.clone(), self.path.to_owned().into(), window);
+
+        Self {
+            batch: Some(Arc::new(batch)),
+            ..self.clone()
+        }
+    }
+
     /// Set the destination for the signal emission.
     ///
     /// Signals are typically broadcasted and thus don't have a destination. However, there are
@@ -92,6 +194,7 @@ impl<'s> SignalEmitter<'s> {
             conn: self.conn.clone(),
             path: self.path.to_owned(),
             destination: self.destination.as_ref().map(|d| d.to_owned()),
+            batch: self.batch.clone(),
         }
     }
 
@@ -101,6 +204,7 @@ impl<'s> SignalEmitter<'s> {
             conn: self.conn,
             path: self.path.into_owned(),
             destination: self.destination.map(|d| d.into_owned()),
+            batch: self.batch,
         }
     }
 }
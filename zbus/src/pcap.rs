@@ -0,0 +1,698 @@
+//! Recording and replaying D-Bus traffic in [pcapng] files.
+//!
+//! [`Writer`] saves [`Message`]s, along with the time they were seen, to a pcapng file using the
+//! D-Bus link type, which tools like Wireshark understand. [`Reader`] gives the messages back,
+//! from both pcapng files and the classic pcap files written by `dbus-monitor --pcap`.
+//!
+//! Note that file descriptors passed along with the messages are not recorded.
+//!
+//! # Example
+//!
+//! Capturing all traffic on the session bus, much like `dbus-monitor --pcap` does:
+//!
+//! ```no_run
+//! use futures_util::TryStreamExt;
+//! use zbus::{fdo::MonitoringProxy, pcap::Writer, Connection, MessageStream};
+//!
+//! # zbus::block_on(async {
+//! let connection = Connection::session().await?;
+//! MonitoringProxy::new(&connection)
+//!     .await?
+//!     .become_monitor(&[], 0)
+//!     .await?;
+//!
+//! let mut writer = Writer::new(std::fs::File::create("session.pcapng")?)?;
+//! let mut stream = MessageStream::from(connection);
+//! while let Some(msg) = stream.try_next().await? {
+//!     writer.write(&msg)?;
+//! }
+//! # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+//! # }).unwrap();
+//! ```
+//!
+//! and reading them back:
+//!
+//! ```no_run
+//! use zbus::pcap::Reader;
+//!
+//! for record in Reader::new(std::fs::File::open("session.pcapng")?)? {
+//!     let (timestamp, msg) = record?;
+//!     println!("{timestamp:?}: {}", msg);
+//! }
+//! # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+//! ```
+//!
+//! [`Reader::replay`] sends the recorded messages on a [`Connection`] instead.
+//!
+//! [pcapng]: https://www.ietf.org/archive/id/draft-ietf-opsawg-pcapng-03.html
+
+use std::{
+    io::{self, Read, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use zvariant::{serialized, Endian};
+
+use crate::{
+    message::{
+        header::{MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE},
+        EndianSig, PrimaryHeader,
+    },
+    utils::padding_for_8_bytes,
+    Connection, Message, Result,
+};
+
+/// The link type of D-Bus captures.
+const LINKTYPE_DBUS: u16 = 231;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+const OPT_END_OF_OPT: u16 = 0;
+const OPT_IF_TSRESOL: u16 = 9;
+
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+
+// A message and the block around it.
+const MAX_BLOCK_SIZE: usize = MAX_MESSAGE_SIZE + 1024;
+
+/// Writes [`Message`]s to a pcapng file.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug)]
+pub struct Writer<W> {
+    inner: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    /// Create a writer, writing the file headers to `inner` right away.
+    pub fn new(mut inner: W) -> Result<Self> {
+        // Section header block, with an unspecified section length.
+        let mut shb = Vec::with_capacity(16);
+        shb.extend(BYTE_ORDER_MAGIC.to_le_bytes());
+        shb.extend(1u16.to_le_bytes());
+        shb.extend(0u16.to_le_bytes());
+        shb.extend((-1i64).to_le_bytes());
+        write_block(&mut inner, SECTION_HEADER_BLOCK, &shb)?;
+
+        // Interface description block, with nanosecond timestamps.
+        let mut idb = Vec::with_capacity(20);
+        idb.extend(LINKTYPE_DBUS.to_le_bytes());
+        idb.extend(0u16.to_le_bytes());
+        idb.extend(0u32.to_le_bytes());
+        idb.extend(OPT_IF_TSRESOL.to_le_bytes());
+        idb.extend(1u16.to_le_bytes());
+        idb.extend([9, 0, 0, 0]);
+        idb.extend(OPT_END_OF_OPT.to_le_bytes());
+        idb.extend(0u16.to_le_bytes());
+        write_block(&mut inner, INTERFACE_DESCRIPTION_BLOCK, &idb)?;
+
+        Ok(Self { inner })
+    }
+
+    /// Write `msg`, timestamped with the current time.
+    pub fn write(&mut self, msg: &Message) -> Result<()> {
+        self.write_with_timestamp(msg, SystemTime::now())
+    }
+
+    /// Write `msg`, timestamped with `timestamp`.
+    pub fn write_with_timestamp(&mut self, msg: &Message, timestamp: SystemTime) -> Result<()> {
+        let bytes = msg.data().bytes();
+        let len = bytes.len() as u32;
+        let nanos = timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+
+        let mut epb = Vec::with_capacity(20 + bytes.len() + 3);
+        epb.extend(0u32.to_le_bytes());
+        epb.extend(((nanos >> 32) as u32).to_le_bytes());
+        epb.extend((nanos as u32).to_le_bytes());
+        epb.extend(len.to_le_bytes());
+        epb.extend(len.to_le_bytes());
+        epb.extend(bytes);
+        epb.resize(epb.len() + padding_for_4_bytes(bytes.len()), 0);
+        write_block(&mut self.inner, ENHANCED_PACKET_BLOCK, &epb)?;
+
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush().map_err(Into::into)
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get the underlying writer, consuming `self`.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+fn write_block<W: Write>(writer: &mut W, block_type: u32, body: &[u8]) -> io::Result<()> {
+    let len = (body.len() as u32 + 12).to_le_bytes();
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&len)?;
+    writer.write_all(body)?;
+    writer.write_all(&len)
+}
+
+/// Reads [`Message`]s from a pcapng or pcap file.
+///
+/// This is an iterator over the recorded messages and the time they were recorded at. Packets of
+/// other link types than D-Bus, if any, are skipped.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug)]
+pub struct Reader<R> {
+    inner: R,
+    format: Format,
+}
+
+#[derive(Debug)]
+enum Format {
+    Pcap {
+        endian: Endian,
+        // Timestamp units per second.
+        resolution: u64,
+    },
+    Pcapng {
+        endian: Endian,
+        interfaces: Vec<Interface>,
+    },
+}
+
+#[derive(Debug)]
+struct Interface {
+    link_type: u16,
+    // Timestamp units per second.
+    resolution: u64,
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Create a reader, reading the file headers from `inner` right away.
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut magic = [0; 4];
+        inner.read_exact(&mut magic)?;
+
+        let format = if u32::from_le_bytes(magic) == SECTION_HEADER_BLOCK {
+            let mut len = [0; 4];
+            inner.read_exact(&mut len)?;
+            let endian = read_section_header(&mut inner, len)?;
+
+            Format::Pcapng {
+                endian,
+                interfaces: vec![],
+            }
+        } else {
+            let (endian, resolution) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic))
+            {
+                (PCAP_MAGIC_MICROS, _) => (Endian::Little, 1_000_000),
+                (PCAP_MAGIC_NANOS, _) => (Endian::Little, 1_000_000_000),
+                (_, PCAP_MAGIC_MICROS) => (Endian::Big, 1_000_000),
+                (_, PCAP_MAGIC_NANOS) => (Endian::Big, 1_000_000_000),
+                _ => return Err(invalid_data("Not a pcap or pcapng file").into()),
+            };
+            let mut header = [0; 20];
+            inner.read_exact(&mut header)?;
+            let link_type = read_u32(endian, &header[16..]);
+            if link_type != LINKTYPE_DBUS as u32 {
+                return Err(invalid_data(format!("Unexpected link type {link_type}")).into());
+            }
+
+            Format::Pcap { endian, resolution }
+        };
+
+        Ok(Self { inner, format })
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get the underlying reader, consuming `self`.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Send all the remaining recorded messages on `connection`, in order, returning how many were
+    /// sent.
+    ///
+    /// The messages are sent as they were recorded, including their serial numbers, and without
+    /// waiting between them. Reading from the underlying reader blocks, so it's best used with
+    /// files or in-memory buffers.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use zbus::{pcap::Reader, Connection};
+    ///
+    /// # zbus::block_on(async {
+    /// let connection = Connection::session().await?;
+    /// let reader = Reader::new(std::fs::File::open("session.pcapng")?)?;
+    /// let sent = reader.replay(&connection).await?;
+    /// println!("Replayed {sent} messages");
+    /// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+    /// # }).unwrap();
+    /// ```
+    pub async fn replay(self, connection: &Connection) -> Result<usize> {
+        let mut sent = 0;
+        for record in self {
+            let (_, msg) = record?;
+            connection.send(&msg).await?;
+            sent += 1;
+        }
+
+        Ok(sent)
+    }
+
+    fn next_record(&mut self) -> Result<Option<(SystemTime, Message)>> {
+        loop {
+            let mut header = [0; 16];
+            let endian = match &self.format {
+                Format::Pcap { endian, .. } => *endian,
+                Format::Pcapng { endian, .. } => *endian,
+            };
+            let header_len = match self.format {
+                Format::Pcap { .. } => 16,
+                Format::Pcapng { .. } => 8,
+            };
+            if !read_header(&mut self.inner, &mut header[..header_len])? {
+                return Ok(None);
+            }
+
+            match &mut self.format {
+                Format::Pcap { resolution, .. } => {
+                    let secs = read_u32(endian, &header[0..]) as u64;
+                    let fraction = read_u32(endian, &header[4..]) as u64;
+                    let len = read_u32(endian, &header[8..]) as usize;
+                    let orig_len = read_u32(endian, &header[12..]) as usize;
+                    let bytes = read_body(&mut self.inner, len)?;
+                    if len != orig_len {
+                        return Err(invalid_data("Truncated message").into());
+                    }
+                    let timestamp =
+                        UNIX_EPOCH + Duration::from_secs(secs) + to_duration(fraction, *resolution);
+
+                    return Ok(Some((timestamp, message_from_bytes(bytes)?)));
+                }
+                Format::Pcapng { interfaces, .. } => {
+                    let block_type = read_u32(endian, &header[0..]);
+                    if block_type == SECTION_HEADER_BLOCK {
+                        // The length was read in the byte order of the previous section.
+                        let len = header[4..8].try_into().unwrap();
+                        let endian = read_section_header(&mut self.inner, len)?;
+                        self.format = Format::Pcapng {
+                            endian,
+                            interfaces: vec![],
+                        };
+
+                        continue;
+                    }
+
+                    let len = read_u32(endian, &header[4..]) as usize;
+                    if len < 12 || len % 4 != 0 {
+                        return Err(invalid_data("Invalid block length").into());
+                    }
+                    // The body, followed by the repeated block length.
+                    let body = read_body(&mut self.inner, len - 8)?;
+                    let body = &body[..body.len() - 4];
+
+                    match block_type {
+                        INTERFACE_DESCRIPTION_BLOCK => {
+                            interfaces.push(read_interface(endian, body)?);
+                        }
+                        ENHANCED_PACKET_BLOCK => {
+                            if body.len() < 20 {
+                                return Err(invalid_data("Invalid enhanced packet block").into());
+                            }
+                            let interface = interfaces
+                                .get(read_u32(endian, body) as usize)
+                                .ok_or_else(|| invalid_data("Unknown interface"))?;
+                            if interface.link_type != LINKTYPE_DBUS {
+                                continue;
+                            }
+                            let ts = (read_u32(endian, &body[4..]) as u64) << 32
+                                | read_u32(endian, &body[8..]) as u64;
+                            let len = read_u32(endian, &body[12..]) as usize;
+                            let orig_len = read_u32(endian, &body[16..]) as usize;
+                            let bytes = body
+                                .get(20..20 + len)
+                                .ok_or_else(|| invalid_data("Invalid enhanced packet block"))?;
+                            if len != orig_len {
+                                return Err(invalid_data("Truncated message").into());
+                            }
+                            let timestamp = UNIX_EPOCH + to_duration(ts, interface.resolution);
+
+                            return Ok(Some((timestamp, message_from_bytes(bytes.to_vec())?)));
+                        }
+                        // Statistics, name resolution, etc.
+                        _ => continue,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: Read,
+{
+    type Item = Result<(SystemTime, Message)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Read the rest of a section header block, after its type and (not yet decoded) length `len`,
+/// returning its byte order.
+fn read_section_header<R: Read>(reader: &mut R, len: [u8; 4]) -> Result<Endian> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    let endian = match u32::from_le_bytes(magic) {
+        BYTE_ORDER_MAGIC => Endian::Little,
+        m if m.swap_bytes() == BYTE_ORDER_MAGIC => Endian::Big,
+        _ => return Err(invalid_data("Invalid byte-order magic").into()),
+    };
+    let len = read_u32(endian, &len) as usize;
+    if len < 16 || len % 4 != 0 {
+        return Err(invalid_data("Invalid block length").into());
+    }
+    // We don't need anything else from it.
+    read_body(reader, len - 12)?;
+
+    Ok(endian)
+}
+
+fn read_interface(endian: Endian, body: &[u8]) -> Result<Interface> {
+    if body.len() < 8 {
+        return Err(invalid_data("Invalid interface description block").into());
+    }
+    let link_type = read_u16(endian, body);
+    let mut resolution = 1_000_000;
+
+    let mut options = &body[8..];
+    while options.len() >= 4 {
+        let code = read_u16(endian, options);
+        let len = read_u16(endian, &options[2..]) as usize;
+        let value = options
+            .get(4..4 + len)
+            .ok_or_else(|| invalid_data("Invalid option"))?;
+        match code {
+            OPT_END_OF_OPT => break,
+            OPT_IF_TSRESOL if len == 1 => {
+                let exponent = value[0] & 0x7f;
+                resolution = if value[0] & 0x80 == 0 {
+                    10u64.checked_pow(exponent as u32)
+                } else {
+                    2u64.checked_pow(exponent as u32)
+                }
+                .ok_or_else(|| invalid_data("Invalid timestamp resolution"))?;
+            }
+            _ => (),
+        }
+        options = options
+            .get(4 + len + padding_for_4_bytes(len)..)
+            .unwrap_or(&[]);
+    }
+
+    Ok(Interface {
+        link_type,
+        resolution,
+    })
+}
+
+fn message_from_bytes(bytes: Vec<u8>) -> Result<Message> {
+    if bytes.len() < MIN_MESSAGE_SIZE {
+        return Err(invalid_data("Message too short").into());
+    }
+    let (primary_header, fields_len) = PrimaryHeader::read(&bytes)?;
+    let header_len = MIN_MESSAGE_SIZE + fields_len as usize;
+    let total_len =
+        header_len + padding_for_8_bytes(header_len) + primary_header.body_len() as usize;
+    if total_len != bytes.len() {
+        return Err(invalid_data("Invalid message length").into());
+    }
+    let endian = Endian::from(EndianSig::try_from(bytes[0])?);
+    let bytes = serialized::Data::new(bytes, serialized::Context::new_dbus(endian, 0));
+
+    Message::from_raw_parts(bytes, 0)
+}
+
+/// Read a record or block header, returning `false` on a clean end of file.
+fn read_header<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut pos = 0;
+    while pos < buf.len() {
+        match reader.read(&mut buf[pos..]) {
+            Ok(0) if pos == 0 => return Ok(false),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => pos += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(true)
+}
+
+fn read_body<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>> {
+    if len > MAX_BLOCK_SIZE {
+        return Err(invalid_data("Record too large").into());
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+
+    Ok(body)
+}
+
+fn read_u16(endian: Endian, bytes: &[u8]) -> u16 {
+    let bytes = bytes[..2].try_into().unwrap();
+    match endian {
+        Endian::Little => u16::from_le_bytes(bytes),
+        Endian::Big => u16::from_be_bytes(bytes),
+    }
+}
+
+fn read_u32(endian: Endian, bytes: &[u8]) -> u32 {
+    let bytes = bytes[..4].try_into().unwrap();
+    match endian {
+        Endian::Little => u32::from_le_bytes(bytes),
+        Endian::Big => u32::from_be_bytes(bytes),
+    }
+}
+
+fn to_duration(ticks: u64, resolution: u64) -> Duration {
+    let secs = ticks / resolution;
+    let nanos = (ticks % resolution) as u128 * 1_000_000_000 / resolution as u128;
+
+    Duration::new(secs, nanos as u32)
+}
+
+fn padding_for_4_bytes(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+    use ntest::timeout;
+    use std::time::{Duration, UNIX_EPOCH};
+    use test_log::test;
+
+    use super::{Reader, Writer};
+    use crate::{message::Message, Connection, MessageStream, Result};
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message::method_call("/org/zbus/Test", "Ping")
+                .unwrap()
+                .destination("org.zbus.Test")
+                .unwrap()
+                .build(&())
+                .unwrap(),
+            Message::signal("/org/zbus/Test", "org.zbus.Test", "Changed")
+                .unwrap()
+                .build(&("odd length", 42u8))
+                .unwrap(),
+        ]
+    }
+
+    #[test]
+    fn pcapng_roundtrip() {
+        let messages = messages();
+        let timestamp = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+
+        let mut writer = Writer::new(vec![]).unwrap();
+        for msg in &messages {
+            writer.write_with_timestamp(msg, timestamp).unwrap();
+        }
+        let bytes = writer.into_inner();
+
+        let records = Reader::new(&bytes[..])
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), messages.len());
+        for ((ts, read), written) in records.iter().zip(&messages) {
+            assert_eq!(*ts, timestamp);
+            assert_eq!(read.data().bytes(), written.data().bytes());
+            assert_eq!(read.header().member(), written.header().member());
+        }
+
+        // A truncated file.
+        let mut reader = Reader::new(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn pcapng_sections() {
+        let messages = messages();
+        let timestamp = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+
+        // A little-endian section, as we write it.
+        let mut writer = Writer::new(vec![]).unwrap();
+        writer
+            .write_with_timestamp(&messages[0], timestamp)
+            .unwrap();
+        let mut bytes = writer.into_inner();
+
+        // Followed by a big-endian one, with the default microsecond timestamps.
+        let block = |bytes: &mut Vec<u8>, block_type: u32, body: &[u8]| {
+            let len = (body.len() as u32 + 12).to_be_bytes();
+            bytes.extend(block_type.to_be_bytes());
+            bytes.extend(len);
+            bytes.extend(body);
+            bytes.extend(len);
+        };
+        let mut shb = vec![];
+        shb.extend(0x1A2B_3C4Du32.to_be_bytes());
+        shb.extend(1u16.to_be_bytes());
+        shb.extend(0u16.to_be_bytes());
+        shb.extend((-1i64).to_be_bytes());
+        // An option, which is skipped along with the rest of the block.
+        shb.extend(1u16.to_be_bytes());
+        shb.extend(4u16.to_be_bytes());
+        shb.extend(*b"test");
+        block(&mut bytes, 0x0A0D_0D0A, &shb);
+        let mut idb = vec![];
+        idb.extend(231u16.to_be_bytes());
+        idb.extend(0u16.to_be_bytes());
+        idb.extend(0u32.to_be_bytes());
+        block(&mut bytes, 1, &idb);
+        let msg = messages[1].data().bytes();
+        let ticks = 1_700_000_001_000_005u64;
+        let mut epb = vec![];
+        epb.extend(0u32.to_be_bytes());
+        epb.extend(((ticks >> 32) as u32).to_be_bytes());
+        epb.extend((ticks as u32).to_be_bytes());
+        epb.extend((msg.len() as u32).to_be_bytes());
+        epb.extend((msg.len() as u32).to_be_bytes());
+        epb.extend(msg);
+        epb.resize(epb.len() + (4 - msg.len() % 4) % 4, 0);
+        block(&mut bytes, 6, &epb);
+
+        let records = Reader::new(&bytes[..])
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, timestamp);
+        assert_eq!(records[0].1.data().bytes(), messages[0].data().bytes());
+        assert_eq!(
+            records[1].0,
+            UNIX_EPOCH + Duration::new(1_700_000_001, 5_000)
+        );
+        assert_eq!(records[1].1.data().bytes(), msg);
+    }
+
+    #[test]
+    #[timeout(15000)]
+    fn replay() {
+        crate::block_on(async {
+            let receiver = Connection::session().await.unwrap();
+            let mut stream = MessageStream::from(&receiver);
+            let name = receiver.unique_name().unwrap();
+
+            let mut writer = Writer::new(vec![]).unwrap();
+            for i in 0..3u32 {
+                let msg = Message::signal("/org/zbus/Test", "org.zbus.Test", "Replayed")
+                    .unwrap()
+                    .destination(name)
+                    .unwrap()
+                    .build(&i)
+                    .unwrap();
+                writer.write(&msg).unwrap();
+            }
+            let bytes = writer.into_inner();
+
+            let sender = Connection::session().await.unwrap();
+            let sent = Reader::new(&bytes[..])
+                .unwrap()
+                .replay(&sender)
+                .await
+                .unwrap();
+            assert_eq!(sent, 3);
+
+            let mut received = vec![];
+            while received.len() < 3 {
+                let msg = stream.try_next().await.unwrap().unwrap();
+                if msg.header().member().is_some_and(|m| m == "Replayed") {
+                    received.push(msg.body().deserialize::<u32>().unwrap());
+                }
+            }
+            assert_eq!(received, [0, 1, 2]);
+        });
+    }
+
+    #[test]
+    fn pcap() {
+        // Like `dbus-monitor --pcap` writes it: big-endian header, microsecond timestamps.
+        let msg = &messages()[0];
+        let len = msg.data().len() as u32;
+        let mut bytes = vec![];
+        bytes.extend(0xA1B2_C3D4u32.to_be_bytes());
+        bytes.extend(2u16.to_be_bytes());
+        bytes.extend(4u16.to_be_bytes());
+        bytes.extend([0; 8]);
+        bytes.extend(u32::MAX.to_be_bytes());
+        bytes.extend(231u32.to_be_bytes());
+        bytes.extend(1_700_000_000u32.to_be_bytes());
+        bytes.extend(5u32.to_be_bytes());
+        bytes.extend(len.to_be_bytes());
+        bytes.extend(len.to_be_bytes());
+        bytes.extend(msg.data().bytes());
+
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        let (ts, read) = reader.next().unwrap().unwrap();
+        assert_eq!(ts, UNIX_EPOCH + Duration::new(1_700_000_000, 5_000));
+        assert_eq!(read.data().bytes(), msg.data().bytes());
+        assert!(reader.next().is_none());
+
+        // Not a D-Bus capture.
+        bytes[20..24].copy_from_slice(&1u32.to_be_bytes());
+        assert!(Reader::new(&bytes[..]).is_err());
+    }
+}
@@ -1,8 +1,14 @@
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
 
 use static_assertions::assert_impl_all;
 use zbus_names::{BusName, InterfaceName};
-use zvariant::{ObjectPath, Str};
+use zvariant::{ObjectPath, OwnedValue, Str};
 
 use crate::{proxy::ProxyInner, Connection, Error, Proxy, Result};
 
@@ -20,6 +26,18 @@ pub enum CacheProperties {
     Lazily,
 }
 
+/// Takes the snapshot of the property values to populate the cache with.
+#[derive(Clone)]
+pub(crate) struct PropertiesSnapshot(
+    Arc<dyn Fn() -> Result<HashMap<String, OwnedValue>> + Send + Sync>,
+);
+
+impl fmt::Debug for PropertiesSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PropertiesSnapshot").finish_non_exhaustive()
+    }
+}
+
 /// Builder for proxies.
 #[derive(Debug)]
 pub struct Builder<'a, T = ()> {
@@ -30,6 +48,8 @@ pub struct Builder<'a, T = ()> {
     proxy_type: PhantomData<T>,
     cache: CacheProperties,
     uncached_properties: Option<HashSet<Str<'a>>>,
+    cached_values: Option<PropertiesSnapshot>,
+    method_timeout: Option<Duration>,
 }
 
 impl<T> Clone for Builder<'_, T> {
@@ -41,6 +61,8 @@ impl<T> Clone for Builder<'_, T> {
             interface: self.interface.clone(),
             cache: self.cache,
             uncached_properties: self.uncached_properties.clone(),
+            cached_values: self.cached_values.clone(),
+            method_timeout: self.method_timeout,
             proxy_type: PhantomData,
         }
     }
@@ -95,6 +117,33 @@ impl<'a, T> Builder<'a, T> {
         self
     }
 
+    /// Populate the properties cache with the values returned by `snapshot`, instead of fetching
+    /// them from the peer.
+    ///
+    /// The snapshot is taken once the proxy is subscribed to the changes of the properties, so that
+    /// none of them is missed. This has no effect if caching is disabled.
+    pub(crate) fn cached_values<F>(mut self, snapshot: F) -> Self
+    where
+        F: Fn() -> Result<HashMap<String, OwnedValue>> + Send + Sync + 'static,
+    {
+        self.cached_values = Some(PropertiesSnapshot(Arc::new(snapshot)));
+
+        self
+    }
+
+    /// Set the default timeout for method call replies.
+    ///
+    /// This overrides the [connection-wide default][cd], if any, for all method calls made through
+    /// the proxy.
+    ///
+    /// [cd]: crate::connection::Builder::method_timeout
+    #[must_use]
+    pub fn method_timeout(mut self, timeout: Duration) -> Self {
+        self.method_timeout = Some(timeout);
+
+        self
+    }
+
     pub(crate) fn build_internal(self) -> Result<Proxy<'a>> {
         let conn = self.conn;
         let destination = self
@@ -104,6 +153,7 @@ impl<'a, T> Builder<'a, T> {
         let interface = self.interface.ok_or(Error::MissingParameter("interface"))?;
         let cache = self.cache;
         let uncached_properties = self.uncached_properties.unwrap_or_default();
+        let method_timeout = self.method_timeout;
 
         Ok(Proxy {
             inner: Arc::new(ProxyInner::new(
@@ -113,6 +163,7 @@ impl<'a, T> Builder<'a, T> {
                 interface,
                 cache,
                 uncached_properties,
+                method_timeout,
             )),
         })
     }
@@ -128,9 +179,17 @@ impl<'a, T> Builder<'a, T> {
         T: From<Proxy<'a>>,
     {
         let cache_upfront = self.cache == CacheProperties::Yes;
+        let snapshot = self.cached_values.clone();
         let proxy = self.build_internal()?;
 
-        if cache_upfront {
+        if let Some(snapshot) = snapshot.filter(|_| proxy.inner.property_cache.is_some()) {
+            // Subscribe before taking the snapshot, so no change in between is missed.
+            let prop_changes = proxy
+                .owned_properties_proxy()
+                .receive_properties_changed()
+                .await?;
+            proxy.init_property_cache(Some((prop_changes, (snapshot.0)()?)));
+        } else if cache_upfront {
             proxy
                 .get_property_cache()
                 .expect("properties cache not initialized")
@@ -156,6 +215,8 @@ where
             interface: T::INTERFACE.clone(),
             cache: CacheProperties::default(),
             uncached_properties: None,
+            cached_values: None,
+            method_timeout: None,
             proxy_type: PhantomData,
         }
     }
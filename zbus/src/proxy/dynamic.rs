@@ -0,0 +1,301 @@
+use futures_core::stream;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use zbus_xml::{Arg, ArgDirection, Interface, Node};
+use zvariant::{OwnedValue, Signature, Structure, Value};
+
+use crate::{object_server::DynamicBody, AsyncDrop, Error, Message, Result};
+
+use super::{Proxy, SignalStream};
+
+/// A proxy for an interface only known at runtime, through introspection.
+///
+/// [`Proxy`] and the [`macro@crate::proxy`] macro expect you to know the interface at compile time.
+/// `DynamicProxy` is for the cases where you don't, e.g generic tooling talking to arbitrary
+/// services. It introspects the remote object on creation and looks the interface of the proxy up
+/// in the introspection data. Its members are then accessed by name, with [`Value`]-typed
+/// arguments that are checked against the declared signatures:
+///
+/// * Calling a method, accessing a property or receiving a signal the interface doesn't declare
+///   fails with [`Error::MemberNotFound`].
+/// * Arguments, property values and replies that don't match the declared signature result in
+///   [`Error::SignatureMismatch`]. The only leeway is that values given for arguments of type
+///   variant (`v`) are wrapped in one for you, if they aren't already.
+///
+/// # Example
+///
+/// ```no_run
+/// use zbus::{proxy::DynamicProxy, zvariant::Value, Connection, Proxy};
+///
+/// # zbus::block_on(async {
+/// let connection = Connection::session().await?;
+/// let proxy = Proxy::new(
+///     &connection,
+///     "org.freedesktop.DBus",
+///     "/org/freedesktop/DBus",
+///     "org.freedesktop.DBus",
+/// )
+/// .await?;
+/// let proxy = DynamicProxy::new(proxy).await?;
+///
+/// for method in proxy.interface().methods() {
+///     println!("{}", method.name());
+/// }
+/// let reply = proxy
+///     .call("NameHasOwner", vec![Value::from("org.freedesktop.DBus")])
+///     .await?;
+/// assert_eq!(reply[0], true.into());
+/// # Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct DynamicProxy<'a> {
+    proxy: Proxy<'a>,
+    interface: Interface<'static>,
+}
+
+impl<'a> DynamicProxy<'a> {
+    /// Create a dynamic proxy for the interface of `proxy`.
+    ///
+    /// The remote object is introspected and [`Error::InterfaceNotFound`] is returned if it
+    /// doesn't have the interface.
+    pub async fn new(proxy: Proxy<'a>) -> Result<Self> {
+        let xml = proxy.introspect().await?;
+        let node = Node::from_reader(xml.as_bytes())
+            .map_err(|e| Error::Failure(format!("Invalid introspection data: {e}")))?;
+        let interface = node
+            .interfaces()
+            .iter()
+            .find(|i| i.name() == *proxy.interface())
+            .cloned()
+            .ok_or(Error::InterfaceNotFound)?;
+
+        Ok(Self { proxy, interface })
+    }
+
+    /// The underlying proxy.
+    pub fn inner(&self) -> &Proxy<'a> {
+        &self.proxy
+    }
+
+    /// The underlying proxy, consuming `self`.
+    pub fn into_inner(self) -> Proxy<'a> {
+        self.proxy
+    }
+
+    /// The introspection data of the interface.
+    pub fn interface(&self) -> &Interface<'static> {
+        &self.interface
+    }
+
+    /// Call the method `name` with the given arguments and return the values of the reply.
+    pub async fn call(&self, name: &str, args: Vec<Value<'_>>) -> Result<Vec<OwnedValue>> {
+        let method = self
+            .interface
+            .methods()
+            .iter()
+            .find(|m| m.name() == name)
+            .ok_or_else(|| Error::MemberNotFound(name.to_string()))?;
+        let (in_args, out_args): (Vec<_>, Vec<_>) = method
+            .args()
+            .iter()
+            .partition(|a| a.direction() != Some(ArgDirection::Out));
+
+        let body = body(name, &in_args, args)?;
+        let reply = self.proxy.call_method(name, &body).await?;
+
+        values(name, &out_args, &reply)
+    }
+
+    /// Get the value of the property `name`.
+    pub async fn get_property(&self, name: &str) -> Result<OwnedValue> {
+        let property = self
+            .interface
+            .properties()
+            .iter()
+            .find(|p| p.name() == name)
+            .ok_or_else(|| Error::MemberNotFound(name.to_string()))?;
+        if !property.access().read() {
+            return Err(Error::Failure(format!("Property `{name}` is not readable")));
+        }
+
+        let value: OwnedValue = self.proxy.get_property(name).await?;
+        let expected = property.ty().inner();
+        // The values of `v` properties are unwrapped, so they can be of any type.
+        if *expected != Signature::Variant && value.value_signature() != expected {
+            return Err(Error::SignatureMismatch(
+                name.to_string(),
+                None,
+                expected.clone(),
+                value.value_signature().clone(),
+            ));
+        }
+
+        Ok(value)
+    }
+
+    /// Set the value of the property `name`.
+    pub async fn set_property(&self, name: &str, value: Value<'_>) -> Result<()> {
+        let property = self
+            .interface
+            .properties()
+            .iter()
+            .find(|p| p.name() == name)
+            .ok_or_else(|| Error::MemberNotFound(name.to_string()))?;
+        if !property.access().write() {
+            return Err(Error::Failure(format!("Property `{name}` is not writable")));
+        }
+
+        let value = conform(name, None, property.ty().inner(), value)?;
+        self.proxy
+            .set_property(name, value)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Receive the signal `name`.
+    pub async fn receive_signal(&self, name: &str) -> Result<DynamicSignalStream<'static>> {
+        let signal = self
+            .interface
+            .signals()
+            .iter()
+            .find(|s| s.name() == name)
+            .ok_or_else(|| Error::MemberNotFound(name.to_string()))?;
+        let stream = self.proxy.receive_signal(name.to_string()).await?;
+
+        Ok(DynamicSignalStream {
+            stream,
+            name: name.to_string(),
+            args: signal.args().to_vec(),
+        })
+    }
+}
+
+/// A [`stream::Stream`] of the arguments of a signal.
+///
+/// Use [`DynamicProxy::receive_signal`] to create an instance of this type. Signals that don't
+/// match the declared signature yield [`Error::SignatureMismatch`].
+#[derive(Debug)]
+pub struct DynamicSignalStream<'a> {
+    stream: SignalStream<'a>,
+    name: String,
+    args: Vec<Arg>,
+}
+
+impl stream::Stream for DynamicSignalStream<'_> {
+    type Item = Result<Vec<OwnedValue>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let args: Vec<_> = this.args.iter().collect();
+
+        Pin::new(&mut this.stream)
+            .poll_next(cx)
+            .map(|msg| msg.map(|msg| values(&this.name, &args, &msg)))
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncDrop for DynamicSignalStream<'_> {
+    async fn async_drop(self) {
+        self.stream.async_drop().await
+    }
+}
+
+/// Check `value` against `signature`, wrapping it in a variant if need be.
+///
+/// `arg` is the index of the argument `value` is for, if any.
+fn conform<'v>(
+    member: &str,
+    arg: Option<usize>,
+    signature: &Signature,
+    value: Value<'v>,
+) -> Result<Value<'v>> {
+    if value.value_signature() == signature {
+        Ok(value)
+    } else if *signature == Signature::Variant {
+        Ok(Value::Value(Box::new(value)))
+    } else {
+        Err(Error::SignatureMismatch(
+            member.to_string(),
+            arg,
+            signature.clone(),
+            value.value_signature().clone(),
+        ))
+    }
+}
+
+fn signature(args: &[&Arg]) -> Signature {
+    DynamicBody::signature_of(args.iter().map(|a| a.ty().inner().clone()).collect())
+}
+
+/// The body of a message made of `values`, checked against `args`.
+fn body<'v>(member: &str, args: &[&Arg], values: Vec<Value<'v>>) -> Result<DynamicBody<'v>> {
+    if values.len() != args.len() {
+        return Err(Error::SignatureMismatch(
+            member.to_string(),
+            None,
+            signature(args),
+            DynamicBody::signature_of(values.iter().map(|v| v.value_signature().clone()).collect()),
+        ));
+    }
+    let values = args
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(i, (arg, value))| conform(member, Some(i), arg.ty().inner(), value))
+        .collect::<Result<_>>()?;
+
+    DynamicBody::new(values).map_err(Into::into)
+}
+
+/// The values of the body of `msg`, checked against `args`.
+fn values(member: &str, args: &[&Arg], msg: &Message) -> Result<Vec<OwnedValue>> {
+    let body = msg.body();
+    let expected = signature(args);
+    if *body.signature() != expected {
+        let actual = match body.signature() {
+            Signature::Unit => vec![],
+            Signature::Structure(fields) if args.len() != 1 => fields.iter().cloned().collect(),
+            signature => vec![signature.clone()],
+        };
+        let arg = (actual.len() == args.len())
+            .then(|| {
+                args.iter()
+                    .zip(&actual)
+                    .position(|(arg, actual)| arg.ty().inner() != actual)
+            })
+            .flatten();
+        return Err(match arg {
+            Some(i) => Error::SignatureMismatch(
+                member.to_string(),
+                Some(i),
+                args[i].ty().inner().clone(),
+                actual[i].clone(),
+            ),
+            None => Error::SignatureMismatch(
+                member.to_string(),
+                None,
+                expected,
+                body.signature().clone(),
+            ),
+        });
+    }
+    if args.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Message bodies are 8-byte aligned, so their arguments can always be read as a structure.
+    let fields: Vec<_> = args.iter().map(|a| a.ty().inner().clone()).collect();
+    let (structure, _) = body
+        .data()
+        .deserialize_for_dynamic_signature::<_, Structure<'_>>(Signature::structure(fields))?;
+
+    structure
+        .into_fields()
+        .iter()
+        .map(|v| v.try_to_owned().map_err(Into::into))
+        .collect()
+}
@@ -14,6 +14,7 @@ use std::{
     pin::Pin,
     sync::{Arc, OnceLock, RwLock, RwLockReadGuard},
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, info_span, instrument, trace, Instrument};
 
@@ -32,6 +33,11 @@ pub use builder::{Builder, CacheProperties};
 mod defaults;
 pub use defaults::Defaults;
 
+#[cfg(feature = "xml")]
+mod dynamic;
+#[cfg(feature = "xml")]
+pub use dynamic::{DynamicProxy, DynamicSignalStream};
+
 /// A client-side interface proxy.
 ///
 /// A `Proxy` is a helper to interact with an interface on a remote object.
@@ -106,6 +112,8 @@ pub(crate) struct ProxyInner<'a> {
     /// Set of properties which do not get cached, by name.
     /// This overrides proxy-level caching behavior.
     uncached_properties: HashSet<Str<'a>>,
+    /// The default method reply timeout, overriding that of the connection.
+    method_timeout: Option<Duration>,
 }
 
 impl Drop for ProxyInnerStatic {
@@ -275,9 +283,24 @@ impl PropertiesCache {
         interface: InterfaceName<'static>,
         executor: &Executor<'_>,
         uncached_properties: HashSet<zvariant::Str<'static>>,
+        populated: Option<(PropertiesChangedStream, HashMap<String, OwnedValue>)>,
     ) -> (Arc<Self>, Task<()>) {
+        let (prop_changes, values) = populated.unzip();
+        let values = values
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| !uncached_properties.contains(&Str::from(name.as_str())))
+            .map(|(name, value)| {
+                let value = PropertyValue {
+                    value: Some(value),
+                    event: Event::new(),
+                };
+
+                (name, value)
+            })
+            .collect();
         let cache = Arc::new(PropertiesCache {
-            values: Default::default(),
+            values: RwLock::new(values),
             caching_result: RwLock::new(CachingResult::Caching {
                 ready: Event::new(),
             }),
@@ -286,9 +309,15 @@ impl PropertiesCache {
         let cache_clone = cache.clone();
         let task_name = format!("{interface} proxy caching");
         let proxy_caching = async move {
-            let result = cache_clone
-                .init(proxy, interface, uncached_properties)
-                .await;
+            let result = match prop_changes {
+                // We only need to keep the given values updated.
+                Some(prop_changes) => Ok((prop_changes, interface, uncached_properties)),
+                None => {
+                    cache_clone
+                        .init(proxy, interface, uncached_properties)
+                        .await
+                }
+            };
             let (prop_changes, interface, uncached_properties) = {
                 let mut caching_result = cache_clone.caching_result.write().expect("lock poisoned");
                 let ready = match &*caching_result {
@@ -350,6 +379,7 @@ impl PropertiesCache {
                 Some(proxy.inner().interface()),
                 "GetAll",
                 BitFlags::empty(),
+                proxy.inner().method_timeout(),
                 &interface,
             )
             .await
@@ -497,6 +527,7 @@ impl<'a> ProxyInner<'a> {
         interface: InterfaceName<'a>,
         cache: CacheProperties,
         uncached_properties: HashSet<Str<'a>>,
+        method_timeout: Option<Duration>,
     ) -> Self {
         let property_cache = match cache {
             CacheProperties::Yes | CacheProperties::Lazily => Some(OnceLock::new()),
@@ -512,6 +543,7 @@ impl<'a> ProxyInner<'a> {
             interface,
             property_cache,
             uncached_properties,
+            method_timeout,
         }
     }
 
@@ -641,6 +673,16 @@ impl<'a> Proxy<'a> {
         &self.inner.interface
     }
 
+    /// The default timeout for method call replies, if any.
+    ///
+    /// This is the timeout set through [`Builder::method_timeout`] or if none was set, that of the
+    /// associated connection.
+    pub fn method_timeout(&self) -> Option<Duration> {
+        self.inner
+            .method_timeout
+            .or_else(|| self.connection().method_timeout())
+    }
+
     /// Introspect the associated object, and return the XML description.
     ///
     /// See the [xml](https://docs.rs/zbus_xml) crate for parsing the
@@ -690,6 +732,17 @@ impl<'a> Proxy<'a> {
     /// Use PropertiesCache::ready() to wait for the cache to be populated and to get any errors
     /// encountered in the population.
     pub(crate) fn get_property_cache(&self) -> Option<&Arc<PropertiesCache>> {
+        self.init_property_cache(None)
+    }
+
+    /// Get the cache, starting it in the background if needed.
+    ///
+    /// If `populated` is `None`, the cache gets populated from the peer. Otherwise, it's populated
+    /// with the given values and kept updated with the given stream of changes.
+    pub(crate) fn init_property_cache(
+        &self,
+        populated: Option<(PropertiesChangedStream, HashMap<String, OwnedValue>)>,
+    ) -> Option<&Arc<PropertiesCache>> {
         let cache = match &self.inner.property_cache {
             Some(cache) => cache,
             None => return None,
@@ -705,7 +758,7 @@ impl<'a> Proxy<'a> {
                 .collect();
             let executor = self.connection().executor();
 
-            PropertiesCache::new(proxy, interface, executor, uncached_properties)
+            PropertiesCache::new(proxy, interface, executor, uncached_properties, populated)
         });
 
         Some(cache)
@@ -827,17 +880,30 @@ impl<'a> Proxy<'a> {
         M::Error: Into<Error>,
         B: serde::ser::Serialize + zvariant::DynamicType,
     {
-        self.inner
-            .inner_without_borrows
-            .conn
-            .call_method(
-                Some(&self.inner.destination),
-                self.inner.path.as_str(),
-                Some(&self.inner.interface),
-                method_name,
-                body,
-            )
+        self.call_method_raw(method_name, BitFlags::empty(), self.method_timeout(), body)
             .await
+            .map(|reply| reply.expect("no reply"))
+    }
+
+    /// Call a method and return the reply, with a specific reply timeout.
+    ///
+    /// Same as [`Proxy::call_method`], except that the given `timeout` is used instead of the
+    /// [default one](Proxy::method_timeout). If no reply is received within `timeout`,
+    /// [`Error::Timeout`] is returned.
+    pub async fn call_method_with_timeout<'m, M, B>(
+        &self,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Message>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
+        self.call_method_raw(method_name, BitFlags::empty(), Some(timeout), body)
+            .await
+            .map(|reply| reply.expect("no reply"))
     }
 
     /// Call a method and return the reply body.
@@ -857,6 +923,30 @@ impl<'a> Proxy<'a> {
         reply.body().deserialize()
     }
 
+    /// Call a method and return the reply body, with a specific reply timeout.
+    ///
+    /// Same as [`Proxy::call`], except that the given `timeout` is used instead of the
+    /// [default one](Proxy::method_timeout). If no reply is received within `timeout`,
+    /// [`Error::Timeout`] is returned.
+    pub async fn call_with_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<R>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        let reply = self
+            .call_method_with_timeout(method_name, timeout, body)
+            .await?;
+
+        reply.body().deserialize()
+    }
+
     /// Call a method and return the reply body, optionally supplying a set of
     /// method flags to control the way the method call message is sent and handled.
     ///
@@ -879,6 +969,55 @@ impl<'a> Proxy<'a> {
         R: for<'d> zvariant::DynamicDeserialize<'d>,
     {
         let flags = flags.iter().map(Flags::from).collect::<BitFlags<_>>();
+        match self
+            .call_method_raw(method_name, flags, self.method_timeout(), body)
+            .await?
+        {
+            Some(reply) => reply.body().deserialize().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Call a method with flags and return the reply body, with a specific reply timeout.
+    ///
+    /// Same as [`Proxy::call_with_flags`], except that the given `timeout` is used instead of the
+    /// [default one](Proxy::method_timeout). The timeout has no effect if the `NoReplyExpected`
+    /// flag is passed.
+    pub async fn call_with_flags_and_timeout<'m, M, B, R>(
+        &self,
+        method_name: M,
+        flags: BitFlags<MethodFlags>,
+        timeout: Duration,
+        body: &B,
+    ) -> Result<Option<R>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+        R: for<'d> zvariant::DynamicDeserialize<'d>,
+    {
+        let flags = flags.iter().map(Flags::from).collect::<BitFlags<_>>();
+        match self
+            .call_method_raw(method_name, flags, Some(timeout), body)
+            .await?
+        {
+            Some(reply) => reply.body().deserialize().map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn call_method_raw<'m, M, B>(
+        &self,
+        method_name: M,
+        flags: BitFlags<Flags>,
+        timeout: Option<Duration>,
+        body: &B,
+    ) -> Result<Option<Message>>
+    where
+        M: TryInto<MemberName<'m>>,
+        M::Error: Into<Error>,
+        B: serde::ser::Serialize + zvariant::DynamicType,
+    {
         match self
             .inner
             .inner_without_borrows
@@ -889,11 +1028,12 @@ impl<'a> Proxy<'a> {
                 Some(self.interface()),
                 method_name,
                 flags,
+                timeout,
                 body,
             )
             .await?
         {
-            Some(reply) => reply.await?.body().deserialize().map(Some),
+            Some(reply) => reply.await.map(Some),
             None => Ok(None),
         }
     }
@@ -1177,6 +1317,7 @@ impl<'a> SignalStream<'a> {
                         Some("org.freedesktop.DBus"),
                         "GetNameOwner",
                         BitFlags::empty(),
+                        conn.method_timeout(),
                         &name,
                     )
                     .await
@@ -1093,3 +1093,1563 @@ async fn iface_and_proxy_(#[allow(unused)] p2p: bool) {
     );
     debug!("Bus confirmed that all names were definitely released.");
 }
+
+fn same_user(_: &Header<'_>, creds: &zbus::fdo::ConnectionCredentials) -> zbus::fdo::Result<()> {
+    #[cfg(unix)]
+    if creds.unix_user_id() != Some(nix::unistd::Uid::effective().as_raw()) {
+        return Err(zbus::fdo::Error::AccessDenied("Not the same user".into()));
+    }
+    #[cfg(not(unix))]
+    let _ = creds;
+
+    Ok(())
+}
+
+fn deny_all(_: &Header<'_>, _: &zbus::fdo::ConnectionCredentials) -> zbus::fdo::Result<()> {
+    Err(zbus::fdo::Error::AccessDenied("Nobody is allowed".into()))
+}
+
+struct AccessControlled;
+
+#[interface(name = "org.zbus.AccessControlled", access_control = "same_user")]
+impl AccessControlled {
+    fn open(&self) -> u32 {
+        1
+    }
+
+    #[zbus(access_control = "deny_all")]
+    fn restricted(&self) -> u32 {
+        2
+    }
+
+    fn forbidden(&self) -> u32 {
+        3
+    }
+}
+
+struct Private;
+
+#[interface(name = "org.zbus.Private", access_control = "deny_all")]
+impl Private {
+    #[zbus(property)]
+    fn secret(&self) -> u32 {
+        42
+    }
+
+    #[zbus(property)]
+    fn set_secret(&mut self, _secret: u32) {}
+}
+
+#[zbus::proxy(
+    interface = "org.zbus.AccessControlled",
+    default_path = "/org/zbus/AccessControlled"
+)]
+trait AccessControlled {
+    fn open(&self) -> zbus::fdo::Result<u32>;
+    fn restricted(&self) -> zbus::fdo::Result<u32>;
+    fn forbidden(&self) -> zbus::fdo::Result<u32>;
+}
+
+#[test]
+#[timeout(15000)]
+fn access_control() {
+    block_on(async {
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/AccessControlled", AccessControlled)
+            .unwrap()
+            .serve_at("/org/zbus/Private", Private)
+            .unwrap()
+            .access_control(|hdr: &Header<'_>, _: &zbus::fdo::ConnectionCredentials| {
+                match hdr.member().map(|m| m.as_str()) {
+                    Some("Forbidden") => Err(zbus::fdo::Error::AccessDenied("Forbidden".into())),
+                    _ => Ok(()),
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy = AccessControlledProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        // Twice, so the credentials are also served from the cache.
+        for _ in 0..2 {
+            assert_eq!(proxy.open().await.unwrap(), 1);
+            assert!(matches!(
+                proxy.restricted().await,
+                Err(zbus::fdo::Error::AccessDenied(_))
+            ));
+            assert!(matches!(
+                proxy.forbidden().await,
+                Err(zbus::fdo::Error::AccessDenied(_))
+            ));
+        }
+
+        // The properties are subject to the access control of their interface.
+        let properties = zbus::fdo::PropertiesProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .path("/org/zbus/Private")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let iface = zbus::names::InterfaceName::from_static_str_unchecked("org.zbus.Private");
+        assert!(matches!(
+            properties.get(iface.clone(), "Secret").await,
+            Err(zbus::fdo::Error::AccessDenied(_))
+        ));
+        assert!(matches!(
+            properties.get_all(iface.clone()).await,
+            Err(zbus::fdo::Error::AccessDenied(_))
+        ));
+        assert!(matches!(
+            properties.set(iface, "Secret", 7.into()).await,
+            Err(zbus::fdo::Error::AccessDenied(_))
+        ));
+    });
+}
+
+struct MockPolkitAuthority;
+
+#[interface(name = "org.freedesktop.PolicyKit1.Authority")]
+impl MockPolkitAuthority {
+    fn check_authorization(
+        &self,
+        subject: zbus::fdo::polkit::Subject,
+        action_id: &str,
+        _details: HashMap<&str, &str>,
+        flags: enumflags2::BitFlags<zbus::fdo::polkit::CheckAuthorizationFlags>,
+        _cancellation_id: &str,
+    ) -> zbus::fdo::polkit::AuthorizationResult {
+        assert_eq!(subject.subject_kind, "system-bus-name");
+        let interactive =
+            flags.contains(zbus::fdo::polkit::CheckAuthorizationFlags::AllowUserInteraction);
+        let (is_authorized, is_challenge) = match action_id {
+            // Pretend the user authenticates successfully when asked to.
+            "org.zbus.test.authenticate" => (interactive, !interactive),
+            _ => (false, false),
+        };
+
+        zbus::fdo::polkit::AuthorizationResult {
+            is_authorized,
+            is_challenge,
+            details: HashMap::new(),
+        }
+    }
+}
+
+struct PolkitProtected;
+
+#[interface(name = "org.zbus.PolkitProtected")]
+impl PolkitProtected {
+    #[zbus(polkit_action = "org.zbus.test.authenticate")]
+    fn authenticated(&self) -> u32 {
+        1
+    }
+
+    #[zbus(polkit_action = "org.zbus.test.denied")]
+    fn denied(&self) -> u32 {
+        2
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.zbus.PolkitProtected",
+    default_path = "/org/zbus/PolkitProtected"
+)]
+trait PolkitProtected {
+    fn authenticated(&self) -> zbus::fdo::Result<u32>;
+
+    #[zbus(name = "Authenticated", allow_interactive_auth)]
+    fn authenticated_interactively(&self) -> zbus::fdo::Result<u32>;
+
+    #[zbus(allow_interactive_auth)]
+    fn denied(&self) -> zbus::fdo::Result<u32>;
+}
+
+#[test]
+#[timeout(15000)]
+fn polkit_action() {
+    block_on(async {
+        let _polkit = connection::Builder::session()
+            .unwrap()
+            .name("org.freedesktop.PolicyKit1")
+            .unwrap()
+            .serve_at("/org/freedesktop/PolicyKit1/Authority", MockPolkitAuthority)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/PolkitProtected", PolkitProtected)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy = PolkitProtectedProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            proxy.authenticated().await,
+            Err(zbus::fdo::Error::InteractiveAuthorizationRequired(_))
+        ));
+        assert_eq!(proxy.authenticated_interactively().await.unwrap(), 1);
+        assert!(matches!(
+            proxy.denied().await,
+            Err(zbus::fdo::Error::AccessDenied(_))
+        ));
+    });
+}
+
+struct Thing {
+    name: String,
+}
+
+#[interface(name = "org.zbus.Thing")]
+impl Thing {
+    #[zbus(property)]
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[zbus::proxy(interface = "org.zbus.Thing", gen_blocking = false)]
+trait Thing {
+    #[zbus(property)]
+    fn name(&self) -> zbus::Result<String>;
+}
+
+#[test]
+#[timeout(15000)]
+fn object_manager_client() {
+    use zbus::fdo::{ObjectManagerClient, ObjectManagerEvent};
+
+    block_on(async {
+        let service_name = "org.zbus.ObjectManagerClientTest";
+        let service = connection::Builder::session()
+            .unwrap()
+            .name(service_name)
+            .unwrap()
+            .serve_at("/org/zbus/Things", ObjectManager)
+            .unwrap()
+            .serve_at(
+                "/org/zbus/Things/first",
+                Thing {
+                    name: "First".into(),
+                },
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client_conn = Connection::session().await.unwrap();
+        let client = ObjectManagerClient::new(&client_conn, service_name, "/org/zbus/Things")
+            .await
+            .unwrap();
+        let first = zvariant::ObjectPath::from_static_str_unchecked("/org/zbus/Things/first");
+        let second = zvariant::ObjectPath::from_static_str_unchecked("/org/zbus/Things/second");
+        let thing = zbus::names::InterfaceName::from_static_str_unchecked("org.zbus.Thing");
+        assert_eq!(client.paths(), [first.clone().into()]);
+        assert_eq!(
+            client.objects_with_interface(&thing),
+            [first.clone().into()]
+        );
+        assert_eq!(
+            client.property::<String>(&first, &thing, "Name").unwrap(),
+            Some("First".to_string())
+        );
+        let mut events = client.receive_events();
+
+        // The signals of other connections are ignored, even for the same paths and if the
+        // connection receives them for another subscription.
+        let rule = zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .path_namespace("/org/zbus/Things")
+            .unwrap()
+            .build();
+        let _signals = MessageStream::for_match_rule(rule, &client_conn, None)
+            .await
+            .unwrap();
+        let impostor = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Things", ObjectManager)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        impostor
+            .object_server()
+            .at(
+                "/org/zbus/Things/impostor",
+                Thing {
+                    name: "Impostor".into(),
+                },
+            )
+            .await
+            .unwrap();
+
+        // Objects showing up.
+        service
+            .object_server()
+            .at(
+                &second,
+                Thing {
+                    name: "Second".into(),
+                },
+            )
+            .await
+            .unwrap();
+        match events.next().await.unwrap() {
+            ObjectManagerEvent::InterfacesAdded { path, interfaces } => {
+                assert_eq!(*path, second);
+                assert!(interfaces.contains_key("org.zbus.Thing"));
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        assert_eq!(client.objects_with_interface(&thing).len(), 2);
+
+        // Properties changing.
+        let iface_ref = service
+            .object_server()
+            .interface::<_, Thing>(&first)
+            .await
+            .unwrap();
+        iface_ref.get_mut().await.name = "Premier".into();
+        iface_ref
+            .get()
+            .await
+            .name_changed(iface_ref.signal_emitter())
+            .await
+            .unwrap();
+        match events.next().await.unwrap() {
+            ObjectManagerEvent::PropertiesChanged {
+                path,
+                interface,
+                changed,
+                ..
+            } => {
+                assert_eq!(*path, first);
+                assert_eq!(interface, thing);
+                assert_eq!(changed["Name"], OwnedValue::from(Str::from("Premier")));
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+
+        // Proxies get their properties from the client.
+        let proxy: ThingProxy<'_> = client.proxy(&first).await.unwrap();
+        assert_eq!(proxy.cached_name().unwrap().unwrap(), "Premier");
+        assert!(matches!(
+            client
+                .proxy::<ThingProxy<'_>, _>("/org/zbus/Things/none")
+                .await,
+            Err(Error::InterfaceNotFound)
+        ));
+
+        // Objects going away.
+        service
+            .object_server()
+            .remove::<Thing, _>(&second)
+            .await
+            .unwrap();
+        match events.next().await.unwrap() {
+            ObjectManagerEvent::InterfacesRemoved { path, interfaces } => {
+                assert_eq!(*path, second);
+                assert_eq!(interfaces.len(), 1);
+                assert_eq!(interfaces[0], thing);
+            }
+            event => panic!("unexpected event: {event:?}"),
+        }
+        assert_eq!(client.paths(), [first.clone().into()]);
+
+        // The whole service going away.
+        drop((iface_ref, service));
+        match events.next().await.unwrap() {
+            ObjectManagerEvent::InterfacesRemoved { path, .. } => assert_eq!(*path, first),
+            event => panic!("unexpected event: {event:?}"),
+        }
+        assert!(client.paths().is_empty());
+    });
+}
+
+struct Row {
+    id: u32,
+}
+
+#[interface(name = "org.zbus.Row", proxy(gen_blocking = false))]
+impl Row {
+    fn describe(&self) -> String {
+        format!("Row #{}", self.id)
+    }
+
+    #[zbus(property)]
+    fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+struct Table;
+
+#[async_trait::async_trait]
+impl zbus::object_server::Fallback for Table {
+    async fn resolve(
+        &self,
+        path: &zvariant::ObjectPath<'_>,
+    ) -> zbus::fdo::Result<Option<zbus::object_server::ResolvedObject>> {
+        let id = path
+            .strip_prefix("/org/zbus/Table/")
+            .and_then(|id| id.parse::<u32>().ok())
+            .filter(|id| *id < 3);
+
+        Ok(id.map(|id| zbus::object_server::ResolvedObject::new().interface(Row { id })))
+    }
+
+    async fn children(&self) -> zbus::fdo::Result<Vec<zvariant::OwnedObjectPath>> {
+        Ok((0..3)
+            .map(|id| {
+                zvariant::ObjectPath::try_from(format!("/org/zbus/Table/{id}"))
+                    .unwrap()
+                    .into()
+            })
+            .collect())
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn fallback() {
+    block_on(async {
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Table", ObjectManager)
+            .unwrap()
+            .serve_fallback_at("/org/zbus/Table", Table)
+            .unwrap()
+            .serve_at("/org/zbus/Table/5", Row { id: 5 })
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let destination = service.unique_name().unwrap().to_owned();
+        let row = |id: u32| {
+            RowProxy::builder(&client)
+                .destination(destination.clone())
+                .unwrap()
+                .path(format!("/org/zbus/Table/{id}"))
+                .unwrap()
+                .cache_properties(CacheProperties::No)
+                .build()
+        };
+
+        // Method calls and properties of resolved objects.
+        let second = row(1).await.unwrap();
+        assert_eq!(second.describe().await.unwrap(), "Row #1");
+        assert_eq!(second.id().await.unwrap(), 1);
+        let missing = row(7).await.unwrap();
+        match missing.describe().await {
+            Err(Error::MethodError(name, _, _)) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.UnknownObject")
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+
+        // Objects registered below the handler don't depend on it, but still lack interfaces.
+        assert_eq!(row(5).await.unwrap().describe().await.unwrap(), "Row #5");
+        let properties = |id: u32| {
+            zbus::fdo::PropertiesProxy::builder(&client)
+                .destination(destination.clone())
+                .unwrap()
+                .path(format!("/org/zbus/Table/{id}"))
+                .unwrap()
+                .build()
+        };
+        let unknown = zbus::names::InterfaceName::from_static_str_unchecked("org.zbus.Unknown");
+        match properties(5).await.unwrap().get_all(unknown.clone()).await {
+            Err(zbus::fdo::Error::UnknownInterface(_)) => (),
+            res => panic!("unexpected result: {res:?}"),
+        }
+        match properties(1).await.unwrap().get_all(unknown).await {
+            Err(zbus::fdo::Error::UnknownInterface(_)) => (),
+            res => panic!("unexpected result: {res:?}"),
+        }
+
+        // Introspection lists the objects of the handler.
+        let introspectable = zbus::fdo::IntrospectableProxy::builder(&client)
+            .destination(destination.clone())
+            .unwrap()
+            .path("/org/zbus/Table")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let xml = introspectable.introspect().await.unwrap();
+        for id in 0..3 {
+            assert!(xml.contains(&format!(r#"<node name="{id}"/>"#)), "{xml}");
+        }
+        let introspectable = zbus::fdo::IntrospectableProxy::builder(&client)
+            .destination(destination.clone())
+            .unwrap()
+            .path("/org/zbus/Table/2")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let xml = introspectable.introspect().await.unwrap();
+        assert!(xml.contains(r#"<interface name="org.zbus.Row">"#), "{xml}");
+
+        // And so does the object manager.
+        let objects = ObjectManagerProxy::builder(&client)
+            .destination(destination.clone())
+            .unwrap()
+            .path("/org/zbus/Table")
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .get_managed_objects()
+            .await
+            .unwrap();
+        // Along with the registered one.
+        assert_eq!(objects.len(), 4);
+        let props = &objects[&zvariant::ObjectPath::try_from("/org/zbus/Table/2")
+            .unwrap()
+            .into()]["org.zbus.Row"];
+        assert_eq!(props["Id"], OwnedValue::from(2u32));
+
+        // The objects go away with the handler.
+        assert!(service
+            .object_server()
+            .remove_fallback("/org/zbus/Table")
+            .await
+            .unwrap());
+        assert!(second.describe().await.is_err());
+    });
+}
+
+#[test]
+#[timeout(15000)]
+fn dynamic_interface() {
+    use std::sync::{Arc, Mutex};
+    use zbus::object_server::DynamicInterface;
+
+    block_on(async {
+        let name = Arc::new(Mutex::new(String::from("zbus")));
+        let getter_name = name.clone();
+        let setter_name = name.clone();
+        let extra = Arc::new(Mutex::new(OwnedValue::from(0u32)));
+        let getter_extra = extra.clone();
+        let setter_extra = extra.clone();
+        let iface = DynamicInterface::new("org.zbus.Dynamic")
+            .unwrap()
+            .method("Greet", &["s", "u"], &["s"], |msg, _| async move {
+                let (who, times): (String, u32) = msg.body().deserialize()?;
+                let greeting = format!("Hello {who}!").repeat(times as usize);
+
+                Ok(vec![Str::from(greeting).into()])
+            })
+            .unwrap()
+            .method("Broken", &[], &["u"], |_, _| async move {
+                Ok(vec![Str::from("not a number").into()])
+            })
+            .unwrap()
+            .method("Swap", &["(su)"], &["(us)", "b"], |msg, _| async move {
+                let ((s, u),): ((String, u32),) = msg.body().deserialize()?;
+                let swapped = zvariant::StructureBuilder::new()
+                    .add_field(u)
+                    .add_field(s)
+                    .build()
+                    .map_err(Error::from)?;
+
+                Ok(vec![
+                    Value::from(swapped).try_into().map_err(Error::from)?,
+                    true.into(),
+                ])
+            })
+            .unwrap()
+            .property("Name", "s", move || {
+                Ok(Str::from(getter_name.lock().unwrap().clone()).into())
+            })
+            .unwrap()
+            .property_setter("Name", move |value| {
+                *setter_name.lock().unwrap() = value.try_into().map_err(Error::from)?;
+
+                Ok(())
+            })
+            .unwrap()
+            .property("Extra", "v", move || {
+                Ok(getter_extra
+                    .lock()
+                    .unwrap()
+                    .try_clone()
+                    .map_err(Error::from)?)
+            })
+            .unwrap()
+            .property_setter("Extra", move |value| {
+                *setter_extra.lock().unwrap() = value.try_to_owned().map_err(Error::from)?;
+
+                Ok(())
+            })
+            .unwrap()
+            .signal("Greeted", &["s"])
+            .unwrap();
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_dynamic_at("/org/zbus/Dynamic", iface.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy = zbus::Proxy::new(
+            &client,
+            service.unique_name().unwrap().to_owned(),
+            "/org/zbus/Dynamic",
+            "org.zbus.Dynamic",
+        )
+        .await
+        .unwrap();
+
+        // Methods, with their arguments checked both ways.
+        let greeting: String = proxy.call("Greet", &("you", 2u32)).await.unwrap();
+        assert_eq!(greeting, "Hello you!Hello you!");
+        match proxy.call::<_, _, String>("Greet", &("you",)).await {
+            Err(Error::MethodError(name, _, _)) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.InvalidArgs")
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        match proxy.call::<_, _, u32>("Broken", &()).await {
+            Err(Error::MethodError(name, _, _)) => {
+                assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.Failed")
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        // A single structure argument isn't mistaken for several arguments.
+        let swapped: ((u32, String), bool) = proxy.call("Swap", &(("you", 2u32),)).await.unwrap();
+        assert_eq!(swapped, ((2, "you".into()), true));
+        assert!(DynamicInterface::new("org.zbus.Dynamic")
+            .unwrap()
+            .signal("Greeted", &["su"])
+            .is_err());
+
+        // Properties.
+        let mut name_changed = proxy.receive_property_changed::<String>("Name").await;
+        assert_eq!(
+            name_changed.next().await.unwrap().get().await.unwrap(),
+            "zbus"
+        );
+        proxy.set_property("Name", "dynamic").await.unwrap();
+        assert_eq!(*name.lock().unwrap(), "dynamic");
+        assert_eq!(
+            name_changed.next().await.unwrap().get().await.unwrap(),
+            "dynamic"
+        );
+        assert!(proxy.set_property("Name", 7u32).await.is_err());
+        // Values of any type are accepted for `v` properties.
+        proxy.set_property("Extra", "extra").await.unwrap();
+        assert_eq!(*extra.lock().unwrap(), OwnedValue::from(Str::from("extra")));
+        proxy.set_property("Extra", 7u32).await.unwrap();
+        assert_eq!(*extra.lock().unwrap(), OwnedValue::from(7u32));
+
+        // Signals.
+        let mut greeted = proxy.receive_signal("Greeted").await.unwrap();
+        let emitter = SignalEmitter::new(&service, "/org/zbus/Dynamic").unwrap();
+        iface
+            .emit_signal(&emitter, "Greeted", vec![Str::from("you").into()])
+            .await
+            .unwrap();
+        let who: String = greeted.next().await.unwrap().body().deserialize().unwrap();
+        assert_eq!(who, "you");
+        assert!(iface
+            .emit_signal(&emitter, "Greeted", vec![OwnedValue::from(7u32)])
+            .await
+            .is_err());
+
+        // Introspection.
+        let xml = proxy.introspect().await.unwrap();
+        let node = zbus_xml::Node::from_reader(xml.as_bytes()).unwrap();
+        let introspected = node
+            .interfaces()
+            .iter()
+            .find(|i| i.name() == "org.zbus.Dynamic")
+            .unwrap();
+        let greet = &introspected.methods()[1];
+        assert_eq!(greet.name(), "Greet");
+        assert_eq!(greet.args().len(), 3);
+        let swap = &introspected.methods()[2];
+        assert_eq!(swap.args().len(), 3);
+        assert_eq!(swap.args()[0].ty().to_string(), "(su)");
+        let property_type = |name| {
+            let property = introspected.properties().iter().find(|p| p.name() == name);
+
+            property.unwrap().ty().to_string()
+        };
+        assert_eq!(property_type("Name"), "s");
+        assert_eq!(property_type("Extra"), "v");
+        assert_eq!(introspected.signals()[0].name(), "Greeted");
+
+        // Interfaces declared from introspection data.
+        #[cfg(feature = "xml")]
+        {
+            let iface = DynamicInterface::from_xml(introspected)
+                .unwrap()
+                .method_handler(
+                    "Greet",
+                    |_, _| async move { Ok(vec![Str::from("Hi!").into()]) },
+                )
+                .unwrap();
+            assert!(service
+                .object_server()
+                .at_dynamic("/org/zbus/Dynamic/Copy", iface)
+                .await
+                .unwrap());
+            let copy = zbus::Proxy::new(
+                &client,
+                service.unique_name().unwrap().to_owned(),
+                "/org/zbus/Dynamic/Copy",
+                "org.zbus.Dynamic",
+            )
+            .await
+            .unwrap();
+            let greeting: String = copy.call("Greet", &("you", 2u32)).await.unwrap();
+            assert_eq!(greeting, "Hi!");
+            match copy.call::<_, _, u32>("Broken", &()).await {
+                Err(Error::MethodError(name, _, _)) => {
+                    assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.NotSupported")
+                }
+                res => panic!("unexpected result: {res:?}"),
+            }
+        }
+
+        assert!(service
+            .object_server()
+            .remove_dynamic("/org/zbus/Dynamic", "org.zbus.Dynamic")
+            .await
+            .unwrap());
+    });
+}
+
+#[cfg(feature = "xml")]
+#[test]
+#[timeout(15000)]
+fn dynamic_proxy() {
+    use zbus::proxy::DynamicProxy;
+
+    struct Counter {
+        count: u32,
+        label: OwnedValue,
+    }
+
+    #[interface(name = "org.zbus.Counter", proxy(gen_blocking = false))]
+    impl Counter {
+        async fn add(
+            &mut self,
+            n: u32,
+            label: Value<'_>,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        ) -> zbus::fdo::Result<(u32, String)> {
+            self.count += n;
+            Self::added(&emitter, n).await?;
+
+            Ok((self.count, label.to_string()))
+        }
+
+        #[zbus(property)]
+        fn count(&self) -> u32 {
+            self.count
+        }
+
+        #[zbus(property)]
+        fn set_count(&mut self, count: u32) {
+            self.count = count;
+        }
+
+        #[zbus(property)]
+        fn label(&self) -> OwnedValue {
+            self.label.try_clone().unwrap()
+        }
+
+        #[zbus(signal)]
+        async fn added(emitter: &SignalEmitter<'_>, n: u32) -> zbus::Result<()>;
+    }
+
+    block_on(async {
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at(
+                "/org/zbus/Counter",
+                Counter {
+                    count: 0,
+                    label: Str::from("counter").into(),
+                },
+            )
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy = zbus::proxy::Builder::new(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .path("/org/zbus/Counter")
+            .unwrap()
+            .interface("org.zbus.Counter")
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+        let proxy = DynamicProxy::new(proxy).await.unwrap();
+        assert_eq!(proxy.interface().methods()[0].name(), "Add");
+
+        // Methods, with the variant argument wrapped for us.
+        let mut added = proxy.receive_signal("Added").await.unwrap();
+        let reply = proxy
+            .call("Add", vec![Value::from(2u32), Value::from("two")])
+            .await
+            .unwrap();
+        assert_eq!(reply, [OwnedValue::from(2u32), Str::from("\"two\"").into()]);
+        assert_eq!(
+            added.next().await.unwrap().unwrap(),
+            [OwnedValue::from(2u32)]
+        );
+        service
+            .emit_signal(
+                None::<()>,
+                "/org/zbus/Counter",
+                "org.zbus.Counter",
+                "Added",
+                &("two",),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            added.next().await.unwrap().unwrap_err(),
+            Error::SignatureMismatch(
+                "Added".into(),
+                Some(0),
+                "u".try_into().unwrap(),
+                "s".try_into().unwrap()
+            )
+        );
+
+        // Mismatches are reported before anything is sent.
+        match proxy.call("Add", vec![Value::from("two")]).await {
+            Err(Error::SignatureMismatch(member, arg, expected, actual)) => {
+                assert_eq!(member, "Add");
+                assert_eq!(arg, None);
+                assert_eq!(expected, "(uv)");
+                assert_eq!(actual, "s");
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        match proxy
+            .call("Add", vec![Value::from("two"), Value::from(2u32)])
+            .await
+        {
+            Err(Error::SignatureMismatch(member, arg, expected, actual)) => {
+                assert_eq!(member, "Add");
+                assert_eq!(arg, Some(0));
+                assert_eq!(expected, "u");
+                assert_eq!(actual, "s");
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+        assert_eq!(
+            proxy.call("Remove", vec![]).await.unwrap_err(),
+            Error::MemberNotFound("Remove".into())
+        );
+
+        // Properties.
+        assert_eq!(
+            proxy.get_property("Count").await.unwrap(),
+            OwnedValue::from(2u32)
+        );
+        proxy
+            .set_property("Count", Value::from(7u32))
+            .await
+            .unwrap();
+        assert_eq!(
+            proxy.get_property("Count").await.unwrap(),
+            OwnedValue::from(7u32)
+        );
+        assert!(matches!(
+            proxy.set_property("Count", Value::from(true)).await,
+            Err(Error::SignatureMismatch(..))
+        ));
+        // The values of `v` properties are of any type.
+        assert_eq!(proxy.interface().properties()[1].ty().to_string(), "v");
+        assert_eq!(
+            proxy.get_property("Label").await.unwrap(),
+            OwnedValue::from(Str::from("counter"))
+        );
+        assert!(matches!(
+            proxy.receive_signal("Removed").await,
+            Err(Error::MemberNotFound(_))
+        ));
+    });
+}
+
+struct Layered;
+
+#[interface(name = "org.zbus.Layered")]
+impl Layered {
+    fn open(&self) -> u32 {
+        1
+    }
+
+    fn gated(&self) -> u32 {
+        2
+    }
+
+    fn fail(&self) -> zbus::fdo::Result<u32> {
+        Err(zbus::fdo::Error::NotSupported("Not today".into()))
+    }
+
+    fn boom(&self) -> u32 {
+        panic!("kaboom")
+    }
+
+    fn deferred(
+        &self,
+        fail: bool,
+        #[zbus(connection)] conn: &Connection,
+        #[zbus(reply_handle)] reply: zbus::object_server::ReplyHandle<u32>,
+    ) {
+        conn.executor()
+            .spawn(
+                async move {
+                    match fail {
+                        true => reply
+                            .reply_error(zbus::fdo::Error::NotSupported("Not today".into()))
+                            .await
+                            .unwrap(),
+                        false => reply.reply(&5).await.unwrap(),
+                    }
+                },
+                "deferred reply",
+            )
+            .detach();
+    }
+}
+
+#[zbus::proxy(interface = "org.zbus.Layered", gen_blocking = false)]
+trait Layered {
+    fn open(&self) -> zbus::fdo::Result<u32>;
+    fn gated(&self) -> zbus::fdo::Result<u32>;
+    fn fail(&self) -> zbus::fdo::Result<u32>;
+    fn boom(&self) -> zbus::fdo::Result<u32>;
+    fn deferred(&self, fail: bool) -> zbus::fdo::Result<u32>;
+}
+
+/// Records the calls it sees, along with their outcome.
+struct Recorder(Sender<String>);
+
+#[async_trait::async_trait]
+impl zbus::object_server::Middleware for Recorder {
+    async fn after(
+        &self,
+        _: &Message,
+        hdr: &Header<'_>,
+        result: &zbus::fdo::Result<()>,
+        elapsed: std::time::Duration,
+    ) {
+        assert!(elapsed < std::time::Duration::from_secs(10));
+        let member = hdr.member().unwrap();
+        let outcome = match result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => e.name().to_string(),
+        };
+        self.0.send(format!("{member}: {outcome}")).await.unwrap();
+    }
+}
+
+/// Rejects the calls to `Gated`.
+struct Gate;
+
+#[async_trait::async_trait]
+impl zbus::object_server::Middleware for Gate {
+    async fn before(&self, _: &Message, hdr: &Header<'_>) -> zbus::fdo::Result<()> {
+        match hdr.member().map(|m| m.as_str()) {
+            Some("Gated") => Err(zbus::fdo::Error::AccessDenied("Gated".into())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn middleware() {
+    block_on(async {
+        let (tx, mut rx) = channel(16);
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Layered/Inner", Layered)
+            .unwrap()
+            .serve_at("/org/zbus/LayeredNot", Layered)
+            .unwrap()
+            .middleware(zbus::object_server::CatchPanics)
+            .middleware(Recorder(tx))
+            .middleware_at("/org/zbus/Layered", Gate)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy_at = |path| {
+            LayeredProxy::builder(&client)
+                .destination(service.unique_name().unwrap().to_owned())
+                .unwrap()
+                .path(path)
+                .unwrap()
+                .build()
+        };
+        let inner = proxy_at("/org/zbus/Layered/Inner").await.unwrap();
+        let other = proxy_at("/org/zbus/LayeredNot").await.unwrap();
+        let missing = proxy_at("/org/zbus/Layered/Missing").await.unwrap();
+
+        assert_eq!(inner.open().await.unwrap(), 1);
+        // Only gated at and below `/org/zbus/Layered`.
+        assert!(matches!(
+            inner.gated().await,
+            Err(zbus::fdo::Error::AccessDenied(_))
+        ));
+        assert_eq!(other.gated().await.unwrap(), 2);
+        assert!(matches!(
+            inner.fail().await,
+            Err(zbus::fdo::Error::NotSupported(_))
+        ));
+        match inner.boom().await {
+            Err(zbus::fdo::Error::Failed(e)) => assert!(e.contains("kaboom"), "{e}"),
+            r => panic!("unexpected reply: {r:?}"),
+        }
+        assert!(matches!(
+            missing.open().await,
+            Err(zbus::fdo::Error::UnknownObject(_))
+        ));
+
+        // The service is still up after the panic.
+        assert_eq!(inner.open().await.unwrap(), 1);
+
+        // The outcome of deferred calls is the reply eventually sent.
+        assert_eq!(inner.deferred(false).await.unwrap(), 5);
+        assert!(matches!(
+            inner.deferred(true).await,
+            Err(zbus::fdo::Error::NotSupported(_))
+        ));
+
+        // The `after` hooks may run after the replies are received.
+        let mut calls = vec![];
+        for _ in 0..9 {
+            calls.push(rx.recv().await.unwrap());
+        }
+        assert_eq!(
+            calls,
+            [
+                "Open: ok",
+                "Gated: org.freedesktop.DBus.Error.AccessDenied",
+                "Gated: ok",
+                "Fail: org.freedesktop.DBus.Error.NotSupported",
+                "Boom: org.freedesktop.DBus.Error.Failed",
+                "Open: org.freedesktop.DBus.Error.UnknownObject",
+                "Open: ok",
+                "Deferred: ok",
+                "Deferred: org.freedesktop.DBus.Error.NotSupported",
+            ]
+        );
+
+        // Middleware can also be added at runtime.
+        service
+            .object_server()
+            .add_middleware_at("/org/zbus/LayeredNot", Gate)
+            .await
+            .unwrap();
+        assert!(matches!(
+            other.gated().await,
+            Err(zbus::fdo::Error::AccessDenied(_))
+        ));
+    });
+}
+
+/// Reports the calls of `Slow` that have started and ended, one way or another.
+struct CallEnd(&'static str, Sender<String>);
+
+impl Drop for CallEnd {
+    fn drop(&mut self) {
+        let _ = self.1.try_send(format!("{} ended", self.0));
+    }
+}
+
+struct Slow {
+    calls: Sender<String>,
+    release: std::sync::Arc<Event>,
+}
+
+#[interface(name = "org.zbus.Slow")]
+impl Slow {
+    async fn wait(&self) {
+        let _end = CallEnd("Wait", self.calls.clone());
+        let released = self.release.listen();
+        self.calls.send("Wait started".into()).await.unwrap();
+        released.await;
+        self.calls.send("Wait completed".into()).await.unwrap();
+    }
+
+    #[zbus(cancellable = false)]
+    async fn wait_to_completion(&self) {
+        let _end = CallEnd("WaitToCompletion", self.calls.clone());
+        let released = self.release.listen();
+        self.calls
+            .send("WaitToCompletion started".into())
+            .await
+            .unwrap();
+        released.await;
+        self.calls
+            .send("WaitToCompletion completed".into())
+            .await
+            .unwrap();
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn cancel_on_caller_exit() {
+    block_on(async {
+        let (tx, mut rx) = channel(16);
+        let release = std::sync::Arc::new(Event::new());
+        let slow = Slow {
+            calls: tx,
+            release: release.clone(),
+        };
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Slow", slow)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        for method in ["Wait", "WaitToCompletion"] {
+            let client = Connection::session().await.unwrap();
+            let call = Message::method_call("/org/zbus/Slow", method)
+                .unwrap()
+                .destination(service.unique_name().unwrap().to_owned())
+                .unwrap()
+                .interface("org.zbus.Slow")
+                .unwrap()
+                .build(&())
+                .unwrap();
+            client.send(&call).await.unwrap();
+            assert_eq!(rx.recv().await.unwrap(), format!("{method} started"));
+
+            client.close().await.unwrap();
+            if method == "Wait" {
+                // Cancelled without ever being released.
+                assert_eq!(rx.recv().await.unwrap(), "Wait ended");
+            } else {
+                // Only released once its caller is long gone.
+                release.notify(usize::MAX);
+                assert_eq!(rx.recv().await.unwrap(), "WaitToCompletion completed");
+                assert_eq!(rx.recv().await.unwrap(), "WaitToCompletion ended");
+            }
+        }
+    });
+}
+
+/// An access control letting the other tasks run before allowing a call, so the concurrent calls
+/// are all checked at once.
+struct Yielding;
+
+const YIELDING: Yielding = Yielding;
+
+#[async_trait::async_trait]
+impl zbus::object_server::AccessControl for Yielding {
+    async fn check(
+        &self,
+        _: &Header<'_>,
+        _: &zbus::fdo::ConnectionCredentials,
+    ) -> zbus::fdo::Result<()> {
+        let mut yielded = false;
+        std::future::poll_fn(|cx| {
+            if yielded {
+                return std::task::Poll::Ready(Ok(()));
+            }
+            yielded = true;
+            cx.waker().wake_by_ref();
+
+            std::task::Poll::Pending
+        })
+        .await
+    }
+}
+
+struct Limited {
+    calls: Sender<String>,
+    release: std::sync::Arc<Event>,
+    count: u32,
+}
+
+#[interface(name = "org.zbus.Limited", limits(max_in_flight = "8"))]
+impl Limited {
+    #[zbus(limits(max_in_flight = "2", max_queued = "1", max_per_sender = "2"))]
+    async fn hold(&self, id: &str) {
+        let released = self.release.listen();
+        self.calls.send(format!("{id} started")).await.unwrap();
+        released.await;
+    }
+
+    #[zbus(limits(max_in_flight = "1"), access_control = "YIELDING")]
+    async fn increment(&mut self) -> u32 {
+        let released = self.release.listen();
+        self.calls.send("increment started".into()).await.unwrap();
+        released.await;
+        self.count += 1;
+
+        self.count
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.zbus.Limited",
+    default_path = "/org/zbus/Limited",
+    gen_blocking = false
+)]
+trait Limited {
+    fn hold(&self, id: &str) -> zbus::fdo::Result<()>;
+    fn increment(&self) -> zbus::fdo::Result<u32>;
+}
+
+#[test]
+#[timeout(15000)]
+fn concurrency_limits() {
+    block_on(async {
+        let (tx, mut rx) = channel(16);
+        let release = std::sync::Arc::new(Event::new());
+        let limited = Limited {
+            calls: tx,
+            release: release.clone(),
+            count: 0,
+        };
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Limited", limited)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let mut proxies = vec![];
+        for _ in 0..2 {
+            let client = Connection::session().await.unwrap();
+            let proxy = LimitedProxy::builder(&client)
+                .destination(service.unique_name().unwrap().to_owned())
+                .unwrap()
+                .build()
+                .await
+                .unwrap();
+            proxies.push(proxy);
+        }
+        let (a, b) = (&proxies[0], &proxies[1]);
+        let call = |id: &'static str| {
+            let executor = a.inner().connection().executor().clone();
+            let proxy = a.clone();
+
+            executor.spawn(async move { proxy.hold(id).await }, id)
+        };
+
+        let mut calls = vec![call("A1")];
+        assert_eq!(rx.recv().await.unwrap(), "A1 started");
+        calls.push(call("A2"));
+        assert_eq!(rx.recv().await.unwrap(), "A2 started");
+        // Too many calls from the same sender.
+        match a.hold("A3").await {
+            Err(zbus::fdo::Error::LimitsExceeded(e)) => assert!(e.contains("same sender"), "{e}"),
+            r => panic!("unexpected reply: {r:?}"),
+        }
+        // Queued until one of the calls in flight completes.
+        let proxy = b.clone();
+        calls.push(
+            b.inner()
+                .connection()
+                .executor()
+                .spawn(async move { proxy.hold("B1").await }, "B1"),
+        );
+
+        release.notify(1);
+        assert_eq!(rx.recv().await.unwrap(), "B1 started");
+
+        release.notify(usize::MAX);
+        for call in calls {
+            call.await.unwrap();
+        }
+    });
+}
+
+#[test]
+#[timeout(15000)]
+fn concurrency_limits_mut() {
+    block_on(async {
+        let (tx, mut rx) = channel(16);
+        let release = std::sync::Arc::new(Event::new());
+        let limited = Limited {
+            calls: tx,
+            release: release.clone(),
+            count: 0,
+        };
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Limited", limited)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy = LimitedProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        // The calls waiting for the permit mustn't keep the one holding it from locking the
+        // interface for writing.
+        let calls: Vec<_> = (0..4)
+            .map(|_| {
+                let proxy = proxy.clone();
+
+                client
+                    .executor()
+                    .spawn(async move { proxy.increment().await }, "increment")
+            })
+            .collect();
+        for _ in 0..4 {
+            assert_eq!(rx.recv().await.unwrap(), "increment started");
+            release.notify(1);
+        }
+        let mut counts = vec![];
+        for call in calls {
+            counts.push(call.await.unwrap());
+        }
+        counts.sort_unstable();
+        assert_eq!(counts, [1, 2, 3, 4]);
+    });
+}
+
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+#[interface(name = "org.zbus.Position")]
+impl Position {
+    #[zbus(property)]
+    fn x(&self) -> i32 {
+        self.x
+    }
+
+    #[zbus(property)]
+    fn y(&self) -> i32 {
+        self.y
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn label(&self) -> String {
+        format!("({}, {})", self.x, self.y)
+    }
+}
+
+#[test]
+#[timeout(15000)]
+fn batched_properties_changed() {
+    block_on(async {
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Position", Position { x: 0, y: 0 })
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let props_proxy = zbus::fdo::PropertiesProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .path("/org/zbus/Position")
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let mut props_changed = props_proxy.receive_properties_changed().await.unwrap();
+        let iface_ref = service
+            .object_server()
+            .interface::<_, Position>("/org/zbus/Position")
+            .await
+            .unwrap();
+
+        let emitter = iface_ref.signal_emitter().batched();
+        {
+            let mut iface = iface_ref.get_mut().await;
+            iface.x = 1;
+            iface.x_changed(&emitter).await.unwrap();
+            iface.label_invalidate(&emitter).await.unwrap();
+            iface.x = 4;
+            iface.y = 2;
+            iface.x_changed(&emitter).await.unwrap();
+            iface.y_changed(&emitter).await.unwrap();
+            iface.label_invalidate(&emitter).await.unwrap();
+        }
+        emitter.flush().await.unwrap();
+
+        let signal = props_changed.next().await.unwrap();
+        let args = signal.args().unwrap();
+        assert_eq!(args.interface_name(), "org.zbus.Position");
+        let mut changed: Vec<_> = args
+            .changed_properties()
+            .iter()
+            .map(|(name, value)| (*name, i32::try_from(value).unwrap()))
+            .collect();
+        changed.sort();
+        assert_eq!(changed, [("X", 4), ("Y", 2)]);
+        assert_eq!(**args.invalidated_properties(), ["Label"]);
+
+        // Emitted on its own once the window has elapsed.
+        let iface_ref = iface_ref.batched_for(std::time::Duration::from_millis(50));
+        {
+            let mut iface = iface_ref.get_mut().await;
+            iface.y = 3;
+            iface.y_changed(iface_ref.signal_emitter()).await.unwrap();
+            iface.y = 5;
+            iface.y_changed(iface_ref.signal_emitter()).await.unwrap();
+        }
+
+        let signal = props_changed.next().await.unwrap();
+        let args = signal.args().unwrap();
+        let changed: Vec<_> = args
+            .changed_properties()
+            .iter()
+            .map(|(name, value)| (*name, i32::try_from(value).unwrap()))
+            .collect();
+        assert_eq!(changed, [("Y", 5)]);
+        assert!(args.invalidated_properties().is_empty());
+    });
+}
+
+struct Deferred {
+    stored: Sender<String>,
+    pending: std::sync::Mutex<HashMap<String, zbus::object_server::ReplyHandle<u32>>>,
+}
+
+#[interface(name = "org.zbus.Deferred")]
+impl Deferred {
+    async fn wait_for(
+        &self,
+        key: String,
+        #[zbus(reply_handle)] reply: zbus::object_server::ReplyHandle<u32>,
+    ) {
+        self.pending.lock().unwrap().insert(key.clone(), reply);
+        self.stored.send(key).await.unwrap();
+    }
+
+    async fn complete(&self, key: &str, value: u32) -> zbus::fdo::Result<()> {
+        let reply = self.pending.lock().unwrap().remove(key);
+        match reply {
+            Some(reply) if value == 0 => reply
+                .reply_error(zbus::fdo::Error::InvalidArgs("Zero".into()))
+                .await
+                .map_err(zbus::fdo::Error::from),
+            Some(reply) => reply.reply(&value).await.map_err(zbus::fdo::Error::from),
+            None => Err(zbus::fdo::Error::InvalidArgs(key.to_string())),
+        }
+    }
+
+    fn forget(&self, key: &str) {
+        self.pending.lock().unwrap().remove(key);
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.zbus.Deferred",
+    default_path = "/org/zbus/Deferred",
+    gen_blocking = false
+)]
+trait Deferred {
+    fn wait_for(&self, key: &str) -> zbus::fdo::Result<u32>;
+    fn complete(&self, key: &str, value: u32) -> zbus::fdo::Result<()>;
+    fn forget(&self, key: &str) -> zbus::fdo::Result<()>;
+}
+
+#[test]
+#[timeout(15000)]
+fn deferred_replies() {
+    block_on(async {
+        let (tx, mut rx) = channel(16);
+        let deferred = Deferred {
+            stored: tx,
+            pending: Default::default(),
+        };
+        let service = connection::Builder::session()
+            .unwrap()
+            .serve_at("/org/zbus/Deferred", deferred)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let client = Connection::session().await.unwrap();
+        let proxy = DeferredProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let wait_for = |key: &'static str| {
+            let proxy = proxy.clone();
+
+            client
+                .executor()
+                .spawn(async move { proxy.wait_for(key).await }, key)
+        };
+
+        let (a, b, c) = (wait_for("a"), wait_for("b"), wait_for("c"));
+        let mut stored = vec![];
+        for _ in 0..3 {
+            stored.push(rx.recv().await.unwrap());
+        }
+        stored.sort();
+        assert_eq!(stored, ["a", "b", "c"]);
+
+        proxy.complete("b", 42).await.unwrap();
+        assert_eq!(b.await.unwrap(), 42);
+        proxy.complete("a", 0).await.unwrap();
+        assert!(matches!(
+            a.await,
+            Err(zbus::fdo::Error::InvalidArgs(e)) if e == "Zero"
+        ));
+        // An error reply is sent if the handle is dropped.
+        proxy.forget("c").await.unwrap();
+        assert!(matches!(c.await, Err(zbus::fdo::Error::NoReply(_))));
+
+        // The reply type is introspected as the output of the method.
+        let xml = zbus::fdo::IntrospectableProxy::builder(&client)
+            .destination(service.unique_name().unwrap().to_owned())
+            .unwrap()
+            .path("/org/zbus/Deferred")
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .introspect()
+            .await
+            .unwrap();
+        assert!(xml.contains(r#"<arg type="u" direction="out"/>"#), "{xml}");
+    });
+}
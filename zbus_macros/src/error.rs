@@ -144,13 +144,22 @@ pub fn expand_derive(input: DeriveInput) -> Result<TokenStream, Error> {
             quote! {
                 impl ::std::convert::From<#zbus::Error> for #name {
                     fn from(value: #zbus::Error) -> #name {
-                        if let #zbus::Error::MethodError(name, desc, _) = &value {
-                            match name.as_str() {
-                                #error_converts
-                                _ => Self::#ident(value),
+                        let timeout_desc;
+                        let (name, desc) = match &value {
+                            #zbus::Error::MethodError(name, desc, _) => (name.as_str(), desc),
+                            // Reply timeouts are reported as `NoReply`, just like the bus does.
+                            #zbus::Error::Timeout => {
+                                timeout_desc = ::std::option::Option::Some(
+                                    ::std::string::ToString::to_string(&value),
+                                );
+
+                                ("org.freedesktop.DBus.Error.NoReply", &timeout_desc)
                             }
-                        } else {
-                            Self::#ident(value)
+                            _ => return Self::#ident(value),
+                        };
+                        match name {
+                            #error_converts
+                            _ => Self::#ident(value),
                         }
                     }
                 }
@@ -1,4 +1,4 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use std::collections::BTreeMap;
 use syn::{
@@ -25,6 +25,14 @@ def_attrs! {
         name str,
         spawn bool,
         introspection_docs bool,
+        access_control str,
+        limits {
+            pub ImplLimitsAttributes("limits") {
+                max_in_flight str,
+                max_queued str,
+                max_per_sender str
+            }
+        },
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -36,7 +44,8 @@ def_attrs! {
                 blocking_name str,
                 gen_async bool,
                 gen_blocking bool,
-                visibility str
+                visibility str,
+                timeout str
             }
         }
     };
@@ -50,6 +59,16 @@ def_attrs! {
             }
         },
         out_args [str],
+        access_control str,
+        polkit_action str,
+        cancellable bool,
+        limits {
+            pub MethodLimitsAttributes("limits") {
+                max_in_flight str,
+                max_queued str,
+                max_per_sender str
+            }
+        },
         proxy {
             // Keep this in sync with proxy's method attributes.
             // TODO: Find a way to share code with proxy module.
@@ -59,7 +78,8 @@ def_attrs! {
                 blocking_object str,
                 no_reply none,
                 no_autostart none,
-                allow_interactive_auth none
+                allow_interactive_auth none,
+                timeout str
             }
         }
     };
@@ -69,7 +89,8 @@ def_attrs! {
         connection none,
         header none,
         signal_context none,
-        signal_emitter none
+        signal_emitter none,
+        reply_handle none
     };
 }
 
@@ -241,18 +262,47 @@ impl MethodInfo {
             None
         };
 
+        // Methods replying through a `ReplyHandle<T>` have an output of type `T`.
+        let reply_handle = typed_inputs
+            .iter()
+            .find(|input| ArgAttributes::parse(&input.attrs).is_ok_and(|a| a.reply_handle));
+        let output = match reply_handle {
+            Some(input) => {
+                if method_type != MethodType::Other {
+                    return Err(Error::new_spanned(
+                        input,
+                        "`reply_handle` can only be used on methods",
+                    ));
+                }
+                if *output != ReturnType::Default {
+                    return Err(Error::new_spanned(
+                        output,
+                        "methods with a `reply_handle` argument reply through it, and can not \
+                         return anything",
+                    ));
+                }
+                let ty = get_reply_handle_type(&input.ty)?;
+
+                parse_quote!(-> #ty)
+            }
+            None => output.clone(),
+        };
+
         let mut intro_args = quote!();
         intro_args.extend(introspect_input_args(&typed_inputs, is_signal, cfg_attrs));
         let is_result_output = introspect_add_output_args(
             &mut intro_args,
-            output,
+            &output,
             attrs.out_args.as_deref(),
             cfg_attrs,
         )?;
 
         let (args_from_msg, args_names) = get_args_from_inputs(&typed_inputs, method_type, zbus)?;
 
-        let reply = if is_result_output {
+        let reply = if reply_handle.is_some() {
+            // The method returns `()`, the reply is sent through the handle.
+            quote!(::std::result::Result::<(), #zbus::Error>::Ok(reply))
+        } else if is_result_output {
             let ret = quote!(r);
 
             quote!(match reply {
@@ -289,7 +339,7 @@ impl MethodInfo {
             reply,
             member_name,
             proxy_attrs: attrs.proxy.clone(),
-            output: output.clone(),
+            output,
             cfg_attrs: cfg_attrs.iter().cloned().cloned().collect(),
             doc_attrs: doc_attrs.iter().cloned().cloned().collect(),
         })
@@ -307,6 +357,10 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
     let mut get_all = quote!();
     let mut call_dispatch = quote!();
     let mut call_mut_dispatch = quote!();
+    let mut access_control_dispatch = quote!();
+    let mut uncancellable_dispatch = quote!();
+    let mut limits_dispatch = quote!();
+    let mut polkit_dispatch = quote!();
     let mut introspect = quote!();
     let mut generated_signals = quote!();
     let mut signals_trait_methods = quote!();
@@ -346,6 +400,24 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         .proxy
         .map(|p| Proxy::new(ty, &iface_name, p, &zbus));
     let introspect_docs = impl_attrs.introspection_docs.unwrap_or(true);
+    let iface_access_control = impl_attrs
+        .access_control
+        .map(|s| parse_str::<Expr>(&s))
+        .transpose()?;
+    let iface_limits = impl_attrs
+        .limits
+        .map(|l| {
+            limits_expr(
+                &zbus,
+                [
+                    ("max_in_flight", l.max_in_flight),
+                    ("max_queued", l.max_queued),
+                    ("max_per_sender", l.max_per_sender),
+                ],
+                input.span(),
+            )
+        })
+        .transpose()?;
 
     // Store parsed information about each method
     let mut methods = vec![];
@@ -421,6 +493,70 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                 ));
             }
         };
+        if let Some(access_control) = &method_attrs.access_control {
+            if method_info.method_type != MethodType::Other {
+                return Err(syn::Error::new(
+                    method.span(),
+                    "`access_control` can only be specified on methods",
+                ));
+            }
+            let access_control = parse_str::<Expr>(access_control)?;
+            let member_name = &method_info.member_name;
+            access_control_dispatch.extend(quote! {
+                #(#cfg_attrs)*
+                ::std::option::Option::Some(#member_name) => ::std::option::Option::Some(&#access_control),
+            });
+        }
+        if let Some(cancellable) = method_attrs.cancellable {
+            if method_info.method_type != MethodType::Other {
+                return Err(syn::Error::new(
+                    method.span(),
+                    "`cancellable` can only be specified on methods",
+                ));
+            }
+            if !cancellable {
+                let member_name = &method_info.member_name;
+                uncancellable_dispatch.extend(quote! {
+                    #(#cfg_attrs)*
+                    #member_name => false,
+                });
+            }
+        }
+        if let Some(limits) = &method_attrs.limits {
+            if method_info.method_type != MethodType::Other {
+                return Err(syn::Error::new(
+                    method.span(),
+                    "`limits` can only be specified on methods",
+                ));
+            }
+            let limits = limits_expr(
+                &zbus,
+                [
+                    ("max_in_flight", limits.max_in_flight.clone()),
+                    ("max_queued", limits.max_queued.clone()),
+                    ("max_per_sender", limits.max_per_sender.clone()),
+                ],
+                method.span(),
+            )?;
+            let member_name = &method_info.member_name;
+            limits_dispatch.extend(quote! {
+                #(#cfg_attrs)*
+                ::std::option::Option::Some(#member_name) => ::std::option::Option::Some(#limits),
+            });
+        }
+        if let Some(action) = &method_attrs.polkit_action {
+            if method_info.method_type != MethodType::Other {
+                return Err(syn::Error::new(
+                    method.span(),
+                    "`polkit_action` can only be specified on methods",
+                ));
+            }
+            let member_name = &method_info.member_name;
+            polkit_dispatch.extend(quote! {
+                #(#cfg_attrs)*
+                #member_name => ::std::option::Option::Some(#action),
+            });
+        }
         methods.push((method, method_info));
     }
 
@@ -562,6 +698,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                             && !a.header
                             && !a.signal_context
                             && !a.signal_emitter
+                            && !a.reply_handle
                     });
 
                     let value_arg = match &*value_param
@@ -852,6 +989,78 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
         }
     };
 
+    let access_control = if access_control_dispatch.is_empty() && iface_access_control.is_none() {
+        quote!()
+    } else {
+        let default = match iface_access_control {
+            Some(access_control) => quote!(::std::option::Option::Some(&#access_control)),
+            None => quote!(::std::option::Option::None),
+        };
+
+        quote! {
+            fn access_control(
+                &self,
+                method: ::std::option::Option<&#zbus::names::MemberName<'_>>,
+            ) -> ::std::option::Option<&'static dyn #zbus::object_server::AccessControl> {
+                match method.map(|m| m.as_str()) {
+                    #access_control_dispatch
+                    _ => #default,
+                }
+            }
+        }
+    };
+
+    let cancellable = if uncancellable_dispatch.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            fn cancellable(&self, method: &#zbus::names::MemberName<'_>) -> bool {
+                match method.as_str() {
+                    #uncancellable_dispatch
+                    _ => true,
+                }
+            }
+        }
+    };
+
+    let polkit_action = if polkit_dispatch.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            fn polkit_action(
+                &self,
+                method: &#zbus::names::MemberName<'_>,
+            ) -> ::std::option::Option<&'static str> {
+                match method.as_str() {
+                    #polkit_dispatch
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    };
+
+    let concurrency_limits = if limits_dispatch.is_empty() && iface_limits.is_none() {
+        quote!()
+    } else {
+        let iface_limits = match iface_limits {
+            Some(limits) => quote!(::std::option::Option::Some(#limits)),
+            None => quote!(::std::option::Option::None),
+        };
+
+        quote! {
+            fn concurrency_limits(
+                &self,
+                method: ::std::option::Option<&#zbus::names::MemberName<'_>>,
+            ) -> ::std::option::Option<#zbus::object_server::ConcurrencyLimits> {
+                match method.map(|m| m.as_str()) {
+                    ::std::option::Option::None => #iface_limits,
+                    #limits_dispatch
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    };
+
     let proxy = proxy.map(|proxy| proxy.gen()).transpose()?;
     let introspect_format_str = format!("{}<interface name=\"{iface_name}\">", "{:indent$}");
 
@@ -962,6 +1171,14 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, mut input: ItemImpl) -> syn::Re
                 }
             }
 
+            #access_control
+
+            #polkit_action
+
+            #cancellable
+
+            #concurrency_limits
+
             fn introspect_to_writer(&self, writer: &mut dyn ::std::fmt::Write, level: usize) {
                 ::std::writeln!(
                     writer,
@@ -995,6 +1212,7 @@ fn get_args_from_inputs(
         let mut conn_arg_decl = None;
         let mut header_arg_decl = None;
         let mut signal_emitter_arg_decl = None;
+        let mut reply_handle_arg_decl = None;
         let mut args_names = Vec::new();
         let mut tys = Vec::new();
 
@@ -1005,6 +1223,7 @@ fn get_args_from_inputs(
                 header,
                 signal_emitter,
                 signal_context,
+                reply_handle,
             } = ArgAttributes::parse(&input.attrs)?;
 
             if object_server {
@@ -1069,6 +1288,18 @@ fn get_args_from_inputs(
                         };
                     }),
                 };
+            } else if reply_handle {
+                if reply_handle_arg_decl.is_some() {
+                    return Err(Error::new_spanned(
+                        input,
+                        "There can only be one reply_handle argument",
+                    ));
+                }
+
+                let reply_handle_arg = &input.pat;
+                reply_handle_arg_decl = Some(quote! {
+                    let #reply_handle_arg = #zbus::object_server::ReplyHandle::new(connection, message);
+                });
             } else {
                 args_names.push(pat_ident(input).unwrap());
                 tys.push(&input.ty);
@@ -1112,6 +1343,8 @@ fn get_args_from_inputs(
             #signal_emitter_arg_decl
 
             #args_decl
+
+            #reply_handle_arg_decl
         };
 
         let all_args_names = inputs.iter().filter_map(pat_ident);
@@ -1209,7 +1442,8 @@ fn is_special_arg(attrs: &[Attribute]) -> bool {
                     path.is_ident("connection") ||
                     path.is_ident("header") ||
                     path.is_ident("signal_context") ||
-                    path.is_ident("signal_emitter")
+                    path.is_ident("signal_emitter") ||
+                    path.is_ident("reply_handle")
             )
         });
 
@@ -1237,6 +1471,25 @@ fn introspect_output_arg(
     )
 }
 
+/// The `T` of a `ReplyHandle<T>` argument.
+fn get_reply_handle_type(ty: &Type) -> syn::Result<&Type> {
+    if let Type::Path(p) = ty {
+        if let Some(PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+            args, ..
+        })) = p.path.segments.last().map(|s| &s.arguments)
+        {
+            if let Some(syn::GenericArgument::Type(ty)) = args.first() {
+                return Ok(ty);
+            }
+        }
+    }
+
+    Err(Error::new_spanned(
+        ty,
+        "expected a `zbus::object_server::ReplyHandle<T>` argument",
+    ))
+}
+
 fn get_result_inner_type(p: &TypePath) -> syn::Result<&Type> {
     if let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = &p
         .path
@@ -1465,6 +1718,7 @@ impl Proxy {
                     && !a.header
                     && !a.signal_context
                     && !a.signal_emitter
+                    && !a.reply_handle
             })
             .cloned()
             .collect();
@@ -1536,6 +1790,9 @@ impl Proxy {
             if attrs.allow_interactive_auth {
                 proxy_method_attrs.extend(quote! { allow_interactive_auth, });
             }
+            if let Some(timeout) = attrs.timeout {
+                proxy_method_attrs.extend(quote! { timeout = #timeout, });
+            }
         }
         let cfg_attrs = method_info.cfg_attrs;
         let doc_attrs = method_info.doc_attrs;
@@ -1559,6 +1816,7 @@ impl Proxy {
             blocking_name,
             gen_async,
             gen_blocking,
+            timeout,
             ty,
             methods,
         ) = (
@@ -1585,6 +1843,10 @@ impl Proxy {
             attrs
                 .gen_blocking
                 .map(|value| quote! { gen_blocking = #value, }),
+            attrs
+                .timeout
+                .as_ref()
+                .map(|value| quote! { timeout = #value, }),
             &self.ty,
             &self.methods,
         );
@@ -1606,6 +1868,7 @@ impl Proxy {
                 #blocking_name
                 #gen_async
                 #gen_blocking
+                #timeout
             )]
             #vis trait #ty {
                 #methods
@@ -1613,3 +1876,27 @@ impl Proxy {
         })
     }
 }
+
+/// The expression creating the `ConcurrencyLimits` out of the values of the `limits` attribute.
+fn limits_expr<const N: usize>(
+    zbus: &TokenStream,
+    values: [(&str, Option<String>); N],
+    span: Span,
+) -> syn::Result<TokenStream> {
+    let mut limits = quote!(#zbus::object_server::ConcurrencyLimits::new());
+    for (name, value) in values {
+        let Some(value) = value else {
+            continue;
+        };
+        let value = value.trim().parse::<usize>().map_err(|_| {
+            syn::Error::new(
+                span,
+                format!("invalid value \"{value}\" for attribute `{name}`, expected an integer"),
+            )
+        })?;
+        let name = format_ident!("{name}");
+        limits = quote!(#limits.#name(#value));
+    }
+
+    Ok(limits)
+}
@@ -53,6 +53,11 @@ mod utils;
 ///   attribute nor one of the default values are specified. Please make sure to explicitly set
 ///   either this attribute or the default values, according to your needs.
 ///
+/// * `timeout` - the default timeout for method call replies, e.g `"500ms"`, `"30s"` or `"2min"`.
+///   If no reply is received in time, the method returns [`zbus::Error::Timeout`]. If not
+///   specified, the timeout of the connection (if any) applies. See
+///   [`zbus::proxy::Builder::method_timeout`] for details.
+///
 /// Each trait method will be expanded to call to the associated D-Bus remote interface.
 ///
 /// Trait methods accept `proxy` attributes:
@@ -87,6 +92,9 @@ mod utils;
 /// * `allow_interactive_auth` - declare a method call that is allowed to trigger an interactive
 ///   prompt for authorization or confirmation from the receiver.
 ///
+/// * `timeout` - the timeout for the method call reply, overriding the default timeout of the
+///   proxy. Same format as the trait-level `timeout` attribute. Can't be combined with `no_reply`.
+///
 /// * `object` - methods that returns an [`ObjectPath`] can be annotated with the `object` attribute
 ///   to specify the proxy object to be constructed from the returned [`ObjectPath`].
 ///
@@ -192,6 +200,8 @@ mod utils;
 /// [`zbus::SignalStream`]: https://docs.rs/zbus/latest/zbus/proxy/struct.SignalStream.html
 /// [`zbus::blocking::SignalIterator`]: https://docs.rs/zbus/latest/zbus/blocking/proxy/struct.SignalIterator.html
 /// [`ObjectPath`]: https://docs.rs/zvariant/latest/zvariant/struct.ObjectPath.html
+/// [`zbus::Error::Timeout`]: https://docs.rs/zbus/latest/zbus/enum.Error.html#variant.Timeout
+/// [`zbus::proxy::Builder::method_timeout`]: https://docs.rs/zbus/latest/zbus/proxy/struct.Builder.html#method.method_timeout
 /// [dbus_emits_changed_signal]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
 #[proc_macro_attribute]
 pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -235,6 +245,16 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   (Default: `true`). If your interface is well-known or well-documented, you may want to set
 ///   this to `false` to reduce the the size of your binary and D-Bus traffic.
 ///
+/// * `access_control` - a path to a function or constant implementing [`AccessControl`], that
+///   checks all method calls of the interface before they're dispatched, as well as the accesses to
+///   its properties through `org.freedesktop.DBus.Properties`. Methods can override it with their
+///   own `access_control` attribute.
+///
+/// * `limits` - the [`ConcurrencyLimits`] of the interface, as `max_in_flight`, `max_queued` and
+///   `max_per_sender` sub-attributes, e.g `limits(max_in_flight = "4", max_queued = "16")`. The
+///   omitted ones are unlimited. They apply to all method calls of the interface, on top of the
+///   limits of the methods.
+///
 /// The methods accepts the `interface` attributes:
 ///
 /// * `name` - override the D-Bus name (pascal case form of the method by default)
@@ -266,10 +286,31 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// * `out_args` - When returning multiple values from a method, naming the out arguments become
 ///   important. You can use `out_args` to specify their names.
 ///
+/// * `access_control` - a path to a function or constant implementing [`AccessControl`], that
+///   checks calls to this method before they're dispatched. It takes precedence over the one of the
+///   interface, if any. This can not be used on properties and signals.
+///
+/// * `polkit_action` - the ID of a [polkit] action the caller must be authorized for. The check is
+///   made before the interface is locked for the call (see [`Interface::polkit_action`]), with
+///   [`zbus::fdo::polkit::check_authorization`]. The caller gets an
+///   `InteractiveAuthorizationRequired` or `AccessDenied` error if it's not authorized. The
+///   connection must be to the system bus. Since the interface isn't locked meanwhile, `spawn` can
+///   be disabled, though the following method calls then wait for polkit to reply. This can not be
+///   used on properties and signals.
+///
+/// * `cancellable` - whether calls to this method are cancelled when the caller disconnects from
+///   the bus, which is the default (see [`Interface::cancellable`]). Set it to `false` for methods
+///   that must run to completion regardless, e.g because they commit changes in several steps. This
+///   can not be used on properties and signals.
+///
+/// * `limits` - the [`ConcurrencyLimits`] of the method, with the same sub-attributes as the
+///   interface-level `limits` attribute. This can not be used on properties and signals.
+///
 /// * `proxy` - Use this to specify the [`macro@proxy`]-specific method sub-attributes (e.g
 ///   `object`). The common sub-attributes (e.g `name`) are automatically forworded to the
 ///   [`macro@proxy`] macro. Moreover, you can use `visibility` sub-attribute to specify the
-///   visibility of the generated proxy type(s).
+///   visibility of the generated proxy type(s) and `timeout` to specify the default method call
+///   timeout of the generated proxy type(s).
 ///
 ///   In such case, your method must return a tuple containing
 ///   your out arguments, in the same order as passed to `out_args`.
@@ -287,7 +328,8 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// method is also generated that much like `_changed` method, emits a "PropertyChanged" signal
 /// but does not send over the new value of the property along with it. It is usually best to avoid
 /// using this since it will force all interested peers to fetch the new value and hence result in
-/// excess traffic on the bus.
+/// excess traffic on the bus. When changing several properties at once, pass these methods a
+/// [batching `SignalEmitter`][batched] to merge their signals into one.
 ///
 /// The method arguments support the following `zbus` attributes:
 ///
@@ -301,6 +343,10 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///   external property access.
 /// * `signal_emitter` - This marks the method argument to receive a [`SignalEmitter`] instance,
 ///   which is needed for emitting signals the easy way.
+/// * `reply_handle` - This marks the method argument to receive a [`ReplyHandle<T>`], to reply to
+///   the call later on, after the method returned. The method then doesn't reply by itself and
+///   can't return anything, while `T` is the type of its reply. This can not be used on properties
+///   and signals.
 ///
 /// # Example
 ///
@@ -371,6 +417,14 @@ pub fn proxy(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// [`Connection::emit_signal()`]: https://docs.rs/zbus/latest/zbus/connection/struct.Connection.html#method.emit_signal
 /// [`SignalEmitter`]: https://docs.rs/zbus/latest/zbus/object_server/struct.SignalEmitter.html
 /// [`Interface`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Interface.html
+/// [`AccessControl`]: https://docs.rs/zbus/latest/zbus/object_server/trait.AccessControl.html
+/// [`Interface::cancellable`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Interface.html#method.cancellable
+/// [`Interface::polkit_action`]: https://docs.rs/zbus/latest/zbus/object_server/trait.Interface.html#method.polkit_action
+/// [`ConcurrencyLimits`]: https://docs.rs/zbus/latest/zbus/object_server/struct.ConcurrencyLimits.html
+/// [`ReplyHandle<T>`]: https://docs.rs/zbus/latest/zbus/object_server/struct.ReplyHandle.html
+/// [batched]: https://docs.rs/zbus/latest/zbus/object_server/struct.SignalEmitter.html#method.batched
+/// [`zbus::fdo::polkit::check_authorization`]: https://docs.rs/zbus/latest/zbus/fdo/polkit/fn.check_authorization.html
+/// [polkit]: https://www.freedesktop.org/software/polkit/docs/latest/
 /// [dbus_emits_changed_signal]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
 #[proc_macro_attribute]
 pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
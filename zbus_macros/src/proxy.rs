@@ -1,4 +1,4 @@
-use crate::utils::{pat_ident, typed_arg, zbus_path, PropertyEmitsChangedSignal};
+use crate::utils::{parse_timeout, pat_ident, typed_arg, zbus_path, PropertyEmitsChangedSignal};
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
@@ -20,7 +20,8 @@ def_attrs! {
         async_name str,
         blocking_name str,
         gen_async bool,
-        gen_blocking bool
+        gen_blocking bool,
+        timeout str
     };
 
     // Keep this in sync with interface's proxy method attributes.
@@ -37,7 +38,8 @@ def_attrs! {
         blocking_object str,
         no_reply none,
         no_autostart none,
-        allow_interactive_auth none
+        allow_interactive_auth none,
+        timeout str
     };
 }
 
@@ -92,6 +94,11 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, input: ItemTrait) -> Result<Tok
         gen_async || attrs.async_name.is_none(),
         "Can't set asynchronous proxy's name if you disabled it. 😸",
     );
+    let timeout = attrs
+        .timeout
+        .as_deref()
+        .map(|t| parse_timeout(t, input.span()))
+        .transpose()?;
 
     let blocking_proxy = if gen_blocking {
         let proxy_name = attrs.blocking_name.unwrap_or_else(|| {
@@ -108,6 +115,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, input: ItemTrait) -> Result<Tok
             attrs.assume_defaults,
             attrs.default_path.as_deref(),
             attrs.default_service.as_deref(),
+            timeout,
             &proxy_name,
             true,
             // Signal args structs are shared between the two proxies so always generate it for
@@ -127,6 +135,7 @@ pub fn expand(args: Punctuated<Meta, Token![,]>, input: ItemTrait) -> Result<Tok
             attrs.assume_defaults,
             attrs.default_path.as_deref(),
             attrs.default_service.as_deref(),
+            timeout,
             &proxy_name,
             false,
             true,
@@ -149,6 +158,7 @@ pub fn create_proxy(
     assume_defaults: Option<bool>,
     default_path: Option<&str>,
     default_service: Option<&str>,
+    timeout: Option<u64>,
     proxy_name: &str,
     blocking: bool,
     gen_sig_args: bool,
@@ -283,6 +293,9 @@ pub fn create_proxy(
 
         (proxy, connection, builder, proxy_trait)
     };
+    let method_timeout = timeout.map(|ms| {
+        quote! { .method_timeout(::std::time::Duration::from_millis(#ms)) }
+    });
 
     let proxy_method_new = match (&default_path, &default_service) {
         (None, None) => {
@@ -383,7 +396,7 @@ pub fn create_proxy(
 
             /// Returns a customizable builder for this proxy.
             pub fn builder(conn: &#connection) -> #builder<'p, Self> {
-                let mut builder = #builder::new(conn) ;
+                let mut builder = #builder::new(conn) #method_timeout;
                 if #has_properties {
                     let uncached = vec![#(#uncached_properties),*];
                     builder.cache_properties(#zbus::proxy::CacheProperties::default())
@@ -542,6 +555,19 @@ fn gen_proxy_method_call(
         _ => None,
     };
 
+    let timeout = method_attrs
+        .timeout
+        .as_deref()
+        .map(|t| parse_timeout(t, m.span()))
+        .transpose()?
+        .map(|ms| quote! { ::std::time::Duration::from_millis(#ms) });
+    if timeout.is_some() && method_attrs.no_reply {
+        return Err(Error::new(
+            m.span(),
+            "`timeout` and `no_reply` attributes can't be used together",
+        ));
+    }
+
     let method = Ident::new(snake_case_name, Span::call_site());
     let inputs = &m.sig.inputs;
     let mut generics = m.sig.generics.clone();
@@ -587,16 +613,17 @@ fn gen_proxy_method_call(
             fn #method #ty_generics(#inputs) -> #zbus::Result<#proxy_path<'p>>
             #where_clause
         };
+        let body = quote! { &#zbus::zvariant::DynamicTuple((#(#args,)*)) };
+        let call = match &timeout {
+            Some(timeout) => quote! { call_with_timeout(#method_name, #timeout, #body) },
+            None => quote! { call(#method_name, #body) },
+        };
 
         Ok(quote! {
             #(#other_attrs)*
             pub #usage #signature {
                 let object_path: #zbus::zvariant::OwnedObjectPath =
-                    self.0.call(
-                        #method_name,
-                        &#zbus::zvariant::DynamicTuple((#(#args,)*)),
-                    )
-                    #wait?;
+                    self.0.#call #wait?;
                 #proxy_path::builder(&self.0.connection())
                     .path(object_path)?
                     .build()
@@ -633,10 +660,16 @@ fn gen_proxy_method_call(
                     }
                 })
             } else {
+                let call_with_flags = match &timeout {
+                    Some(timeout) => quote! {
+                        call_with_flags_and_timeout(#method_name, #method_flags, #timeout, #body)
+                    },
+                    None => quote! { call_with_flags(#method_name, #method_flags, #body) },
+                };
                 Ok(quote! {
                     #(#other_attrs)*
                     pub #usage #signature {
-                        let reply = self.0.call_with_flags(#method_name, #method_flags, #body)#wait?;
+                        let reply = self.0.#call_with_flags #wait?;
 
                         // SAFETY: This unwrap() cannot fail due to the guarantees in
                         // call_with_flags, which can only return Ok(None) if the
@@ -649,10 +682,14 @@ fn gen_proxy_method_call(
                 })
             }
         } else {
+            let call = match &timeout {
+                Some(timeout) => quote! { call_with_timeout(#method_name, #timeout, #body) },
+                None => quote! { call(#method_name, #body) },
+            };
             Ok(quote! {
                 #(#other_attrs)*
                 pub #usage #signature {
-                    let reply = self.0.call(#method_name, #body)#wait?;
+                    let reply = self.0.#call #wait?;
                     ::std::result::Result::Ok(reply)
                 }
             })
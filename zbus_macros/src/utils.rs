@@ -78,6 +78,35 @@ impl Display for PropertyEmitsChangedSignal {
     }
 }
 
+/// Parse a method timeout attribute value, such as `"500ms"` or `"30s"`, into milliseconds.
+pub fn parse_timeout(s: &str, span: Span) -> syn::Result<u64> {
+    let (value, factor) = if let Some(ms) = s.strip_suffix("ms") {
+        (ms, 1)
+    } else if let Some(secs) = s.strip_suffix('s') {
+        (secs, 1000)
+    } else if let Some(mins) = s.strip_suffix("min") {
+        (mins, 60 * 1000)
+    } else {
+        (s, 0)
+    };
+
+    value
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|_| factor != 0)
+        .and_then(|value| value.checked_mul(factor))
+        .ok_or_else(|| {
+            syn::Error::new(
+                span,
+                format!(
+                    "invalid value \"{s}\" for attribute `timeout`, \
+                     expected an integer followed by `ms`, `s` or `min`"
+                ),
+            )
+        })
+}
+
 impl PropertyEmitsChangedSignal {
     pub fn parse(s: &str, span: Span) -> syn::Result<Self> {
         use PropertyEmitsChangedSignal::*;
@@ -10,11 +10,15 @@ mod param {
     #[zbus_macros::proxy(
         interface = "org.freedesktop.zbus_macros.ProxyParam",
         default_service = "org.freedesktop.zbus_macros",
-        default_path = "/org/freedesktop/zbus_macros/test"
+        default_path = "/org/freedesktop/zbus_macros/test",
+        timeout = "30s"
     )]
-    trait ProxyParam {
+    pub(super) trait ProxyParam {
         #[zbus(object = "super::test::Test")]
         fn some_method<T>(&self, test: &T);
+
+        #[zbus(object = "super::test::Test", timeout = "2min")]
+        fn some_method_with_timeout<T>(&self, test: &T);
     }
 }
 
@@ -46,6 +50,12 @@ mod test {
         #[zbus(name = "CheckRENAMING")]
         fn check_renaming(&self) -> zbus::Result<Vec<u8>>;
 
+        #[zbus(timeout = "500ms")]
+        fn with_timeout(&self) -> zbus::Result<()>;
+
+        #[zbus(no_autostart, timeout = "5s")]
+        fn with_flags_and_timeout(&self) -> zbus::Result<u32>;
+
         #[zbus(property)]
         fn property(&self) -> fdo::Result<Vec<String>>;
 
@@ -76,6 +86,11 @@ fn test_proxy() {
             .build()
             .await
             .unwrap();
+        let param_proxy = param::ProxyParamProxy::new(&connection).await.unwrap();
+        assert_eq!(
+            param_proxy.inner().method_timeout(),
+            Some(std::time::Duration::from_secs(30)),
+        );
         fdo::DBusProxy::builder(&connection)
             .build()
             .await
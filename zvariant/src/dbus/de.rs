@@ -7,9 +7,10 @@ use std::os::fd::AsFd;
 
 use crate::{
     de::{DeserializerCommon, ValueParseStage},
+    fixed_array,
     serialized::{Context, Format},
     utils::*,
-    Basic, Error, ObjectPath, Result, Signature,
+    Basic, Error, ObjectPath, Result, Signature, NATIVE_ENDIAN,
 };
 
 /// Our D-Bus deserialization implementation.
@@ -281,11 +282,43 @@ impl<'de, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F> de::Deserializer<'de>
         visitor.visit_unit()
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        let Some(element) = fixed_array::element_signature(name) else {
+            return visitor.visit_newtype_struct(self);
+        };
+        let element_size = match self.0.signature {
+            Signature::Array(child) if *child.signature() == *element => match child.signature() {
+                Signature::U8 => 1,
+                Signature::I16 | Signature::U16 => 2,
+                Signature::I32 | Signature::U32 => 4,
+                Signature::I64 | Signature::U64 | Signature::F64 => 8,
+                _ => unreachable!("not a fixed-size basic type"),
+            },
+            // The elements of other arrays would be reinterpreted as the requested type.
+            _ => {
+                return Err(Error::SignatureMismatch(
+                    self.0.signature.clone(),
+                    format!("a{element}"),
+                ))
+            }
+        };
+        let bytes = next_array_slice(self)?;
+
+        if element_size == 1 || self.0.ctxt.endian() == NATIVE_ENDIAN {
+            visitor.visit_borrowed_bytes(bytes)
+        } else {
+            // The elements have to be swapped to the native endianness.
+            let bytes = bytes
+                .chunks(element_size)
+                .flat_map(|element| element.iter().rev())
+                .copied()
+                .collect::<Vec<_>>();
+
+            visitor.visit_byte_buf(bytes)
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
@@ -479,6 +512,13 @@ fn deserialize_ay<'de, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
         return Err(de::Error::invalid_type(de::Unexpected::Seq, &"ay"));
     }
 
+    next_array_slice(de)
+}
+
+/// The bytes of all the elements of the array at once.
+fn next_array_slice<'de, #[cfg(unix)] F: AsFd, #[cfg(not(unix))] F>(
+    de: &mut Deserializer<'de, '_, '_, F>,
+) -> Result<&'de [u8]> {
     let mut ad = ArrayDeserializer::new(de)?;
     let len = ad.len;
     ad.end();
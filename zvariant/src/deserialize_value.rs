@@ -1,10 +1,10 @@
 use core::str;
 use std::marker::PhantomData;
 
-use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::de::{Deserialize, DeserializeSeed, Deserializer, SeqAccess, Visitor};
 use static_assertions::assert_impl_all;
 
-use crate::{Signature, Type};
+use crate::{DynamicType, Signature, Type};
 
 /// A wrapper to deserialize a value to `T: Type + Deserialize`.
 ///
@@ -40,16 +40,74 @@ impl<'de, T: Type + Deserialize<'de>> Deserialize<'de> for DeserializeValue<'de,
             deserializer.deserialize_struct(
                 "Variant",
                 FIELDS,
-                DeserializeValueVisitor(PhantomData),
+                DeserializeValueVisitor(T::SIGNATURE, PhantomData),
             )?,
             PhantomData,
         ))
     }
 }
 
-struct DeserializeValueVisitor<T>(PhantomData<T>);
+/// A seed to deserialize a value of the given signature to `T: Deserialize`.
+///
+/// This is the counterpart of [`SerializeValueWithSignature`], for when the signature of the
+/// value isn't the one of `T`, e.g a `String` in a value of type `o`.
+///
+/// ```
+/// # use zvariant::{
+/// #     to_bytes, serialized::Context, DeserializeValueWithSignature, SerializeValueWithSignature,
+/// #     Signature, LE,
+/// # };
+/// #
+/// # let ctxt = Context::new_dbus(LE, 0);
+/// let path = String::from("/org/example");
+/// let value = SerializeValueWithSignature(&path, &Signature::ObjectPath);
+/// let encoded = to_bytes(ctxt, &value).unwrap();
+/// let seed = DeserializeValueWithSignature::<String>::new(&Signature::ObjectPath);
+/// let decoded = encoded.deserialize_with_seed(seed).unwrap().0;
+/// assert_eq!(decoded, path);
+/// ```
+///
+/// [`SerializeValueWithSignature`]: struct.SerializeValueWithSignature.html
+pub struct DeserializeValueWithSignature<'s, T> {
+    signature: &'s Signature,
+    phantom: PhantomData<T>,
+}
+
+impl<'s, T> DeserializeValueWithSignature<'s, T> {
+    /// Create a seed for values of the given `signature`.
+    pub fn new(signature: &'s Signature) -> Self {
+        Self {
+            signature,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for DeserializeValueWithSignature<'_, T> {
+    type Value = T;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &["signature", "value"];
+        deserializer.deserialize_struct(
+            "Variant",
+            FIELDS,
+            DeserializeValueVisitor(self.signature, PhantomData),
+        )
+    }
+}
+
+impl<T> DynamicType for DeserializeValueWithSignature<'_, T> {
+    fn signature(&self) -> Signature {
+        Signature::Variant
+    }
+}
+
+struct DeserializeValueVisitor<'s, T>(&'s Signature, PhantomData<T>);
 
-impl<'de, T: Type + Deserialize<'de>> Visitor<'de> for DeserializeValueVisitor<T> {
+impl<'de, T: Deserialize<'de>> Visitor<'de> for DeserializeValueVisitor<'_, T> {
     type Value = T;
 
     fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -63,7 +121,7 @@ impl<'de, T: Type + Deserialize<'de>> Visitor<'de> for DeserializeValueVisitor<T
         let sig: Signature = seq
             .next_element()?
             .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
-        if T::SIGNATURE != &sig {
+        if self.0 != &sig {
             return Err(serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(&sig.to_string()),
                 &"the value signature",
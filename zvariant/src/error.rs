@@ -68,6 +68,11 @@ pub enum Error {
     EmptyStructure,
     /// Invalid object path.
     InvalidObjectPath,
+    /// Error from parsing a value in the text format.
+    TextParse(crate::text::Error),
+    /// Error from converting JSON to a value.
+    #[cfg(feature = "serde_json")]
+    Json(crate::json::Error),
 }
 
 assert_impl_all!(Error: Send, Sync, Unpin);
@@ -95,6 +100,9 @@ impl PartialEq for Error {
             (Error::SignatureParse(e1), Error::SignatureParse(e2)) => e1 == e2,
             (Error::EmptyStructure, Error::EmptyStructure) => true,
             (Error::InvalidObjectPath, Error::InvalidObjectPath) => true,
+            (Error::TextParse(e1), Error::TextParse(e2)) => e1 == e2,
+            #[cfg(feature = "serde_json")]
+            (Error::Json(e1), Error::Json(e2)) => e1 == e2,
             (_, _) => false,
         }
     }
@@ -139,6 +147,9 @@ impl fmt::Display for Error {
             Error::SignatureParse(e) => write!(f, "{e}"),
             Error::EmptyStructure => write!(f, "Attempted to create an empty structure"),
             Error::InvalidObjectPath => write!(f, "Invalid object path"),
+            Error::TextParse(e) => write!(f, "{e}"),
+            #[cfg(feature = "serde_json")]
+            Error::Json(e) => write!(f, "{e}"),
         }
     }
 }
@@ -164,6 +175,9 @@ impl Clone for Error {
             Error::SignatureParse(e) => Error::SignatureParse(*e),
             Error::EmptyStructure => Error::EmptyStructure,
             Error::InvalidObjectPath => Error::InvalidObjectPath,
+            Error::TextParse(e) => Error::TextParse(e.clone()),
+            #[cfg(feature = "serde_json")]
+            Error::Json(e) => Error::Json(e.clone()),
         }
     }
 }
@@ -0,0 +1,231 @@
+//! Borrowed deserialization of arrays of fixed-size basic types.
+//!
+//! Arrays of numbers, e.g `ay` or `au`, are laid out contiguously in the D-Bus format. Instead of
+//! deserializing them element by element, this module allows deserializing them at once, into
+//! `&[T]`, `Cow<[T]>` or `Vec<T>`, through serde's `with` attribute:
+//!
+//! ```
+//! use std::borrow::Cow;
+//! use serde::{Deserialize, Serialize};
+//! use zvariant::{serialized::Context, to_bytes, Type, NATIVE_ENDIAN};
+//!
+//! #[derive(Deserialize, Serialize, Type)]
+//! struct Image<'a> {
+//!     width: u32,
+//!     #[serde(borrow, with = "zvariant::fixed_array")]
+//!     pixels: Cow<'a, [u32]>,
+//! }
+//!
+//! let ctxt = Context::new_dbus(NATIVE_ENDIAN, 0);
+//! let image = Image {
+//!     width: 2,
+//!     pixels: Cow::Owned(vec![0xff0000ff, 0x00ff00ff, 0x0000ffff, 0xffffffff]),
+//! };
+//! let encoded = to_bytes(ctxt, &image).unwrap();
+//! let decoded: Image<'_> = encoded.deserialize().unwrap().0;
+//! assert_eq!(decoded.pixels, image.pixels);
+//! ```
+//!
+//! The elements are borrowed from the serialized bytes if they're suitably aligned in memory and
+//! in the native endianness. Otherwise, they're copied. Deserializing into `&[T]` fails in the
+//! latter case, while `Cow<[T]>` falls back to an owned copy.
+//! Arrays of another element type, even of the same size, are rejected.
+//!
+//! In other formats, e.g GVariant, the elements are deserialized one by one.
+
+use serde::{
+    de::{self, Deserializer, SeqAccess, Visitor},
+    Serialize, Serializer,
+};
+use std::{borrow::Cow, fmt, marker::PhantomData, mem};
+
+use crate::Basic;
+
+/// The prefix of the names of the newtype structs requested from deserializers, to recognize
+/// fixed-size arrays. It's followed by the signature of the elements.
+const STRUCT_NAME_PREFIX: &str = "zvariant::FixedArray::";
+
+/// The signature of the elements of a fixed-size array, if `name` is the one requested for it.
+pub(crate) fn element_signature(name: &str) -> Option<&str> {
+    name.strip_prefix(STRUCT_NAME_PREFIX)
+}
+
+/// The fixed-size basic types, that can be borrowed from the serialized bytes.
+///
+/// It's implemented for `u8`, `i16`, `u16`, `i32`, `u32`, `i64`, `u64` and `f64`. `bool` and file
+/// descriptors are excluded as not every bit pattern of their encoding is valid.
+pub trait Element:
+    Basic + Copy + 'static + for<'de> de::Deserialize<'de> + private::Sealed
+{
+}
+
+mod private {
+    pub trait Sealed: Sized {
+        const STRUCT_NAME: &'static str;
+
+        fn from_ne_slice(bytes: &[u8]) -> Self;
+    }
+}
+
+macro_rules! element_impl {
+    ($($ty:ty => $signature:literal)*) => {
+        $(
+            impl private::Sealed for $ty {
+                const STRUCT_NAME: &'static str = concat!("zvariant::FixedArray::", $signature);
+
+                fn from_ne_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_ne_bytes(bytes.try_into().expect("element sized slice"))
+                }
+            }
+
+            impl Element for $ty {}
+        )*
+    };
+}
+
+element_impl!(
+    u8 => "y"
+    i16 => "n"
+    u16 => "q"
+    i32 => "i"
+    u32 => "u"
+    i64 => "x"
+    u64 => "t"
+    f64 => "d"
+);
+
+/// Types that arrays of fixed-size basic types can be deserialized into.
+pub trait FixedArray<'de>: Sized {
+    /// The type of the elements.
+    type Element: Element;
+
+    #[doc(hidden)]
+    fn from_cow<E: de::Error>(array: Cow<'de, [Self::Element]>) -> Result<Self, E>;
+}
+
+impl<'de, T: Element> FixedArray<'de> for &'de [T] {
+    type Element = T;
+
+    fn from_cow<E: de::Error>(array: Cow<'de, [T]>) -> Result<Self, E> {
+        match array {
+            Cow::Borrowed(array) => Ok(array),
+            Cow::Owned(_) => Err(E::custom(
+                "array can't be borrowed as it's not aligned or not in native endianness",
+            )),
+        }
+    }
+}
+
+impl<'de, T: Element> FixedArray<'de> for Cow<'de, [T]> {
+    type Element = T;
+
+    fn from_cow<E: de::Error>(array: Cow<'de, [T]>) -> Result<Self, E> {
+        Ok(array)
+    }
+}
+
+impl<'de, T: Element> FixedArray<'de> for Vec<T> {
+    type Element = T;
+
+    fn from_cow<E: de::Error>(array: Cow<'de, [T]>) -> Result<Self, E> {
+        Ok(array.into_owned())
+    }
+}
+
+/// Deserialize an array of fixed-size basic types, borrowing it if possible.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FixedArray<'de>,
+    D: Deserializer<'de>,
+{
+    let array = deserializer.deserialize_newtype_struct(
+        <T::Element as private::Sealed>::STRUCT_NAME,
+        ArrayVisitor::<T::Element>(PhantomData),
+    )?;
+
+    T::from_cow(array)
+}
+
+/// Serialize an array of fixed-size basic types.
+///
+/// This is the same as serializing `array` directly, provided for the `with` attribute.
+pub fn serialize<T, S>(array: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize,
+    S: Serializer,
+{
+    array.serialize(serializer)
+}
+
+struct ArrayVisitor<T>(PhantomData<T>);
+
+impl<T: Element> ArrayVisitor<T> {
+    /// The elements, from their bytes in the native endianness.
+    fn copy<E: de::Error>(bytes: &[u8]) -> Result<Vec<T>, E> {
+        let size = mem::size_of::<T>();
+        if bytes.len() % size != 0 {
+            return Err(E::invalid_length(
+                bytes.len(),
+                &format!("a multiple of {size} bytes").as_str(),
+            ));
+        }
+
+        Ok(bytes.chunks_exact(size).map(T::from_ne_slice).collect())
+    }
+}
+
+impl<'de, T: Element> Visitor<'de> for ArrayVisitor<T> {
+    type Value = Cow<'de, [T]>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("an array of fixed-size basic values")
+    }
+
+    // Deserializers that don't handle fixed-size arrays specially.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if mem::size_of::<T>() == 1 {
+            deserializer.deserialize_bytes(self)
+        } else {
+            deserializer.deserialize_seq(self)
+        }
+    }
+
+    fn visit_borrowed_bytes<E>(self, bytes: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let size = mem::size_of::<T>();
+        if bytes.len() % size != 0 || bytes.as_ptr().align_offset(mem::align_of::<T>()) != 0 {
+            return Self::copy(bytes).map(Cow::Owned);
+        }
+
+        // SAFETY: The pointer is aligned for `T` and the length is a multiple of its size. `T`
+        // being a number type, any bit pattern is a valid value.
+        let array =
+            unsafe { std::slice::from_raw_parts(bytes.as_ptr().cast::<T>(), bytes.len() / size) };
+
+        Ok(Cow::Borrowed(array))
+    }
+
+    fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Self::copy(bytes).map(Cow::Owned)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            array.push(element);
+        }
+
+        Ok(Cow::Owned(array))
+    }
+}
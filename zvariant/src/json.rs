@@ -0,0 +1,527 @@
+//! Conversion of [`Value`](enum@Value) to and from JSON.
+//!
+//! [`to_json`] converts any [`Value`](enum@Value) to a [`serde_json::Value`] and [`from_json`]
+//! converts it back, given the signature of the value. The mapping is lossless, so the round-trip
+//! always gives back the original value:
+//!
+//! | D-Bus type | JSON |
+//! | --- | --- |
+//! | `b` | boolean |
+//! | `y`, `n`, `q`, `i`, `u`, `x`, `t` | number |
+//! | `d` | number, or `"NaN"`, `"Infinity"` and `"-Infinity"` strings for the values JSON lacks |
+//! | `s`, `o`, `g` | string |
+//! | `v` | `{"signature": "<signature>", "value": <value>}` object |
+//! | `a*` (including `ay`) | array |
+//! | `a{**}` | object, with keys in their textual form (e.g `"42"` or `"true"`) |
+//! | `(*)` | array of the fields |
+//! | `m*` | `null` for nothing, the value for just, or the value wrapped in an array if it's a maybe itself |
+//! | `h` | number, i.e the file descriptor |
+//!
+//! Since file descriptors only make sense in the process they belong to, they can be converted to
+//! JSON (e.g for logging) but not back.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use serde_json::json;
+//! use zvariant::{json, ObjectPath, Signature, Value};
+//!
+//! let path = ObjectPath::try_from("/org/example/Device").unwrap();
+//! let value = Value::new(HashMap::from([
+//!     ("path", Value::new(path)),
+//!     ("levels", Value::new(vec![1u8, 2])),
+//! ]));
+//! let json = json::to_json(&value);
+//! assert_eq!(
+//!     json["path"],
+//!     json!({ "signature": "o", "value": "/org/example/Device" }),
+//! );
+//!
+//! let signature = Signature::try_from("a{sv}").unwrap();
+//! assert_eq!(json::from_json(&json, &signature).unwrap(), value);
+//!
+//! // Errors point at the offending part of the JSON.
+//! let json = json!({ "levels": { "signature": "ay", "value": [1, 256] } });
+//! let err = json::from_json(&json, &signature).unwrap_err();
+//! assert_eq!(err.path(), "/levels/value/1");
+//! ```
+
+use serde_json::{Map, Number, Value as Json};
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{Array, Dict, ObjectPath, Signature, Str, StructureBuilder, Value};
+
+/// Convert the given `value` to JSON.
+///
+/// See the [module documentation](self) for the mapping.
+pub fn to_json(value: &Value<'_>) -> Json {
+    match value {
+        Value::U8(n) => Json::from(*n),
+        Value::Bool(b) => Json::from(*b),
+        Value::I16(n) => Json::from(*n),
+        Value::U16(n) => Json::from(*n),
+        Value::I32(n) => Json::from(*n),
+        Value::U32(n) => Json::from(*n),
+        Value::I64(n) => Json::from(*n),
+        Value::U64(n) => Json::from(*n),
+        Value::F64(n) => match Number::from_f64(*n) {
+            Some(n) => Json::Number(n),
+            None if n.is_nan() => Json::from("NaN"),
+            None if *n > 0. => Json::from("Infinity"),
+            None => Json::from("-Infinity"),
+        },
+        Value::Str(s) => Json::from(s.as_str()),
+        Value::Signature(s) => Json::from(s.to_string()),
+        Value::ObjectPath(p) => Json::from(p.as_str()),
+        Value::Value(v) => {
+            let mut object = Map::new();
+            object.insert("signature".into(), v.value_signature().to_string().into());
+            object.insert("value".into(), to_json(v));
+
+            Json::Object(object)
+        }
+        Value::Array(array) => Json::Array(array.iter().map(to_json).collect()),
+        Value::Dict(dict) => Json::Object(
+            dict.iter()
+                .map(|(k, v)| (key_to_string(k), to_json(v)))
+                .collect(),
+        ),
+        Value::Structure(structure) => {
+            Json::Array(structure.fields().iter().map(to_json).collect())
+        }
+        #[cfg(feature = "gvariant")]
+        Value::Maybe(maybe) => match maybe.inner() {
+            None => Json::Null,
+            // Wrapped so that `just nothing` & `nothing` can be told apart.
+            Some(v @ Value::Maybe(_)) => Json::Array(vec![to_json(v)]),
+            Some(v) => to_json(v),
+        },
+        #[cfg(unix)]
+        Value::Fd(fd) => {
+            use std::os::fd::AsRawFd;
+
+            Json::from(fd.as_raw_fd())
+        }
+    }
+}
+
+/// Convert the given `json` to a value of the type of the given `signature`.
+///
+/// See the [module documentation](self) for the mapping.
+pub fn from_json(json: &Json, signature: &Signature) -> Result<Value<'static>, Error> {
+    Converter {
+        path: String::new(),
+    }
+    .value(json, signature)
+}
+
+/// Error you get on failure to convert JSON to a [`Value`](enum@Value).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    path: String,
+    message: String,
+}
+
+impl Error {
+    /// The location of the offending part of the JSON, as a [JSON pointer].
+    ///
+    /// This is empty if the JSON is wrong as a whole.
+    ///
+    /// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{} at `{}`", self.message, self.path)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for crate::Error {
+    fn from(e: Error) -> Self {
+        crate::Error::Json(e)
+    }
+}
+
+/// Keeps track of the location in the JSON being converted, for errors.
+struct Converter {
+    path: String,
+}
+
+impl Converter {
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error {
+            path: self.path.clone(),
+            message: message.into(),
+        }
+    }
+
+    /// Run `f` with `segment` appended to the path.
+    fn child<T>(&mut self, segment: impl fmt::Display, f: impl FnOnce(&mut Self) -> T) -> T {
+        let len = self.path.len();
+        let segment = segment.to_string();
+        self.path.push('/');
+        self.path
+            .push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        let ret = f(self);
+        self.path.truncate(len);
+
+        ret
+    }
+
+    fn value(&mut self, json: &Json, signature: &Signature) -> Result<Value<'static>, Error> {
+        let mismatch = |c: &Self| {
+            c.error(format!(
+                "expected a value of type `{signature}`, got {}",
+                kind(json)
+            ))
+        };
+
+        let value = match (signature, json) {
+            (Signature::Bool, Json::Bool(b)) => Value::Bool(*b),
+            (
+                Signature::U8
+                | Signature::I16
+                | Signature::U16
+                | Signature::I32
+                | Signature::U32
+                | Signature::I64
+                | Signature::U64,
+                Json::Number(n),
+            ) => integer(n, signature).map_err(|m| self.error(m))?,
+            (Signature::F64, Json::Number(n)) => {
+                Value::F64(n.as_f64().ok_or_else(|| mismatch(self))?)
+            }
+            (Signature::F64, Json::String(s)) => match s.as_str() {
+                "NaN" => Value::F64(f64::NAN),
+                "Infinity" => Value::F64(f64::INFINITY),
+                "-Infinity" => Value::F64(f64::NEG_INFINITY),
+                _ => return Err(mismatch(self)),
+            },
+            (Signature::Str, Json::String(s)) => Value::Str(Str::from(s.clone())),
+            (Signature::ObjectPath, Json::String(s)) => ObjectPath::try_from(s.clone())
+                .map(Value::ObjectPath)
+                .map_err(|e| self.error(e.to_string()))?,
+            (Signature::Signature, Json::String(s)) => Signature::from_str(s)
+                .map(Value::Signature)
+                .map_err(|e| self.error(e.to_string()))?,
+            (Signature::Variant, Json::Object(object)) => {
+                let (Some(Json::String(s)), Some(inner), 2) =
+                    (object.get("signature"), object.get("value"), object.len())
+                else {
+                    return Err(self.error(
+                        "expected a variant as a `{\"signature\": ..., \"value\": ...}` object",
+                    ));
+                };
+                let signature = self.child("signature", |c| {
+                    Signature::from_str(s).map_err(|e| c.error(e.to_string()))
+                })?;
+                let inner = self.child("value", |c| c.value(inner, &signature))?;
+
+                Value::Value(Box::new(inner))
+            }
+            (Signature::Array(child), Json::Array(items)) => {
+                let mut array = Array::new(child.signature());
+                for (i, item) in items.iter().enumerate() {
+                    let item = self.child(i, |c| c.value(item, child.signature()))?;
+                    array.append(item).map_err(|e| self.error(e.to_string()))?;
+                }
+
+                Value::Array(array)
+            }
+            (Signature::Dict { key, value }, Json::Object(object)) => {
+                let mut dict = Dict::new(key.signature(), value.signature());
+                for (k, v) in object {
+                    let (k, v) = self.child(k, |c| {
+                        let k = key_from_string(k, key.signature()).map_err(|m| c.error(m))?;
+                        let v = c.value(v, value.signature())?;
+
+                        Ok::<_, Error>((k, v))
+                    })?;
+                    dict.append(k, v).map_err(|e| self.error(e.to_string()))?;
+                }
+
+                Value::Dict(dict)
+            }
+            (Signature::Structure(fields), Json::Array(items)) => {
+                if fields.len() != items.len() {
+                    return Err(self.error(format!(
+                        "expected an array of {} fields, got {}",
+                        fields.len(),
+                        items.len()
+                    )));
+                }
+                let mut builder = StructureBuilder::new();
+                for (i, (field, item)) in fields.iter().zip(items).enumerate() {
+                    builder = builder.append_field(self.child(i, |c| c.value(item, field))?);
+                }
+
+                Value::Structure(builder.build_with_signature(signature))
+            }
+            #[cfg(feature = "gvariant")]
+            (Signature::Maybe(_), Json::Null) => {
+                Value::Maybe(Maybe::nothing_full_signature(signature))
+            }
+            #[cfg(feature = "gvariant")]
+            (Signature::Maybe(child), json) => {
+                let inner = match (child.signature(), json) {
+                    (Signature::Maybe(_), Json::Array(items)) if items.len() == 1 => {
+                        self.child(0, |c| c.value(&items[0], child.signature()))?
+                    }
+                    (Signature::Maybe(_), _) => {
+                        return Err(self.error(
+                            "expected `null` or an array of a single value for a nested maybe",
+                        ))
+                    }
+                    (child, json) => self.value(json, child)?,
+                };
+
+                Value::Maybe(Maybe::just_full_signature(inner, signature))
+            }
+            #[cfg(unix)]
+            (Signature::Fd, _) => {
+                return Err(self.error("file descriptors can't be converted from JSON"))
+            }
+            _ => return Err(mismatch(self)),
+        };
+
+        Ok(value)
+    }
+}
+
+/// The JSON `number` as a value of the integer type of the given `signature`.
+fn integer(number: &Number, signature: &Signature) -> Result<Value<'static>, String> {
+    let out_of_range = || format!("{number} is out of range for type `{signature}`");
+    let n = match (number.as_i64(), number.as_u64()) {
+        (Some(n), _) => i128::from(n),
+        (None, Some(n)) => i128::from(n),
+        (None, None) => {
+            return Err(format!(
+                "expected an integer of type `{signature}`, got {number}"
+            ))
+        }
+    };
+
+    let value = match signature {
+        Signature::U8 => Value::U8(n.try_into().map_err(|_| out_of_range())?),
+        Signature::I16 => Value::I16(n.try_into().map_err(|_| out_of_range())?),
+        Signature::U16 => Value::U16(n.try_into().map_err(|_| out_of_range())?),
+        Signature::I32 => Value::I32(n.try_into().map_err(|_| out_of_range())?),
+        Signature::U32 => Value::U32(n.try_into().map_err(|_| out_of_range())?),
+        Signature::I64 => Value::I64(n.try_into().map_err(|_| out_of_range())?),
+        Signature::U64 => Value::U64(n.try_into().map_err(|_| out_of_range())?),
+        _ => unreachable!("not an integer type"),
+    };
+
+    Ok(value)
+}
+
+/// The textual form of the given dictionary `key`, for a JSON object key.
+fn key_to_string(key: &Value<'_>) -> String {
+    match key {
+        Value::Str(s) => s.to_string(),
+        Value::ObjectPath(p) => p.to_string(),
+        Value::Signature(s) => s.to_string(),
+        Value::U8(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::I16(n) => n.to_string(),
+        Value::U16(n) => n.to_string(),
+        Value::I32(n) => n.to_string(),
+        Value::U32(n) => n.to_string(),
+        Value::I64(n) => n.to_string(),
+        Value::U64(n) => n.to_string(),
+        Value::F64(n) => n.to_string(),
+        // Not basic types so not valid keys, but whatever is JSON-compatible will do.
+        key => to_json(key).to_string(),
+    }
+}
+
+/// The dictionary key of the type of the given `signature`, from its textual form.
+fn key_from_string(key: &str, signature: &Signature) -> Result<Value<'static>, String> {
+    let invalid = || format!("invalid dictionary key of type `{signature}`");
+
+    let value = match signature {
+        Signature::Str => Value::Str(Str::from(key.to_string())),
+        Signature::ObjectPath => ObjectPath::try_from(key.to_string())
+            .map(Value::ObjectPath)
+            .map_err(|e| e.to_string())?,
+        Signature::Signature => Signature::from_str(key)
+            .map(Value::Signature)
+            .map_err(|e| e.to_string())?,
+        Signature::Bool => Value::Bool(key.parse().map_err(|_| invalid())?),
+        Signature::U8 => Value::U8(key.parse().map_err(|_| invalid())?),
+        Signature::I16 => Value::I16(key.parse().map_err(|_| invalid())?),
+        Signature::U16 => Value::U16(key.parse().map_err(|_| invalid())?),
+        Signature::I32 => Value::I32(key.parse().map_err(|_| invalid())?),
+        Signature::U32 => Value::U32(key.parse().map_err(|_| invalid())?),
+        Signature::I64 => Value::I64(key.parse().map_err(|_| invalid())?),
+        Signature::U64 => Value::U64(key.parse().map_err(|_| invalid())?),
+        Signature::F64 => Value::F64(key.parse().map_err(|_| invalid())?),
+        _ => {
+            return Err(format!(
+                "dictionary keys of type `{signature}` are not supported"
+            ))
+        }
+    };
+
+    Ok(value)
+}
+
+/// A description of the kind of the given JSON value, for errors.
+fn kind(json: &Json) -> &'static str {
+    match json {
+        Json::Null => "null",
+        Json::Bool(_) => "a boolean",
+        Json::Number(_) => "a number",
+        Json::String(_) => "a string",
+        Json::Array(_) => "an array",
+        Json::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[track_caller]
+    fn round_trip(value: Value<'_>, expected: Json) {
+        let json = to_json(&value);
+        assert_eq!(json, expected);
+        assert_eq!(from_json(&json, value.value_signature()).unwrap(), value);
+    }
+
+    #[test]
+    fn mapping() {
+        round_trip(
+            Value::new((
+                255_u8,
+                true,
+                -1_i16,
+                65535_u16,
+                -1,
+                1_u32,
+                i64::MIN,
+                u64::MAX,
+                1.5,
+            )),
+            json!([255, true, -1, 65535, -1, 1, i64::MIN, u64::MAX, 1.5]),
+        );
+        round_trip(
+            Value::new((f64::INFINITY, f64::NEG_INFINITY)),
+            json!(["Infinity", "-Infinity"]),
+        );
+        let nan = to_json(&Value::new(f64::NAN));
+        assert_eq!(nan, json!("NaN"));
+        assert!(matches!(
+            from_json(&nan, &Signature::F64).unwrap(),
+            Value::F64(n) if n.is_nan()
+        ));
+        round_trip(
+            Value::new((
+                "a/~",
+                ObjectPath::from_static_str("/a").unwrap(),
+                Signature::try_from("a{sv}").unwrap(),
+            )),
+            json!(["a/~", "/a", "a{sv}"]),
+        );
+        round_trip(
+            Value::new(Value::new(vec![b"ab".to_vec(), vec![]])),
+            json!({ "signature": "aay", "value": [[97, 98], []] }),
+        );
+        round_trip(
+            Value::new(HashMap::from([(-1_i64, Value::new(true))])),
+            json!({ "-1": { "signature": "b", "value": true } }),
+        );
+        round_trip(
+            Value::new(HashMap::from([(false, "a")])),
+            json!({ "false": "a" }),
+        );
+        round_trip(
+            Value::new(HashMap::from([(
+                ObjectPath::from_static_str("/a").unwrap(),
+                (1_u8, Value::new(Value::new(2_u16))),
+            )])),
+            json!({
+                "/a": [1, { "signature": "v", "value": { "signature": "q", "value": 2 } }],
+            }),
+        );
+        round_trip(Value::new(HashMap::<String, u32>::new()), json!({}));
+    }
+
+    #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+    #[test]
+    fn maybe() {
+        round_trip(Value::new((Some(5_u32), None::<u32>)), json!([5, null]));
+        round_trip(
+            Value::new((Some(Some(5_u32)), Some(None::<u32>), None::<Option<u32>>)),
+            json!([[5], [null], null]),
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fd() {
+        use std::os::fd::BorrowedFd;
+
+        let fd = crate::Fd::from(unsafe { BorrowedFd::borrow_raw(0) });
+        assert_eq!(to_json(&Value::new(fd)), json!(0));
+        assert_eq!(
+            from_json(&json!(0), &Signature::Fd).unwrap_err().message(),
+            "file descriptors can't be converted from JSON"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |json: Json, signature: &str| {
+            from_json(&json, &Signature::try_from(signature).unwrap()).unwrap_err()
+        };
+
+        let err = error(json!("1"), "u");
+        assert_eq!(err.path(), "");
+        assert_eq!(
+            err.to_string(),
+            "expected a value of type `u`, got a string"
+        );
+
+        let err = error(json!([1, [2, -3]]), "(yay)");
+        assert_eq!(err.path(), "/1/1");
+        assert_eq!(err.to_string(), "-3 is out of range for type `y` at `/1/1`");
+
+        assert_eq!(error(json!([1.5]), "ai").path(), "/0");
+        assert_eq!(error(json!([1]), "(ii)").path(), "");
+        assert_eq!(error(json!({ "a/b": 1 }), "a{ss}").path(), "/a~1b");
+        assert_eq!(error(json!({ "x": 1 }), "a{ys}").path(), "/x");
+        assert_eq!(error(json!({ "signature": "i" }), "v").path(), "");
+        assert_eq!(
+            error(json!({ "signature": "z", "value": 1 }), "v").path(),
+            "/signature"
+        );
+        assert_eq!(
+            error(json!({ "a": { "signature": "o", "value": "a" } }), "a{sv}").path(),
+            "/a/value"
+        );
+
+        let err: crate::Error = error(json!(null), "s").into();
+        assert_eq!(err.to_string(), "expected a value of type `s`, got null");
+    }
+}
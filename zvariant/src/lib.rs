@@ -26,6 +26,8 @@ pub use basic::*;
 mod dict;
 pub use dict::*;
 
+pub mod fixed_array;
+
 pub mod serialized;
 
 #[cfg(unix)]
@@ -51,6 +53,11 @@ pub use signature::Signature;
 mod str;
 pub use crate::str::*;
 
+pub mod text;
+
+#[cfg(feature = "serde_json")]
+pub mod json;
+
 mod structure;
 pub use crate::structure::*;
 
@@ -95,7 +102,9 @@ mod framing_offsets;
 
 mod container_depths;
 
-pub use zvariant_derive::{DeserializeDict, OwnedValue, SerializeDict, Type, Value};
+pub use zvariant_derive::{
+    DeserializeDict, DeserializeEnum, OwnedValue, SerializeDict, SerializeEnum, Type, Value,
+};
 
 // Required for the macros to function within this crate.
 extern crate self as zvariant;
@@ -874,6 +883,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fixed_array() {
+        use std::borrow::Cow;
+
+        #[derive(Deserialize, Serialize, Type, PartialEq, Debug)]
+        struct Image<'a> {
+            width: u32,
+            #[serde(borrow, with = "crate::fixed_array")]
+            pixels: Cow<'a, [u32]>,
+            #[serde(borrow, with = "crate::fixed_array")]
+            alpha: &'a [u8],
+        }
+
+        #[derive(Deserialize, Type, Debug)]
+        struct BorrowedImage<'a> {
+            width: u32,
+            #[serde(borrow, deserialize_with = "crate::fixed_array::deserialize")]
+            pixels: &'a [u32],
+        }
+
+        let image = Image {
+            width: 2,
+            pixels: Cow::Owned(vec![0xff0000ff, 0x00ff00ff, 0x0000ffff, 0xffffffff]),
+            alpha: &[0, 64, 128, 255],
+        };
+        let other_endian = if NATIVE_ENDIAN == LE { BE } else { LE };
+
+        // Borrowed from the serialized bytes in the native endianness.
+        let ctxt = Context::new_dbus(NATIVE_ENDIAN, 0);
+        let encoded = to_bytes(ctxt, &image).unwrap();
+        let decoded: Image<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, image);
+        assert!(matches!(decoded.pixels, Cow::Borrowed(_)));
+        assert!(encoded.as_ptr_range().contains(&decoded.alpha.as_ptr()));
+        let decoded: BorrowedImage<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded.width, image.width);
+        assert_eq!(decoded.pixels, &*image.pixels);
+        assert!(encoded
+            .as_ptr_range()
+            .contains(&decoded.pixels.as_ptr().cast()));
+
+        // Copied and swapped otherwise.
+        let ctxt = Context::new_dbus(other_endian, 0);
+        let encoded = to_bytes(ctxt, &image).unwrap();
+        let decoded: Image<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, image);
+        assert!(matches!(decoded.pixels, Cow::Owned(_)));
+        // Bytes have no endianness.
+        assert!(encoded.as_ptr_range().contains(&decoded.alpha.as_ptr()));
+        encoded.deserialize::<BorrowedImage<'_>>().unwrap_err();
+
+        // Copied if unaligned in memory.
+        let ctxt = Context::new_dbus(NATIVE_ENDIAN, 0);
+        let encoded = to_bytes(ctxt, &image).unwrap();
+        let mut unaligned = vec![0u8];
+        unaligned.extend_from_slice(&encoded);
+        let encoded = crate::serialized::Data::new(&unaligned[1..], ctxt);
+        let decoded: Image<'_> = encoded.deserialize().unwrap().0;
+        assert_eq!(decoded, image);
+        assert!(matches!(decoded.pixels, Cow::Owned(_)));
+
+        // Only arrays of fixed-size basic types are accepted.
+        let encoded = to_bytes(ctxt, &(2u32, vec!["a", "b"], vec![0u8])).unwrap();
+        encoded
+            .deserialize_for_signature::<_, Image<'_>>("(uasay)")
+            .unwrap_err();
+
+        // Nor arrays of other types, even of the same size.
+        let encoded = to_bytes(ctxt, &(2u32, vec![1f64, 2.], vec![0u8])).unwrap();
+        encoded
+            .deserialize_for_signature::<_, BorrowedImage<'_>>("(uad)")
+            .unwrap_err();
+        let ctxt = Context::new_dbus(other_endian, 0);
+        let encoded = to_bytes(ctxt, &(2u32, vec![-1i16, 1], vec![0u8])).unwrap();
+        encoded
+            .deserialize_for_signature::<_, Image<'_>>("(uanay)")
+            .unwrap_err();
+        let encoded = to_bytes(ctxt, &(2u32, vec![1u32], vec![0i16])).unwrap();
+        encoded
+            .deserialize_for_signature::<_, Image<'_>>("(uauan)")
+            .unwrap_err();
+
+        #[cfg(feature = "gvariant")]
+        {
+            // Deserialized one by one in the GVariant format.
+            let ctxt = Context::new_gvariant(NATIVE_ENDIAN, 0);
+            let encoded = to_bytes(ctxt, &image).unwrap();
+            let decoded: Image<'_> = encoded.deserialize().unwrap().0;
+            assert_eq!(decoded, image);
+        }
+
+        #[cfg(feature = "bytes")]
+        {
+            let ctxt = Context::new_dbus(NATIVE_ENDIAN, 0);
+            let encoded = to_bytes(ctxt, &image).unwrap();
+            let decoded: Image<'_> = encoded.deserialize().unwrap().0;
+            let alpha = encoded.slice_ref(decoded.alpha);
+            assert_eq!(alpha.as_ptr(), decoded.alpha.as_ptr());
+            assert_eq!(&alpha[..], image.alpha);
+            drop(encoded);
+            assert_eq!(&alpha[..], image.alpha);
+        }
+    }
+
     #[test]
     fn struct_byte_array() {
         let ctxt = Context::new_dbus(LE, 0);
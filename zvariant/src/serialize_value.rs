@@ -1,7 +1,7 @@
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 use static_assertions::assert_impl_all;
 
-use crate::Type;
+use crate::{Signature, Type};
 
 /// A wrapper to serialize `T: Type + Serialize` as a value.
 ///
@@ -38,3 +38,41 @@ impl<T: Type + Serialize> Serialize for SerializeValue<'_, T> {
 impl<T: Type + Serialize> Type for SerializeValue<'_, T> {
     const SIGNATURE: &'static crate::Signature = &crate::Signature::Variant;
 }
+
+/// A wrapper to serialize `T: Serialize` as a value of the given signature.
+///
+/// This is like [`SerializeValue`], for when the signature of the value isn't the one of `T`, e.g
+/// a `String` to be sent as an object path (`o`). It's up to you to ensure `T` serializes to
+/// something of the given signature.
+///
+/// ```
+/// # use zvariant::{to_bytes, serialized::Context, SerializeValueWithSignature, Signature, LE};
+/// #
+/// # let ctxt = Context::new_dbus(LE, 0);
+/// let path = String::from("/org/example");
+/// let _ = to_bytes(ctxt, &SerializeValueWithSignature(&path, &Signature::ObjectPath)).unwrap();
+/// ```
+///
+/// [`SerializeValue`]: struct.SerializeValue.html
+pub struct SerializeValueWithSignature<'a, T: Serialize>(pub &'a T, pub &'a Signature);
+
+assert_impl_all!(SerializeValueWithSignature<'_, i32>: Send, Sync, Unpin);
+
+impl<T: Serialize> Serialize for SerializeValueWithSignature<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Serializer implementation needs to ensure padding isn't added for Value.
+        let mut structure = serializer.serialize_struct("Variant", 2)?;
+
+        structure.serialize_field("signature", self.1)?;
+        structure.serialize_field("value", self.0)?;
+
+        structure.end()
+    }
+}
+
+impl<T: Serialize> Type for SerializeValueWithSignature<'_, T> {
+    const SIGNATURE: &'static Signature = &Signature::Variant;
+}
@@ -400,6 +400,58 @@ impl<'bytes> Data<'bytes, 'static> {
     }
 }
 
+#[cfg(feature = "bytes")]
+impl Data<'static, 'static> {
+    /// A [`bytes::Bytes`] for the given subset of the serialized bytes, sharing their buffer.
+    ///
+    /// This allows keeping a part of the data, e.g a large byte array deserialized as a `&[u8]`,
+    /// around without copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use zvariant::{serialized::Context, to_bytes, LE};
+    ///
+    /// let ctxt = Context::new_dbus(LE, 0);
+    /// let encoded = to_bytes(ctxt, &("image", vec![0xffu8; 1024])).unwrap();
+    /// let (_, pixels): (&str, &[u8]) = encoded.deserialize().unwrap().0;
+    /// let pixels = encoded.slice_ref(pixels);
+    /// drop(encoded);
+    /// assert_eq!(&pixels[..], &[0xffu8; 1024][..]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Requires that `subset` is a slice of [`Data::bytes`], otherwise this will panic.
+    pub fn slice_ref(&self, subset: &[u8]) -> bytes::Bytes {
+        // Empty slices can come from anywhere.
+        if subset.is_empty() {
+            return bytes::Bytes::new();
+        }
+
+        let bytes = self.bytes().as_ptr_range();
+        let subset_bytes = subset.as_ptr_range();
+        assert!(
+            bytes.start <= subset_bytes.start && subset_bytes.end <= bytes.end,
+            "subset not contained in the serialized bytes",
+        );
+        let start = self.range.start + (subset_bytes.start as usize - bytes.start as usize);
+
+        bytes::Bytes::from_owner(SharedBytes(self.inner.clone())).slice(start..start + subset.len())
+    }
+}
+
+/// The owner of the buffer of [`bytes::Bytes`] created from [`Data`].
+#[cfg(feature = "bytes")]
+struct SharedBytes(Arc<Inner<'static, 'static>>);
+
+#[cfg(feature = "bytes")]
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0.bytes
+    }
+}
+
 impl Deref for Data<'_, '_> {
     type Target = [u8];
 
@@ -0,0 +1,639 @@
+//! The GVariant text format.
+//!
+//! This is the human-readable format of values used by GLib's `g_variant_print` and
+//! `g_variant_parse`, and thus by tools like `gdbus` and `busctl`, e.g `{'a': <int32 1>}` or
+//! `@as []`.
+//!
+//! Values are printed in this format by [`to_string`], and parsed with [`parse`] or
+//! [`parse_with_signature`]. Besides the syntax of GLib (including the `b"bytestring"` and maybe
+//! types, with the `gvariant` feature), the parser accepts everything [`Value`](enum@Value) prints,
+//! so values survive the round-trip.
+//!
+//! # Canonical form
+//!
+//! [`to_string`] prints values in the canonical form, i.e the one of `g_variant_print` with type
+//! annotations:
+//!
+//! * The types that can't be inferred from the text are annotated, once per array or dictionary:
+//!   `byte 0x01`, `uint32 5`, `objectpath '/a'`, `@as []`, `@mi nothing`, etc. Values in variants
+//!   are always annotated.
+//! * Strings are single-quoted, unless they contain a single quote, in which case they are
+//!   double-quoted. Control and format characters are escaped, e.g `\n` or `\u200b`.
+//! * Arrays of bytes whose only nul byte is the last one are printed as bytestrings, e.g `b'abc'`.
+//! * Doubles are printed with 17 significant digits, like `printf("%.17g")` does, e.g
+//!   `0.10000000000000001`, with a `.0` suffix if they would look like an integer.
+//! * Elements are separated by `, `, dictionary keys and values by `: `, and one-field tuples have
+//!   a trailing comma, e.g `(1,)`.
+//!
+//! Since [`Signature`] doesn't tell several types from a structure of them, signatures made of
+//! several types are printed as a structure, e.g `signature '(ss)'` rather than `signature 'ss'`.
+//!
+//! The [`Display`](std::fmt::Display) implementation of [`Value`](enum@Value) prints the same
+//! syntax, but isn't canonical: it double-quotes strings and bytestrings, escapes characters the
+//! Rust way and prints doubles with the least digits needed. It also prints bytestrings that
+//! aren't valid UTF-8 lossily, so only the canonical form can be parsed back into them.
+//!
+//! Unless they are annotated (e.g `@as []`, `uint32 5`), the types of the values are inferred from
+//! their text: integers are `i` (int32), numbers with a fractional part or an exponent are `d`
+//! (double) and quoted strings are `s`. The elements of arrays and dictionaries all have the same
+//! type, so annotating one of them is enough. File descriptors (`h`) can't be parsed, since they
+//! are indexes into a list of file descriptors that the text format has no way to refer to.
+//!
+//! # Examples
+//!
+//! ```
+//! use zvariant::{text, Signature};
+//!
+//! let value = text::parse("{'a': <int16 1>, 'b': <[objectpath '/x']>}").unwrap();
+//! assert_eq!(value.value_signature(), "a{sv}");
+//! assert_eq!(
+//!     text::parse(&value.to_string()).unwrap(),
+//!     value,
+//! );
+//!
+//! // The expected type can fill in what's missing.
+//! let signature = Signature::try_from("(uao)").unwrap();
+//! let value = text::parse_with_signature("(5, [])", &signature).unwrap();
+//! assert_eq!(value.value_signature(), "(uao)");
+//!
+//! let err = text::parse("[1, 'two']").unwrap_err();
+//! assert_eq!(err.offset(), 4);
+//! ```
+
+use std::{fmt, str::FromStr};
+
+mod parser;
+use parser::{Node, NodeKind};
+
+mod pattern;
+use pattern::Pattern;
+
+mod printer;
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{Array, Dict, ObjectPath, OwnedValue, Signature, Str, StructureBuilder, Value};
+
+/// Print a value in the canonical text format.
+///
+/// See the [module documentation](self#canonical-form) for details.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{text, Value};
+///
+/// let value = Value::from(HashMap::from([("a", Value::from(0.1)), ("it's", Value::from(5u8))]));
+/// assert_eq!(
+///     text::to_string(&value),
+///     "{'a': <0.10000000000000001>, \"it's\": <byte 0x05>}",
+/// );
+/// ```
+pub fn to_string(value: &Value<'_>) -> String {
+    let mut out = String::new();
+    printer::print(&mut out, value, true);
+
+    out
+}
+
+/// Parse a value in the text format, inferring its type.
+///
+/// See the [module documentation](self) for details.
+pub fn parse(text: &str) -> Result<Value<'static>, Error> {
+    let node = parser::parse(text)?;
+
+    typed_value(node)
+}
+
+/// Parse a value of the type of the given `signature`, in the text format.
+///
+/// The signature fills in the types that can't be inferred from the text, e.g `[]` is a valid
+/// value of type `as`, and disambiguates the others, e.g `5` is a valid value of type `y`.
+///
+/// See the [module documentation](self) for details.
+pub fn parse_with_signature(text: &str, signature: &Signature) -> Result<Value<'static>, Error> {
+    let node = parser::parse(text)?;
+
+    value(node, signature)
+}
+
+/// Error you get on failure to parse a value in the text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    offset: usize,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    /// The offset (in bytes) of the error in the text.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for crate::Error {
+    fn from(e: Error) -> Self {
+        crate::Error::TextParse(e)
+    }
+}
+
+impl FromStr for OwnedValue {
+    type Err = crate::Error;
+
+    /// Parse a value in the text format, see [`parse`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)?.try_into()
+    }
+}
+
+/// The value of the given `node`, with its type inferred from its text.
+fn typed_value(node: Node) -> Result<Value<'static>, Error> {
+    let signature = Pattern::of(&node)?.default_signature().ok_or_else(|| {
+        Error::new(
+            node.offset,
+            "unable to infer the type of the value, a type annotation is needed",
+        )
+    })?;
+
+    value(node, &signature)
+}
+
+/// The value of the given `node`, of the type of the given `signature`.
+fn value(node: Node, signature: &Signature) -> Result<Value<'static>, Error> {
+    let offset = node.offset;
+    let error = |message: String| Error::new(offset, message);
+
+    // Values of maybe types can be written without `just`.
+    #[cfg(feature = "gvariant")]
+    if let Signature::Maybe(child) = signature {
+        let maybe = match node.kind {
+            NodeKind::Just(inner) => Maybe::just_full_signature(value(*inner, child)?, signature),
+            NodeKind::Nothing => Maybe::nothing_full_signature(signature),
+            NodeKind::Annotated(ref s, _) if s == signature => return annotated(node, signature),
+            _ => Maybe::just_full_signature(value(node, child)?, signature),
+        };
+
+        return Ok(Value::Maybe(maybe));
+    }
+
+    if let NodeKind::Annotated(..) = node.kind {
+        return annotated(node, signature);
+    }
+
+    let value = match (node.kind, signature) {
+        (NodeKind::Bool(b), Signature::Bool) => Value::Bool(b),
+        (NodeKind::Integer(text), signature) => integer(&text, signature).map_err(error)?,
+        (NodeKind::Float(text), Signature::F64) => text
+            .parse()
+            .map(Value::F64)
+            .map_err(|_| error(format!("invalid number `{text}`")))?,
+        (NodeKind::Str(s), Signature::Str) => Value::Str(Str::from(s)),
+        (NodeKind::Str(s), Signature::ObjectPath) => ObjectPath::try_from(s)
+            .map(Value::ObjectPath)
+            .map_err(|e| error(e.to_string()))?,
+        (NodeKind::Str(s), Signature::Signature) => Signature::from_str(&s)
+            .map(Value::Signature)
+            .map_err(|e| error(e.to_string()))?,
+        (NodeKind::ByteStr(bytes), Signature::Array(child))
+            if *child.signature() == Signature::U8 =>
+        {
+            let mut array = Array::new(&Signature::U8);
+            for byte in bytes.into_iter().chain([0]) {
+                array.append(Value::U8(byte)).expect("matching signature");
+            }
+
+            Value::Array(array)
+        }
+        (NodeKind::Array(items), Signature::Array(child)) => {
+            let mut array = Array::new(child.signature());
+            for item in items {
+                let item_offset = item.offset;
+                let item = value(item, child.signature())?;
+                array
+                    .append(item)
+                    .map_err(|e| Error::new(item_offset, e.to_string()))?;
+            }
+
+            Value::Array(array)
+        }
+        // Dictionaries are arrays of dictionary entries, after all.
+        (NodeKind::Array(items), Signature::Dict { key, value }) if items.is_empty() => {
+            Value::Dict(Dict::new(key.signature(), value.signature()))
+        }
+        (NodeKind::Dict(entries), Signature::Dict { key, value: v }) => {
+            let mut dict = Dict::new(key.signature(), v.signature());
+            for (k, v_node) in entries {
+                let key_offset = k.offset;
+                let k = value(k, key.signature())?;
+                let v_node = value(v_node, v.signature())?;
+                dict.append(k, v_node)
+                    .map_err(|e| Error::new(key_offset, e.to_string()))?;
+            }
+
+            Value::Dict(dict)
+        }
+        (NodeKind::Tuple(fields), Signature::Structure(signatures)) => {
+            if fields.len() != signatures.len() {
+                return Err(error(format!(
+                    "expected a tuple of {} fields, got {}",
+                    signatures.len(),
+                    fields.len()
+                )));
+            }
+            let mut builder = StructureBuilder::new();
+            for (field, signature) in fields.into_iter().zip(signatures.iter()) {
+                builder = builder.append_field(value(field, signature)?);
+            }
+
+            Value::Structure(builder.build_with_signature(signature))
+        }
+        (NodeKind::Tuple(fields), _) if fields.is_empty() => {
+            return Err(error("empty tuples are not supported".into()))
+        }
+        (NodeKind::Variant(inner), Signature::Variant) => {
+            Value::Value(Box::new(typed_value(*inner)?))
+        }
+        #[cfg(unix)]
+        (_, Signature::Fd) => {
+            return Err(error(
+                "file descriptors can't be expressed in the text format".into(),
+            ))
+        }
+        (_, signature) => return Err(error(format!("expected a value of type `{signature}`"))),
+    };
+
+    Ok(value)
+}
+
+/// The value of the annotated `node`, of the type of the given `signature`.
+fn annotated(node: Node, signature: &Signature) -> Result<Value<'static>, Error> {
+    let NodeKind::Annotated(annotation, inner) = node.kind else {
+        unreachable!("not an annotated node");
+    };
+    if annotation != *signature {
+        return Err(Error::new(
+            node.offset,
+            format!("type `{annotation}` doesn't match the expected type `{signature}`"),
+        ));
+    }
+
+    value(*inner, signature)
+}
+
+/// The integer of the given `text`, as a value of the type of the given `signature`.
+fn integer(text: &str, signature: &Signature) -> Result<Value<'static>, String> {
+    let (negative, unsigned) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let magnitude = match unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16),
+        None => unsigned.parse(),
+    }
+    .map_err(|_| format!("invalid number `{text}`"))?;
+    let n = if negative { -magnitude } else { magnitude };
+    let out_of_range = |_| format!("`{text}` is out of range for type `{signature}`");
+
+    let value = match signature {
+        Signature::U8 => Value::U8(n.try_into().map_err(out_of_range)?),
+        Signature::I16 => Value::I16(n.try_into().map_err(out_of_range)?),
+        Signature::U16 => Value::U16(n.try_into().map_err(out_of_range)?),
+        Signature::I32 => Value::I32(n.try_into().map_err(out_of_range)?),
+        Signature::U32 => Value::U32(n.try_into().map_err(out_of_range)?),
+        Signature::I64 => Value::I64(n.try_into().map_err(out_of_range)?),
+        Signature::U64 => Value::U64(n.try_into().map_err(out_of_range)?),
+        Signature::F64 => Value::F64(n as f64),
+        _ => return Err(format!("expected a value of type `{signature}`")),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[track_caller]
+    fn round_trip(value: Value<'_>) {
+        let text = value.to_string();
+        let parsed = parse(&text).unwrap_or_else(|e| panic!("failed to parse `{text}`: {e}"));
+        assert_eq!(parsed, value, "`{text}`");
+        assert_eq!(
+            parsed.value_signature(),
+            value.value_signature(),
+            "`{text}`"
+        );
+    }
+
+    #[test]
+    fn display_round_trip() {
+        round_trip(Value::new((
+            255_u8,
+            true,
+            -1_i16,
+            65535_u16,
+            -1,
+            1_u32,
+            -9223372036854775808_i64,
+            18446744073709551615_u64,
+            (-1., 1.0, 11000000000., 1.1e-10),
+        )));
+        round_trip(Value::new(vec![
+            "", " ", "a", r#"""#, "'", "a'b", "a'\"b", "\\", "\n'\"",
+        ]));
+        round_trip(Value::new(vec![
+            "\x07\x08\x09\x0A\x0B\x0C\x0D",
+            "\x7F",
+            char::from_u32(0xD8000).unwrap().to_string().as_str(),
+        ]));
+        round_trip(Value::new((
+            vec![
+                Signature::try_from("").unwrap(),
+                Signature::try_from("(ysa{sd})").unwrap(),
+            ],
+            vec![
+                ObjectPath::from_static_str("/").unwrap(),
+                ObjectPath::from_static_str("/a/b").unwrap(),
+            ],
+            vec![
+                Value::new(0_u8),
+                Value::new((Value::new(51), Value::new(Value::new(1_u32)))),
+            ],
+        )));
+        round_trip(Value::new(vec![] as Vec<Vec<i64>>));
+        round_trip(Value::new(vec![vec![0_i16, 1], vec![2, 3]]));
+        round_trip(Value::new(vec![
+            b"Hello".to_vec(),
+            b"Hell\0o".to_vec(),
+            b"Hello\0".to_vec(),
+            b"\0".to_vec(),
+            b"\n'\"\x07\0".to_vec(),
+        ]));
+        round_trip(Value::new(HashMap::<bool, bool>::new()));
+        round_trip(Value::new(HashMap::from([
+            (32_u16, 64_i64),
+            (100_u16, 200_i64),
+        ])));
+        round_trip(Value::new(HashMap::from([(
+            "a",
+            Value::new(vec![Value::new(1.5), Value::new("b")]),
+        )])));
+        round_trip(Value::new(((true,), (true, false), (true, true, false))));
+        round_trip(Value::new((f64::INFINITY, f64::NEG_INFINITY)));
+    }
+
+    #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+    #[test]
+    fn display_round_trip_maybe() {
+        round_trip(Value::new((
+            (Some(0_i16), Some(Some(0_i16)), Some(Some(Some(0_i16)))),
+            (None::<i16>, Some(None::<i16>), Some(Some(None::<i16>))),
+            (None::<Option<i16>>, Some(None::<Option<i16>>)),
+        )));
+        round_trip(Value::new((
+            None::<bool>,
+            Some(HashMap::from([("size", Value::new((800, 600)))])),
+            vec![Value::new(Some(Some(200_i16)))],
+        )));
+    }
+
+    #[test]
+    fn gvariant_syntax() {
+        assert_eq!(
+            parse("{'a': <int32 1>}").unwrap(),
+            Value::new(HashMap::from([("a", Value::new(1))])),
+        );
+        assert_eq!(parse("@as []").unwrap(), Value::new(Vec::<String>::new()));
+        assert_eq!(
+            parse("[objectpath '/x', '/y']").unwrap(),
+            Value::new(vec![
+                ObjectPath::from_static_str("/x").unwrap(),
+                ObjectPath::from_static_str("/y").unwrap(),
+            ]),
+        );
+        assert_eq!(parse("b'foo'").unwrap(), Value::new(b"foo\0".to_vec()));
+        assert_eq!(
+            parse(r#"b"\101\n""#).unwrap(),
+            Value::new(b"A\n\0".to_vec())
+        );
+        assert_eq!(parse(r"'é\U0001F600'").unwrap(), Value::new("é😀"));
+        assert_eq!(parse("[1, 2.5]").unwrap(), Value::new(vec![1., 2.5]));
+        assert_eq!(
+            parse("[byte 0x10, 32]").unwrap(),
+            Value::new(vec![16_u8, 32])
+        );
+        assert_eq!(parse("(1,)").unwrap(), Value::new((1,)));
+        assert_eq!(parse(" ( 1 , 'a' ) ").unwrap(), Value::new((1, "a")));
+        assert_eq!(parse("@(ux) (1, 2)").unwrap(), Value::new((1_u32, 2_i64)));
+        assert_eq!(
+            parse("[@a{sv} {}, {'x': <true>}]").unwrap(),
+            Value::new(vec![
+                HashMap::<&str, Value<'_>>::new(),
+                HashMap::from([("x", Value::new(true))]),
+            ]),
+        );
+        #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+        {
+            assert_eq!(parse("just 5").unwrap(), Value::new(Some(5)));
+            assert_eq!(
+                parse("[just 5, nothing]").unwrap(),
+                Value::new(vec![Some(5), None])
+            );
+        }
+
+        let value: OwnedValue = "uint64 3".parse().unwrap();
+        assert_eq!(value, OwnedValue::from(3_u64));
+    }
+
+    #[test]
+    fn with_signature() {
+        let parse = |text, signature: &str| {
+            parse_with_signature(text, &Signature::try_from(signature).unwrap())
+        };
+
+        assert_eq!(parse("5", "y").unwrap(), Value::new(5_u8));
+        assert_eq!(parse("5", "d").unwrap(), Value::new(5.));
+        assert_eq!(parse("[]", "as").unwrap(), Value::new(Vec::<String>::new()));
+        assert_eq!(
+            parse("[]", "a{sv}").unwrap(),
+            Value::new(HashMap::<&str, Value<'_>>::new()),
+        );
+        assert_eq!(
+            parse("('/a', 'a{sv}')", "(og)").unwrap(),
+            Value::new((
+                ObjectPath::from_static_str("/a").unwrap(),
+                Signature::try_from("a{sv}").unwrap(),
+            )),
+        );
+        assert_eq!(parse("<[]>", "v").unwrap_err().offset(), 1);
+        assert_eq!(parse("256", "y").unwrap_err().offset(), 0);
+        assert_eq!(parse("(1, 'a')", "(is)").unwrap(), Value::new((1, "a")));
+        assert_eq!(parse("(1, 2)", "(is)").unwrap_err().offset(), 4);
+        assert_eq!(parse("int16 1", "i").unwrap_err().offset(), 0);
+        assert_eq!(parse("'a'", "o").unwrap_err().offset(), 0);
+        #[cfg(all(feature = "gvariant", not(feature = "option-as-array")))]
+        assert_eq!(
+            parse("[5, nothing]", "amu").unwrap(),
+            Value::new(vec![Some(5_u32), None])
+        );
+    }
+
+    #[track_caller]
+    fn canonical(value: Value<'_>, expected: &str) {
+        let text = to_string(&value);
+        assert_eq!(text, expected);
+        assert_eq!(parse(&text).unwrap(), value, "`{text}`");
+    }
+
+    #[test]
+    fn canonical_form() {
+        // The expected texts are the ones `g_variant_print (value, TRUE)` prints.
+        canonical(
+            Value::new((
+                255_u8,
+                true,
+                -1_i16,
+                65535_u16,
+                -1,
+                1_u32,
+                i64::MIN,
+                u64::MAX,
+            )),
+            "(byte 0xff, true, int16 -1, uint16 65535, -1, uint32 1, \
+             int64 -9223372036854775808, uint64 18446744073709551615)",
+        );
+        canonical(
+            Value::new(vec![
+                0.1,
+                1.,
+                -0.,
+                1e300,
+                -2.5e-7,
+                1e17,
+                1e16,
+                123456.789,
+                0.0001,
+                0.00001,
+                f64::MIN_POSITIVE,
+                f64::MAX,
+            ]),
+            "[0.10000000000000001, 1.0, -0.0, 1.0000000000000001e+300, -2.4999999999999999e-07, \
+             1e+17, 10000000000000000.0, 123456.789, 0.0001, 1.0000000000000001e-05, \
+             2.2250738585072014e-308, 1.7976931348623157e+308]",
+        );
+        canonical(
+            Value::new(vec![
+                "",
+                "it's",
+                "\"",
+                "a'\"b",
+                "\\",
+                "é😀\u{e000}",
+                "\x07\x08\t\n\x0B\x0C\r\x1b\x7f",
+                "\u{200b}\u{feff}\u{85}\u{10ffff}",
+            ]),
+            "['', \"it's\", '\"', \"a'\\\"b\", '\\\\', 'é😀\u{e000}', \
+             '\\a\\b\\t\\n\\v\\f\\r\\u001b\\u007f', '\\u200b\\ufeff\\u0085\\U0010ffff']",
+        );
+        canonical(
+            Value::new(vec![
+                b"ab\x01\xff\"\\\0".to_vec(),
+                b"it's\0".to_vec(),
+                b"\0".to_vec(),
+                b"a\0b\0".to_vec(),
+                vec![],
+                b"\x08\x0c\n\r\t\x0b\x7f\0".to_vec(),
+            ]),
+            "[b'ab\\001\\377\\\"\\\\', b\"it's\", b'', [0x61, 0x00, 0x62, 0x00], [], \
+             b'\\b\\f\\n\\r\\t\\v\\177']",
+        );
+        canonical(
+            Value::new((
+                Signature::try_from("a{sv}").unwrap(),
+                ObjectPath::from_static_str("/a/b").unwrap(),
+                Value::new(Value::new(1_u32)),
+            )),
+            "(signature 'a{sv}', objectpath '/a/b', <<uint32 1>>)",
+        );
+        canonical(
+            Value::new(HashMap::from([
+                ("a", Value::new(1)),
+                ("b", Value::new(Vec::<String>::new())),
+            ])),
+            "{'a': <1>, 'b': <@as []>}",
+        );
+        canonical(Value::new(HashMap::<u16, bool>::new()), "@a{qb} {}");
+        canonical(
+            Value::new(vec![HashMap::from([(1_u64, (1_u8,))])]),
+            "[{uint64 1: (byte 0x01,)}]",
+        );
+        canonical(
+            Value::new(vec![(Vec::<i16>::new(), 2_u16)]),
+            "[(@an [], uint16 2)]",
+        );
+        #[cfg(feature = "gvariant")]
+        for text in [
+            "@mmi just nothing",
+            "@mmu 5",
+            "@mas nothing",
+            "[@mu 5, nothing]",
+        ] {
+            canonical(parse(text).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let offset = |text| parse(text).unwrap_err().offset();
+
+        assert_eq!(offset(""), 0);
+        assert_eq!(offset("[1, 'two']"), 4);
+        assert_eq!(offset("{'a': 1, 'b': true}"), 14);
+        assert_eq!(offset("[1 2]"), 3);
+        assert_eq!(offset("'abc"), 1);
+        assert_eq!(offset("(1, 2) x"), 7);
+        assert_eq!(offset("  bogus"), 2);
+        assert_eq!(offset("@z 1"), 1);
+        assert_eq!(offset("[]"), 0);
+        assert_eq!(offset("<@ai [1, 'a']>"), 9);
+        assert_eq!(offset("int32 99999999999"), 6);
+        assert_eq!(offset("()"), 0);
+
+        let err = parse("[true, 1]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "array element of a different type at offset 7"
+        );
+        assert!(matches!(
+            "[".parse::<OwnedValue>(),
+            Err(crate::Error::TextParse(e)) if e.offset() == 1
+        ));
+    }
+}
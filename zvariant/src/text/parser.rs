@@ -0,0 +1,372 @@
+use std::str::FromStr;
+
+use super::Error;
+use crate::Signature;
+
+/// A value in the text format, as written, before its type is known.
+#[derive(Debug)]
+pub(super) struct Node {
+    /// The offset of the value in the text.
+    pub offset: usize,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug)]
+pub(super) enum NodeKind {
+    /// A value with a type annotation, e.g `@as []` or `int16 5`.
+    Annotated(Signature, Box<Node>),
+    Bool(bool),
+    /// An integer, in its written form.
+    Integer(String),
+    /// A floating point number, in its written form.
+    Float(String),
+    Str(String),
+    /// A byte string, e.g `b"foo"`, without its terminating nul byte.
+    ByteStr(Vec<u8>),
+    Array(Vec<Node>),
+    Dict(Vec<(Node, Node)>),
+    Tuple(Vec<Node>),
+    Variant(Box<Node>),
+    #[cfg(feature = "gvariant")]
+    Just(Box<Node>),
+    #[cfg(feature = "gvariant")]
+    Nothing,
+}
+
+/// Parse the whole `text` as a single value.
+pub(super) fn parse(text: &str) -> Result<Node, Error> {
+    let mut parser = Parser { text, pos: 0 };
+    let node = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+
+    Ok(node)
+}
+
+struct Parser<'t> {
+    text: &'t str,
+    pos: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::new(self.pos, message)
+    }
+
+    fn rest(&self) -> &'t str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `c` if it's the next character, skipping whitespace before it.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{c}`")))
+        }
+    }
+
+    /// Consume the next word (keyword or number), if any.
+    fn word(&mut self) -> &'t str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+
+        &rest[..len]
+    }
+
+    fn value(&mut self) -> Result<Node, Error> {
+        self.skip_whitespace();
+        let offset = self.pos;
+        let node = |kind| Ok(Node { offset, kind });
+
+        match self.peek() {
+            None => Err(self.error("expected a value")),
+            Some('@') => {
+                self.pos += 1;
+                let signature = self.signature()?;
+                let value = self.value()?;
+
+                node(NodeKind::Annotated(signature, Box::new(value)))
+            }
+            Some('[') => {
+                self.pos += 1;
+                let items = self.sequence(']')?;
+
+                node(NodeKind::Array(items))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let mut fields = vec![];
+                if !self.eat(')') {
+                    loop {
+                        fields.push(self.value()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        self.expect(',')?;
+                        // A single field is followed by a comma, e.g `(1,)`.
+                        if self.eat(')') {
+                            break;
+                        }
+                    }
+                }
+
+                node(NodeKind::Tuple(fields))
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut entries = vec![];
+                if !self.eat('}') {
+                    loop {
+                        let key = self.value()?;
+                        self.expect(':')?;
+                        let value = self.value()?;
+                        entries.push((key, value));
+                        if self.eat('}') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+
+                node(NodeKind::Dict(entries))
+            }
+            Some('<') => {
+                self.pos += 1;
+                let value = self.value()?;
+                self.expect('>')?;
+
+                node(NodeKind::Variant(Box::new(value)))
+            }
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let s = self.string(quote, false)?;
+                let s = String::from_utf8(s).expect("valid UTF-8");
+
+                node(NodeKind::Str(s))
+            }
+            Some('b') if matches!(self.rest()[1..].chars().next(), Some('"' | '\'')) => {
+                let quote = self.rest()[1..].chars().next().expect("quote");
+                self.pos += 2;
+                let bytes = self.string(quote, true)?;
+
+                node(NodeKind::ByteStr(bytes))
+            }
+            Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.') => {
+                let word = self.word();
+                let annotation = match word {
+                    "true" => return node(NodeKind::Bool(true)),
+                    "false" => return node(NodeKind::Bool(false)),
+                    #[cfg(feature = "gvariant")]
+                    "nothing" => return node(NodeKind::Nothing),
+                    #[cfg(feature = "gvariant")]
+                    "just" => {
+                        let value = self.value()?;
+
+                        return node(NodeKind::Just(Box::new(value)));
+                    }
+                    "boolean" => Signature::Bool,
+                    "byte" => Signature::U8,
+                    "int16" => Signature::I16,
+                    "uint16" => Signature::U16,
+                    "int32" => Signature::I32,
+                    "uint32" => Signature::U32,
+                    "int64" => Signature::I64,
+                    "uint64" => Signature::U64,
+                    "double" => Signature::F64,
+                    "string" => Signature::Str,
+                    "objectpath" => Signature::ObjectPath,
+                    "signature" => Signature::Signature,
+                    #[cfg(unix)]
+                    "handle" => Signature::Fd,
+                    _ => return self.number(offset, word),
+                };
+                let value = self.value()?;
+
+                node(NodeKind::Annotated(annotation, Box::new(value)))
+            }
+            Some(c) => Err(self.error(format!("unexpected character `{c}`"))),
+        }
+    }
+
+    /// The comma-separated values up to the `end` character.
+    fn sequence(&mut self, end: char) -> Result<Vec<Node>, Error> {
+        let mut items = vec![];
+        if self.eat(end) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(end) {
+                return Ok(items);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn number(&mut self, offset: usize, word: &str) -> Result<Node, Error> {
+        let unsigned = word.trim_start_matches(['+', '-']);
+        let is_hex = unsigned.starts_with("0x") || unsigned.starts_with("0X");
+        let kind = if !is_hex
+            && (unsigned.contains(['.', 'e', 'E'])
+                || unsigned.eq_ignore_ascii_case("inf")
+                || unsigned.eq_ignore_ascii_case("nan"))
+        {
+            NodeKind::Float(word.to_string())
+        } else if unsigned.starts_with(|c: char| c.is_ascii_digit()) {
+            NodeKind::Integer(word.to_string())
+        } else {
+            return Err(Error::new(offset, format!("unknown keyword `{word}`")));
+        };
+
+        Ok(Node { offset, kind })
+    }
+
+    /// The type of an annotation, i.e a single complete type.
+    fn signature(&mut self) -> Result<Signature, Error> {
+        let rest = self.rest();
+        let len = single_type_len(rest.as_bytes())
+            .ok_or_else(|| self.error("expected a type after `@`"))?;
+        let signature = Signature::from_str(&rest[..len])
+            .map_err(|e| self.error(format!("invalid type `{}`: {e}", &rest[..len])))?;
+        self.pos += len;
+
+        Ok(signature)
+    }
+
+    /// The contents of a string, after its opening `quote`.
+    fn string(&mut self, quote: char, bytes: bool) -> Result<Vec<u8>, Error> {
+        let mut s = vec![];
+        let mut chars = self.rest().char_indices();
+        let start = self.pos;
+        let error = |i: usize, message: &str| Error::new(start + i, message);
+
+        loop {
+            let (i, c) = chars
+                .next()
+                .ok_or_else(|| error(0, "unterminated string"))?;
+            let c = match c {
+                c if c == quote => {
+                    self.pos = start + i + 1;
+
+                    return Ok(s);
+                }
+                '\\' => {
+                    let (_, escape) = chars
+                        .next()
+                        .ok_or_else(|| error(i, "unterminated string"))?;
+                    match escape {
+                        'a' => '\x07',
+                        'b' => '\x08',
+                        'f' => '\x0c',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'v' => '\x0b',
+                        '0'..='7' if bytes => {
+                            // Octal escapes, up to 3 digits.
+                            let mut byte = escape.to_digit(8).expect("octal digit");
+                            for _ in 0..2 {
+                                let next = chars.clone().next().and_then(|(_, c)| c.to_digit(8));
+                                match next {
+                                    Some(digit) => {
+                                        chars.next();
+                                        byte = byte * 8 + digit;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            let byte = u8::try_from(byte)
+                                .map_err(|_| error(i, "octal escape out of range"))?;
+                            s.push(byte);
+
+                            continue;
+                        }
+                        'u' | 'U' => {
+                            let digits = chars.as_str();
+                            // Both `é` & `\U000000e9` and the `\u{e9}` form.
+                            let (hex, len) = if let Some(braced) = digits.strip_prefix('{') {
+                                let end = braced
+                                    .find('}')
+                                    .ok_or_else(|| error(i, "unterminated unicode escape"))?;
+
+                                (&braced[..end], end + 2)
+                            } else {
+                                let len = if escape == 'u' { 4 } else { 8 };
+                                let hex = digits
+                                    .get(..len)
+                                    .ok_or_else(|| error(i, "truncated unicode escape"))?;
+
+                                (hex, len)
+                            };
+                            let c = u32::from_str_radix(hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| error(i, "invalid unicode escape"))?;
+                            for _ in 0..len {
+                                chars.next();
+                            }
+
+                            c
+                        }
+                        // Including the quotes and the backslash itself.
+                        c => c,
+                    }
+                }
+                c => c,
+            };
+            let mut buf = [0; 4];
+            s.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+}
+
+/// The length of the single complete type at the start of `s`, if any.
+fn single_type_len(s: &[u8]) -> Option<usize> {
+    match s.first()? {
+        b'y' | b'b' | b'n' | b'q' | b'i' | b'u' | b'x' | b't' | b'd' | b's' | b'o' | b'g'
+        | b'v' | b'h' => Some(1),
+        b'a' | b'm' => single_type_len(&s[1..]).map(|len| len + 1),
+        b'(' => {
+            let mut len = 1;
+            while *s.get(len)? != b')' {
+                len += single_type_len(&s[len..])?;
+            }
+
+            Some(len + 1)
+        }
+        b'{' => {
+            let key = single_type_len(&s[1..])?;
+            let value = single_type_len(&s[1 + key..])?;
+            let len = 1 + key + value;
+
+            (*s.get(len)? == b'}').then_some(len + 1)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,170 @@
+use super::{
+    parser::{Node, NodeKind},
+    Error,
+};
+use crate::Signature;
+
+/// The type of a value as far as it can be told from its text, i.e a signature with holes.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Pattern {
+    /// Anything, e.g the elements of an empty array.
+    Any,
+    /// An integer of any type, or a double. Defaults to `i`.
+    Integer,
+    /// A double.
+    Float,
+    /// A string, object path or signature. Defaults to `s`.
+    Text,
+    /// A fully known type.
+    Exact(Signature),
+    Array(Box<Pattern>),
+    Dict(Box<Pattern>, Box<Pattern>),
+    Tuple(Vec<Pattern>),
+    #[cfg(feature = "gvariant")]
+    Maybe(Box<Pattern>),
+}
+
+impl Pattern {
+    /// The pattern of the given node, unifying the types of the elements of its containers.
+    pub fn of(node: &Node) -> Result<Self, Error> {
+        let pattern = match &node.kind {
+            NodeKind::Annotated(signature, _) => Pattern::Exact(signature.clone()),
+            NodeKind::Bool(_) => Pattern::Exact(Signature::Bool),
+            NodeKind::Integer(_) => Pattern::Integer,
+            NodeKind::Float(_) => Pattern::Float,
+            NodeKind::Str(_) => Pattern::Text,
+            NodeKind::ByteStr(_) => Pattern::Exact(Signature::static_array(&Signature::U8)),
+            NodeKind::Array(items) => {
+                let mut element = Pattern::Any;
+                for item in items {
+                    element = element.unify(Pattern::of(item)?).ok_or_else(|| {
+                        Error::new(item.offset, "array element of a different type")
+                    })?;
+                }
+
+                Pattern::Array(Box::new(element))
+            }
+            NodeKind::Dict(entries) => {
+                let (mut key, mut value) = (Pattern::Any, Pattern::Any);
+                for (k, v) in entries {
+                    key = key.unify(Pattern::of(k)?).ok_or_else(|| {
+                        Error::new(k.offset, "dictionary key of a different type")
+                    })?;
+                    value = value.unify(Pattern::of(v)?).ok_or_else(|| {
+                        Error::new(v.offset, "dictionary value of a different type")
+                    })?;
+                }
+
+                Pattern::Dict(Box::new(key), Box::new(value))
+            }
+            NodeKind::Tuple(fields) => {
+                Pattern::Tuple(fields.iter().map(Pattern::of).collect::<Result<_, _>>()?)
+            }
+            NodeKind::Variant(_) => Pattern::Exact(Signature::Variant),
+            #[cfg(feature = "gvariant")]
+            NodeKind::Just(value) => Pattern::Maybe(Box::new(Pattern::of(value)?)),
+            #[cfg(feature = "gvariant")]
+            NodeKind::Nothing => Pattern::Maybe(Box::new(Pattern::Any)),
+        };
+
+        Ok(pattern)
+    }
+
+    /// The pattern matching both `self` and `other`, if any.
+    fn unify(self, other: Pattern) -> Option<Pattern> {
+        let pattern = match (self, other) {
+            (Pattern::Any, p) | (p, Pattern::Any) => p,
+            (Pattern::Integer, Pattern::Integer) => Pattern::Integer,
+            (Pattern::Integer | Pattern::Float, Pattern::Integer | Pattern::Float) => {
+                Pattern::Float
+            }
+            (Pattern::Text, Pattern::Text) => Pattern::Text,
+            (Pattern::Exact(s), p) | (p, Pattern::Exact(s)) => {
+                p.fits(&s).then_some(Pattern::Exact(s))?
+            }
+            (Pattern::Array(a), Pattern::Array(b)) => Pattern::Array(Box::new(a.unify(*b)?)),
+            (Pattern::Dict(k1, v1), Pattern::Dict(k2, v2)) => {
+                Pattern::Dict(Box::new(k1.unify(*k2)?), Box::new(v1.unify(*v2)?))
+            }
+            (Pattern::Tuple(a), Pattern::Tuple(b)) if a.len() == b.len() => Pattern::Tuple(
+                a.into_iter()
+                    .zip(b)
+                    .map(|(a, b)| a.unify(b))
+                    .collect::<Option<_>>()?,
+            ),
+            #[cfg(feature = "gvariant")]
+            (Pattern::Maybe(a), Pattern::Maybe(b)) => Pattern::Maybe(Box::new(a.unify(*b)?)),
+            // Values of maybe types can be written without `just`.
+            #[cfg(feature = "gvariant")]
+            (Pattern::Maybe(a), b) | (b, Pattern::Maybe(a)) => {
+                Pattern::Maybe(Box::new(a.unify(b)?))
+            }
+            _ => return None,
+        };
+
+        Some(pattern)
+    }
+
+    /// Whether the values matching `self` can be of the type of the given `signature`.
+    pub fn fits(&self, signature: &Signature) -> bool {
+        match (self, signature) {
+            (Pattern::Any, _) => true,
+            (
+                Pattern::Integer,
+                Signature::U8
+                | Signature::I16
+                | Signature::U16
+                | Signature::I32
+                | Signature::U32
+                | Signature::I64
+                | Signature::U64
+                | Signature::F64,
+            ) => true,
+            (Pattern::Float, Signature::F64) => true,
+            (Pattern::Text, Signature::Str | Signature::ObjectPath | Signature::Signature) => true,
+            (Pattern::Exact(s), signature) if s == signature => true,
+            (Pattern::Array(element), Signature::Array(child)) => element.fits(child.signature()),
+            (Pattern::Dict(k, v), Signature::Dict { key, value }) => {
+                k.fits(key.signature()) && v.fits(value.signature())
+            }
+            (Pattern::Tuple(patterns), Signature::Structure(fields)) => {
+                patterns.len() == fields.len()
+                    && patterns.iter().zip(fields.iter()).all(|(p, s)| p.fits(s))
+            }
+            #[cfg(feature = "gvariant")]
+            (Pattern::Maybe(p), Signature::Maybe(child)) => p.fits(child.signature()),
+            #[cfg(feature = "gvariant")]
+            (p, Signature::Maybe(child)) => p.fits(child.signature()),
+            _ => false,
+        }
+    }
+
+    /// The signature of the values matching `self`, with the defaults filled in.
+    ///
+    /// Returns `None` if the pattern has holes that can't be filled, e.g the elements of an empty
+    /// array.
+    pub fn default_signature(&self) -> Option<Signature> {
+        let signature = match self {
+            Pattern::Any => return None,
+            Pattern::Integer => Signature::I32,
+            Pattern::Float => Signature::F64,
+            Pattern::Text => Signature::Str,
+            Pattern::Exact(s) => s.clone(),
+            Pattern::Array(element) => Signature::array(element.default_signature()?),
+            Pattern::Dict(key, value) => {
+                Signature::dict(key.default_signature()?, value.default_signature()?)
+            }
+            Pattern::Tuple(fields) if fields.is_empty() => return None,
+            Pattern::Tuple(fields) => Signature::structure(
+                fields
+                    .iter()
+                    .map(Pattern::default_signature)
+                    .collect::<Option<Vec<_>>>()?,
+            ),
+            #[cfg(feature = "gvariant")]
+            Pattern::Maybe(child) => Signature::maybe(child.default_signature()?),
+        };
+
+        Some(signature)
+    }
+}
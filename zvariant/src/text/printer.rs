@@ -0,0 +1,296 @@
+use std::fmt::Write;
+
+#[cfg(feature = "gvariant")]
+use crate::Maybe;
+use crate::{Array, Dict, Structure, Value};
+
+/// Print `value` in the canonical text format, with type annotations if `annotate` is `true`.
+pub(super) fn print(out: &mut String, value: &Value<'_>, annotate: bool) {
+    let prefix = |out: &mut String, type_name: &str| {
+        if annotate {
+            out.push_str(type_name);
+            out.push(' ');
+        }
+    };
+
+    match value {
+        Value::U8(n) => {
+            prefix(out, "byte");
+            write!(out, "0x{n:02x}").unwrap();
+        }
+        Value::Bool(b) => write!(out, "{b}").unwrap(),
+        Value::I16(n) => {
+            prefix(out, "int16");
+            write!(out, "{n}").unwrap();
+        }
+        Value::U16(n) => {
+            prefix(out, "uint16");
+            write!(out, "{n}").unwrap();
+        }
+        // The default type of integers, so it's never annotated.
+        Value::I32(n) => write!(out, "{n}").unwrap(),
+        Value::U32(n) => {
+            prefix(out, "uint32");
+            write!(out, "{n}").unwrap();
+        }
+        Value::I64(n) => {
+            prefix(out, "int64");
+            write!(out, "{n}").unwrap();
+        }
+        Value::U64(n) => {
+            prefix(out, "uint64");
+            write!(out, "{n}").unwrap();
+        }
+        Value::F64(n) => print_double(out, *n),
+        Value::Str(s) => print_string(out, s.as_str()),
+        Value::Signature(s) => {
+            prefix(out, "signature");
+            write!(out, "'{s}'").unwrap();
+        }
+        Value::ObjectPath(p) => {
+            prefix(out, "objectpath");
+            write!(out, "'{p}'").unwrap();
+        }
+        Value::Value(child) => {
+            // Always annotated, since its type can't be inferred from the context.
+            out.push('<');
+            print(out, child, true);
+            out.push('>');
+        }
+        Value::Array(array) => print_array(out, array, annotate),
+        Value::Dict(dict) => print_dict(out, dict, annotate),
+        Value::Structure(structure) => print_structure(out, structure, annotate),
+        #[cfg(feature = "gvariant")]
+        Value::Maybe(maybe) => print_maybe(out, maybe, annotate),
+        #[cfg(unix)]
+        Value::Fd(fd) => {
+            prefix(out, "handle");
+            write!(out, "{fd}").unwrap();
+        }
+    }
+}
+
+/// Print `n` like `printf("%.17g")` does, with a `.0` suffix if it would look like an integer.
+fn print_double(out: &mut String, n: f64) {
+    if n.is_nan() {
+        out.push_str("nan");
+
+        return;
+    } else if n.is_infinite() {
+        out.push_str(if n > 0. { "inf" } else { "-inf" });
+
+        return;
+    }
+
+    // The exponent of `n`, once rounded to 17 significant digits.
+    let scientific = format!("{n:.16e}");
+    let (mantissa, exponent) = scientific.split_once('e').expect("exponent");
+    let exponent: i32 = exponent.parse().expect("integer exponent");
+    // Trailing zeros of the fractional part, if any, are dropped.
+    let trim = |digits: &str| match digits.contains('.') {
+        true => digits
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_owned(),
+        false => digits.to_owned(),
+    };
+
+    if (-4..17).contains(&exponent) {
+        let fixed = trim(&format!("{n:.*}", (16 - exponent) as usize));
+        out.push_str(&fixed);
+        if !fixed.contains('.') {
+            out.push_str(".0");
+        }
+    } else {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        write!(out, "{}e{sign}{:02}", trim(mantissa), exponent.abs()).unwrap();
+    }
+}
+
+/// Print `s` quoted, with the characters that aren't printable escaped.
+fn print_string(out: &mut String, s: &str) {
+    let quote = if s.contains('\'') { '"' } else { '\'' };
+
+    out.push(quote);
+    for c in s.chars() {
+        if c == quote || c == '\\' {
+            out.push('\\');
+        }
+        if is_printable(c) {
+            out.push(c);
+
+            continue;
+        }
+        match c {
+            '\x07' => out.push_str("\\a"),
+            '\x08' => out.push_str("\\b"),
+            '\x0C' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\x0B' => out.push_str("\\v"),
+            c if (c as u32) < 0x10000 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => write!(out, "\\U{:08x}", c as u32).unwrap(),
+        }
+    }
+    out.push(quote);
+}
+
+fn print_array(out: &mut String, array: &Array<'_>, mut annotate: bool) {
+    // Byte arrays with a single nul byte, at their end, are printed as bytestrings.
+    if let [bytes @ .., Value::U8(b'\0')] = array.as_ref() {
+        let bytes: Option<Vec<u8>> = bytes
+            .iter()
+            .map(|v| match v {
+                Value::U8(b'\0') => None,
+                Value::U8(b) => Some(*b),
+                _ => unreachable!("item must be a byte"),
+            })
+            .collect();
+        if let Some(bytes) = bytes {
+            print_bytestring(out, &bytes);
+
+            return;
+        }
+    }
+
+    if array.is_empty() {
+        if annotate {
+            write!(out, "@{} ", array.signature()).unwrap();
+        }
+        out.push_str("[]");
+
+        return;
+    }
+
+    out.push('[');
+    for (i, item) in array.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        // The other items are of the same type.
+        print(out, item, annotate);
+        annotate = false;
+    }
+    out.push(']');
+}
+
+/// Print `bytes` quoted, escaped like `g_strescape` does.
+fn print_bytestring(out: &mut String, bytes: &[u8]) {
+    let quote = if bytes.contains(&b'\'') { '"' } else { '\'' };
+
+    out.push('b');
+    out.push(quote);
+    for &b in bytes {
+        match b {
+            b'\x08' => out.push_str("\\b"),
+            b'\x0C' => out.push_str("\\f"),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            b'\x0B' => out.push_str("\\v"),
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b if !(b' '..0x7F).contains(&b) => write!(out, "\\{b:03o}").unwrap(),
+            b => out.push(b as char),
+        }
+    }
+    out.push(quote);
+}
+
+fn print_dict(out: &mut String, dict: &Dict<'_, '_>, mut annotate: bool) {
+    if dict.iter().next().is_none() {
+        if annotate {
+            write!(out, "@{} ", dict.signature()).unwrap();
+        }
+        out.push_str("{}");
+
+        return;
+    }
+
+    out.push('{');
+    for (i, (key, value)) in dict.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        // The other entries are of the same type.
+        print(out, key, annotate);
+        out.push_str(": ");
+        print(out, value, annotate);
+        annotate = false;
+    }
+    out.push('}');
+}
+
+fn print_structure(out: &mut String, structure: &Structure<'_>, annotate: bool) {
+    let fields = structure.fields();
+
+    out.push('(');
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        print(out, field, annotate);
+    }
+    if fields.len() == 1 {
+        out.push(',');
+    }
+    out.push(')');
+}
+
+#[cfg(feature = "gvariant")]
+fn print_maybe(out: &mut String, maybe: &Maybe<'_>, annotate: bool) {
+    if annotate {
+        write!(out, "@{} ", maybe.signature()).unwrap();
+    }
+
+    // Nested maybes are only told apart when one of them is nothing.
+    let mut inner = maybe.inner();
+    let mut depth = 0;
+    while let Some(Value::Maybe(child)) = inner {
+        inner = child.inner();
+        depth += 1;
+    }
+    match inner {
+        Some(inner) => print(out, inner, false),
+        None => {
+            for _ in 0..depth {
+                out.push_str("just ");
+            }
+            out.push_str("nothing");
+        }
+    }
+}
+
+/// Whether `c` is printed as is, i.e it's neither a control, a format character nor a
+/// noncharacter.
+fn is_printable(c: char) -> bool {
+    let noncharacter = ('\u{FDD0}'..='\u{FDEF}').contains(&c) || (c as u32) & 0xFFFE == 0xFFFE;
+
+    !c.is_control() && !noncharacter && !FORMAT_CHARS.iter().any(|r| r.contains(&c))
+}
+
+/// The characters of the Unicode "Format" (Cf) general category.
+const FORMAT_CHARS: &[std::ops::RangeInclusive<char>] = &[
+    '\u{00AD}'..='\u{00AD}',
+    '\u{0600}'..='\u{0605}',
+    '\u{061C}'..='\u{061C}',
+    '\u{06DD}'..='\u{06DD}',
+    '\u{070F}'..='\u{070F}',
+    '\u{0890}'..='\u{0891}',
+    '\u{08E2}'..='\u{08E2}',
+    '\u{180E}'..='\u{180E}',
+    '\u{200B}'..='\u{200F}',
+    '\u{202A}'..='\u{202E}',
+    '\u{2060}'..='\u{2064}',
+    '\u{2066}'..='\u{206F}',
+    '\u{FEFF}'..='\u{FEFF}',
+    '\u{FFF9}'..='\u{FFFB}',
+    '\u{110BD}'..='\u{110BD}',
+    '\u{110CD}'..='\u{110CD}',
+    '\u{13430}'..='\u{1343F}',
+    '\u{1BCA0}'..='\u{1BCA3}',
+    '\u{1D173}'..='\u{1D17A}',
+    '\u{E0001}'..='\u{E0001}',
+    '\u{E0020}'..='\u{E007F}',
+];
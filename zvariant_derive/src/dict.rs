@@ -1,34 +1,32 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, ToTokens};
 use syn::{punctuated::Punctuated, spanned::Spanned, Data, DeriveInput, Error, Field};
-use zvariant_utils::{case, macros};
+use zvariant_utils::macros;
 
-use crate::utils::*;
+use crate::{
+    r#type::{parse_signature, signature_to_tokens},
+    utils::*,
+};
 
 fn dict_name_for_field(
     f: &Field,
     rename_attr: Option<String>,
     rename_all_attr: Option<&str>,
 ) -> Result<String, Error> {
-    if let Some(name) = rename_attr {
-        Ok(name)
-    } else {
-        let ident = f.ident.as_ref().unwrap().to_string();
-
-        match rename_all_attr {
-            Some("lowercase") => Ok(ident.to_ascii_lowercase()),
-            Some("UPPERCASE") => Ok(ident.to_ascii_uppercase()),
-            Some("PascalCase") => Ok(case::pascal_or_camel_case(&ident, true)),
-            Some("camelCase") => Ok(case::pascal_or_camel_case(&ident, false)),
-            Some("snake_case") => Ok(case::snake_or_kebab_case(&ident, true)),
-            Some("kebab-case") => Ok(case::snake_or_kebab_case(&ident, false)),
-            None => Ok(ident),
-            Some(other) => Err(Error::new(
-                f.span(),
-                format!("invalid `rename_all` attribute value {other}"),
-            )),
-        }
-    }
+    let ident = f.ident.as_ref().unwrap().to_string();
+
+    rename_identifier(ident, f.span(), rename_attr, rename_all_attr)
+}
+
+/// The `signature` attribute of a field, as the tokens of a `zvariant::Signature`.
+fn field_signature(
+    f: &Field,
+    signature: Option<String>,
+    zv: &TokenStream,
+) -> Result<Option<TokenStream>, Error> {
+    signature
+        .map(|s| Ok(signature_to_tokens(&parse_signature(&s, f.span())?, zv)))
+        .transpose()
 }
 
 pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -42,24 +40,64 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
     let zv = zvariant_path();
     let mut entries = quote! {};
     let mut num_entries: usize = 0;
+    let mut flattened = Vec::new();
 
     for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
+        let FieldAttributes {
+            rename,
+            skip,
+            skip_serializing_if,
+            flatten,
+            signature,
+            ..
+        } = FieldAttributes::parse(&f.attrs)?;
 
         let name = &f.ident;
+        if skip {
+            continue;
+        }
+        if flatten {
+            entries.extend(quote! {
+                for (key, value) in &self.#name {
+                    map.serialize_entry(key, value)?;
+                }
+            });
+            flattened.push(name);
+
+            continue;
+        }
+
         let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
         let is_option = macros::ty_is_option(&f.ty);
 
-        let e = if is_option {
+        let value = if is_option {
+            quote! { self.#name.as_ref().unwrap() }
+        } else {
+            quote! { &self.#name }
+        };
+        let value = match field_signature(f, signature, &zv)? {
+            Some(signature) => quote! { #zv::SerializeValueWithSignature(#value, &#signature) },
+            None => quote! { #zv::SerializeValue(#value) },
+        };
+        let mut conditions = Vec::new();
+        if is_option {
+            conditions.push(quote! { self.#name.is_some() });
+        }
+        if let Some(path) = skip_serializing_if {
+            let path = syn::parse_str::<syn::ExprPath>(&path)?;
+            conditions.push(quote! { !#path(&self.#name) });
+        }
+
+        let e = if conditions.is_empty() {
             quote! {
-                if self.#name.is_some() {
-                    map.serialize_entry(#dict_name, &#zv::SerializeValue(self.#name.as_ref().unwrap()))?;
-                }
+                map.serialize_entry(#dict_name, &#value)?;
             }
         } else {
             quote! {
-                map.serialize_entry(#dict_name, &#zv::SerializeValue(&self.#name))?;
+                if #(#conditions)&&* {
+                    map.serialize_entry(#dict_name, &#value)?;
+                }
             }
         };
 
@@ -71,6 +109,7 @@ pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error>
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let num_entries = num_entries.to_token_stream();
+    let num_entries = quote! { #num_entries #(+ self.#flattened.len())* };
     Ok(quote! {
         #[allow(deprecated)]
         impl #impl_generics #zv::export::serde::ser::Serialize for #name #ty_generics
@@ -110,25 +149,69 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
     let mut dict_names = Vec::new();
     let mut entries = Vec::new();
 
+    let mut skipped = Vec::new();
+    let mut defaulted = Vec::new();
+    let mut flattened = None;
+
     for f in &data.fields {
-        let FieldAttributes { rename } = FieldAttributes::parse(&f.attrs)?;
+        let FieldAttributes {
+            rename,
+            default,
+            skip,
+            flatten,
+            signature,
+            ..
+        } = FieldAttributes::parse(&f.attrs)?;
 
         let name = &f.ident;
+        if skip {
+            skipped.push(name);
+
+            continue;
+        }
+        if flatten {
+            if deny_unknown_fields {
+                return Err(Error::new(
+                    f.span(),
+                    "`flatten` can't be used with `deny_unknown_fields`",
+                ));
+            }
+            if flattened.is_some() {
+                return Err(Error::new(f.span(), "only one field can be flattened"));
+            }
+            flattened = Some((name, &f.ty));
+
+            continue;
+        }
+
         let dict_name = dict_name_for_field(f, rename, rename_all.as_deref())?;
 
         let is_option = macros::ty_is_option(&f.ty);
 
+        let value = match field_signature(f, signature, &zv)? {
+            Some(signature) => quote! {
+                access.next_value_seed(#zv::DeserializeValueWithSignature::new(&#signature)).ok()
+            },
+            None => quote! {
+                access.next_value::<#zv::DeserializeValue<_>>().map(|v| v.0).ok()
+            },
+        };
         entries.push(quote! {
             #dict_name => {
                 // FIXME: add an option about strict parsing (instead of silently skipping the field)
-                #name = access.next_value::<#zv::DeserializeValue<_>>().map(|v| v.0).ok();
+                #name = #value;
             }
         });
 
         dict_names.push(dict_name);
         fields.push(name);
 
-        if !is_option {
+        if is_option {
+            continue;
+        }
+        if default {
+            defaulted.push(name);
+        } else {
             req_fields.push(name);
         }
     }
@@ -144,6 +227,15 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                 );
             }
         }
+    } else if let Some((name, _)) = flattened {
+        quote! {
+            unknown => {
+                #name.insert(
+                    ::std::string::ToString::to_string(unknown),
+                    access.next_value::<#zv::OwnedValue>()?,
+                );
+            }
+        }
     } else {
         quote! {
             unknown => {
@@ -152,6 +244,7 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
         }
     };
     entries.push(fallback);
+    let (flattened, flattened_ty): (Vec<_>, Vec<_>) = flattened.into_iter().unzip();
 
     let (_, ty_generics, _) = input.generics.split_for_impl();
     let mut generics = input.generics.clone();
@@ -194,6 +287,7 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                         M: #zv::export::serde::de::MapAccess<'de>,
                     {
                         #( let mut #fields = ::std::default::Default::default(); )*
+                        #( let mut #flattened = <#flattened_ty as ::std::default::Default>::default(); )*
 
                         // does not check duplicated fields, since those shouldn't exist in stream
                         while let ::std::option::Option::Some(key) = access.next_key::<&str>()? {
@@ -211,8 +305,13 @@ pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Erro
                                 ),
                             );
                         };)*
+                        #( let #defaulted = #defaulted.unwrap_or_default(); )*
 
-                        ::std::result::Result::Ok(#name { #(#fields),* })
+                        ::std::result::Result::Ok(#name {
+                            #(#fields,)*
+                            #(#flattened,)*
+                            #(#skipped: ::std::default::Default::default(),)*
+                        })
                     }
                 }
 
@@ -15,6 +15,7 @@ use proc_macro::TokenStream;
 use syn::DeriveInput;
 
 mod dict;
+mod tagged_enum;
 mod r#type;
 mod utils;
 mod value;
@@ -49,8 +50,9 @@ mod value;
 /// ```
 ///
 /// Same with enum, except that all variants of the enum must have the same number and types of
-/// fields (if any). If you want the encoding size of the (unit-type) enum to be dictated by
-/// `repr` attribute (like in the example below), you'll also need [serde_repr] crate.
+/// fields (if any), unless the enum uses one of the [tagged encodings](#tagged-enums). If you want
+/// the encoding size of the (unit-type) enum to be dictated by `repr` attribute (like in the
+/// example below), you'll also need [serde_repr] crate.
 ///
 /// ```
 /// use zvariant::{serialized::Context, to_bytes, Type, LE};
@@ -133,7 +135,8 @@ mod value;
 /// assert_eq!(decoded, s);
 /// ```
 ///
-/// Another common use for custom signatures is (de)serialization of unit enums as strings:
+/// Another common use for custom signatures is (de)serialization of unit enums as strings (see
+/// below for enums with fields):
 ///
 /// ```
 /// use zvariant::{serialized::Context, to_bytes, Type, LE};
@@ -155,10 +158,58 @@ mod value;
 /// assert_eq!(decoded, StrEnum::Variant2);
 /// ```
 ///
+/// # Tagged enums
+///
+/// Enums whose variants have different fields can be encoded as a tag identifying the variant,
+/// along with its fields in a variant. The `signature` attribute selects the encoding:
+///
+/// * `"(uv)"`: a structure of the index of the variant and its fields. Like in Rust, the index is
+///   the explicit discriminant of the variant, if any, which must then be an integer literal.
+/// * `"(sv)"`: a structure of the name of the variant and its fields.
+/// * `"a{sv}"` (or `"dict"`): a dictionary with a single entry, from the name of the variant to its
+///   fields.
+///
+/// The fields of a variant are encoded like those of a structure: a single unnamed field as is, and
+/// multiple fields (or named ones) as a structure. Unit variants are encoded as a byte (`0`), like
+/// empty structures. The names of the variants can be changed with the `rename` and `rename_all`
+/// attributes, just like the names of the fields of [`SerializeDict`].
+///
+/// Since they need to know the types of the fields, the encoding and decoding is provided by the
+/// [`SerializeEnum`] and [`DeserializeEnum`] macros, rather than serde's. The [`Value`] and
+/// [`OwnedValue`] macros support these encodings as well.
+///
+/// ```
+/// use zvariant::{serialized::Context, to_bytes, DeserializeEnum, SerializeEnum, Type, LE};
+///
+/// #[derive(DeserializeEnum, SerializeEnum, Type, PartialEq, Debug)]
+/// #[zvariant(signature = "(sv)", rename_all = "kebab-case")]
+/// enum Shape {
+///     Circle(f64),
+///     Rectangle { width: f64, height: f64 },
+///     #[zvariant(rename = "nothing")]
+///     Empty,
+/// }
+///
+/// assert_eq!(Shape::SIGNATURE, "(sv)");
+/// let ctxt = Context::new_dbus(LE, 0);
+/// let shape = Shape::Rectangle { width: 2., height: 3. };
+/// let encoded = to_bytes(ctxt, &shape).unwrap();
+/// let (name, fields): (&str, zvariant::Value<'_>) = encoded.deserialize().unwrap().0;
+/// assert_eq!(name, "rectangle");
+/// assert_eq!(fields, zvariant::Value::new((2., 3.)));
+/// let decoded: Shape = encoded.deserialize().unwrap().0;
+/// assert_eq!(decoded, shape);
+/// ```
+///
 /// [`Type`]: https://docs.rs/zvariant/latest/zvariant/trait.Type.html
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
 /// [serde_repr]: https://crates.io/crates/serde_repr
+/// [`SerializeDict`]: derive.SerializeDict.html
+/// [`SerializeEnum`]: derive.SerializeEnum.html
+/// [`DeserializeEnum`]: derive.DeserializeEnum.html
+/// [`Value`]: derive.Value.html
+/// [`OwnedValue`]: derive.OwnedValue.html
 #[proc_macro_derive(Type, attributes(zbus, zvariant))]
 pub fn type_macro_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
@@ -226,6 +277,36 @@ pub fn type_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"snake_case"`
 /// * `"kebab-case"`
 ///
+/// # Field attributes
+///
+/// Besides `rename`, the following attributes are supported on fields:
+///
+/// * `skip`: the field isn't serialized.
+/// * `skip_serializing_if = "path"`: the field isn't serialized if the function at `path`, taking
+///   a reference to the field, returns `true`.
+/// * `flatten`: the entries of the field, a map from strings to values (e.g a
+///   `HashMap<String, OwnedValue>`), are serialized as entries of the dictionary itself.
+/// * `signature = "signature"`: the value is serialized with the given signature, rather than the
+///   one of its type, e.g to encode a `String` as an object path.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{OwnedValue, SerializeDict, Type};
+///
+/// #[derive(SerializeDict, Type)]
+/// #[zvariant(signature = "a{sv}")]
+/// struct Struct {
+///     #[zvariant(skip_serializing_if = "Vec::is_empty")]
+///     tags: Vec<String>,
+///     #[zvariant(signature = "o")]
+///     path: String,
+///     #[zvariant(skip)]
+///     cache: u32,
+///     #[zvariant(flatten)]
+///     extra: HashMap<String, OwnedValue>,
+/// }
+/// ```
+///
 /// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
 #[proc_macro_derive(SerializeDict, attributes(zbus, zvariant))]
 pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
@@ -295,6 +376,38 @@ pub fn serialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// * `"snake_case"`
 /// * `"kebab-case"`
 ///
+/// # Field attributes
+///
+/// Besides `rename`, the following attributes are supported on fields:
+///
+/// * `default`: the field is set to its [`Default`] value if missing from the dictionary, rather
+///   than failing.
+/// * `skip`: the field isn't deserialized, and is always set to its [`Default`] value.
+/// * `flatten`: the entries of the dictionary not matching any other field are collected in the
+///   field, a map from strings to values (e.g a `HashMap<String, OwnedValue>`). It can't be used
+///   along with `deny_unknown_fields`, and only on a single field.
+/// * `signature = "signature"`: the value is deserialized with the given signature, rather than
+///   the one of its type, e.g to decode a `String` from an object path.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use zvariant::{DeserializeDict, OwnedValue, Type};
+///
+/// #[derive(DeserializeDict, Type)]
+/// #[zvariant(signature = "a{sv}")]
+/// ##[allow(unused)]
+/// struct Struct {
+///     #[zvariant(default)]
+///     tags: Vec<String>,
+///     #[zvariant(signature = "o")]
+///     path: String,
+///     #[zvariant(skip)]
+///     cache: u32,
+///     #[zvariant(flatten)]
+///     extra: HashMap<String, OwnedValue>,
+/// }
+/// ```
+///
 /// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
 #[proc_macro_derive(DeserializeDict, attributes(zbus, zvariant))]
 pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
@@ -304,6 +417,37 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Adds [`Serialize`] implementation to enums to be serialized in one of the tagged encodings.
+///
+/// The encoding is selected with the `signature` attribute, which must be `(uv)`, `(sv)` or `a{sv}`
+/// (`dict`). See the [`Type`] macro for details and an example.
+///
+/// [`Serialize`]: https://docs.serde.rs/serde/trait.Serialize.html
+/// [`Type`]: derive.Type.html#tagged-enums
+#[proc_macro_derive(SerializeEnum, attributes(zbus, zvariant))]
+pub fn serialize_enum_macro_derive(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    tagged_enum::expand_serialize_derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Adds [`Deserialize`] implementation to enums to be deserialized from one of the tagged
+/// encodings.
+///
+/// The encoding is selected with the `signature` attribute, which must be `(uv)`, `(sv)` or `a{sv}`
+/// (`dict`). See the [`Type`] macro for details and an example.
+///
+/// [`Deserialize`]: https://docs.serde.rs/serde/de/trait.Deserialize.html
+/// [`Type`]: derive.Type.html#tagged-enums
+#[proc_macro_derive(DeserializeEnum, attributes(zbus, zvariant))]
+pub fn deserialize_enum_macro_derive(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).unwrap();
+    tagged_enum::expand_deserialize_derive(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// Implements conversions for your type to/from [`Value`].
 ///
 /// Implements `TryFrom<Value>` and `Into<Value>` for your type.
@@ -382,7 +526,7 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// assert_eq!(s.field2.as_str(), "/blah");
 /// ```
 ///
-/// Enums also supported but currently only simple ones w/ an integer representation:
+/// Enums also supported, either simple ones w/ an integer representation:
 ///
 /// ```
 /// # use zvariant::{OwnedValue, Value};
@@ -402,15 +546,41 @@ pub fn deserialize_dict_macro_derive(input: TokenStream) -> TokenStream {
 /// assert_eq!(e, Enum::Variant2);
 /// ```
 ///
+/// or ones in one of the [tagged encodings](derive.Type.html#tagged-enums):
+///
+/// ```
+/// # use zvariant::{OwnedValue, Value};
+/// #
+/// #[derive(Debug, PartialEq, Value, OwnedValue)]
+/// #[zvariant(signature = "a{sv}")]
+/// enum Event {
+///     Click { x: i32, y: i32 },
+///     Key(u32),
+/// }
+///
+/// let value = Value::from(Event::Key(42));
+/// assert_eq!(value.value_signature(), "a{sv}");
+/// let e = Event::try_from(value).unwrap();
+/// assert_eq!(e, Event::Key(42));
+/// let value = OwnedValue::try_from(Event::Click { x: 1, y: 2 }).unwrap();
+/// let e = Event::try_from(value).unwrap();
+/// assert_eq!(e, Event::Click { x: 1, y: 2 });
+/// ```
+///
 /// # Dictionary encoding
 ///
 /// For treating your type as a dictionary, you can use the `signature = "dict"` attribute. See
 /// [`Type`] for more details and an example use. Please note that this macro can only handle
 /// `dict` or `a{sv}` values. All other values will be ignored.
 ///
+/// The `default`, `skip`, `skip_serializing_if`, `flatten` and `signature` field attributes are
+/// supported, with the same meaning as for [`SerializeDict`] and [`DeserializeDict`].
+///
 /// [`Value`]: https://docs.rs/zvariant/latest/zvariant/enum.Value.html
 /// [`Type`]: derive.Type.html#custom-types
-#[proc_macro_derive(Value)]
+/// [`SerializeDict`]: derive.SerializeDict.html#field-attributes
+/// [`DeserializeDict`]: derive.DeserializeDict.html#field-attributes
+#[proc_macro_derive(Value, attributes(zbus, zvariant))]
 pub fn value_macro_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
     value::expand_derive(ast, value::ValueType::Value)
@@ -425,7 +595,7 @@ pub fn value_macro_derive(input: TokenStream) -> TokenStream {
 /// See [`Value`] documentation for examples.
 ///
 /// [`OwnedValue`]: https://docs.rs/zvariant/latest/zvariant/struct.OwnedValue.html
-#[proc_macro_derive(OwnedValue)]
+#[proc_macro_derive(OwnedValue, attributes(zbus, zvariant))]
 pub fn owned_value_macro_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
     value::expand_derive(ast, value::ValueType::OwnedValue)
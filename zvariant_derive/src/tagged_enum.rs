@@ -0,0 +1,393 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    punctuated::Punctuated, spanned::Spanned, Data, DataEnum, DeriveInput, Error, Expr, ExprLit,
+    Fields, Ident, Lit, Type,
+};
+
+use crate::utils::*;
+
+/// The encodings of enums with differently shaped variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// `(uv)`: the index of the variant and its fields.
+    Index,
+    /// `(sv)`: the name of the variant and its fields.
+    Name,
+    /// `a{sv}`: a single entry, from the name of the variant to its fields.
+    Dict,
+}
+
+impl Encoding {
+    /// The encoding of the given `signature` attribute, if it's one of the tagged encodings.
+    pub fn from_signature(signature: &str) -> Option<Self> {
+        match signature {
+            "(uv)" => Some(Self::Index),
+            "(sv)" => Some(Self::Name),
+            "a{sv}" | "dict" => Some(Self::Dict),
+            _ => None,
+        }
+    }
+
+    fn parse(input: &DeriveInput) -> Result<Self, Error> {
+        StructAttributes::parse(&input.attrs)?
+            .signature
+            .as_deref()
+            .and_then(Self::from_signature)
+            .ok_or_else(|| {
+                Error::new(
+                    input.span(),
+                    "expected a `signature` attribute of `(uv)`, `(sv)` or `a{sv}`",
+                )
+            })
+    }
+}
+
+/// A variant of a tagged enum.
+pub struct Variant<'a> {
+    pub ident: &'a Ident,
+    pub index: u32,
+    pub name: String,
+    pub fields: &'a Fields,
+}
+
+impl Variant<'_> {
+    /// The pattern matching the variant of the enum `name`, binding its fields to `f0`, `f1`, etc.
+    ///
+    /// This is also the expression constructing the variant from these bindings.
+    pub fn pattern(&self, name: &Ident) -> TokenStream {
+        let ident = self.ident;
+        let bindings = self.bindings();
+        match self.fields {
+            Fields::Unit => quote! { #name::#ident },
+            Fields::Unnamed(_) => quote! { #name::#ident(#(#bindings),*) },
+            Fields::Named(_) => {
+                let names = self.fields.iter().map(|f| &f.ident);
+                quote! { #name::#ident { #(#names: #bindings),* } }
+            }
+        }
+    }
+
+    /// The bindings of the fields of the variant, in the pattern.
+    pub fn bindings(&self) -> Vec<Ident> {
+        (0..self.fields.len())
+            .map(|i| format_ident!("f{}", i))
+            .collect()
+    }
+
+    /// Whether the fields of the variant are encoded as a single value, rather than a structure.
+    pub fn is_newtype(&self) -> bool {
+        matches!(self.fields, Fields::Unnamed(_)) && self.fields.len() == 1
+    }
+}
+
+/// The variants of the enum, with their names as per the `rename` & `rename_all` attributes.
+///
+/// With the `Index` encoding, their indexes are their discriminants, which are assigned like Rust
+/// does: explicitly, or by incrementing the one of the previous variant.
+pub fn variants<'a>(
+    data: &'a DataEnum,
+    encoding: Encoding,
+    rename_all: Option<&str>,
+) -> Result<Vec<Variant<'a>>, Error> {
+    let mut next_index = Some(0);
+
+    data.variants
+        .iter()
+        .map(|variant| {
+            let index = match &variant.discriminant {
+                Some((_, discriminant)) if encoding == Encoding::Index => {
+                    discriminant_value(discriminant)?
+                }
+                _ => next_index
+                    .ok_or_else(|| Error::new(variant.span(), "discriminant overflows a `u32`"))?,
+            };
+            next_index = index.checked_add(1);
+            let VariantAttributes { rename } = VariantAttributes::parse(&variant.attrs)?;
+            let name = rename_identifier(
+                variant.ident.to_string(),
+                variant.span(),
+                rename,
+                rename_all,
+            )?;
+
+            Ok(Variant {
+                ident: &variant.ident,
+                index,
+                name,
+                fields: &variant.fields,
+            })
+        })
+        .collect()
+}
+
+/// The value of an explicit discriminant, which must be an integer literal fitting a `u32`.
+fn discriminant_value(discriminant: &Expr) -> Result<u32, Error> {
+    let error = || {
+        Error::new(
+            discriminant.span(),
+            "expected an integer literal discriminant, fitting a `u32`",
+        )
+    };
+    match discriminant {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit.base10_parse().map_err(|_| error()),
+        _ => Err(error()),
+    }
+}
+
+fn parse_enum(input: &DeriveInput) -> Result<(Encoding, Vec<Variant<'_>>), Error> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(input.span(), "only enums supported"));
+    };
+    let encoding = Encoding::parse(input)?;
+    let StructAttributes { rename_all, .. } = StructAttributes::parse(&input.attrs)?;
+
+    Ok((encoding, variants(data, encoding, rename_all.as_deref())?))
+}
+
+pub fn expand_serialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let (encoding, variants) = parse_enum(&input)?;
+    let name = &input.ident;
+    let zv = zvariant_path();
+
+    let arms = variants.iter().map(|variant| {
+        let pattern = variant.pattern(name);
+        let bindings = variant.bindings();
+        let value = match variant.fields {
+            // Like empty structures, unit variants are encoded as a single byte.
+            Fields::Unit => quote! { &0u8 },
+            _ if variant.is_newtype() => quote! { f0 },
+            _ => quote! { &(#(#bindings),*,) },
+        };
+        let tag = match encoding {
+            Encoding::Index => {
+                let index = variant.index;
+                quote! { &#index }
+            }
+            Encoding::Name | Encoding::Dict => {
+                let name = &variant.name;
+                quote! { #name }
+            }
+        };
+        let serialize = match encoding {
+            Encoding::Index | Encoding::Name => quote! {
+                let mut s = serializer.serialize_struct(::std::stringify!(#name), 2)?;
+                s.serialize_field("tag", #tag)?;
+                s.serialize_field("value", &#zv::SerializeValue(#value))?;
+                s.end()
+            },
+            Encoding::Dict => quote! {
+                let mut map = serializer.serialize_map(::std::option::Option::Some(1))?;
+                map.serialize_entry(#tag, &#zv::SerializeValue(#value))?;
+                map.end()
+            },
+        };
+
+        quote! {
+            #pattern => { #serialize }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #zv::export::serde::ser::Serialize for #name #ty_generics
+        #where_clause
+        {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: #zv::export::serde::ser::Serializer,
+            {
+                #[allow(unused_imports)]
+                use #zv::export::serde::ser::{SerializeMap, SerializeStruct};
+
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+pub fn expand_deserialize_derive(input: DeriveInput) -> Result<TokenStream, Error> {
+    let (encoding, variants) = parse_enum(&input)?;
+    let name = &input.ident;
+    let visitor = format_ident!("{}Visitor", name);
+    let zv = zvariant_path();
+    let de = quote! { #zv::export::serde::de };
+
+    // The value of each variant, from its fields deserialized with `next`, an expression of the
+    // given type wrapped in a `DeserializeValue`.
+    let arms = |next: &dyn Fn(TokenStream) -> TokenStream| {
+        variants
+            .iter()
+            .map(|variant| {
+                let tag = match encoding {
+                    Encoding::Index => {
+                        let index = variant.index;
+                        quote! { #index }
+                    }
+                    Encoding::Name | Encoding::Dict => {
+                        let name = &variant.name;
+                        quote! { #name }
+                    }
+                };
+                let pattern = variant.pattern(name);
+                let bindings = variant.bindings();
+                let types = variant.fields.iter().map(|f| &f.ty).collect::<Vec<&Type>>();
+                let (ty, destructure) = match variant.fields {
+                    Fields::Unit => (quote! { u8 }, quote! { _ }),
+                    _ if variant.is_newtype() => (quote! { #(#types)* }, quote! { f0 }),
+                    _ => (quote! { (#(#types),*,) }, quote! { (#(#bindings),*,) }),
+                };
+
+                let next = next(quote! { #zv::DeserializeValue<#ty> });
+
+                quote! {
+                    #tag => {
+                        let #destructure = #next.0;
+
+                        #pattern
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let names = variants.iter().map(|v| &v.name);
+    let unknown = match encoding {
+        Encoding::Index => quote! {
+            tag => {
+                return ::std::result::Result::Err(<A::Error as #de::Error>::invalid_value(
+                    #de::Unexpected::Unsigned(tag.into()),
+                    &self,
+                ));
+            }
+        },
+        Encoding::Name | Encoding::Dict => quote! {
+            tag => {
+                return ::std::result::Result::Err(
+                    <A::Error as #de::Error>::unknown_variant(tag, &[#(#names),*]),
+                );
+            }
+        },
+    };
+    let (visit, deserialize, expecting) = match encoding {
+        Encoding::Index | Encoding::Name => {
+            let tag_ty = match encoding {
+                Encoding::Index => quote! { u32 },
+                _ => quote! { &str },
+            };
+            let arms = arms(&|ty| {
+                quote! {
+                    seq.next_element::<#ty>()?
+                        .ok_or_else(|| <A::Error as #de::Error>::invalid_length(1, &self))?
+                }
+            });
+            let expecting = match encoding {
+                Encoding::Index => "an enum encoded as `(uv)`",
+                _ => "an enum encoded as `(sv)`",
+            };
+
+            (
+                quote! {
+                    fn visit_seq<A>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error>
+                    where
+                        A: #de::SeqAccess<'de>,
+                    {
+                        let tag = seq
+                            .next_element::<#tag_ty>()?
+                            .ok_or_else(|| <A::Error as #de::Error>::invalid_length(0, &self))?;
+
+                        ::std::result::Result::Ok(match tag {
+                            #(#arms)*
+                            #unknown
+                        })
+                    }
+                },
+                quote! {
+                    deserializer.deserialize_struct(
+                        ::std::stringify!(#name),
+                        &["tag", "value"],
+                        #visitor(::std::marker::PhantomData),
+                    )
+                },
+                expecting,
+            )
+        }
+        Encoding::Dict => {
+            let arms = arms(&|ty| quote! { map.next_value::<#ty>()? });
+
+            (
+                quote! {
+                    fn visit_map<A>(self, mut map: A) -> ::std::result::Result<Self::Value, A::Error>
+                    where
+                        A: #de::MapAccess<'de>,
+                    {
+                        let tag = map
+                            .next_key::<&str>()?
+                            .ok_or_else(|| <A::Error as #de::Error>::invalid_length(0, &self))?;
+                        let value = match tag {
+                            #(#arms)*
+                            #unknown
+                        };
+                        if map.next_key::<&str>()?.is_some() {
+                            return ::std::result::Result::Err(
+                                <A::Error as #de::Error>::invalid_length(2, &self),
+                            );
+                        }
+
+                        ::std::result::Result::Ok(value)
+                    }
+                },
+                quote! {
+                    deserializer.deserialize_map(#visitor(::std::marker::PhantomData))
+                },
+                "an enum encoded as a single-entry `a{sv}`",
+            )
+        }
+    };
+
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let mut generics = input.generics.clone();
+    let def = syn::LifetimeParam {
+        attrs: Vec::new(),
+        lifetime: syn::Lifetime::new("'de", Span::call_site()),
+        colon_token: None,
+        bounds: Punctuated::new(),
+    };
+    generics.params = Some(syn::GenericParam::Lifetime(def))
+        .into_iter()
+        .chain(generics.params)
+        .collect();
+
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[allow(deprecated)]
+        impl #impl_generics #de::Deserialize<'de> for #name #ty_generics
+        #where_clause
+        {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: #de::Deserializer<'de>,
+            {
+                struct #visitor #ty_generics(::std::marker::PhantomData<#name #ty_generics>);
+
+                impl #impl_generics #de::Visitor<'de> for #visitor #ty_generics {
+                    type Value = #name #ty_generics;
+
+                    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                        formatter.write_str(#expecting)
+                    }
+
+                    #visit
+                }
+
+                #deserialize
+            }
+        }
+    })
+}
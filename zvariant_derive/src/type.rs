@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::{
     spanned::Spanned, Attribute, Data, DataEnum, DeriveInput, Error, Fields, Generics, Ident,
@@ -16,10 +16,7 @@ pub fn expand_derive(ast: DeriveInput) -> Result<TokenStream, Error> {
     if let Some(signature_str) = signature {
         // Signature already provided, easy then!
 
-        let signature = match signature_str.as_str() {
-            "dict" => Signature::dict(Signature::Str, Signature::Variant),
-            s => Signature::from_str(s).map_err(|e| Error::new(ast.span(), e))?,
-        };
+        let signature = parse_signature(&signature_str, ast.span())?;
         let signature_tokens = signature_to_tokens(&signature, &zv);
 
         let name = ast.ident;
@@ -192,7 +189,15 @@ fn signature_for_variant(
     }
 }
 
-fn signature_to_tokens(signature: &Signature, zv: &TokenStream) -> TokenStream {
+/// Parse the value of a `signature` attribute, where `dict` is short for `a{sv}`.
+pub fn parse_signature(signature: &str, span: Span) -> Result<Signature, Error> {
+    match signature {
+        "dict" => Ok(Signature::dict(Signature::Str, Signature::Variant)),
+        s => Signature::from_str(s).map_err(|e| Error::new(span, e)),
+    }
+}
+
+pub fn signature_to_tokens(signature: &Signature, zv: &TokenStream) -> TokenStream {
     match signature {
         Signature::Unit => quote! { #zv::Signature::Unit },
         Signature::Bool => quote! { #zv::Signature::Bool },
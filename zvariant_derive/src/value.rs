@@ -5,7 +5,11 @@ use syn::{
     Lifetime, LifetimeParam,
 };
 
-use crate::utils::*;
+use crate::{
+    r#type::{parse_signature, signature_to_tokens},
+    tagged_enum::{self, Encoding},
+    utils::*,
+};
 
 pub enum ValueType {
     Value,
@@ -35,7 +39,25 @@ pub fn expand_derive(ast: DeriveInput, value_type: ValueType) -> Result<TokenStr
             }
             Fields::Unit => Err(Error::new(ast.span(), "Unit structures not supported")),
         },
-        Data::Enum(data) => impl_enum(value_type, ast.ident, ast.generics, ast.attrs, data, &zv),
+        Data::Enum(data) => {
+            let StructAttributes {
+                signature,
+                rename_all,
+                ..
+            } = StructAttributes::parse(&ast.attrs)?;
+            match signature.as_deref().and_then(Encoding::from_signature) {
+                Some(encoding) => impl_tagged_enum(
+                    value_type,
+                    ast.ident,
+                    ast.generics,
+                    data,
+                    encoding,
+                    rename_all.as_deref(),
+                    &zv,
+                ),
+                None => impl_enum(value_type, ast.ident, ast.generics, ast.attrs, data, &zv),
+            }
+        }
         _ => Err(Error::new(
             ast.span(),
             "only structs and enums are supported",
@@ -43,14 +65,25 @@ pub fn expand_derive(ast: DeriveInput, value_type: ValueType) -> Result<TokenStr
     }
 }
 
-fn impl_struct(
+/// The pieces of the conversions of a type to & from `Value` or `OwnedValue`.
+struct Conversions {
+    value_type: TokenStream,
+    value_lifetime: LifetimeParam,
+    into_value_trait: TokenStream,
+    into_value_method: TokenStream,
+    into_value_error_decl: TokenStream,
+    into_value_ret: TokenStream,
+    into_value_error_transform: TokenStream,
+    from_value_where_clause: Option<TokenStream>,
+    into_value_where_clause: Option<TokenStream>,
+}
+
+fn conversions(
     value_type: ValueType,
-    name: Ident,
-    generics: Generics,
-    fields: &Fields,
-    signature: Option<String>,
+    name: &Ident,
+    generics: &Generics,
     zv: &TokenStream,
-) -> Result<TokenStream, Error> {
+) -> Result<Conversions, Error> {
     let statc_lifetime = LifetimeParam::new(Lifetime::new("'static", Span::call_site()));
     let (
         value_type,
@@ -115,6 +148,39 @@ fn impl_struct(
     } else {
         (None, None)
     };
+
+    Ok(Conversions {
+        value_type,
+        value_lifetime,
+        into_value_trait,
+        into_value_method,
+        into_value_error_decl,
+        into_value_ret,
+        into_value_error_transform,
+        from_value_where_clause,
+        into_value_where_clause,
+    })
+}
+
+fn impl_struct(
+    value_type: ValueType,
+    name: Ident,
+    generics: Generics,
+    fields: &Fields,
+    signature: Option<String>,
+    zv: &TokenStream,
+) -> Result<TokenStream, Error> {
+    let Conversions {
+        value_type,
+        into_value_trait,
+        into_value_method,
+        into_value_error_decl,
+        into_value_ret,
+        into_value_error_transform,
+        from_value_where_clause,
+        into_value_where_clause,
+        ..
+    } = conversions(value_type, &name, &generics, zv)?;
     let (impl_generics, ty_generics, _) = generics.split_for_impl();
     match fields {
         Fields::Named(_) => {
@@ -123,32 +189,28 @@ fn impl_struct(
                 .map(|field| field.ident.to_token_stream())
                 .collect();
             let (from_value_impl, into_value_impl) = match signature {
-                Some(signature) if signature == "a{sv}" => (
+                Some(signature) if signature == "a{sv}" => {
                     // User wants the type to be encoded as a dict.
-                    // FIXME: Not the most efficient implementation.
-                    quote! {
-                        let mut fields = <::std::collections::HashMap::<::std::string::String, #zv::Value>>::try_from(value)?;
+                    let (from_fields, into_fields) = dict_fields(fields, zv)?;
 
-                        ::std::result::Result::Ok(Self {
-                            #(
-                                #field_names:
-                                    fields
-                                        .remove(stringify!(#field_names))
-                                        .ok_or_else(|| #zv::Error::IncorrectType)?
-                                        .downcast()?
-                            ),*
-                        })
-                    },
-                    quote! {
-                        let mut fields = ::std::collections::HashMap::new();
-                        #(
-                            fields.insert(stringify!(#field_names), #zv::Value::from(s.#field_names));
-                        )*
+                    (
+                        // FIXME: Not the most efficient implementation.
+                        quote! {
+                            let mut fields = <::std::collections::HashMap::<::std::string::String, #zv::Value>>::try_from(value)?;
 
-                        <#value_type>::#into_value_method(#zv::Value::from(fields))
-                            #into_value_error_transform
-                    },
-                ),
+                            ::std::result::Result::Ok(Self {
+                                #(#from_fields),*
+                            })
+                        },
+                        quote! {
+                            let mut fields = ::std::collections::HashMap::<::std::string::String, #zv::Value>::new();
+                            #(#into_fields)*
+
+                            <#value_type>::#into_value_method(#zv::Value::from(fields))
+                                #into_value_error_transform
+                        },
+                    )
+                }
                 Some(_) | None => (
                     quote! {
                         let mut fields = #zv::Structure::try_from(value)?.into_fields();
@@ -224,6 +286,121 @@ fn impl_struct(
     }
 }
 
+/// The conversions of the fields of a structure encoded as a dict, from & into the `fields` map.
+///
+/// Like `SerializeDict` and `DeserializeDict`, this honors the `default`, `skip`,
+/// `skip_serializing_if`, `flatten` and `signature` field attributes.
+fn dict_fields(
+    fields: &Fields,
+    zv: &TokenStream,
+) -> Result<(Vec<TokenStream>, Vec<TokenStream>), Error> {
+    let mut from_fields = vec![];
+    let mut into_fields = vec![];
+    let mut flattened = None;
+
+    for f in fields {
+        let FieldAttributes {
+            default,
+            skip,
+            skip_serializing_if,
+            flatten,
+            signature,
+            ..
+        } = FieldAttributes::parse(&f.attrs)?;
+        let name = &f.ident;
+
+        if skip {
+            from_fields.push(quote! { #name: ::std::default::Default::default() });
+
+            continue;
+        }
+        if flatten {
+            if flattened.is_some() {
+                return Err(Error::new(f.span(), "only one field can be flattened"));
+            }
+            // The remaining entries are only known once all the other fields are taken out.
+            flattened = Some(quote! {
+                #name: fields
+                    .into_iter()
+                    .map(|(key, value)| {
+                        ::std::result::Result::Ok((key, ::std::convert::TryFrom::try_from(value)?))
+                    })
+                    .collect::<#zv::Result<_>>()?
+            });
+            into_fields.push(quote! {
+                for (key, value) in s.#name {
+                    fields.insert(key, #zv::Value::from(value));
+                }
+            });
+
+            continue;
+        }
+
+        let ctxt = quote! { #zv::serialized::Context::new_dbus(#zv::LE, 0) };
+        let (from_value, into_value) = match signature {
+            Some(signature) => {
+                // Go through the encoding, since a value can't be converted to another signature.
+                let signature = signature_to_tokens(&parse_signature(&signature, f.span())?, zv);
+
+                (
+                    quote! {
+                        #zv::to_bytes(#ctxt, &value).and_then(|data| {
+                            data.deserialize_with_seed(
+                                #zv::DeserializeValueWithSignature::new(&#signature),
+                            )
+                            .map(|(value, _)| value)
+                        })?
+                    },
+                    quote! {
+                        #zv::Value::from(
+                            #zv::to_bytes(
+                                #ctxt,
+                                &#zv::SerializeValueWithSignature(&s.#name, &#signature),
+                            )
+                            .and_then(|data| data.deserialize::<#zv::OwnedValue>())
+                            .expect("field must be of the signature given by its `signature` attribute")
+                            .0,
+                        )
+                    },
+                )
+            }
+            None => (
+                quote! { value.downcast()? },
+                quote! { #zv::Value::from(s.#name) },
+            ),
+        };
+        let missing = if default {
+            quote! { ::std::default::Default::default() }
+        } else {
+            quote! { return ::std::result::Result::Err(#zv::Error::IncorrectType) }
+        };
+        from_fields.push(quote! {
+            #name: match fields.remove(stringify!(#name)) {
+                ::std::option::Option::Some(value) => #from_value,
+                ::std::option::Option::None => #missing,
+            }
+        });
+
+        let insert = quote! {
+            fields.insert(::std::string::String::from(stringify!(#name)), #into_value);
+        };
+        into_fields.push(match skip_serializing_if {
+            Some(path) => {
+                let path = syn::parse_str::<syn::ExprPath>(&path)?;
+                quote! {
+                    if !#path(&s.#name) {
+                        #insert
+                    }
+                }
+            }
+            None => insert,
+        });
+    }
+    from_fields.extend(flattened);
+
+    Ok((from_fields, into_fields))
+}
+
 fn impl_enum(
     value_type: ValueType,
     name: Ident,
@@ -325,3 +502,172 @@ fn impl_enum(
         #into_value
     })
 }
+
+fn impl_tagged_enum(
+    value_type: ValueType,
+    name: Ident,
+    generics: Generics,
+    data: &DataEnum,
+    encoding: Encoding,
+    rename_all: Option<&str>,
+    zv: &TokenStream,
+) -> Result<TokenStream, Error> {
+    let Conversions {
+        value_type,
+        value_lifetime,
+        into_value_trait,
+        into_value_method,
+        into_value_error_decl,
+        into_value_ret,
+        into_value_error_transform,
+        from_value_where_clause,
+        into_value_where_clause,
+    } = conversions(value_type, &name, &generics, zv)?;
+    let variants = tagged_enum::variants(data, encoding, rename_all)?;
+
+    let mut from_value_arms = vec![];
+    let mut into_value_arms = vec![];
+    for variant in &variants {
+        let tag = match encoding {
+            Encoding::Index => {
+                let index = variant.index;
+                quote! { #index }
+            }
+            Encoding::Name | Encoding::Dict => {
+                let name = &variant.name;
+                quote! { #name }
+            }
+        };
+        let pattern = variant.pattern(&name);
+        let bindings = variant.bindings();
+        let num_fields = bindings.len();
+        let (from_payload, into_payload) = match variant.fields {
+            // Like empty structures, unit variants are encoded as a single byte.
+            Fields::Unit => (
+                quote! { let _: u8 = payload.downcast()?; },
+                quote! { #zv::Value::U8(0) },
+            ),
+            _ if variant.is_newtype() => (
+                quote! { let f0 = payload.downcast()?; },
+                quote! { #zv::Value::from(f0) },
+            ),
+            _ => (
+                quote! {
+                    let mut fields = payload.downcast::<#zv::Structure>()?.into_fields();
+                    if fields.len() != #num_fields {
+                        return ::std::result::Result::Err(#zv::Error::IncorrectType);
+                    }
+                    #(
+                        let #bindings = fields.remove(0).downcast()?;
+                    )*
+                },
+                quote! {
+                    #zv::Value::from(#zv::StructureBuilder::new()
+                    #(
+                        .add_field(#bindings)
+                    )*
+                    .build().unwrap())
+                },
+            ),
+        };
+
+        from_value_arms.push(quote! {
+            #tag => {
+                #from_payload
+
+                #pattern
+            }
+        });
+        into_value_arms.push(quote! {
+            #pattern => (#tag, #into_payload),
+        });
+    }
+
+    let (from_value, into_value) = match encoding {
+        Encoding::Index | Encoding::Name => {
+            let tag_ty = match encoding {
+                Encoding::Index => quote! { u32 },
+                _ => quote! { ::std::string::String },
+            };
+            let tag_expr = match encoding {
+                Encoding::Index => quote! { tag },
+                _ => quote! { tag.as_str() },
+            };
+
+            (
+                quote! {
+                    let mut fields = #zv::Structure::try_from(value)?.into_fields();
+                    if fields.len() != 2 {
+                        return ::std::result::Result::Err(#zv::Error::IncorrectType);
+                    }
+                    let payload = fields.remove(1);
+                    let tag: #tag_ty = fields.remove(0).downcast()?;
+
+                    ::std::result::Result::Ok(match #tag_expr {
+                        #(#from_value_arms)*
+                        _ => return ::std::result::Result::Err(#zv::Error::IncorrectType),
+                    })
+                },
+                quote! {
+                    #zv::Value::from(#zv::StructureBuilder::new()
+                        .add_field(tag)
+                        .add_field(payload)
+                        .build().unwrap())
+                },
+            )
+        }
+        Encoding::Dict => (
+            quote! {
+                let entries = <::std::collections::HashMap::<::std::string::String, #zv::Value>>::try_from(value)?;
+                if entries.len() != 1 {
+                    return ::std::result::Result::Err(#zv::Error::IncorrectType);
+                }
+                let (tag, payload) = entries.into_iter().next().unwrap();
+
+                ::std::result::Result::Ok(match tag.as_str() {
+                    #(#from_value_arms)*
+                    _ => return ::std::result::Result::Err(#zv::Error::IncorrectType),
+                })
+            },
+            quote! {
+                let mut fields = ::std::collections::HashMap::new();
+                fields.insert(tag, payload);
+
+                #zv::Value::from(fields)
+            },
+        ),
+    };
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::std::convert::TryFrom<#value_type> for #name #ty_generics
+            #from_value_where_clause
+        {
+            type Error = #zv::Error;
+
+            #[inline]
+            fn try_from(value: #value_type) -> #zv::Result<Self> {
+                let value = <#zv::Value<#value_lifetime> as ::std::convert::From<_>>::from(value);
+
+                #from_value
+            }
+        }
+
+        impl #impl_generics #into_value_trait<#name #ty_generics> for #value_type
+            #into_value_where_clause
+        {
+            #into_value_error_decl
+
+            #[inline]
+            fn #into_value_method(e: #name #ty_generics) -> #into_value_ret {
+                let (tag, payload): (_, #zv::Value<#value_lifetime>) = match e {
+                    #(#into_value_arms)*
+                };
+                let value = { #into_value };
+
+                <#value_type>::#into_value_method(value) #into_value_error_transform
+            }
+        }
+    })
+}
@@ -3,7 +3,8 @@
 use std::collections::HashMap;
 use zvariant::{
     serialized::{Context, Format},
-    DeserializeDict, OwnedValue, SerializeDict, Type, Value, LE,
+    DeserializeDict, DeserializeEnum, ObjectPath, OwnedValue, SerializeDict, SerializeEnum, Type,
+    Value, LE,
 };
 
 #[test]
@@ -82,3 +83,351 @@ fn derive_dict() {
 
     assert_eq!(Test::SIGNATURE, "a{sv}")
 }
+
+#[test]
+fn derive_dict_field_attributes() {
+    #[derive(SerializeDict, DeserializeDict, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}", rename_all = "kebab-case")]
+    struct Test {
+        #[zvariant(default)]
+        count: u32,
+        #[zvariant(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[zvariant(signature = "o")]
+        path: String,
+        #[zvariant(skip)]
+        cache: Option<u64>,
+        #[zvariant(flatten)]
+        extra: HashMap<String, OwnedValue>,
+    }
+
+    let test = Test {
+        count: 0,
+        tags: vec![],
+        path: "/foo".to_string(),
+        cache: Some(42),
+        extra: HashMap::from([("other".to_string(), OwnedValue::from(7i16))]),
+    };
+
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    let serialized = zvariant::to_bytes(ctxt, &test).unwrap();
+    let dict: HashMap<String, OwnedValue> = serialized.deserialize().unwrap().0;
+
+    assert_eq!(dict.len(), 3);
+    assert_eq!(dict["count"], OwnedValue::from(0u32));
+    assert_eq!(
+        dict["path"],
+        Value::from(ObjectPath::from_static_str_unchecked("/foo"))
+            .try_into()
+            .unwrap()
+    );
+    assert_eq!(dict["other"], OwnedValue::from(7i16));
+
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let deserialized: Test = serialized.deserialize().unwrap().0;
+    assert_eq!(
+        deserialized,
+        Test {
+            cache: None,
+            ..test
+        }
+    );
+
+    // Missing fields with `default` are defaulted, unlike other non-optional fields.
+    let dict = HashMap::from([(
+        "path",
+        Value::from(ObjectPath::from_static_str_unchecked("/bar")),
+    )]);
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    let deserialized: Test = serialized.deserialize().unwrap().0;
+    assert_eq!(deserialized.count, 0);
+    assert!(deserialized.tags.is_empty());
+    assert_eq!(deserialized.path, "/bar");
+    assert!(deserialized.extra.is_empty());
+
+    let dict = HashMap::from([("count", Value::from(1u32))]);
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    serialized.deserialize::<Test>().unwrap_err();
+
+    // A value of the wrong signature isn't accepted for a field with a `signature` attribute.
+    let dict = HashMap::from([("path", Value::from("/bar"))]);
+    let serialized = zvariant::to_bytes(ctxt, &dict).unwrap();
+    serialized.deserialize::<Test>().unwrap_err();
+}
+
+#[test]
+fn derive_value_dict_field_attributes() {
+    #[derive(Value, OwnedValue, Type, PartialEq, Debug)]
+    #[zvariant(signature = "a{sv}")]
+    struct Test {
+        #[zvariant(default)]
+        count: u32,
+        #[zvariant(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[zvariant(signature = "o")]
+        path: String,
+        #[zvariant(skip)]
+        cache: Option<u64>,
+        #[zvariant(flatten)]
+        extra: HashMap<String, OwnedValue>,
+    }
+
+    let test = || Test {
+        count: 0,
+        tags: vec![],
+        path: "/foo".to_string(),
+        cache: Some(42),
+        extra: HashMap::from([("other".to_string(), OwnedValue::from(7i16))]),
+    };
+
+    let value = Value::from(test());
+    let dict = HashMap::<String, OwnedValue>::try_from(value.try_clone().unwrap()).unwrap();
+    assert_eq!(dict.len(), 3);
+    assert_eq!(dict["count"], OwnedValue::from(0u32));
+    assert_eq!(
+        dict["path"],
+        Value::from(ObjectPath::from_static_str_unchecked("/foo"))
+            .try_into()
+            .unwrap()
+    );
+    assert_eq!(dict["other"], OwnedValue::from(7i16));
+
+    let expected = Test {
+        cache: None,
+        ..test()
+    };
+    assert_eq!(Test::try_from(value).unwrap(), expected);
+    let value = OwnedValue::try_from(test()).unwrap();
+    assert_eq!(Test::try_from(value).unwrap(), expected);
+
+    // Missing fields with `default` are defaulted, unlike other non-optional fields.
+    let value = Value::from(HashMap::from([(
+        "path",
+        Value::from(ObjectPath::from_static_str_unchecked("/bar")),
+    )]));
+    let test = Test::try_from(value).unwrap();
+    assert_eq!(test.count, 0);
+    assert!(test.tags.is_empty());
+    assert_eq!(test.path, "/bar");
+    assert!(test.extra.is_empty());
+
+    let value = Value::from(HashMap::from([("count", Value::from(1u32))]));
+    Test::try_from(value).unwrap_err();
+
+    // A value of the wrong signature isn't accepted for a field with a `signature` attribute.
+    let value = Value::from(HashMap::from([("path", Value::from("/bar"))]));
+    Test::try_from(value).unwrap_err();
+}
+
+#[derive(DeserializeEnum, SerializeEnum, Type, Value, OwnedValue, PartialEq, Debug, Clone)]
+#[zvariant(signature = "(uv)")]
+enum IndexTagged {
+    Unit,
+    Newtype(String),
+    Tuple(u8, Vec<i64>),
+    Struct { a: bool, b: HashMap<String, u16> },
+    Single { c: u32 },
+}
+
+#[derive(DeserializeEnum, SerializeEnum, Type, Value, OwnedValue, PartialEq, Debug, Clone)]
+#[zvariant(signature = "(uv)")]
+#[repr(u32)]
+enum DiscriminantTagged {
+    Unit = 5,
+    Newtype(String),
+    Tuple(u8, i64) = 10,
+}
+
+#[derive(DeserializeEnum, SerializeEnum, Type, Value, OwnedValue, PartialEq, Debug, Clone)]
+#[zvariant(signature = "(sv)", rename_all = "snake_case")]
+enum NameTagged {
+    Unit,
+    #[zvariant(rename = "newtype")]
+    NewType(String),
+    Tuple(u8, Vec<i64>),
+    Struct {
+        a: bool,
+        b: HashMap<String, u16>,
+    },
+}
+
+#[derive(DeserializeEnum, SerializeEnum, Type, Value, OwnedValue, PartialEq, Debug, Clone)]
+#[zvariant(signature = "dict")]
+enum DictTagged {
+    Unit,
+    Newtype(String),
+    Tuple(u8, Vec<i64>),
+    Struct { a: bool, b: HashMap<String, u16> },
+}
+
+#[track_caller]
+fn tagged_enum_round_trip<T>(value: T, tag: Value<'_>, fields: Value<'_>)
+where
+    T: serde::Serialize
+        + for<'de> serde::Deserialize<'de>
+        + Type
+        + Into<Value<'static>>
+        + TryFrom<Value<'static>, Error = zvariant::Error>
+        + TryInto<OwnedValue, Error = zvariant::Error>
+        + TryFrom<OwnedValue, Error = zvariant::Error>
+        + PartialEq
+        + std::fmt::Debug
+        + Clone,
+{
+    // Only a single format without the `gvariant` feature.
+    #[allow(clippy::single_element_loop)]
+    for format in [
+        Format::DBus,
+        #[cfg(feature = "gvariant")]
+        Format::GVariant,
+    ] {
+        let ctxt = Context::new(format, LE, 0);
+        let encoded = zvariant::to_bytes(ctxt, &value).unwrap();
+        assert_eq!(encoded.deserialize::<T>().unwrap().0, value);
+
+        // Check the encoding itself.
+        let encoded_value: Value<'_> = match T::SIGNATURE.to_string().as_str() {
+            "a{sv}" => {
+                let dict: HashMap<String, Value<'_>> = encoded.deserialize().unwrap().0;
+                assert_eq!(dict.len(), 1);
+                let (name, fields) = dict.into_iter().next().unwrap();
+                assert_eq!(Value::from(name), tag);
+
+                fields
+            }
+            _ => {
+                let (t, fields): (Value<'_>, Value<'_>) = match tag {
+                    Value::U32(_) => {
+                        let (t, fields): (u32, Value<'_>) = encoded.deserialize().unwrap().0;
+                        (Value::from(t), fields)
+                    }
+                    _ => {
+                        let (t, fields): (String, Value<'_>) = encoded.deserialize().unwrap().0;
+                        (Value::from(t), fields)
+                    }
+                };
+                assert_eq!(t, tag);
+
+                fields
+            }
+        };
+        assert_eq!(
+            encoded_value.try_to_owned().unwrap(),
+            fields.try_to_owned().unwrap()
+        );
+    }
+
+    let v: Value<'static> = value.clone().into();
+    assert_eq!(v.value_signature(), T::SIGNATURE);
+    assert_eq!(T::try_from(v).unwrap(), value);
+    let v: OwnedValue = value.clone().try_into().unwrap();
+    assert_eq!(T::try_from(v).unwrap(), value);
+}
+
+#[test]
+fn derive_tagged_enum() {
+    assert_eq!(IndexTagged::SIGNATURE, "(uv)");
+    assert_eq!(NameTagged::SIGNATURE, "(sv)");
+    assert_eq!(DictTagged::SIGNATURE, "a{sv}");
+
+    let map = HashMap::from([("x".to_string(), 7u16)]);
+
+    tagged_enum_round_trip(IndexTagged::Unit, Value::from(0u32), Value::from(0u8));
+    tagged_enum_round_trip(
+        IndexTagged::Newtype("hi".into()),
+        Value::from(1u32),
+        Value::from("hi"),
+    );
+    tagged_enum_round_trip(
+        IndexTagged::Tuple(3, vec![-1, 1]),
+        Value::from(2u32),
+        Value::new((3u8, vec![-1i64, 1])),
+    );
+    tagged_enum_round_trip(
+        IndexTagged::Struct {
+            a: true,
+            b: map.clone(),
+        },
+        Value::from(3u32),
+        Value::new((true, map.clone())),
+    );
+    tagged_enum_round_trip(
+        IndexTagged::Single { c: 5 },
+        Value::from(4u32),
+        Value::new((5u32,)),
+    );
+
+    // Explicit discriminants are the indexes of their variants, and the following ones.
+    tagged_enum_round_trip(
+        DiscriminantTagged::Unit,
+        Value::from(5u32),
+        Value::from(0u8),
+    );
+    tagged_enum_round_trip(
+        DiscriminantTagged::Newtype("hi".into()),
+        Value::from(6u32),
+        Value::from("hi"),
+    );
+    tagged_enum_round_trip(
+        DiscriminantTagged::Tuple(3, -1),
+        Value::from(10u32),
+        Value::new((3u8, -1i64)),
+    );
+
+    tagged_enum_round_trip(NameTagged::Unit, Value::from("unit"), Value::from(0u8));
+    tagged_enum_round_trip(
+        NameTagged::NewType("hi".into()),
+        Value::from("newtype"),
+        Value::from("hi"),
+    );
+    tagged_enum_round_trip(
+        NameTagged::Tuple(3, vec![]),
+        Value::from("tuple"),
+        Value::new((3u8, Vec::<i64>::new())),
+    );
+    tagged_enum_round_trip(
+        NameTagged::Struct {
+            a: false,
+            b: map.clone(),
+        },
+        Value::from("struct"),
+        Value::new((false, map.clone())),
+    );
+
+    tagged_enum_round_trip(DictTagged::Unit, Value::from("Unit"), Value::from(0u8));
+    tagged_enum_round_trip(
+        DictTagged::Newtype("hi".into()),
+        Value::from("Newtype"),
+        Value::from("hi"),
+    );
+    tagged_enum_round_trip(
+        DictTagged::Tuple(3, vec![9]),
+        Value::from("Tuple"),
+        Value::new((3u8, vec![9i64])),
+    );
+    tagged_enum_round_trip(
+        DictTagged::Struct {
+            a: true,
+            b: map.clone(),
+        },
+        Value::from("Struct"),
+        Value::new((true, map)),
+    );
+
+    // Unknown tags and payloads of the wrong type are rejected.
+    let ctxt = Context::new(Format::DBus, LE, 0);
+    let encoded = zvariant::to_bytes(ctxt, &(5u32, Value::from(0u8))).unwrap();
+    assert!(encoded.deserialize::<IndexTagged>().is_err());
+    let encoded = zvariant::to_bytes(ctxt, &("tuple", Value::from(0u8))).unwrap();
+    assert!(encoded.deserialize::<NameTagged>().is_err());
+    let encoded = zvariant::to_bytes(
+        ctxt,
+        &HashMap::from([("Unit", Value::from(0u8)), ("Newtype", Value::from("a"))]),
+    )
+    .unwrap();
+    assert!(encoded.deserialize::<DictTagged>().is_err());
+    assert_eq!(
+        NameTagged::try_from(Value::new(("bogus", Value::from(0u8)))),
+        Err(zvariant::Error::IncorrectType)
+    );
+}